@@ -0,0 +1,168 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Ahead-of-time compilation of templates to Rust code - for build scripts.
+//!
+//! Templates are parsed at build time, i.e. syntax errors fail the build. At
+//! runtime the engine only applies the node visitors of its extensions, see
+//! `Engine::set_precompiled()`.
+//!
+//! # Examples
+//!
+//! `build.rs`:
+//!
+//! ```no_run
+//! extern crate twig;
+//!
+//! use std::env;
+//! use std::path::Path;
+//!
+//! fn main() {
+//!     let out = Path::new(&env::var("OUT_DIR").unwrap()).join("templates.rs");
+//!
+//!     match twig::build::compile_templates(Path::new("templates"), &out) {
+//!         Ok(paths) => {
+//!             for path in paths {
+//!                 println!("cargo:rerun-if-changed={}", path.display());
+//!             }
+//!         }
+//!         Err(e) => panic!("{}", e),
+//!     }
+//! }
+//! ```
+//!
+//! `src/main.rs`:
+//!
+//! ```ignore
+//! mod templates {
+//!     include!(concat!(env!("OUT_DIR"), "/templates.rs"));
+//! }
+//!
+//! let mut engine = Engine::new(Setup::default()).unwrap();
+//! engine.set_precompiled(templates::templates());
+//! let html = engine.load_template("index.html", None).unwrap().render(&runtime).unwrap();
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use engine::{Engine, Setup, TwigError};
+use engine::node::codegen;
+use api::error::Traced;
+
+/// Compiles all templates of `dir` into the Rust module `out`.
+///
+/// Templates are named by their path relative to `dir`, e.g. `partials/nav.html`,
+/// hidden files are skipped. Returns the paths of the templates, e.g. for
+/// `cargo:rerun-if-changed`.
+///
+/// # Failures
+/// * When a template can't be parsed or compiled to Rust
+/// * When reading the templates or writing the module fails
+pub fn compile_templates(dir: &Path, out: &Path) -> Result<Vec<PathBuf>, Traced<TwigError>> {
+    let engine = try_traced!(Engine::new(Setup::default()));
+
+    compile_templates_with(&engine, dir, out)
+}
+
+/// Compiles all templates of `dir` into the Rust module `out` - with the
+/// settings and extensions of `engine`, e.g. for custom tags.
+///
+/// The engine rendering the templates must have the same settings. See
+/// `compile_templates()`.
+pub fn compile_templates_with(engine: &Engine,
+                              dir: &Path,
+                              out: &Path)
+                              -> Result<Vec<PathBuf>, Traced<TwigError>> {
+    let mut paths = Vec::new();
+    try_untraced!(find_templates(dir, &mut paths).map_err(|e| build_error(dir, e)));
+    paths.sort();
+
+    let mut templates = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let mut source = String::new();
+        try_untraced!(File::open(path)
+                          .and_then(|mut file| file.read_to_string(&mut source))
+                          .map_err(|e| build_error(path, e)));
+
+        templates.push((template_name(dir, path), source));
+    }
+
+    let code = try_traced!(generate(engine, &templates));
+    try_untraced!(File::create(out)
+                      .and_then(|mut file| file.write_all(code.as_bytes()))
+                      .map_err(|e| build_error(out, e)));
+
+    Ok(paths)
+}
+
+/// Returns the Rust module for the templates, given by name and source.
+///
+/// The module exposes `pub fn templates() -> twig::template::Precompiled`,
+/// which records the settings of `engine`.
+pub fn generate(engine: &Engine,
+                templates: &[(String, String)])
+                -> Result<String, Traced<TwigError>> {
+    let prefix = engine.template_class_prefix();
+    let parser = try_traced!(engine.templates());
+
+    let mut registry = String::new();
+    let mut functions = String::new();
+    for &(ref name, ref source) in templates {
+        let module = try_traced!(parser.parse_module(source, name));
+        let function = codegen::function_name(prefix, name);
+
+        registry.push_str(&format!("    templates.add({:?}, {});\n", name, function));
+        functions.push_str(&format!("\n/// {:?}\n#[allow(non_snake_case)]\nfn {}() -> \
+                                     ::twig::engine::node::Module {}\n",
+                                    name,
+                                    function,
+                                    try_traced!(codegen::generate(&module))));
+    }
+
+    Ok(format!("// Generated by twig::build - do not edit.\n\n/// Returns the precompiled \
+                templates, see `twig::Engine::set_precompiled()`.\npub fn templates() -> \
+                ::twig::template::Precompiled {{\n    #[allow(unused_mut)]\n    let mut \
+                templates = ::twig::template::Precompiled::built_with({:#018x});\n{}    \
+                templates\n}}\n{}",
+               parser.fingerprint(),
+               registry,
+               functions))
+}
+
+/// Collects the files of `dir` recursively - except hidden files.
+fn find_templates(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in try!(fs::read_dir(dir)) {
+        let path = try!(entry).path();
+        let hidden = path.file_name().and_then(|x| x.to_str()).is_some_and(|x| x.starts_with('.'));
+
+        if hidden {
+            continue;
+        } else if path.is_dir() {
+            try!(find_templates(&path, paths));
+        } else {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the path relative to `dir` with `/` as separator.
+fn template_name(dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    let components: Vec<String> = relative.components()
+                                          .map(|x| x.as_os_str().to_string_lossy().into_owned())
+                                          .collect();
+
+    components.join("/")
+}
+
+fn build_error(path: &Path, error: io::Error) -> TwigError {
+    TwigError::Build {
+        path: path.to_path_buf(),
+        reason: error.to_string(),
+    }
+}
@@ -7,10 +7,13 @@
 
 use std::fmt::{self, Display};
 use std::error::Error;
+use std::io;
+use std::path::PathBuf;
 
 use loader::LoaderError;
-use engine::parser::{ParserError, LexerError};
+use engine::parser::{ParserError, LexerError, NodeError};
 use extension;
+use runtime::RuntimeError;
 
 
 #[derive(Debug)]
@@ -18,13 +21,27 @@ pub enum TwigError {
     Loader(LoaderError),
     Lexer(LexerError),
     Parser(ParserError),
+    Node(NodeError),
     ExtensionRegistry(ExtensionRegistryError),
     Unreachable {
         reason: String,
     },
     LoaderNotInitialized,
     LexerNotInitialized,
-    Runtime,
+    ParserNotInitialized,
+    Runtime(RuntimeError),
+    Cache {
+        path: PathBuf,
+        reason: String,
+    },
+    Build {
+        path: PathBuf,
+        reason: String,
+    },
+    Precompiled {
+        name: String,
+    },
+    Output(io::Error),
 }
 
 impl From<LoaderError> for TwigError {
@@ -45,6 +62,18 @@ impl From<ParserError> for TwigError {
     }
 }
 
+impl From<NodeError> for TwigError {
+    fn from(err: NodeError) -> TwigError {
+        TwigError::Node(err)
+    }
+}
+
+impl From<RuntimeError> for TwigError {
+    fn from(err: RuntimeError) -> TwigError {
+        TwigError::Runtime(err)
+    }
+}
+
 impl From<ExtensionRegistryError> for TwigError {
     fn from(err: ExtensionRegistryError) -> TwigError {
         TwigError::ExtensionRegistry(err)
@@ -57,6 +86,7 @@ impl Error for TwigError {
             TwigError::Loader(..) => "Twig loader error.",
             TwigError::Lexer(..) => "Twig lexer error.",
             TwigError::Parser(..) => "Twig parser error.",
+            TwigError::Node(..) => "Twig node error.",
             TwigError::ExtensionRegistry(..) => "Twig extension registry error.",
             TwigError::Unreachable{..} => {
                 "Unexptected twig error (please report as bug with details)."
@@ -67,7 +97,14 @@ impl Error for TwigError {
             TwigError::LexerNotInitialized => {
                 "The template lexer must be initializied prior usage."
             }
-            TwigError::Runtime => "Twig runtime error.",
+            TwigError::ParserNotInitialized => {
+                "The template parser must be initializied prior usage."
+            }
+            TwigError::Runtime(..) => "Twig runtime error.",
+            TwigError::Cache{..} => "Twig cache error.",
+            TwigError::Build{..} => "Twig build error.",
+            TwigError::Precompiled{..} => "Twig precompiled template error.",
+            TwigError::Output(..) => "Cannot write the template output.",
         }
     }
 }
@@ -80,13 +117,31 @@ impl Display for TwigError {
             TwigError::Loader(ref e) => Display::fmt(e, f),
             TwigError::Lexer(ref e) => Display::fmt(e, f),
             TwigError::Parser(ref e) => Display::fmt(e, f),
+            TwigError::Node(ref e) => Display::fmt(e, f),
             TwigError::ExtensionRegistry(ref e) => Display::fmt(e, f),
+            TwigError::Runtime(ref e) => Display::fmt(e, f),
+            TwigError::Output(ref e) => write!(f, " {}.", e),
             TwigError::Unreachable {
                 ref reason
             } => write!(f, " {}.", reason),
+            TwigError::Cache {
+                ref path, ref reason
+            } |
+            TwigError::Build {
+                ref path, ref reason
+            } => write!(f, " Cannot access {path:?}: {reason}.", path = path, reason = reason),
+            TwigError::Precompiled {
+                ref name
+            } => {
+                write!(f,
+                       " The template {name:?} was built with other settings - autoescape mode, \
+                        escapers, extensions or lexer options. Rebuild it with the engine, see \
+                        `twig::build::compile_templates_with()`.",
+                       name = name)
+            }
             TwigError::LoaderNotInitialized |
             TwigError::LexerNotInitialized |
-            TwigError::Runtime => Ok(()),
+            TwigError::ParserNotInitialized => Ok(()),
         }
     }
 }
@@ -126,6 +181,10 @@ pub enum ExtensionRegistryError {
         prev: Box<extension::api::TokenParser>,
         ext_name: &'static str,
     },
+    DuplicateGlobal {
+        prev: Box<extension::api::Global>,
+        ext_name: &'static str,
+    },
 }
 
 impl Error for ExtensionRegistryError {
@@ -143,6 +202,7 @@ impl Error for ExtensionRegistryError {
             ExtensionRegistryError::DuplicateTest{..} => "Duplicate test.",
             ExtensionRegistryError::DuplicateTagHandler{..} => "Duplicate tag handler.",
             ExtensionRegistryError::DuplicateTokenParser{..} => "Duplicate token parser.",
+            ExtensionRegistryError::DuplicateGlobal{..} => "Duplicate global.",
         }
     }
 }
@@ -213,6 +273,14 @@ impl Display for ExtensionRegistryError {
                        prev = p,
                        ext = x)
             }
+            ExtensionRegistryError::DuplicateGlobal {
+                prev: ref p, ext_name: ref x
+            } => {
+                write!(f,
+                       " {prev:?} has already been registered, while loading extension {ext:?}.",
+                       prev = p,
+                       ext = x)
+            }
         }
     }
 }
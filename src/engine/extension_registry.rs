@@ -7,6 +7,7 @@
 
 use extension::api::{self, Extension};
 use std::collections::HashMap;
+use std::mem;
 use engine::Engine;
 use engine::error::ExtensionRegistryError;
 use api::error::Traced;
@@ -25,7 +26,7 @@ pub struct ExtensionRegistry {
     node_visitors: Vec<Box<api::NodeVisitor>>,
     operators_unary: HashMap<String, api::UnaryOperator>,
     operators_binary: HashMap<String, api::BinaryOperator>,
-    _globals: Vec<Box<api::Global>>,
+    globals: HashMap<String, Box<api::Global>>,
 }
 
 impl ExtensionRegistry {
@@ -82,72 +83,121 @@ impl ExtensionRegistry {
             return traced_err!(ExtensionRegistryError::AlreadyInitialized);
         }
 
-        for (_, ext) in self.ext.iter() {
-            ext.init(engine);
+        let extensions = mem::take(&mut self.ext);
+        let mut result = Ok(());
+        for (_, ext) in extensions.iter() {
+            result = self.init_extension(&**ext, engine);
+            if result.is_err() {
+                break;
+            }
+        }
+        self.ext = extensions;
+        self.initialized = true;
+
+        result
+    }
+
+    /// Registers an extension after all other extensions have been initialized,
+    /// e.g. the staging extension.
+    ///
+    /// Its definitions are collected, but `Extension::init()` is not called -
+    /// the engine is already set up.
+    pub fn push_initialized(&mut self,
+                            extension: Box<Extension>)
+                            -> Result<&mut Self, Traced<ExtensionRegistryError>> {
+        try_traced!(self.check_initialized());
+        try_traced!(self.collect(&*extension));
+
+        if let Some(prev) = self.ext.insert(extension.name().to_string(), extension) {
+            return traced_err!(ExtensionRegistryError::DuplicateExtension { prev: prev });
+        };
+
+        Ok(self)
+    }
 
-            for (k, v) in ext.filters() {
-                if let Some(prev) = self.filters.insert(k, v) {
-                    return traced_err!(ExtensionRegistryError::DuplicateFilter {
-                        prev: prev,
-                        ext_name: ext.name(),
-                    });
-                }
+    /// Initializes the extension and collects its definitions.
+    fn init_extension(&mut self,
+                      ext: &Extension,
+                      engine: &mut Engine)
+                      -> Result<(), Traced<ExtensionRegistryError>> {
+        ext.init(engine);
+
+        self.collect(ext)
+    }
+
+    /// Collects the definitions of the extension.
+    fn collect(&mut self, ext: &Extension) -> Result<(), Traced<ExtensionRegistryError>> {
+        for (k, v) in ext.filters() {
+            if let Some(prev) = self.filters.insert(k, v) {
+                return traced_err!(ExtensionRegistryError::DuplicateFilter {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
-            for (k, v) in ext.functions() {
-                if let Some(prev) = self.functions.insert(k, v) {
-                    return traced_err!(ExtensionRegistryError::DuplicateFunction {
-                        prev: prev,
-                        ext_name: ext.name(),
-                    });
-                }
+        }
+        for (k, v) in ext.functions() {
+            if let Some(prev) = self.functions.insert(k, v) {
+                return traced_err!(ExtensionRegistryError::DuplicateFunction {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
-            for (k, v) in ext.tests() {
-                if let Some(prev) = self.tests.insert(k, v) {
-                    return traced_err!(ExtensionRegistryError::DuplicateTest {
-                        prev: prev,
-                        ext_name: ext.name(),
-                    });
-                }
+        }
+        for (k, v) in ext.tests() {
+            if let Some(prev) = self.tests.insert(k, v) {
+                return traced_err!(ExtensionRegistryError::DuplicateTest {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
-            for (k, v) in ext.token_parsers() {
-                // #NOTE:60 can't have a reference to something owned within the same struct
-                // and don't want to clone!
-                //
-                // if let Some(prev) = self._token_parser_by_tags.insert(v.tag().to_string(), &v) {
-                //     return traced_err!(ExtensionRegistryError::DuplicateTagHandler {
-                //         prev: prev,
-                //         ext_name: ext.name()
-                //     })
-                // }
-
-                if let Some(prev) = self.token_parsers.insert(k, v) {
-                    return traced_err!(ExtensionRegistryError::DuplicateTokenParser {
-                        prev: prev,
-                        ext_name: ext.name(),
-                    });
-                }
+        }
+        for (k, v) in ext.token_parsers() {
+            // #NOTE:60 can't have a reference to something owned within the same struct
+            // and don't want to clone!
+            //
+            // if let Some(prev) = self._token_parser_by_tags.insert(v.tag().to_string(), &v) {
+            //     return traced_err!(ExtensionRegistryError::DuplicateTagHandler {
+            //         prev: prev,
+            //         ext_name: ext.name()
+            //     })
+            // }
+
+            if let Some(prev) = self.token_parsers.insert(k, v) {
+                return traced_err!(ExtensionRegistryError::DuplicateTokenParser {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
+        }
 
-            // TODO: `vec.append()` is not yet stable ...
-            for v in ext.node_visitors() {
-                self.node_visitors.push(v)
+        // TODO: `vec.append()` is not yet stable ...
+        for v in ext.node_visitors() {
+            self.node_visitors.push(v)
+        }
+        for v in ext.operators_unary() {
+            if let Some(prev) = self.operators_unary.insert(v.repr.clone(), v) {
+                return traced_err!(ExtensionRegistryError::DuplicateOperatorUnary {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
-            for v in ext.operators_unary() {
-                if let Some(prev) = self.operators_unary.insert(v.repr.clone(), v) {
-                    return traced_err!(ExtensionRegistryError::DuplicateOperatorUnary {
-                        prev: prev,
-                        ext_name: ext.name(),
-                    });
-                }
+        }
+
+        for v in ext.operators_binary() {
+            if let Some(prev) = self.operators_binary.insert(v.repr.clone(), v) {
+                return traced_err!(ExtensionRegistryError::DuplicateOperatorBinary {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
+        }
 
-            for v in ext.operators_binary() {
-                if let Some(prev) = self.operators_binary.insert(v.repr.clone(), v) {
-                    return traced_err!(ExtensionRegistryError::DuplicateOperatorBinary {
-                        prev: prev,
-                        ext_name: ext.name(),
-                    });
-                }
+        for v in ext.globals() {
+            if let Some(prev) = self.globals.insert(v.name().to_string(), v) {
+                return traced_err!(ExtensionRegistryError::DuplicateGlobal {
+                    prev: prev,
+                    ext_name: ext.name(),
+                });
             }
         }
 
@@ -195,7 +245,7 @@ impl ExtensionRegistry {
     }
 
     /// Get the global variables defined by engine extensions.
-    pub fn _globals(&self) -> &Vec<Box<api::Global>> {
-        &self._globals
+    pub fn globals(&self) -> &HashMap<String, Box<api::Global>> {
+        &self.globals
     }
 }
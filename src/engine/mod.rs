@@ -8,9 +8,14 @@
 #[cfg(test)]
 mod test;
 mod template_cache;
+use self::template_cache::Cache;
 use loader::Loader;
-use std::rc::Rc;
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
 use template;
+use template::api::Template;
+use extension::{Sandbox, Profiler, Staging};
+use extension::api::{SimpleFilter, SimpleFunction, Global, Test, TokenParser};
 use api::error::Traced;
 
 pub mod error;
@@ -19,28 +24,42 @@ pub mod setup;
 pub mod extension_registry;
 pub mod parser;
 pub mod node;
+pub mod templates;
 pub use self::node::Node;
 pub use self::parser::{Parser, lexer, Lexer};
 pub use self::error::{TwigError, ExtensionRegistryError};
 pub use self::options::Options;
 pub use self::setup::Setup;
 pub use self::extension_registry::ExtensionRegistry;
+pub use self::templates::Templates;
 pub use runtime::{self, Runtime};
 
 
+/// The engine is `Send + Sync` - it can be shared across threads, e.g. in an `Arc`.
+///
+/// Configuration methods take `&mut self`. Templates are loaded with `&self`,
+/// the lexer and parser are set up when the first template is loaded.
 #[derive(Default, Debug)] // TODO: - provide a different constructor
 pub struct Engine {
     options: Options,
-    ext: Option<Rc<ExtensionRegistry>>,
-    // ext_staging: Option<Box<ext::Staging>>,
-    loader: Option<Box<Loader>>,
-    lexer: Option<Lexer>,
-    parser: Option<Parser>,
+    state: Mutex<State>,
+    loader: Option<Arc<Loader>>,
+    sandbox: Option<Sandbox>,
+    profiler: Option<Profiler>,
     // runtime: Option<Runtime>,
-    _function_callbacks: Vec<()>,
-    _filter_callbacks: Vec<()>,
-    _template_class_prefix: String, // default: '__TwigTemplate_'
-    _loaded_templates: Vec<()>,
+    template_class_prefix: String, // default: '__TwigTemplate_'
+    cache: Arc<RwLock<Cache>>,
+    precompiled: Option<Arc<template::Precompiled>>,
+}
+
+/// Set up on first use - and then shared by all threads loading templates.
+#[derive(Default, Debug)]
+struct State {
+    ext: Option<Arc<ExtensionRegistry>>,
+    ext_staging: Option<Box<Staging>>,
+    lexer: Option<Arc<Lexer>>,
+    parser: Option<Arc<Parser>>,
+    templates: Option<Arc<Templates>>,
 }
 
 
@@ -58,143 +77,355 @@ impl Engine {
         setup.engine()
     }
 
-    /// Renders a template.
+    /// Renders a template with the context, e.g. a `Runtime` or a `value::Map`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twig::{Engine, Setup};
+    /// use twig::loader::array::Array;
+    /// use twig::runtime::value::Map;
+    ///
+    /// let mut loader = Array::default();
+    /// loader.set_template("hello", "Hello {{ name }}!");
+    ///
+    /// let mut twig = Engine::new(Setup::default()).unwrap();
+    /// twig.set_loader(Box::new(loader));
+    ///
+    /// let mut context = Map::new();
+    /// context.insert("name", "World");
+    /// assert_eq!(twig.render("hello", context).unwrap(), "Hello World!");
+    /// ```
     ///
     /// # Failures
     /// * When the template cannot be found
     /// * When an error occurred during compilation
     /// * When an error occurred during rendering
-    pub fn render(&mut self, _path: &str, _data: ()) -> Result<String, Traced<TwigError>> {
-        unimplemented!()
-        // return Ok(try_traced!(self.load_template(path, None)).render(context));
+    pub fn render<C>(&self, path: &str, context: C) -> Result<String, Traced<TwigError>>
+        where C: Into<Runtime>
+    {
+        let template = try_traced!(self.load_template(path, None));
+
+        template.render(&context.into())
     }
 
-    /// Displays a template.
+    /// Displays a template with the context - i.e. writes it to `out`.
+    ///
+    /// See `render()`.
     ///
     /// # Failures
     /// * When the template cannot be found
     /// * When an error occurred during compilation
     /// * When an error occurred during rendering
-    pub fn display(&mut self, _path: &str, _data: ()) -> Result<(), Traced<TwigError>> {
-        unimplemented!()
-        // return Ok(try_traced!(self.load_template(path, None)).display(context, None));
+    /// * When writing to `out` fails
+    pub fn display<C, W>(&self, path: &str, context: C, out: &mut W) -> Result<(), Traced<TwigError>>
+        where C: Into<Runtime>,
+              W: Write
+    {
+        let template = try_traced!(self.load_template(path, None));
+
+        template.display(&context.into(), out)
     }
 
-    /// Loads and compiles a template.
+    /// Loads and compiles a template - or returns the cached template.
     ///
     /// # Failures
     /// * When the template cannot be found
     /// * When an error occurred during compilation
-    pub fn load_template(&mut self,
+    pub fn load_template(&self,
                          path: &str,
                          _index: Option<u32>)
-                         -> Result<template::Compiled, Traced<TwigError>> {
-        // TODO: Cache compiled templates
-        //  * cache lookup
-        //  * check if cache is fresh
-        //  * store in cache
+                         -> Result<Arc<template::Compiled>, Traced<TwigError>> {
+        let templates = try_traced!(self.templates());
 
-        let template_raw = try_traced!(self.load_template_raw(path));
-        return self.compile_template(&template_raw);
+        Templates::load(&templates, path, false)
     }
 
-    /// Loads raw template.
-    ///
-    /// # Failures
-    /// * When the template cannot be found
-    fn load_template_raw(&mut self, path: &str) -> Result<template::Raw, Traced<TwigError>> {
-        let loader = try_traced!(self.loader());
-        let source = try_traced!(loader.source(path));
-        Ok(template::Raw::new(source, path))
+    /// Clears the cache of compiled templates.
+    pub fn clear_template_cache(&self) {
+        self.cache.write().unwrap().clear()
     }
 
-    /// Compiles a template.
-    ///
-    /// # Failures
-    /// * When an error occurred during lexing or parsing.
-    fn compile_template(&mut self,
-                        template: &template::Raw)
-                        -> Result<template::Compiled, Traced<TwigError>> {
-        let tokenstream = {
-            let lexer = try_traced!(self.lexer());
-            try_traced!(lexer.tokenize(template))
-        };
+    /// Clears the template cache files in the cache directory.
+    pub fn clear_cache_files(&self) -> Result<(), Traced<TwigError>> {
+        self.cache.read().unwrap().clear_filecache()
+    }
 
-        let compiled = {
-            let parser = try_traced!(self.parser());
-            try_traced!(parser.parse(&tokenstream))
-        };
+    /// Removes the template `name` from the cache of compiled templates.
+    ///
+    /// Returns true if the template was cached.
+    pub fn evict_template(&self, name: &str) -> bool {
+        self.cache.write().unwrap().evict(name)
+    }
 
-        Ok(compiled)
+    /// Gets the engine options.
+    pub fn options(&self) -> &Options {
+        &self.options
     }
 
     /// Sets the engine extensions.
     pub fn set_extensions(&mut self, ext: ExtensionRegistry) -> &mut Engine {
-        self.ext = Some(Rc::new(ext)); // TODO: switch to callback pattern to provide arguments
+        // TODO: switch to callback pattern to provide arguments
+        self.state_mut().ext = Some(Arc::new(ext));
+        self.reset();
 
         self
     }
 
     /// Gets the engine extensions.
-    pub fn extensions(&self) -> Result<&Rc<ExtensionRegistry>, Traced<TwigError>> {
-        match self.ext {
-            Some(ref ext) => Ok(ext),
+    pub fn extensions(&self) -> Result<Arc<ExtensionRegistry>, Traced<TwigError>> {
+        match self.state().ext {
+            Some(ref ext) => Ok(ext.clone()),
             None => {
                 return traced_err!(TwigError::ExtensionRegistry(ExtensionRegistryError::NotInitialized))
             }
         }
     }
 
+    /// Registers a filter - e.g. a closure without writing an extension.
+    ///
+    /// # Failures
+    /// * When the engine already compiled templates
+    pub fn add_filter(&mut self, filter: SimpleFilter) -> Result<&mut Engine, Traced<TwigError>> {
+        try_traced!(self.staging()).add_filter(filter);
+
+        Ok(self)
+    }
+
+    /// Registers a function - e.g. a closure without writing an extension.
+    ///
+    /// # Failures
+    /// * When the engine already compiled templates
+    pub fn add_function(&mut self,
+                        function: SimpleFunction)
+                        -> Result<&mut Engine, Traced<TwigError>> {
+        try_traced!(self.staging()).add_function(function);
+
+        Ok(self)
+    }
+
+    /// Registers a test.
+    ///
+    /// # Failures
+    /// * When the engine already compiled templates
+    pub fn add_test(&mut self,
+                    name: &str,
+                    test: Box<Test>)
+                    -> Result<&mut Engine, Traced<TwigError>> {
+        try_traced!(self.staging()).add_test(name, test);
+
+        Ok(self)
+    }
+
+    /// Registers a token parser for its tag.
+    ///
+    /// # Failures
+    /// * When the engine already compiled templates
+    pub fn add_token_parser(&mut self,
+                            token_parser: Box<TokenParser>)
+                            -> Result<&mut Engine, Traced<TwigError>> {
+        try_traced!(self.staging()).add_token_parser(token_parser);
+
+        Ok(self)
+    }
+
+    /// Registers a global variable, which is visible in every template.
+    ///
+    /// # Failures
+    /// * When the engine already compiled templates
+    pub fn add_global(&mut self, global: Box<Global>) -> Result<&mut Engine, Traced<TwigError>> {
+        try_traced!(self.staging()).add_global(global);
+
+        Ok(self)
+    }
+
+    /// Gets the staging extension - until the engine compiles templates.
+    fn staging(&mut self) -> Result<&mut Staging, Traced<TwigError>> {
+        match self.state_mut().ext_staging {
+            Some(ref mut staging) => Ok(staging),
+            None => {
+                traced_err!(TwigError::ExtensionRegistry(ExtensionRegistryError::AlreadyInitialized))
+            }
+        }
+    }
+
+    /// Registers the staging extension with the other extensions.
+    ///
+    /// Called before the lexer or parser is created - they share the extensions.
+    fn init_staging(&self) -> Result<(), Traced<TwigError>> {
+        let mut state = self.state();
+        let state = &mut *state;
+        if state.ext_staging.is_none() {
+            return Ok(());
+        }
+
+        // the staging is kept until it can be registered - e.g. when the extensions
+        // are shared with a template that is still alive
+        let registry = match state.ext.as_mut().map(Arc::get_mut) {
+            Some(Some(registry)) => registry,
+            Some(None) => return traced_err!(ExtensionRegistryError::AlreadyInitialized.into()),
+            None => return traced_err!(ExtensionRegistryError::NotInitialized.into()),
+        };
+
+        if let Some(staging) = state.ext_staging.take() {
+            try_traced!(registry.push_initialized(staging));
+        }
+
+        Ok(())
+    }
+
+    /// Enables sandboxing - see `extension::Sandbox`.
+    pub fn set_sandbox(&mut self, sandbox: Sandbox) -> &mut Engine {
+        self.sandbox = Some(sandbox);
+
+        self
+    }
+
+    /// Gets the sandbox settings - if the sandbox extension is registered.
+    pub fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_ref()
+    }
+
+    /// Enables profiling - see `extension::Profiler`.
+    pub fn set_profiler(&mut self, profiler: Profiler) -> &mut Engine {
+        self.profiler = Some(profiler);
+
+        self
+    }
+
+    /// Gets the profiler - if the profiler extension is registered.
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// Sets the templates compiled to Rust code ahead of time, see `twig::build`.
+    ///
+    /// They take precedence over the templates of the loader - except sandboxed
+    /// templates, which are always compiled from source. Templates built with
+    /// other settings, e.g. another autoescape mode or other extensions, fail to
+    /// load.
+    pub fn set_precompiled(&mut self, precompiled: template::Precompiled) -> &mut Engine {
+        self.precompiled = Some(Arc::new(precompiled));
+        self.reset();
+
+        self
+    }
+
+    /// Gets the templates compiled to Rust code ahead of time.
+    pub fn precompiled(&self) -> Option<&template::Precompiled> {
+        self.precompiled.as_deref()
+    }
+
+    /// Gets the prefix of the functions generated for precompiled templates.
+    pub fn template_class_prefix(&self) -> &str {
+        &self.template_class_prefix
+    }
+
     /// Sets the loader instance.
     pub fn set_loader(&mut self, loader: Box<Loader>) -> &mut Engine {
-        self.loader = Some(loader); // TODO: switch to callback pattern to provide arguments
+        // TODO: switch to callback pattern to provide arguments
+        self.loader = Some(Arc::from(loader));
+        self.reset();
 
         self
     }
 
     /// Gets the loader instance.
-    pub fn loader(&mut self) -> Result<&mut Loader, Traced<TwigError>> {
+    pub fn loader(&self) -> Result<&Loader, Traced<TwigError>> {
         match self.loader {
-            Some(ref mut loader) => return Ok(&mut **loader), // dereferencing the Box<>
+            Some(ref loader) => return Ok(&**loader),
             None => return traced_err!(TwigError::LoaderNotInitialized),
         }
     }
 
     /// Sets the lexer instance.
     pub fn set_lexer(&mut self, lexer: Lexer) -> &mut Engine {
-        self.lexer = Some(lexer); // TODO: switch to callback pattern to provide arguments
+        // TODO: switch to callback pattern to provide arguments
+        self.state_mut().lexer = Some(Arc::new(lexer));
+        self.reset();
 
         self
     }
 
     /// Gets the lexer instance.
-    pub fn lexer(&mut self) -> Result<&Lexer, Traced<TwigError>> {
-        match self.lexer {
-            Some(ref lexer) => return Ok(lexer),
-            None => {
-                self.lexer = Some(try_traced!(Lexer::new(self, lexer::Options::default())));
-                return self.lexer();
-            }
+    pub fn lexer(&self) -> Result<Arc<Lexer>, Traced<TwigError>> {
+        try_traced!(self.templates());
+
+        match self.state().lexer {
+            Some(ref lexer) => Ok(lexer.clone()),
+            None => traced_err!(TwigError::LexerNotInitialized),
         }
     }
 
     /// Sets the parser instance.
     pub fn set_parser(&mut self, parser: Parser) -> &mut Engine {
-        self.parser = Some(parser); // TODO: switch to callback pattern to provide arguments
+        // TODO: switch to callback pattern to provide arguments
+        self.state_mut().parser = Some(Arc::new(parser));
+        self.reset();
 
         self
     }
 
     /// Gets the parser instance.
-    pub fn parser(&mut self) -> Result<&Parser, Traced<TwigError>> {
-        match self.parser {
-            Some(ref parser) => return Ok(parser),
-            None => {
-                self.parser = Some(try_traced!(Parser::new(&self)));
+    pub fn parser(&self) -> Result<Arc<Parser>, Traced<TwigError>> {
+        try_traced!(self.templates());
 
-                return self.parser();
-            }
+        match self.state().parser {
+            Some(ref parser) => Ok(parser.clone()),
+            None => traced_err!(TwigError::ParserNotInitialized),
+        }
+    }
+
+    /// Gets access to the loader, lexer and parser for running templates.
+    ///
+    /// Sets up the lexer and parser on first use.
+    pub fn templates(&self) -> Result<Arc<Templates>, Traced<TwigError>> {
+        if let Some(ref templates) = self.state().templates {
+            return Ok(templates.clone());
         }
+
+        try_traced!(self.init_staging());
+        // the lock is released while the lexer and parser ask for the extensions
+        let (lexer, parser) = {
+            let state = self.state();
+            (state.lexer.clone(), state.parser.clone())
+        };
+        let lexer = match lexer {
+            Some(lexer) => lexer,
+            None => Arc::new(try_traced!(Lexer::new(self, lexer::Options::default()))),
+        };
+        let parser = match parser {
+            Some(parser) => parser,
+            None => Arc::new(try_traced!(Parser::new(self))),
+        };
+
+        // another thread might have been faster
+        let mut state = self.state();
+        let state = &mut *state;
+        let lexer = state.lexer.get_or_insert(lexer).clone();
+        let parser = state.parser.get_or_insert(parser).clone();
+        let templates = state.templates.get_or_insert_with(|| {
+            Arc::new(Templates::new(self.loader.clone(),
+                                    lexer,
+                                    parser,
+                                    Arc::downgrade(&self.cache),
+                                    self.precompiled.clone()))
+        });
+
+        Ok(templates.clone())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn state_mut(&mut self) -> &mut State {
+        self.state.get_mut().unwrap()
+    }
+
+    /// Discards the shared templates - after the loader, lexer or parser changed.
+    fn reset(&mut self) {
+        self.state_mut().templates = None;
     }
 
     // /// Sets the runtime instance.
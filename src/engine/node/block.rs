@@ -0,0 +1,87 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Block Node.
+
+use super::GenericNode;
+use engine::Node;
+use engine::parser::token::stream::Position;
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
+
+/// Definition of the block `name` - the children are the body.
+///
+/// orig: Twig_Node_Block
+pub type Block = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    name: String,
+}
+
+impl Block {
+    pub fn boxed(name: String, body: Vec<Box<Node>>, position: &Position) -> Box<Block> {
+        Box::new(Block {
+            data: Data { name: name },
+            position: (*position).clone(),
+            nodes: body,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+}
+
+impl Execute for Block {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        for node in &self.nodes {
+            try_traced!(node.execute(runtime, job));
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the block `name` - the first definition in the inheritance chain,
+/// starting with the template at `from`, see `Job::frame()`.
+pub fn display(name: &str,
+               from: usize,
+               runtime: &Runtime,
+               job: &mut Job)
+               -> Result<(), Traced<RuntimeError>> {
+    let frame = match job.frame() {
+        Some(frame) => frame.clone(),
+        None => {
+            return traced_err!(RuntimeError::Unreachable {
+                reason: format!("The block {:?} was displayed outside of a template", name),
+            })
+        }
+    };
+
+    let index = match frame.find_block(name, from) {
+        Some(index) => index,
+        None => {
+            let template = frame.chain().get(from).unwrap_or(frame.module());
+            return traced_err!(RuntimeError::UnknownBlock {
+                name: name.to_string(),
+                template: template.filename().to_string(),
+            });
+        }
+    };
+
+    let frame = frame.block_at(index, name);
+    let module = frame.module().clone();
+
+    job.push_frame(frame);
+    let result = match module.block(name) {
+        Some(block) => block.execute(runtime, job),
+        None => Ok(()),
+    };
+    job.pop_frame();
+
+    result
+}
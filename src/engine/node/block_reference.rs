@@ -0,0 +1,41 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Block Reference Node.
+
+use super::{GenericNode, block};
+use engine::parser::token::stream::Position;
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
+
+/// Displays the block `name` where it is defined, e.g. `{% block content %}`.
+///
+/// orig: Twig_Node_BlockReference
+pub type BlockReference = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    name: String,
+}
+
+impl BlockReference {
+    pub fn boxed(name: String, position: &Position) -> Box<BlockReference> {
+        Box::new(BlockReference {
+            data: Data { name: name },
+            position: (*position).clone(),
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+}
+
+impl Execute for BlockReference {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        block::display(&self.data.name, 0, runtime, job)
+    }
+}
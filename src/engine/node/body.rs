@@ -6,7 +6,8 @@
 //! Root node of the template body.
 
 use super::GenericNode;
-use runtime::{Runtime, Execute, Job};
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
 use engine::Node;
 
 pub type Body = GenericNode<Data>;
@@ -21,10 +22,12 @@ impl Body {
 }
 
 impl Execute for Body {
-    fn execute(&self, runtime: &Runtime, job: &mut Job) {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
         for node in &self.nodes {
-            node.execute(runtime, job)
+            try_traced!(node.execute(runtime, job));
         }
+
+        Ok(())
     }
 }
 
@@ -0,0 +1,682 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Serialization of parsed templates, e.g. for the filesystem cache.
+//!
+//! Modules are encoded as parsed, i.e. *before* the node visitors are applied.
+//! Nodes with runtime state can't be encoded - like sandbox sections, profiles
+//! or closures and dates - these templates are always compiled from source.
+//!
+//! The format is a sequence of integers `42;` and length prefixed strings
+//! `5:hello`. It is only guaranteed to be readable by the same crate version.
+
+use std::collections::HashMap;
+use engine::Node;
+use engine::node::{attributes, GenericNode, NodeDataAttibutes, Module, Body, Virtual, Text, Print, Include,
+                   Block, BlockReference, Macro, For};
+use engine::node::expression::{self, Name, Escape, Constant, Filter, Function, Array, Arrow,
+                               GetAttr, Binary, Unary, Parent, MethodCall};
+use engine::node::expression::get_attr;
+use engine::parser::Parser;
+use engine::parser::token::stream::Position;
+use runtime::Value;
+use runtime::value::{Map, Key};
+
+/// Version of the format - bump it on incompatible changes.
+pub const VERSION: u32 = 3;
+
+/// Returns the encoded module - or None if it contains nodes which can't be encoded.
+pub fn encode(module: &Module) -> Option<String> {
+    let mut encoder = Encoder { out: String::new() };
+    encoder.str(module.filename());
+
+    match module.parent() {
+        Some(parent) => {
+            encoder.bool(true);
+            if encoder.node(parent).is_err() {
+                return None;
+            }
+        }
+        None => encoder.bool(false),
+    }
+
+    // the body, the blocks and the macros
+    encoder.int(module.children().len() as i64);
+    for child in module.children() {
+        if encoder.node(&**child).is_err() {
+            return None;
+        }
+    }
+
+    Some(encoder.out)
+}
+
+/// Returns the decoded module - or None if the data is invalid.
+///
+/// Custom escaping strategies are resolved with the parser.
+pub fn decode(data: &str, parser: &Parser) -> Option<Module> {
+    let mut decoder = Decoder {
+        data: data,
+        parser: parser,
+    };
+
+    decoder.module().ok()
+}
+
+struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    fn int(&mut self, value: i64) {
+        self.out.push_str(&value.to_string());
+        self.out.push(';');
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.int(value as i64)
+    }
+
+    fn str(&mut self, value: &str) {
+        self.out.push_str(&value.len().to_string());
+        self.out.push(':');
+        self.out.push_str(value);
+    }
+
+    /// Writes the kind, the common fields, the kind specific data and the children.
+    fn node(&mut self, node: &Node) -> Result<(), ()> {
+        let any = node.as_any();
+
+        self.str(try!(kind(node)));
+        self.int(node.position().line as i64);
+        self.int(node.position().column as i64);
+        self.str(node.tag());
+
+        let attributes = try!(attributes(node).ok_or(()));
+        self.int(attributes.len() as i64);
+        for (key, value) in attributes {
+            self.str(key);
+            self.str(value);
+        }
+
+        if let Some(text) = any.downcast_ref::<Text>() {
+            self.str(text.text());
+        } else if let Some(include) = any.downcast_ref::<Include>() {
+            self.bool(include.ignore_missing());
+            self.bool(include.only());
+        } else if let Some(block) = any.downcast_ref::<Block>() {
+            self.str(block.name());
+        } else if let Some(block) = any.downcast_ref::<BlockReference>() {
+            self.str(block.name());
+        } else if let Some(macro_) = any.downcast_ref::<Macro>() {
+            self.str(macro_.name());
+        } else if let Some(for_) = any.downcast_ref::<For>() {
+            self.str(for_.key_target());
+            self.str(for_.value_target());
+        } else if let Some(parent) = any.downcast_ref::<Parent>() {
+            self.str(parent.name());
+        } else if let Some(call) = any.downcast_ref::<MethodCall>() {
+            self.str(call.method());
+        } else if let Some(name) = any.downcast_ref::<Name>() {
+            self.str(name.key());
+        } else if let Some(escape) = any.downcast_ref::<Escape>() {
+            self.str(escape.strategy().name());
+        } else if let Some(constant) = any.downcast_ref::<Constant>() {
+            try!(self.value(constant.value()));
+        } else if let Some(filter) = any.downcast_ref::<Filter>() {
+            self.str(filter.name());
+        } else if let Some(function) = any.downcast_ref::<Function>() {
+            self.str(function.name());
+        } else if let Some(array) = any.downcast_ref::<Array>() {
+            self.bool(array.is_hash());
+        } else if let Some(arrow) = any.downcast_ref::<Arrow>() {
+            self.int(arrow.params().len() as i64);
+            for param in arrow.params() {
+                self.str(param);
+            }
+            try!(self.node(arrow.body()));
+        } else if let Some(binary) = any.downcast_ref::<Binary>() {
+            self.str(binary.operator());
+        } else if let Some(unary) = any.downcast_ref::<Unary>() {
+            self.str(unary.operator());
+        } else if let Some(get_attr) = any.downcast_ref::<GetAttr>() {
+            self.int(match get_attr.kind() {
+                get_attr::Kind::Any => 0,
+                get_attr::Kind::Array => 1,
+                get_attr::Kind::Method => 2,
+            });
+        }
+
+        self.int(node.children().len() as i64);
+        for child in node.children() {
+            try!(self.node(&**child));
+        }
+
+        Ok(())
+    }
+
+    fn value(&mut self, value: &Value) -> Result<(), ()> {
+        match *value {
+            Value::Null => self.str("null"),
+            Value::Boolean(x) => {
+                self.str("bool");
+                self.bool(x);
+            }
+            Value::Integer(x) => {
+                self.str("int");
+                self.int(x);
+            }
+            Value::Float(x) => {
+                self.str("float");
+                self.int(x.to_bits() as i64);
+            }
+            Value::String(ref x) => {
+                self.str("string");
+                self.str(x);
+            }
+            Value::Markup(ref x, ref safe) => {
+                self.str("markup");
+                self.str(x);
+                self.str(safe);
+            }
+            Value::Sequence(ref x) => {
+                self.str("sequence");
+                self.int(x.len() as i64);
+                for value in x {
+                    try!(self.value(value));
+                }
+            }
+            Value::Map(ref x) => {
+                self.str("map");
+                self.int(x.len() as i64);
+                for &(ref key, ref value) in x.iter() {
+                    match *key {
+                        Key::Integer(i) => {
+                            self.str("int");
+                            self.int(i);
+                        }
+                        Key::String(ref s) => {
+                            self.str("string");
+                            self.str(s);
+                        }
+                    }
+                    try!(self.value(value));
+                }
+            }
+            Value::Closure(..) | Value::Date(..) => return Err(()),
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the name of the node type.
+fn kind(node: &Node) -> Result<&'static str, ()> {
+    let any = node.as_any();
+
+    Ok(if any.is::<Body>() {
+        "body"
+    } else if any.is::<Virtual>() {
+        "virtual"
+    } else if any.is::<Text>() {
+        "text"
+    } else if any.is::<Print>() {
+        "print"
+    } else if any.is::<Include>() {
+        "include"
+    } else if any.is::<Block>() {
+        "block"
+    } else if any.is::<BlockReference>() {
+        "block_reference"
+    } else if any.is::<Macro>() {
+        "macro"
+    } else if any.is::<For>() {
+        "for"
+    } else if any.is::<Name>() {
+        "name"
+    } else if any.is::<Escape>() {
+        "escape"
+    } else if any.is::<Constant>() {
+        "constant"
+    } else if any.is::<Filter>() {
+        "filter"
+    } else if any.is::<Function>() {
+        "function"
+    } else if any.is::<Array>() {
+        "array"
+    } else if any.is::<Arrow>() {
+        "arrow"
+    } else if any.is::<GetAttr>() {
+        "get_attr"
+    } else if any.is::<Binary>() {
+        "binary"
+    } else if any.is::<Unary>() {
+        "unary"
+    } else if any.is::<expression::BlockReference>() {
+        "block_function"
+    } else if any.is::<Parent>() {
+        "parent"
+    } else if any.is::<MethodCall>() {
+        "method_call"
+    } else {
+        return Err(());
+    })
+}
+
+struct Decoder<'a> {
+    data: &'a str,
+    parser: &'a Parser,
+}
+
+impl<'a> Decoder<'a> {
+    fn int(&mut self) -> Result<i64, ()> {
+        let end = try!(self.data.find(';').ok_or(()));
+        let value = try!(self.data[..end].parse::<i64>().map_err(|_| ()));
+        self.data = &self.data[end + 1..];
+
+        Ok(value)
+    }
+
+    fn len(&mut self) -> Result<usize, ()> {
+        let value = try!(self.int());
+        if value < 0 {
+            return Err(());
+        }
+
+        Ok(value as usize)
+    }
+
+    fn bool(&mut self) -> Result<bool, ()> {
+        Ok(try!(self.int()) != 0)
+    }
+
+    fn str(&mut self) -> Result<&'a str, ()> {
+        let colon = try!(self.data.find(':').ok_or(()));
+        let len = try!(self.data[..colon].parse::<usize>().map_err(|_| ()));
+        let data = &self.data[colon + 1..];
+        let value = try!(data.get(..len).ok_or(()));
+        self.data = &data[len..];
+
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String, ()> {
+        self.str().map(|x| x.to_string())
+    }
+
+    fn module(&mut self) -> Result<Module, ()> {
+        let filename = try!(self.str());
+        let parent = if try!(self.bool()) {
+            Some(try!(self.node()))
+        } else {
+            None
+        };
+
+        let mut children = try!(self.children()).into_iter().map(body);
+        let body = try!(try!(children.next().ok_or(())));
+        let blocks = try!(try!(children.next().ok_or(())));
+        let macros = try!(try!(children.next().ok_or(())));
+
+        if !self.data.is_empty() || children.next().is_some() {
+            return Err(());
+        }
+
+        Ok(Module::new(body, parent, blocks.nodes, macros.nodes, vec![], vec![], filename))
+    }
+
+    fn node(&mut self) -> Result<Box<Node>, ()> {
+        let kind = try!(self.str());
+        let position = Position {
+            line: try!(self.len()),
+            column: try!(self.len()),
+        };
+        let tag = try!(self.string());
+
+        let mut attributes = HashMap::new();
+        for _ in 0..try!(self.len()) {
+            let key = try!(self.string());
+            attributes.insert(key, try!(self.string()));
+        }
+
+        let node: Box<Node> = match kind {
+            "body" => {
+                let children = try!(self.children());
+                restore(Body::boxed(children), tag, position, attributes)
+            }
+            "virtual" => {
+                let mut node = Virtual::boxed(&position);
+                node.nodes = try!(self.children());
+                restore(node, tag, position, attributes)
+            }
+            "text" => {
+                let text = try!(self.string());
+                try!(self.no_children());
+                restore(Text::boxed(text, &position), tag, position, attributes)
+            }
+            "print" => {
+                let mut children = try!(self.children());
+                let expr = try!(first(&mut children));
+                restore(Print::boxed(expr, &position), tag, position, attributes)
+            }
+            "include" => {
+                let ignore_missing = try!(self.bool());
+                let only = try!(self.bool());
+                let mut children = try!(self.children());
+                let name = try!(first(&mut children));
+                let variables = first(&mut children).ok();
+                restore(Include::boxed(name, variables, ignore_missing, only, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "block" => {
+                let name = try!(self.string());
+                let children = try!(self.children());
+                restore(Block::boxed(name, children, &position), tag, position, attributes)
+            }
+            "block_reference" => {
+                let name = try!(self.string());
+                try!(self.no_children());
+                restore(BlockReference::boxed(name, &position), tag, position, attributes)
+            }
+            "macro" => {
+                let name = try!(self.string());
+                let mut children = try!(self.children());
+                let arguments = try!(first(&mut children));
+                let body = try!(first(&mut children).and_then(body));
+                restore(Macro::boxed(name, arguments, body.nodes, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "for" => {
+                let key_target = try!(self.string());
+                let value_target = try!(self.string());
+                let mut children = try!(self.children());
+                let seq = try!(first(&mut children));
+                let body = try!(first(&mut children).and_then(body));
+                let else_ = try!(first(&mut children).and_then(self::body));
+                let condition = first(&mut children).ok();
+                restore(For::boxed(key_target,
+                                   value_target,
+                                   seq,
+                                   condition,
+                                   body.nodes,
+                                   else_.nodes,
+                                   &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "name" => {
+                let key = try!(self.string());
+                try!(self.no_children());
+                restore(Name::boxed(key, &position), tag, position, attributes)
+            }
+            "escape" => {
+                let strategy = try!(self.parser.strategy(try!(self.str())).ok_or(()));
+                let mut children = try!(self.children());
+                let expr = try!(first(&mut children));
+                restore(Escape::boxed(expr, strategy, &position), tag, position, attributes)
+            }
+            "constant" => {
+                let value = try!(self.value());
+                try!(self.no_children());
+                restore(Constant::boxed(value, &position), tag, position, attributes)
+            }
+            "filter" => {
+                let name = try!(self.string());
+                let mut children = try!(self.children());
+                let input = try!(first(&mut children));
+                restore(Filter::boxed(name, input, children, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "function" => {
+                let name = try!(self.string());
+                let args = try!(self.children());
+                restore(Function::boxed(name, args, &position), tag, position, attributes)
+            }
+            "array" => {
+                let node = if try!(self.bool()) {
+                    Array::hash(try!(self.children()), &position)
+                } else {
+                    Array::sequence(try!(self.children()), &position)
+                };
+                restore(node, tag, position, attributes)
+            }
+            "arrow" => {
+                let mut params = Vec::new();
+                for _ in 0..try!(self.len()) {
+                    params.push(try!(self.string()));
+                }
+                let body = try!(self.node());
+                try!(self.no_children());
+                restore(Arrow::boxed(params, body, &position), tag, position, attributes)
+            }
+            "get_attr" => {
+                let kind = match try!(self.int()) {
+                    0 => get_attr::Kind::Any,
+                    1 => get_attr::Kind::Array,
+                    2 => get_attr::Kind::Method,
+                    _ => return Err(()),
+                };
+                let mut children = try!(self.children());
+                let object = try!(first(&mut children));
+                let attribute = try!(first(&mut children));
+                restore(GetAttr::boxed(object, attribute, children, kind, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "binary" => {
+                let operator = try!(self.string());
+                let mut children = try!(self.children());
+                let left = try!(first(&mut children));
+                let right = try!(first(&mut children));
+                restore(Binary::boxed(operator, left, right, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "unary" => {
+                let operator = try!(self.string());
+                let mut children = try!(self.children());
+                let operand = try!(first(&mut children));
+                restore(Unary::boxed(operator, operand, &position), tag, position, attributes)
+            }
+            "block_function" => {
+                let mut children = try!(self.children());
+                let name = try!(first(&mut children));
+                restore(expression::BlockReference::boxed(name, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            "parent" => {
+                let name = try!(self.string());
+                try!(self.no_children());
+                restore(Parent::boxed(name, &position), tag, position, attributes)
+            }
+            "method_call" => {
+                let method = try!(self.string());
+                let mut children = try!(self.children());
+                let template = try!(first(&mut children));
+                restore(MethodCall::boxed(template, method, children, &position),
+                        tag,
+                        position,
+                        attributes)
+            }
+            _ => return Err(()),
+        };
+
+        Ok(node)
+    }
+
+    fn children(&mut self) -> Result<Vec<Box<Node>>, ()> {
+        let len = try!(self.len());
+        let mut children = Vec::with_capacity(len);
+        for _ in 0..len {
+            children.push(try!(self.node()));
+        }
+
+        Ok(children)
+    }
+
+    fn no_children(&mut self) -> Result<(), ()> {
+        match try!(self.len()) {
+            0 => Ok(()),
+            _ => Err(()),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ()> {
+        Ok(match try!(self.str()) {
+            "null" => Value::Null,
+            "bool" => Value::Boolean(try!(self.bool())),
+            "int" => Value::Integer(try!(self.int())),
+            "float" => Value::Float(f64::from_bits(try!(self.int()) as u64)),
+            "string" => Value::String(try!(self.string())),
+            "markup" => Value::Markup(try!(self.string()), try!(self.string())),
+            "sequence" => {
+                let mut values = Vec::new();
+                for _ in 0..try!(self.len()) {
+                    values.push(try!(self.value()));
+                }
+                Value::Sequence(values)
+            }
+            "map" => {
+                let mut map = Map::new();
+                for _ in 0..try!(self.len()) {
+                    let key = match try!(self.str()) {
+                        "int" => Key::Integer(try!(self.int())),
+                        "string" => Key::String(try!(self.string())),
+                        _ => return Err(()),
+                    };
+                    map.insert(key, try!(self.value()));
+                }
+                Value::Map(map)
+            }
+            _ => return Err(()),
+        })
+    }
+}
+
+/// Restores the common fields, which the constructors don't take.
+fn restore<T>(mut node: Box<GenericNode<T>>,
+              tag: String,
+              position: Position,
+              attributes: NodeDataAttibutes)
+              -> Box<Node>
+    where T: 'static,
+          GenericNode<T>: Node
+{
+    node.tag = tag;
+    node.position = position;
+    node.attributes = attributes;
+
+    node
+}
+
+fn body(node: Box<Node>) -> Result<Box<Body>, ()> {
+    ::engine::node::downcast::<Body>(node).map_err(|_| ())
+}
+
+fn first(children: &mut Vec<Box<Node>>) -> Result<Box<Node>, ()> {
+    if children.is_empty() {
+        return Err(());
+    }
+
+    Ok(children.remove(0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use engine::{Engine, Setup};
+    use engine::parser::{Parser, Lexer};
+    use engine::parser::lexer;
+    use runtime::{Runtime, Value};
+    use template;
+    use template::api::Template;
+
+    fn parse(parser: &Parser, source: &str) -> Module {
+        parse_with(&Lexer::default(), parser, source)
+    }
+
+    fn parse_with(lexer: &Lexer, parser: &Parser, source: &str) -> Module {
+        let template = template::Raw::new(source, "test.html");
+        let stream = lexer.tokenize(&template).unwrap();
+
+        parser.parse_module(&stream).unwrap()
+    }
+
+    #[test]
+    pub fn roundtrip() {
+        let engine = Engine::new(Setup::default()).unwrap();
+        let lexer = Lexer::new(&engine, lexer::Options::default()).unwrap();
+        let parser = Parser::new(&engine).unwrap();
+        let source = "Hi {{ name|upper }}! {{ {a: [1, 2.5, true, null]}|json_encode }} \
+                      {{ user.name }} {{ ['a', 'b']|map(v => v|upper)|join(',') }} \
+                      {{ 1 + 2 * 3 }} {{ -(1 + 1) }}{{ not false }}";
+        let module = parse_with(&lexer, &parser, source);
+        let encoded = encode(&module).unwrap();
+        let decoded = decode(&encoded, &parser).unwrap();
+        assert_eq!(encode(&decoded).unwrap(), encoded);
+
+        let mut runtime = Runtime::default();
+        runtime.set("name", "<b>");
+        runtime.set("user", Value::Map(vec![("name", "Jane")].into_iter().collect()));
+        let expected = parser.compile_module(module).unwrap().render(&runtime).unwrap();
+        let actual = parser.compile_module(decoded).unwrap().render(&runtime).unwrap();
+
+        assert_eq!(actual,
+                   "Hi &lt;B&gt;! {&quot;a&quot;:[1,2.5,true,null]} Jane A,B 7 -21");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    pub fn include() {
+        let engine = Engine::new(Setup::default()).unwrap();
+        let parser = Parser::new(&engine).unwrap();
+        let module = parse(&parser, "{% include 'x' ignore missing with {b: 1} only %}");
+        let encoded = encode(&module).unwrap();
+
+        assert_eq!(encode(&decode(&encoded, &parser).unwrap()).unwrap(), encoded);
+    }
+
+    #[test]
+    pub fn blocks_and_macros() {
+        let engine = Engine::new(Setup::default()).unwrap();
+        let parser = Parser::new(&engine).unwrap();
+        let module = parse(&parser,
+                           "{% extends 'layout.html' %}{% import _self as forms %}\
+                            {% macro input(name, type = 'text') %}{{ name }}{% endmacro %}\
+                            {% block title %}{{ parent() }}{{ block('x') }}{% endblock %}\
+                            {% block x forms.input('a') %}\
+                            {% block y %}{% for k, v in [1] if v %}{{ loop.index }}\
+                            {% else %}-{% endfor %}{% endblock %}");
+        let encoded = encode(&module).unwrap();
+        let decoded = decode(&encoded, &parser).unwrap();
+
+        assert_eq!(encode(&decoded).unwrap(), encoded);
+        assert!(decoded.parent().is_some());
+        assert_eq!(decoded.blocks().len(), 3);
+        assert!(decoded.block("title").is_some());
+        assert!(decoded.macro_("input").is_some());
+    }
+
+    #[test]
+    pub fn invalid() {
+        let parser = Parser::default();
+        let encoded = encode(&parse(&parser, "Hello {{ name }}")).unwrap();
+
+        assert!(decode(&encoded, &parser).is_some());
+        assert!(decode(&encoded[..encoded.len() - 1], &parser).is_none());
+        assert!(decode(&format!("{}1;", encoded), &parser).is_none());
+        assert!(decode("", &parser).is_none());
+    }
+}
@@ -0,0 +1,387 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Generation of Rust code for parsed templates, see `twig::build`.
+//!
+//! orig: Twig_Compiler
+//!
+//! The generated code builds the module with the public node constructors -
+//! like `codec`, it takes the module *before* the node visitors are applied.
+//! Sandbox sections and nodes of custom extensions can't be compiled to Rust.
+
+use engine::Node;
+use engine::node::{attributes, Module, Body, Virtual, Text, Print, Include, Sandbox, Block,
+                   BlockReference, Macro, For};
+use engine::node::expression::{self, Name, Escape, Constant, Filter, Function, Array, Arrow,
+                               GetAttr, Binary, Unary, Parent, MethodCall};
+use engine::node::expression::get_attr;
+use engine::parser::NodeError;
+use engine::template_cache;
+use extension::escaper::Strategy;
+use runtime::Value;
+use runtime::value::Key;
+use api::error::Traced;
+
+/// Imports of the generated code - local to the block of the module.
+const IMPORTS: &'static str = "use ::twig::engine::node::{self as n, expression as e};
+    use ::twig::template::precompiled::{node, position as at};
+    #[allow(unused_imports)]
+    use ::twig::runtime::Value as V;
+    #[allow(unused_imports)]
+    use ::twig::runtime::value::Key as K;
+    #[allow(unused_imports)]
+    use ::twig::extension::escaper::Strategy as S;";
+
+/// Returns a Rust block expression, which builds the module.
+///
+/// # Failures
+/// * When the module contains nodes which can't be compiled to Rust
+pub fn generate(module: &Module) -> Result<String, Traced<NodeError>> {
+    let generator = Generator { template: module.filename() };
+    let body = match module.children().first() {
+        Some(body) => body,
+        None => return generator.error(module, "the module has no body"),
+    };
+
+    let body = match body.as_any().downcast_ref::<Body>() {
+        Some(body) => try_traced!(generator.body(body, 1)),
+        None => return generator.error(&**body, "the module body is no `node::Body`"),
+    };
+
+    let parent = match module.parent() {
+        Some(parent) => format!("Some({})", try_traced!(generator.node(parent, 1))),
+        None => "None".to_string(),
+    };
+
+    Ok(format!("{{\n    {imports}\n\n    n::Module::new({body},\n{indent}{parent},\n\
+                {indent}{blocks},\n{indent}{macros},\n{indent}vec![], vec![], {name:?})\n}}",
+               indent = " ".repeat(19),
+               imports = IMPORTS,
+               body = body,
+               parent = parent,
+               blocks = try_traced!(generator.nodes(module.blocks(), 1)),
+               macros = try_traced!(generator.nodes(module.macros(), 1)),
+               name = module.filename()))
+}
+
+/// Returns the name of the generated function for the template `name`, e.g.
+/// `__TwigTemplate_8f14e45fceea167a`.
+pub fn function_name(prefix: &str, name: &str) -> String {
+    format!("{}{:016x}", prefix, template_cache::hash(name))
+}
+
+struct Generator<'a> {
+    template: &'a str,
+}
+
+impl<'a> Generator<'a> {
+    fn error<T>(&self, node: &Node, reason: &str) -> Result<T, Traced<NodeError>> {
+        traced_err!(NodeError::NotCompilable {
+            template: self.template.to_string(),
+            position: node.position().clone(),
+            reason: reason.to_string(),
+        })
+    }
+
+    fn body(&self, body: &Body, indent: usize) -> Result<String, Traced<NodeError>> {
+        let children = try_traced!(self.nodes(body.children(), indent));
+
+        Ok(format!("n::Body::boxed({})", children))
+    }
+
+    /// Returns the expression of the node as `Box<Node>`.
+    fn node(&self, node: &Node, indent: usize) -> Result<String, Traced<NodeError>> {
+        let any = node.as_any();
+        let position = format!("&at({}, {})", node.position().line, node.position().column);
+        let children = node.children();
+        // children which are passed to the constructor
+        let mut taken = 0;
+
+        if !node.tag().is_empty() {
+            return self.error(node, "nodes with tags are not supported");
+        }
+
+        let constructor = if let Some(body) = any.downcast_ref::<Body>() {
+            taken = children.len();
+            try_traced!(self.body(body, indent))
+        } else if any.is::<Virtual>() {
+            format!("n::Virtual::boxed({})", position)
+        } else if let Some(text) = any.downcast_ref::<Text>() {
+            format!("n::Text::boxed({:?}.to_string(), {})", text.text(), position)
+        } else if any.is::<Print>() {
+            taken = 1;
+            format!("n::Print::boxed({}, {})",
+                    try_traced!(self.child(node, 0, indent)),
+                    position)
+        } else if let Some(include) = any.downcast_ref::<Include>() {
+            taken = children.len().min(2);
+            let variables = match children.get(1) {
+                Some(variables) => format!("Some({})", try_traced!(self.node(&**variables, indent))),
+                None => "None".to_string(),
+            };
+            format!("n::Include::boxed({}, {}, {}, {}, {})",
+                    try_traced!(self.child(node, 0, indent)),
+                    variables,
+                    include.ignore_missing(),
+                    include.only(),
+                    position)
+        } else if let Some(block) = any.downcast_ref::<Block>() {
+            taken = children.len();
+            format!("n::Block::boxed({:?}.to_string(), {}, {})",
+                    block.name(),
+                    try_traced!(self.nodes(children, indent)),
+                    position)
+        } else if let Some(block) = any.downcast_ref::<BlockReference>() {
+            format!("n::BlockReference::boxed({:?}.to_string(), {})", block.name(), position)
+        } else if let Some(macro_) = any.downcast_ref::<Macro>() {
+            taken = 2;
+            let body = match children.get(1) {
+                Some(body) if body.as_any().is::<Body>() => body,
+                _ => return self.error(node, "the macro body is no `node::Body`"),
+            };
+            format!("n::Macro::boxed({:?}.to_string(), {}, {}, {})",
+                    macro_.name(),
+                    try_traced!(self.child(node, 0, indent)),
+                    try_traced!(self.nodes(body.children(), indent)),
+                    position)
+        } else if let Some(for_) = any.downcast_ref::<For>() {
+            taken = children.len();
+            let (body, else_) = match (children.get(1), children.get(2)) {
+                (Some(body), Some(else_)) if body.as_any().is::<Body>() &&
+                                             else_.as_any().is::<Body>() => (body, else_),
+                _ => return self.error(node, "the loop bodies are no `node::Body`"),
+            };
+            let condition = match children.get(3) {
+                Some(condition) => format!("Some({})", try_traced!(self.node(&**condition, indent))),
+                None => "None".to_string(),
+            };
+            format!("n::For::boxed({:?}.to_string(), {:?}.to_string(), {}, {}, {}, {}, {})",
+                    for_.key_target(),
+                    for_.value_target(),
+                    try_traced!(self.child(node, 0, indent)),
+                    condition,
+                    try_traced!(self.nodes(body.children(), indent)),
+                    try_traced!(self.nodes(else_.children(), indent)),
+                    position)
+        } else if let Some(name) = any.downcast_ref::<Name>() {
+            format!("e::Name::boxed({:?}.to_string(), {})", name.key(), position)
+        } else if let Some(escape) = any.downcast_ref::<Escape>() {
+            taken = 1;
+            let strategy = match *escape.strategy() {
+                Strategy::Html => "Html",
+                Strategy::Js => "Js",
+                Strategy::Css => "Css",
+                Strategy::Url => "Url",
+                Strategy::HtmlAttr => "HtmlAttr",
+                Strategy::Custom(..) => {
+                    return self.error(node, "custom escaping strategies are not supported")
+                }
+            };
+            format!("e::Escape::boxed({}, S::{}, {})",
+                    try_traced!(self.child(node, 0, indent)),
+                    strategy,
+                    position)
+        } else if let Some(constant) = any.downcast_ref::<Constant>() {
+            let value = match value(constant.value()) {
+                Some(value) => value,
+                None => return self.error(node, "closures and dates are not supported"),
+            };
+            format!("e::Constant::boxed({}, {})", value, position)
+        } else if let Some(filter) = any.downcast_ref::<Filter>() {
+            taken = children.len();
+            format!("e::Filter::boxed({:?}.to_string(), {}, {}, {})",
+                    filter.name(),
+                    try_traced!(self.child(node, 0, indent)),
+                    try_traced!(self.nodes(children.get(1..).unwrap_or(&[]), indent)),
+                    position)
+        } else if let Some(function) = any.downcast_ref::<Function>() {
+            taken = children.len();
+            format!("e::Function::boxed({:?}.to_string(), {}, {})",
+                    function.name(),
+                    try_traced!(self.nodes(children, indent)),
+                    position)
+        } else if let Some(array) = any.downcast_ref::<Array>() {
+            taken = children.len();
+            format!("e::Array::{}({}, {})",
+                    if array.is_hash() { "hash" } else { "sequence" },
+                    try_traced!(self.nodes(children, indent)),
+                    position)
+        } else if let Some(arrow) = any.downcast_ref::<Arrow>() {
+            let params: Vec<String> = arrow.params()
+                                           .iter()
+                                           .map(|x| format!("{:?}.to_string()", x))
+                                           .collect();
+            format!("e::Arrow::boxed(vec![{}], {}, {})",
+                    params.join(", "),
+                    try_traced!(self.node(arrow.body(), indent)),
+                    position)
+        } else if let Some(get_attr) = any.downcast_ref::<GetAttr>() {
+            taken = children.len();
+            let kind = match get_attr.kind() {
+                get_attr::Kind::Any => "Any",
+                get_attr::Kind::Array => "Array",
+                get_attr::Kind::Method => "Method",
+            };
+            format!("e::GetAttr::boxed({}, {}, {}, e::get_attr::Kind::{}, {})",
+                    try_traced!(self.child(node, 0, indent)),
+                    try_traced!(self.child(node, 1, indent)),
+                    try_traced!(self.nodes(children.get(2..).unwrap_or(&[]), indent)),
+                    kind,
+                    position)
+        } else if let Some(binary) = any.downcast_ref::<Binary>() {
+            taken = 2;
+            format!("e::Binary::boxed({:?}.to_string(), {}, {}, {})",
+                    binary.operator(),
+                    try_traced!(self.child(node, 0, indent)),
+                    try_traced!(self.child(node, 1, indent)),
+                    position)
+        } else if let Some(unary) = any.downcast_ref::<Unary>() {
+            taken = 1;
+            format!("e::Unary::boxed({:?}.to_string(), {}, {})",
+                    unary.operator(),
+                    try_traced!(self.child(node, 0, indent)),
+                    position)
+        } else if any.is::<expression::BlockReference>() {
+            taken = 1;
+            format!("e::BlockReference::boxed({}, {})",
+                    try_traced!(self.child(node, 0, indent)),
+                    position)
+        } else if let Some(parent) = any.downcast_ref::<Parent>() {
+            format!("e::Parent::boxed({:?}.to_string(), {})", parent.name(), position)
+        } else if let Some(call) = any.downcast_ref::<MethodCall>() {
+            taken = children.len();
+            format!("e::MethodCall::boxed({}, {:?}.to_string(), {}, {})",
+                    try_traced!(self.child(node, 0, indent)),
+                    call.method(),
+                    try_traced!(self.nodes(children.get(1..).unwrap_or(&[]), indent)),
+                    position)
+        } else if any.is::<Sandbox>() {
+            return self.error(node, "sandboxed sections are not supported");
+        } else {
+            return self.error(node, "unknown node type");
+        };
+
+        let constructor = match children.get(taken..) {
+            Some(rest) if !rest.is_empty() => {
+                format!("::twig::template::precompiled::with_children({}, {})",
+                        constructor,
+                        try_traced!(self.nodes(rest, indent)))
+            }
+            _ => constructor,
+        };
+
+        let attributes: Vec<String> = match attributes(node) {
+            Some(attributes) => {
+                attributes.iter().map(|&(k, v)| format!("({:?}, {:?})", k, v)).collect()
+            }
+            None => return self.error(node, "unknown node type"),
+        };
+
+        Ok(format!("node({}, &[{}])", constructor, attributes.join(", ")))
+    }
+
+    fn child(&self, node: &Node, index: usize, indent: usize) -> Result<String, Traced<NodeError>> {
+        match node.children().get(index) {
+            Some(child) => self.node(&**child, indent),
+            None => self.error(node, "a child node is missing"),
+        }
+    }
+
+    /// Returns the nodes as `Vec<Box<Node>>` - one per line.
+    fn nodes(&self, nodes: &[Box<Node>], indent: usize) -> Result<String, Traced<NodeError>> {
+        if nodes.is_empty() {
+            return Ok("vec![]".to_string());
+        }
+
+        let mut lines = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            lines.push(format!("{}{}",
+                               "    ".repeat(indent + 1),
+                               try_traced!(self.node(&**node, indent + 1))));
+        }
+
+        Ok(format!("vec![\n{}\n{}]", lines.join(",\n"), "    ".repeat(indent)))
+    }
+}
+
+/// Returns the expression of the value - or None for closures and dates.
+fn value(value: &Value) -> Option<String> {
+    Some(match *value {
+        Value::Null => "V::Null".to_string(),
+        Value::Boolean(x) => format!("V::Boolean({})", x),
+        Value::Integer(x) => format!("V::Integer({}i64)", x),
+        Value::Float(x) => format!("V::Float(f64::from_bits({:#x}))", x.to_bits()),
+        Value::String(ref x) => format!("V::String({:?}.to_string())", x),
+        Value::Markup(ref x, ref safe) => {
+            format!("V::Markup({:?}.to_string(), {:?}.to_string())", x, safe)
+        }
+        Value::Sequence(ref x) => {
+            let mut values = Vec::with_capacity(x.len());
+            for item in x {
+                match self::value(item) {
+                    Some(item) => values.push(item),
+                    None => return None,
+                }
+            }
+            format!("V::Sequence(vec![{}])", values.join(", "))
+        }
+        Value::Map(ref x) if x.is_empty() => {
+            "V::Map(::twig::runtime::value::Map::new())".to_string()
+        }
+        Value::Map(ref x) => {
+            let mut entries = Vec::with_capacity(x.len());
+            for &(ref key, ref item) in x.iter() {
+                let key = match *key {
+                    Key::Integer(i) => format!("K::Integer({}i64)", i),
+                    Key::String(ref s) => format!("K::String({:?}.to_string())", s),
+                };
+                match self::value(item) {
+                    Some(item) => entries.push(format!("({}, {})", key, item)),
+                    None => return None,
+                }
+            }
+            format!("V::Map(vec![{}].into_iter().collect())", entries.join(", "))
+        }
+        Value::Closure(..) | Value::Date(..) => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use engine::{Engine, Setup};
+    use engine::parser::{Parser, Lexer};
+    use extension::sandbox::{self, Sandbox, SecurityPolicy};
+    use template;
+
+    fn parse(source: &str) -> Result<String, Traced<NodeError>> {
+        let sandbox = Sandbox::new(SecurityPolicy::default(), sandbox::Mode::Local);
+        let engine = Engine::new(Setup::default().add_extension(sandbox).unwrap()).unwrap();
+        let parser = Parser::new(&engine).unwrap();
+        let template = template::Raw::new(source, "test.html");
+        let stream = Lexer::default().tokenize(&template).unwrap();
+
+        generate(&parser.parse_module(&stream).unwrap())
+    }
+
+    #[test]
+    pub fn _generate() {
+        let code = parse("Hi {{ name }}!").unwrap();
+
+        assert!(code.contains("n::Module::new(n::Body::boxed(vec![\n        \
+                               node(n::Text::boxed(\"Hi \".to_string(), &at(1, 1)), &[]),"));
+        assert!(code.ends_with("None,\n                   vec![],\n                   \
+                                vec![],\n                   vec![], vec![], \"test.html\")\n}"));
+    }
+
+    #[test]
+    pub fn not_compilable() {
+        let error = parse("{% sandbox %}{% endsandbox %}").unwrap_err();
+
+        assert_eq!(error.error().to_string(),
+                   "Node cannot be compiled to Rust. In template \"test.html\" at 1:4: \
+                    sandboxed sections are not supported.");
+    }
+}
@@ -0,0 +1,79 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Array Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use runtime::value::{Map, Key};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::Traced;
+use std::clone::Clone;
+
+/// Sequence literal `[a, b]` or hash literal `{key: value}`.
+///
+/// The children of a hash alternate between keys and values.
+pub type Array = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    hash: bool,
+}
+
+impl Array {
+    pub fn sequence(values: Vec<Box<Node>>, position: &Position) -> Box<Array> {
+        Array::boxed(false, values, position)
+    }
+
+    pub fn hash(pairs: Vec<Box<Node>>, position: &Position) -> Box<Array> {
+        Array::boxed(true, pairs, position)
+    }
+
+    fn boxed(hash: bool, nodes: Vec<Box<Node>>, position: &Position) -> Box<Array> {
+        Box::new(Array {
+            data: Data { hash: hash },
+            position: (*position).clone(),
+            nodes: nodes,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn is_hash(&self) -> bool {
+        self.data.hash
+    }
+}
+
+impl Execute for Array {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        if !self.data.hash {
+            let mut values = Vec::with_capacity(self.nodes.len());
+            for node in &self.nodes {
+                values.push(try_traced!(node.evaluate(runtime, job)));
+            }
+
+            return Ok(Value::Sequence(values));
+        }
+
+        let mut map = Map::new();
+        for pair in self.nodes.chunks(2) {
+            let key = try_traced!(pair[0].evaluate(runtime, job));
+            let value = match pair.get(1) {
+                Some(node) => try_traced!(node.evaluate(runtime, job)),
+                None => Value::Null,
+            };
+            map.insert(Key::from(&key), value);
+        }
+
+        Ok(Value::Map(map))
+    }
+}
@@ -0,0 +1,63 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Arrow Function Expression Node.
+
+use std::sync::Arc;
+use std::collections::HashMap;
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use runtime::value::Closure;
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::Traced;
+use std::clone::Clone;
+
+/// Arrow function `(a, b) => body`, which evaluates to a closure.
+pub type Arrow = GenericNode<Data>;
+
+#[derive(Debug)]
+pub struct Data {
+    params: Vec<String>,
+    body: Arc<Node>,
+}
+
+impl Arrow {
+    pub fn boxed(params: Vec<String>, body: Box<Node>, position: &Position) -> Box<Arrow> {
+        Box::new(Arrow {
+            tag: String::new(),
+            position: (*position).clone(),
+            nodes: Vec::new(),
+            attributes: HashMap::new(),
+            data: Data {
+                params: params,
+                body: Arc::from(body),
+            },
+        })
+    }
+
+    pub fn params(&self) -> &[String] {
+        &self.data.params
+    }
+
+    pub fn body(&self) -> &Node {
+        &*self.data.body
+    }
+}
+
+impl Execute for Arrow {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, _job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        Ok(Value::Closure(Closure::new(self.data.params.clone(),
+                                       self.data.body.clone(),
+                                       Arc::new(runtime.clone()))))
+    }
+}
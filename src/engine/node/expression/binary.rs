@@ -0,0 +1,84 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Binary Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use extension::api::op;
+use api::error::{Traced, ErrorExt};
+use std::clone::Clone;
+
+/// Applies the binary operator to the two children, e.g. `a + b`.
+pub type Binary = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    operator: String,
+}
+
+impl Binary {
+    pub fn boxed(operator: String,
+                 left: Box<Node>,
+                 right: Box<Node>,
+                 position: &Position)
+                 -> Box<Binary> {
+        Box::new(Binary {
+            data: Data { operator: operator },
+            position: (*position).clone(),
+            nodes: vec![left, right],
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn operator(&self) -> &str {
+        &self.data.operator
+    }
+
+    fn operand(&self,
+               index: usize,
+               runtime: &Runtime,
+               job: &mut Job)
+               -> Result<Value, Traced<RuntimeError>> {
+        match self.nodes.get(index) {
+            Some(node) => node.evaluate(runtime, job),
+            None => {
+                traced_err!(RuntimeError::Unreachable {
+                    reason: format!("The operator {:?} has no operand #{}",
+                                    self.data.operator,
+                                    index),
+                })
+            }
+        }
+    }
+}
+
+impl Execute for Binary {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let ext = job.extensions().clone();
+        let operator = try_traced!(ext.operators_binary().get(&self.data.operator).ok_or_else(|| {
+            RuntimeError::UnknownOperator { name: self.data.operator.clone() }.at(loc!())
+        }));
+
+        let left = try_traced!(self.operand(0, runtime, job));
+
+        match operator.op {
+            op::Binary::Callable(ref f) => f(&left, &try_traced!(self.operand(1, runtime, job))),
+            op::Binary::ShortCircuit(value) if left.is_true() == value => Ok(Value::Boolean(value)),
+            op::Binary::ShortCircuit(_) => {
+                Ok(Value::Boolean(try_traced!(self.operand(1, runtime, job)).is_true()))
+            }
+        }
+    }
+}
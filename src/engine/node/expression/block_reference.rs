@@ -0,0 +1,68 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Block Reference Expression Node.
+
+use engine::node::{GenericNode, block};
+use engine::node::expression::OUTPUT;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::Traced;
+use std::clone::Clone;
+
+/// Renders the block named by the child, e.g. `{{ block('title') }}`.
+///
+/// orig: Twig_Node_Expression_BlockReference
+pub type BlockReference = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data;
+
+impl BlockReference {
+    pub fn boxed(name: Box<Node>, position: &Position) -> Box<BlockReference> {
+        Box::new(BlockReference {
+            position: (*position).clone(),
+            nodes: vec![name],
+            ..GenericNode::default()
+        })
+    }
+}
+
+impl BlockReference {
+    fn display(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let name = match self.nodes.first() {
+            Some(node) => try_traced!(node.evaluate(runtime, job)).to_string(),
+            None => {
+                return traced_err!(RuntimeError::Unreachable {
+                    reason: "The block reference has no name".to_string(),
+                })
+            }
+        };
+
+        block::display(&name, 0, runtime, job)
+    }
+}
+
+impl Execute for BlockReference {
+    /// Writes the block directly with the `OUTPUT` attribute - it is buffered
+    /// otherwise.
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        if self.has_attribute(OUTPUT) {
+            return self.display(runtime, job);
+        }
+
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let output = try_traced!(job.capture(|job| self.display(runtime, job)));
+
+        Ok(Value::String(output))
+    }
+}
@@ -0,0 +1,49 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Constant Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use api::error::Traced;
+use std::clone::Clone;
+
+pub type Constant = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    value: Value,
+}
+
+impl Constant {
+    pub fn boxed(value: Value, position: &Position) -> Box<Constant> {
+        Box::new(Constant {
+            data: Data { value: value },
+            position: (*position).clone(),
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.data.value
+    }
+}
+
+impl Execute for Constant {
+    fn execute(&self, _runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        job.write(self.data.value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, _runtime: &Runtime, _job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        Ok(self.data.value.clone())
+    }
+
+    fn constant(&self) -> Option<&Value> {
+        Some(&self.data.value)
+    }
+}
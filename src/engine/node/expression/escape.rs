@@ -0,0 +1,104 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Escape Expression Node.
+
+use engine::node::{GenericNode, expression};
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use api::error::Traced;
+use engine::parser::token::stream::Position;
+use engine::Node;
+use extension::escaper::Strategy;
+use std::clone::Clone;
+
+pub type Escape = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    strategy: Strategy,
+}
+
+impl Escape {
+    pub fn boxed(expr: Box<Node>, strategy: Strategy, position: &Position) -> Box<Escape> {
+        let name = strategy.name().to_string();
+        let mut node = Box::new(Escape {
+            data: Data { strategy: strategy },
+            position: (*position).clone(),
+            nodes: vec![expr],
+            ..GenericNode::default()
+        });
+        node.set_attribute(expression::SAFE, &name);
+
+        node
+    }
+
+    pub fn strategy(&self) -> &Strategy {
+        &self.data.strategy
+    }
+}
+
+impl Execute for Escape {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let strategy = self.data.strategy.name();
+        let mut escaped = String::new();
+
+        for node in &self.nodes {
+            match try_traced!(node.evaluate(runtime, job)) {
+                // markup which is safe for this strategy - don't escape it twice
+                Value::Markup(ref text, ref safe) if safe == strategy || safe == "all" => {
+                    escaped.push_str(text)
+                }
+                value => escaped.push_str(&self.data.strategy.escape(&value.to_string())),
+            }
+        }
+
+        Ok(Value::Markup(escaped, strategy.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use engine::node::expression::Name;
+    use extension::escaper::Strategy;
+    use runtime::{Runtime, Value};
+    use std::default::Default;
+
+    #[test]
+    fn run() {
+        let mut rt = Runtime::default();
+        rt.set("message", "<b>Hello</b> & 'World'");
+
+        let name = Name::boxed("message".to_string(), &Default::default());
+        let node = Escape::boxed(name, Strategy::Html, &Default::default());
+
+        assert_eq!(rt.run(&*node).unwrap(),
+                   "&lt;b&gt;Hello&lt;/b&gt; &amp; &#039;World&#039;");
+    }
+
+    #[test]
+    fn markup() {
+        let mut rt = Runtime::default();
+        rt.set("html", Value::Markup("<b>Hello</b>".to_string(), "html".to_string()));
+        rt.set("js", Value::Markup("<b>Hello</b>".to_string(), "js".to_string()));
+        rt.set("raw", Value::Markup("<b>Hello</b>".to_string(), "all".to_string()));
+
+        for &(name, expected) in &[("html", "<b>Hello</b>"),
+                                   ("js", "&lt;b&gt;Hello&lt;/b&gt;"),
+                                   ("raw", "<b>Hello</b>")] {
+            let name = Name::boxed(name.to_string(), &Default::default());
+            let node = Escape::boxed(name, Strategy::Html, &Default::default());
+
+            assert_eq!(rt.run(&*node).unwrap(), expected);
+        }
+    }
+}
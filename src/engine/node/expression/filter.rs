@@ -0,0 +1,67 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Filter Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::{Traced, ErrorExt};
+use std::clone::Clone;
+
+/// Applies the filter `name` to the first child - the remaining children are arguments.
+pub type Filter = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    name: String,
+}
+
+impl Filter {
+    pub fn boxed(name: String,
+                 input: Box<Node>,
+                 args: Vec<Box<Node>>,
+                 position: &Position)
+                 -> Box<Filter> {
+        let mut nodes = vec![input];
+        nodes.extend(args);
+
+        Box::new(Filter {
+            data: Data { name: name },
+            position: (*position).clone(),
+            nodes: nodes,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+}
+
+impl Execute for Filter {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let mut values = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            values.push(try_traced!(node.evaluate(runtime, job)));
+        }
+        let input = values.remove(0);
+
+        let ext = job.extensions().clone();
+        let filter = try_traced!(ext.filters().get(&self.data.name).ok_or_else(|| {
+            RuntimeError::UnknownFilter { name: self.data.name.clone() }.at(loc!())
+        }));
+
+        filter.apply(runtime, job, input, values)
+    }
+}
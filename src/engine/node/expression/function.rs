@@ -0,0 +1,59 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Function Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::{Traced, ErrorExt};
+use std::clone::Clone;
+
+/// Calls the function `name` with the children as arguments.
+pub type Function = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    name: String,
+}
+
+impl Function {
+    pub fn boxed(name: String, args: Vec<Box<Node>>, position: &Position) -> Box<Function> {
+        Box::new(Function {
+            data: Data { name: name },
+            position: (*position).clone(),
+            nodes: args,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+}
+
+impl Execute for Function {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let mut args = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            args.push(try_traced!(node.evaluate(runtime, job)));
+        }
+
+        let ext = job.extensions().clone();
+        let function = try_traced!(ext.functions().get(&self.data.name).ok_or_else(|| {
+            RuntimeError::UnknownFunction { name: self.data.name.clone() }.at(loc!())
+        }));
+
+        function.call(runtime, job, args)
+    }
+}
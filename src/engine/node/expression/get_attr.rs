@@ -0,0 +1,148 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Attribute Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use runtime::value::{Key, Date};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::Traced;
+use std::clone::Clone;
+
+/// Methods of date values.
+///
+/// orig: \DateTime::getTimestamp(), ..
+const DATE_METHODS: &'static [&'static str] = &["getTimestamp", "getOffset", "getTimezone"];
+
+/// Accesses the attribute given by the second child of the first child - the
+/// remaining children are method arguments.
+pub type GetAttr = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    kind: Kind,
+}
+
+/// Kind of attribute access.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Kind {
+    /// `a.b` - array item, property or method
+    #[default]
+    Any,
+    /// `a[b]` - array item
+    Array,
+    /// `a.b()` - method
+    Method,
+}
+
+impl GetAttr {
+    pub fn boxed(object: Box<Node>,
+                 attribute: Box<Node>,
+                 args: Vec<Box<Node>>,
+                 kind: Kind,
+                 position: &Position)
+                 -> Box<GetAttr> {
+        let mut nodes = vec![object, attribute];
+        nodes.extend(args);
+
+        Box::new(GetAttr {
+            data: Data { kind: kind },
+            position: (*position).clone(),
+            nodes: nodes,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.data.kind
+    }
+
+    /// Returns the date property or the result of the date method `name`.
+    ///
+    /// `date.timestamp` is a shortcut for `date.getTimestamp()`.
+    fn date_attribute(&self,
+                      job: &mut Job,
+                      date: &Date,
+                      name: &str)
+                      -> Result<Value, Traced<RuntimeError>> {
+        if self.data.kind == Kind::Any {
+            if let Some(value) = date.properties().get(name) {
+                if let Some(policy) = job.policy() {
+                    try_traced!(policy.check_property("date", name, &self.position));
+                }
+
+                return Ok(value.clone());
+            }
+        }
+
+        let lowercase = name.to_lowercase();
+        let method = DATE_METHODS.iter().find(|method| {
+            let method = method.to_lowercase();
+            method == lowercase ||
+            (self.data.kind == Kind::Any &&
+             (method == format!("get{}", lowercase) || method == format!("is{}", lowercase)))
+        });
+        let method = match method {
+            Some(method) => *method,
+            None => return Ok(Value::Null),
+        };
+
+        if let Some(policy) = job.policy() {
+            try_traced!(policy.check_method("date", method, &self.position));
+        }
+
+        Ok(match method {
+            "getTimestamp" => Value::Integer(date.timestamp()),
+            "getOffset" => Value::Integer(date.offset() as i64),
+            _ => Value::from(date.timezone()),
+        })
+    }
+}
+
+impl Execute for GetAttr {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    /// Items of sequences and maps are not checked by the sandbox - like PHP arrays.
+    ///
+    /// orig: twig_get_attribute($env, $source, $object, $item, $arguments, $type)
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let object = try_traced!(self.nodes[0].evaluate(runtime, job));
+        let attribute = try_traced!(self.nodes[1].evaluate(runtime, job));
+
+        if self.data.kind != Kind::Method {
+            match object {
+                Value::Map(ref map) => {
+                    if let Some(value) = map.get(&attribute) {
+                        return Ok(value.clone());
+                    }
+                }
+                Value::Sequence(ref sequence) => {
+                    if let Key::Integer(i) = Key::from(&attribute) {
+                        if i >= 0 && (i as usize) < sequence.len() {
+                            return Ok(sequence[i as usize].clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            if self.data.kind == Kind::Array {
+                return Ok(Value::Null);
+            }
+        }
+
+        match object {
+            Value::Date(ref date) => self.date_attribute(job, date, &attribute.to_string()),
+            _ => Ok(Value::Null),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Method Call Expression Node.
+
+use engine::node::{GenericNode, macro_};
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use api::error::Traced;
+use std::clone::Clone;
+
+/// Calls the macro `method` of the template named by the first child - the
+/// other children are the arguments, e.g. `{{ forms.input('name') }}`.
+///
+/// orig: Twig_Node_Expression_MethodCall
+pub type MethodCall = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    method: String,
+}
+
+impl MethodCall {
+    pub fn boxed(template: Box<Node>,
+                 method: String,
+                 args: Vec<Box<Node>>,
+                 position: &Position)
+                 -> Box<MethodCall> {
+        let mut nodes = vec![template];
+        nodes.extend(args);
+
+        Box::new(MethodCall {
+            data: Data { method: method },
+            position: (*position).clone(),
+            nodes: nodes,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn method(&self) -> &str {
+        &self.data.method
+    }
+}
+
+impl Execute for MethodCall {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    /// Macros of the template being executed are called without loading it again.
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let mut values = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            values.push(try_traced!(node.evaluate(runtime, job)));
+        }
+        if values.is_empty() {
+            return traced_err!(RuntimeError::Unreachable {
+                reason: format!("The call of the macro {:?} has no template", self.data.method),
+            });
+        }
+
+        let name = values.remove(0).to_string();
+        let module = match job.frame() {
+            Some(frame) if frame.module().filename() == name => Some(frame.module().clone()),
+            _ => None,
+        };
+        let module = match module {
+            Some(module) => module,
+            None => {
+                match try_traced!(job.load_template(&name, false)) {
+                    Some(compiled) => compiled.module().clone(),
+                    None => {
+                        return traced_err!(RuntimeError::Unreachable {
+                            reason: format!("The template {:?} was not loaded", name),
+                        })
+                    }
+                }
+            }
+        };
+
+        macro_::call(&module, &self.data.method, values, job)
+    }
+}
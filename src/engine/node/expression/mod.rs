@@ -5,5 +5,62 @@
 
 //! Twig library for rust
 
+use engine::Node;
+
 pub mod name;
+pub mod escape;
+pub mod constant;
+pub mod filter;
+pub mod function;
+pub mod array;
+pub mod arrow;
+pub mod get_attr;
+pub mod binary;
+pub mod unary;
+pub mod block_reference;
+pub mod parent;
+pub mod method_call;
 pub use self::name::Name;
+pub use self::escape::Escape;
+pub use self::constant::Constant;
+pub use self::filter::Filter;
+pub use self::function::Function;
+pub use self::array::Array;
+pub use self::arrow::Arrow;
+pub use self::get_attr::GetAttr;
+pub use self::binary::Binary;
+pub use self::unary::Unary;
+pub use self::block_reference::BlockReference;
+pub use self::parent::Parent;
+pub use self::method_call::MethodCall;
+
+/// Node attribute with the escaping strategies an expression is safe for.
+///
+/// The value is a comma separated list, like "html,js" or "all".
+pub const SAFE: &'static str = "is_safe";
+
+/// Node attribute of `parent()` and `block()` calls, which write the block
+/// directly instead of buffering it - see `optimizer::Mode::OUTPUT`.
+pub const OUTPUT: &'static str = "output";
+
+/// Returns the escaping strategies the expression is safe for.
+pub fn safe_for(node: &Node) -> Vec<String> {
+    match node.attribute(SAFE) {
+        Ok(x) if !x.is_empty() => x.split(',').map(|x| x.to_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Marks the expression as safe for the given escaping strategies.
+pub fn set_safe_for(node: &mut Node, strategies: &[String]) {
+    if strategies.is_empty() {
+        node.rm_attribute(SAFE);
+    } else {
+        node.set_attribute(SAFE, &strategies.join(","));
+    }
+}
+
+/// Returns true if the expression does not need escaping with `strategy`.
+pub fn is_safe_for(node: &Node, strategy: &str) -> bool {
+    safe_for(node).iter().any(|x| x == strategy || x == "all")
+}
@@ -6,7 +6,8 @@
 //! Name Expression Node.
 
 use engine::node::GenericNode;
-use runtime::{Runtime, Execute, Job};
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use api::error::Traced;
 use engine::parser::token::stream::Position;
 use std::clone::Clone;
 
@@ -25,14 +26,29 @@ impl Name {
             ..GenericNode::default()
         })
     }
+
+    pub fn key(&self) -> &str {
+        &self.data.key
+    }
 }
 
 impl Execute for Name {
-    fn execute(&self, runtime: &Runtime, job: &mut Job) {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    /// Variables of the context take precedence over globals.
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
         // TODO: Add some logging if lookup failed
         //      -> might make sense to do that locally
-        let text: &str = runtime.get(&self.data.key).unwrap_or("");
-        job.write(text)
+        if let Some(value) = runtime.get(&self.data.key) {
+            return Ok(value.clone());
+        }
+
+        Ok(job.extensions().globals().get(&self.data.key).map(|x| x.value()).unwrap_or_default())
     }
 }
 
@@ -46,10 +62,10 @@ mod test {
     fn run() {
         let key = "message";
         let mut rt = Runtime::default();
-        rt.set("message".to_string(), "Hello World".to_string());
+        rt.set("message", "Hello World");
 
         let node = Name { data: Data { key: key.to_string() }, ..Default::default() };
 
-        assert_eq!(rt.run(&node), "Hello World");
+        assert_eq!(rt.run(&node).unwrap(), "Hello World");
     }
 }
@@ -0,0 +1,65 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Parent Expression Node.
+
+use engine::node::{GenericNode, block};
+use engine::node::expression::OUTPUT;
+use engine::Node;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use api::error::Traced;
+use std::clone::Clone;
+
+/// Renders the parent definition of the block `name`, i.e. `{{ parent() }}`.
+///
+/// orig: Twig_Node_Expression_Parent
+pub type Parent = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    name: String,
+}
+
+impl Parent {
+    pub fn boxed(name: String, position: &Position) -> Box<Parent> {
+        Box::new(Parent {
+            data: Data { name: name },
+            position: (*position).clone(),
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+
+    fn display(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let from = job.frame().map_or(0, |x| x.index() + 1);
+
+        block::display(&self.data.name, from, runtime, job)
+    }
+}
+
+impl Execute for Parent {
+    /// Writes the block directly with the `OUTPUT` attribute - it is buffered
+    /// otherwise.
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        if self.has_attribute(OUTPUT) {
+            return self.display(runtime, job);
+        }
+
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let output = try_traced!(job.capture(|job| self.display(runtime, job)));
+
+        Ok(Value::String(output))
+    }
+}
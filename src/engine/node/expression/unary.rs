@@ -0,0 +1,66 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Unary Expression Node.
+
+use engine::node::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use engine::parser::token::stream::Position;
+use engine::Node;
+use extension::api::op;
+use api::error::{Traced, ErrorExt};
+use std::clone::Clone;
+
+/// Applies the unary operator to the child, e.g. `-a` or `not a`.
+pub type Unary = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    operator: String,
+}
+
+impl Unary {
+    pub fn boxed(operator: String, operand: Box<Node>, position: &Position) -> Box<Unary> {
+        Box::new(Unary {
+            data: Data { operator: operator },
+            position: (*position).clone(),
+            nodes: vec![operand],
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn operator(&self) -> &str {
+        &self.data.operator
+    }
+}
+
+impl Execute for Unary {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let value = try_traced!(self.evaluate(runtime, job));
+        job.write(value.to_string());
+
+        Ok(())
+    }
+
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let ext = job.extensions().clone();
+        let operator = try_traced!(ext.operators_unary().get(&self.data.operator).ok_or_else(|| {
+            RuntimeError::UnknownOperator { name: self.data.operator.clone() }.at(loc!())
+        }));
+
+        let operand = match self.nodes.first() {
+            Some(node) => try_traced!(node.evaluate(runtime, job)),
+            None => {
+                return traced_err!(RuntimeError::Unreachable {
+                    reason: format!("The operator {:?} has no operand", self.data.operator),
+                })
+            }
+        };
+
+        match operator.op {
+            op::Unary::Callable(ref f) => f(&operand),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! For Node.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use super::{GenericNode, Body};
+use engine::Node;
+use engine::parser::token::stream::Position;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use runtime::value::Map;
+use api::error::Traced;
+
+/// Node attribute, which is "false" if the body does not access the `loop`
+/// variable - see `optimizer::Mode::LOOP`.
+pub const WITH_LOOP: &'static str = "with_loop";
+
+/// Loops over a sequence or map - the children are the sequence, the body,
+/// the else-branch and the optional condition.
+///
+/// ```text
+/// {% for key, user in users if user.active %}
+///     {{ loop.index }}. {{ user.name }}
+/// {% else %}
+///     no users
+/// {% endfor %}
+/// ```
+///
+/// orig: Twig_Node_For
+pub type For = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    key_target: String,
+    value_target: String,
+}
+
+impl For {
+    pub fn boxed(key_target: String,
+                 value_target: String,
+                 seq: Box<Node>,
+                 condition: Option<Box<Node>>,
+                 body: Vec<Box<Node>>,
+                 else_: Vec<Box<Node>>,
+                 position: &Position)
+                 -> Box<For> {
+        let mut nodes = vec![seq, Body::boxed(body) as Box<Node>, Body::boxed(else_)];
+        nodes.extend(condition);
+
+        Box::new(For {
+            data: Data {
+                key_target: key_target,
+                value_target: value_target,
+            },
+            position: (*position).clone(),
+            nodes: nodes,
+            ..GenericNode::default()
+        })
+    }
+
+    /// Returns the variable name of the keys, `_key` by default.
+    pub fn key_target(&self) -> &str {
+        &self.data.key_target
+    }
+
+    pub fn value_target(&self) -> &str {
+        &self.data.value_target
+    }
+
+    /// Returns true if the `loop` variable is defined.
+    pub fn with_loop(&self) -> bool {
+        self.attribute(WITH_LOOP).map_or(true, |x| x != "false")
+    }
+
+    /// Returns the variables of one iteration.
+    fn targets(&self, key: &Value, value: &Value) -> HashMap<String, Value> {
+        let mut data = HashMap::new();
+        data.insert(self.data.key_target.clone(), key.clone());
+        data.insert(self.data.value_target.clone(), value.clone());
+
+        data
+    }
+}
+
+impl Execute for For {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let entries = match try_traced!(self.nodes[0].evaluate(runtime, job)) {
+            Value::Sequence(x) => Map::from_sequence(x),
+            Value::Map(x) => x,
+            _ => Map::new(),
+        };
+        let parent = Arc::new(runtime.clone());
+
+        let mut items = Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            if let Some(condition) = self.nodes.get(3) {
+                let scope = Runtime::layered(self.targets(&key.clone().into(), &value),
+                                             parent.clone());
+                if !try_traced!(condition.evaluate(&scope, job)).is_true() {
+                    continue;
+                }
+            }
+            items.push((key, value));
+        }
+
+        if items.is_empty() {
+            return self.nodes[2].execute(runtime, job);
+        }
+
+        let context = if self.with_loop() {
+            Some(Value::Map(runtime.variables().into_iter().collect()))
+        } else {
+            None
+        };
+
+        let length = items.len() as i64;
+        for (index, (key, value)) in (0..length).zip(items) {
+            let mut data = self.targets(&key.into(), &value);
+            if let Some(ref context) = context {
+                let mut loop_ = Map::new();
+                loop_.insert("parent", context.clone());
+                loop_.insert("index0", index);
+                loop_.insert("index", index + 1);
+                loop_.insert("revindex0", length - index - 1);
+                loop_.insert("revindex", length - index);
+                loop_.insert("first", index == 0);
+                loop_.insert("last", index == length - 1);
+                loop_.insert("length", length);
+                data.insert("loop".to_string(), Value::Map(loop_));
+            }
+
+            let scope = Runtime::layered(data, parent.clone());
+            try_traced!(self.nodes[1].execute(&scope, job));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Include Node.
+
+use std::collections::HashMap;
+use super::GenericNode;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use api::error::Traced;
+use engine::parser::token::stream::Position;
+use engine::Node;
+use std::clone::Clone;
+
+/// Renders the template named by the first child - the optional second child
+/// evaluates to additional variables.
+pub type Include = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    ignore_missing: bool,
+    only: bool,
+}
+
+impl Include {
+    pub fn boxed(name: Box<Node>,
+                 variables: Option<Box<Node>>,
+                 ignore_missing: bool,
+                 only: bool,
+                 position: &Position)
+                 -> Box<Include> {
+        let mut nodes = vec![name];
+        nodes.extend(variables);
+
+        Box::new(Include {
+            data: Data {
+                ignore_missing: ignore_missing,
+                only: only,
+            },
+            position: (*position).clone(),
+            nodes: nodes,
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn ignore_missing(&self) -> bool {
+        self.data.ignore_missing
+    }
+
+    pub fn only(&self) -> bool {
+        self.data.only
+    }
+}
+
+impl Execute for Include {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let name = try_traced!(self.nodes[0].evaluate(runtime, job)).to_string();
+        let variables = match self.nodes.get(1) {
+            Some(node) => Some(try_traced!(node.evaluate(runtime, job))),
+            None => None,
+        };
+
+        let compiled = match try_traced!(job.load_template(&name, self.data.ignore_missing)) {
+            Some(compiled) => compiled,
+            None => return Ok(()),
+        };
+
+        let mut data = if self.data.only {
+            HashMap::new()
+        } else {
+            runtime.variables()
+        };
+        match variables {
+            Some(Value::Map(map)) => {
+                for &(ref key, ref value) in map.iter() {
+                    data.insert(key.to_string(), value.clone());
+                }
+            }
+            Some(Value::Sequence(ref x)) if x.is_empty() => {}
+            Some(x) => {
+                return traced_err!(RuntimeError::InvalidArgument {
+                    reason: format!("Variables passed to the include of {:?} must be a \
+                                     mapping, found {}",
+                                    name,
+                                    x.type_name()),
+                })
+            }
+            None => {}
+        }
+
+        let previous = job.policy().cloned();
+        job.set_policy(compiled.policy().cloned());
+        let result = compiled.execute(&Runtime::new(data), job);
+        job.set_policy(previous);
+
+        result
+    }
+}
@@ -0,0 +1,100 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Macro Node.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use super::{GenericNode, Body, Module};
+use engine::Node;
+use engine::parser::token::stream::Position;
+use runtime::{Runtime, Execute, Job, Value, RuntimeError};
+use runtime::job::Frame;
+use api::error::Traced;
+
+/// Definition of the macro `name` - the children are the arguments and the
+/// body.
+///
+/// The arguments are a hash of the names and the default values, e.g.
+/// `{'name': null, 'type': 'text'}` for `{% macro input(name, type = 'text') %}`.
+///
+/// orig: Twig_Node_Macro
+pub type Macro = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    name: String,
+}
+
+impl Macro {
+    pub fn boxed(name: String,
+                 arguments: Box<Node>,
+                 body: Vec<Box<Node>>,
+                 position: &Position)
+                 -> Box<Macro> {
+        Box::new(Macro {
+            data: Data { name: name },
+            position: (*position).clone(),
+            nodes: vec![arguments, Body::boxed(body)],
+            ..GenericNode::default()
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.data.name
+    }
+}
+
+/// Executes the body.
+impl Execute for Macro {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        match self.nodes.get(1) {
+            Some(body) => body.execute(runtime, job),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Calls the macro `name` of the template and returns the output as markup.
+///
+/// The macro only sees its arguments and the globals - additional arguments
+/// are passed as `varargs`.
+pub fn call(module: &Arc<Module>,
+            name: &str,
+            args: Vec<Value>,
+            job: &mut Job)
+            -> Result<Value, Traced<RuntimeError>> {
+    let macro_ = match module.macro_(name) {
+        Some(macro_) => macro_,
+        None => {
+            return traced_err!(RuntimeError::UnknownMacro {
+                name: name.to_string(),
+                template: module.filename().to_string(),
+            })
+        }
+    };
+
+    // the arguments are constant - and may be folded by the optimizer
+    let arguments = match macro_.nodes.first() {
+        Some(arguments) => try_traced!(arguments.evaluate(&Runtime::default(), job)),
+        None => Value::Null,
+    };
+
+    let mut args = args.into_iter();
+    let mut data = HashMap::new();
+    if let Value::Map(ref arguments) = arguments {
+        for &(ref key, ref default) in arguments.iter() {
+            data.insert(key.to_string(), args.next().unwrap_or_else(|| default.clone()));
+        }
+    }
+    data.insert("varargs".to_string(), Value::Sequence(args.collect()));
+
+    let runtime = Runtime::new(data);
+    job.push_frame(Frame::new(Arc::new(vec![module.clone()]), 0));
+    let output = job.capture(|job| macro_.execute(&runtime, job));
+    job.pop_frame();
+
+    Ok(Value::Markup(try_traced!(output), "all".to_string()))
+}
@@ -5,6 +5,7 @@
 
 //! Node.
 
+use std::any::Any;
 use std::fmt::Debug;
 use std::collections::HashMap;
 use engine::parser::error::NodeError;
@@ -18,13 +19,31 @@ pub mod virtual_;
 pub mod text;
 pub mod print;
 pub mod expression;
+pub mod include;
+pub mod block;
+pub mod block_reference;
+pub mod macro_;
+pub mod for_;
+pub mod sandbox;
+pub mod profile;
+pub mod traverser;
+pub mod codec;
+pub mod codegen;
 pub use self::module::Module;
 pub use self::body::Body;
 pub use self::text::Text;
 pub use self::virtual_::Virtual;
 pub use self::print::Print;
+pub use self::include::Include;
+pub use self::block::Block;
+pub use self::block_reference::BlockReference;
+pub use self::macro_::Macro;
+pub use self::for_::For;
+pub use self::sandbox::Sandbox;
+pub use self::profile::Profile;
+pub use self::traverser::NodeTraverser;
 
-pub trait Node : Debug + Execute {
+pub trait Node : Debug + Execute + Send + Sync {
     fn tag(&self) -> &str;
     fn position(&self) -> &Position;
     fn children(&self) -> &Vec<Box<Node>>;
@@ -33,10 +52,68 @@ pub trait Node : Debug + Execute {
     fn attribute(&self, key: &str) -> Result<&str, Traced<NodeError>>;
     fn set_attribute(&mut self, key: &str, value: &str) -> Option<String>;
     fn rm_attribute(&mut self, key: &str) -> Option<String>;
+    fn as_any(&self) -> &Any;
+    fn as_any_mut(&mut self) -> &mut Any;
+    fn into_any(self: Box<Self>) -> Box<Any>;
+}
+
+/// Returns the node as `T`, if it is a `T` - e.g. a `node::Text`.
+pub fn downcast<T: Node + 'static>(node: Box<Node>) -> Result<Box<T>, Box<Node>> {
+    if !node.as_any().is::<T>() {
+        return Err(node);
+    }
+
+    Ok(node.into_any().downcast::<T>().unwrap())
 }
 
 type NodeDataAttibutes = HashMap<String, String>;
 
+/// Returns the attributes of the built-in nodes ordered by key - the trait
+/// only gives access by key.
+fn attributes(node: &Node) -> Option<Vec<(&str, &str)>> {
+    fn of<T: 'static>(node: &Node) -> Option<&NodeDataAttibutes> {
+        node.as_any().downcast_ref::<GenericNode<T>>().map(|x| &x.attributes)
+    }
+
+    use self::expression::{name, escape, constant, filter, function, array, arrow, get_attr,
+                             binary, unary, method_call, parent};
+
+    let attributes = match of::<body::Data>(node)
+                               .or_else(|| of::<virtual_::Data>(node))
+                               .or_else(|| of::<text::Data>(node))
+                               .or_else(|| of::<print::Data>(node))
+                               .or_else(|| of::<include::Data>(node))
+                               .or_else(|| of::<block::Data>(node))
+                               .or_else(|| of::<block_reference::Data>(node))
+                               .or_else(|| of::<macro_::Data>(node))
+                               .or_else(|| of::<for_::Data>(node))
+                               .or_else(|| of::<sandbox::Data>(node))
+                               .or_else(|| of::<profile::Data>(node))
+                               .or_else(|| of::<name::Data>(node))
+                               .or_else(|| of::<escape::Data>(node))
+                               .or_else(|| of::<constant::Data>(node))
+                               .or_else(|| of::<filter::Data>(node))
+                               .or_else(|| of::<function::Data>(node))
+                               .or_else(|| of::<array::Data>(node))
+                               .or_else(|| of::<arrow::Data>(node))
+                               .or_else(|| of::<get_attr::Data>(node))
+                               .or_else(|| of::<binary::Data>(node))
+                               .or_else(|| of::<unary::Data>(node))
+                               .or_else(|| of::<expression::block_reference::Data>(node))
+                               .or_else(|| of::<parent::Data>(node))
+                               .or_else(|| of::<method_call::Data>(node)) {
+        Some(attributes) => attributes,
+        None => return None,
+    };
+
+    let mut attributes: Vec<(&str, &str)> = attributes.iter()
+                                                      .map(|(k, v)| (&k[..], &v[..]))
+                                                      .collect();
+    attributes.sort();
+
+    Some(attributes)
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Default)]
 pub struct GenericNode<T> {
@@ -49,7 +126,7 @@ pub struct GenericNode<T> {
 
 #[allow(dead_code)]
 impl<T> Node for GenericNode<T>
-    where T: Debug,
+    where T: Debug + Send + Sync + 'static,
           GenericNode<T>: Execute
 {
     fn position(&self) -> &Position {
@@ -91,4 +168,16 @@ impl<T> Node for GenericNode<T>
     fn children_mut(&mut self) -> &mut Vec<Box<Node>> {
         &mut self.nodes
     }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<Any> {
+        self
+    }
 }
@@ -5,27 +5,32 @@
 
 //! Root node of the template.
 
+use std::sync::Arc;
 use super::GenericNode;
-use runtime::{Runtime, Execute, Job};
-use engine::node;
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use runtime::job::Frame;
+use api::error::Traced;
+use engine::{node, Node};
+use engine::node::{Block, Macro};
 
+/// Root node of the template - its children are the body, the blocks and the
+/// macros, in that order.
 pub type Module = GenericNode<Data>;
 
 #[derive(Debug, Default)]
 pub struct Data {
-    parent: Option<()>,
-    blocks: Vec<()>, // as nodes?
-    macros: Vec<()>, // as nodes?
+    parent: Option<Box<Node>>,
     traits: Vec<()>, // as nodes?
     embedded_templates: Vec<()>,
     filename: String,
 }
 
 impl Module {
+    /// `blocks` and `macros` are `node::Block` and `node::Macro` definitions.
     pub fn new(body: Box<node::Body>,
-               parent: Option<()>,
-               blocks: Vec<()>,
-               macros: Vec<()>,
+               parent: Option<Box<Node>>,
+               blocks: Vec<Box<Node>>,
+               macros: Vec<Box<Node>>,
                traits: Vec<()>,
                embedded_templates: Vec<()>,
                filename: &str)
@@ -33,24 +38,107 @@ impl Module {
         Module {
             data: Data {
                 parent: parent,
-                blocks: blocks,
-                macros: macros,
                 traits: traits,
                 embedded_templates: embedded_templates,
                 filename: filename.to_string(),
             },
-            nodes: vec![body],
+            nodes: vec![body, node::Body::boxed(blocks), node::Body::boxed(macros)],
             ..GenericNode::default()
         }
     }
+
+    pub fn filename(&self) -> &str {
+        &self.data.filename
+    }
+
+    /// Returns the expression of `{% extends %}`, if any.
+    pub fn parent(&self) -> Option<&Node> {
+        self.data.parent.as_deref()
+    }
+
+    pub fn body(&self) -> Option<&Node> {
+        self.nodes.first().map(|x| &**x)
+    }
+
+    pub fn blocks(&self) -> &[Box<Node>] {
+        self.nodes.get(1).map_or(&[], |x| &x.children()[..])
+    }
+
+    pub fn macros(&self) -> &[Box<Node>] {
+        self.nodes.get(2).map_or(&[], |x| &x.children()[..])
+    }
+
+    /// Returns the definition of the block `name`.
+    pub fn block(&self, name: &str) -> Option<&Block> {
+        self.blocks()
+            .iter()
+            .filter_map(|x| x.as_any().downcast_ref::<Block>())
+            .find(|x| x.name() == name)
+    }
+
+    /// Returns the definition of the macro `name`.
+    pub fn macro_(&self, name: &str) -> Option<&Macro> {
+        self.macros()
+            .iter()
+            .filter_map(|x| x.as_any().downcast_ref::<Macro>())
+            .find(|x| x.name() == name)
+    }
 }
 
+/// Executes the body - the parents of the template are displayed by `display()`.
 impl Execute for Module {
-    fn execute(&self, runtime: &Runtime, job: &mut Job) {
-        for node in &self.nodes {
-            node.execute(runtime, job)
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        match self.body() {
+            Some(body) => body.execute(runtime, job),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Displays the template and its parents, e.g. `{% extends 'layout.html' %}`.
+///
+/// The bodies are executed from the template to the root layout - only the
+/// body of the root layout produces output, see `Job::frame()`.
+pub fn display(module: &Arc<Module>,
+               runtime: &Runtime,
+               job: &mut Job)
+               -> Result<(), Traced<RuntimeError>> {
+    let mut chain = vec![module.clone()];
+    loop {
+        let child = chain[chain.len() - 1].clone();
+        let name = match child.parent() {
+            Some(parent) => try_traced!(parent.evaluate(runtime, job)).to_string(),
+            None => break,
+        };
+
+        if chain.iter().any(|x| x.filename() == name) {
+            return traced_err!(RuntimeError::Template {
+                name: name,
+                reason: format!("the template {:?} extends it, which is circular",
+                                child.filename()),
+            });
         }
+
+        match try_traced!(job.load_template(&name, false)) {
+            Some(compiled) => chain.push(compiled.module().clone()),
+            None => {
+                return traced_err!(RuntimeError::Unreachable {
+                    reason: format!("The parent {:?} was not loaded", name),
+                })
+            }
+        }
+    }
+
+    let chain = Arc::new(chain);
+    for (index, module) in chain.iter().enumerate() {
+        job.push_frame(Frame::new(chain.clone(), index));
+        let result = module.execute(runtime, job);
+        job.pop_frame();
+
+        try_traced!(result);
     }
+
+    Ok(())
 }
 
 // #[cfg(test)]
@@ -6,7 +6,8 @@
 //! Represents a node that executes an expression.
 
 use super::GenericNode;
-use runtime::{Runtime, Execute, Job};
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
 use engine::parser::token::stream::Position;
 use engine::Node;
 use std::clone::Clone;
@@ -27,9 +28,11 @@ impl Print {
 }
 
 impl Execute for Print {
-    fn execute(&self, runtime: &Runtime, job: &mut Job) {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
         for node in &self.nodes {
-            node.execute(runtime, job)
+            try_traced!(node.execute(runtime, job));
         }
+
+        Ok(())
     }
 }
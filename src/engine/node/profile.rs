@@ -0,0 +1,79 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Profile Node.
+
+use super::GenericNode;
+use engine::Node;
+use engine::parser::token::stream::Position;
+use extension::profiler::{Profiler, Kind};
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
+
+/// Records a profile of the children - see `extension::Profiler`.
+///
+/// orig: Twig_Profiler_Node_EnterProfile, Twig_Profiler_Node_LeaveProfile
+pub type Profile = GenericNode<Data>;
+
+#[derive(Debug)]
+pub struct Data {
+    profiler: Profiler,
+    kind: Kind,
+    template: String,
+    name: String,
+}
+
+impl Default for Data {
+    fn default() -> Data {
+        Data {
+            profiler: Profiler::default(),
+            kind: Kind::Template,
+            template: String::new(),
+            name: String::new(),
+        }
+    }
+}
+
+impl Profile {
+    pub fn boxed(profiler: Profiler,
+                 kind: Kind,
+                 template: &str,
+                 name: &str,
+                 body: Vec<Box<Node>>,
+                 position: &Position)
+                 -> Box<Profile> {
+        Box::new(Profile {
+            data: Data {
+                profiler: profiler,
+                kind: kind,
+                template: template.to_string(),
+                name: name.to_string(),
+            },
+            position: (*position).clone(),
+            nodes: body,
+            ..GenericNode::default()
+        })
+    }
+}
+
+impl Execute for Profile {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        self.data.profiler.enter(self.data.kind,
+                                 &self.data.template,
+                                 &self.data.name,
+                                 job.result().len());
+
+        let mut result = Ok(());
+        for node in &self.nodes {
+            result = node.execute(runtime, job);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.data.profiler.leave(job.result().len());
+        result
+    }
+}
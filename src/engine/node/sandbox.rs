@@ -0,0 +1,51 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Sandbox Node.
+
+use std::sync::Arc;
+use super::GenericNode;
+use engine::Node;
+use engine::parser::token::stream::Position;
+use extension::sandbox::SecurityPolicy;
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
+
+/// Executes the children with the sandbox enabled.
+pub type Sandbox = GenericNode<Data>;
+
+#[derive(Debug, Default)]
+pub struct Data {
+    policy: Arc<SecurityPolicy>,
+}
+
+impl Sandbox {
+    pub fn boxed(policy: Arc<SecurityPolicy>, body: Vec<Box<Node>>, position: &Position) -> Box<Sandbox> {
+        Box::new(Sandbox {
+            data: Data { policy: policy },
+            position: (*position).clone(),
+            nodes: body,
+            ..GenericNode::default()
+        })
+    }
+}
+
+impl Execute for Sandbox {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        let previous = job.policy().cloned();
+        job.set_policy(Some(self.data.policy.clone()));
+
+        let mut result = Ok(());
+        for node in &self.nodes {
+            result = node.execute(runtime, job);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        job.set_policy(previous);
+        result
+    }
+}
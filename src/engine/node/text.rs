@@ -6,7 +6,8 @@
 //! Text Node.
 
 use super::GenericNode;
-use runtime::{Runtime, Execute, Job};
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
 use engine::parser::token::stream::Position;
 use std::clone::Clone;
 
@@ -26,11 +27,17 @@ impl Text {
             ..GenericNode::default()
         })
     }
+
+    pub fn text(&self) -> &str {
+        &self.data.text
+    }
 }
 
 impl Execute for Text {
-    fn execute(&self, _runtime: &Runtime, job: &mut Job) {
-        job.write(&self.data.text)
+    fn execute(&self, _runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        job.write(&self.data.text);
+
+        Ok(())
     }
 }
 
@@ -47,6 +54,6 @@ mod test {
 
         let node = Text { data: Data { text: text.to_string() }, ..Default::default() };
 
-        assert_eq!(rt.run(&node), "Hello World");
+        assert_eq!(rt.run(&node).unwrap(), "Hello World");
     }
 }
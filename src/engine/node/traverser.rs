@@ -0,0 +1,166 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Node Traverser.
+
+use std::mem;
+use engine::Node;
+use engine::node::{self, Module};
+use engine::parser::{Parser, ParserError};
+use extension::api::NodeVisitor;
+use api::error::Traced;
+
+/// Visits the node tree with each visitor in order of priority.
+///
+/// orig: Twig_NodeTraverser
+#[derive(Debug)]
+pub struct NodeTraverser<'a> {
+    parser: &'a Parser,
+    visitors: Vec<&'a NodeVisitor>,
+}
+
+impl<'a> NodeTraverser<'a> {
+    pub fn new(parser: &'a Parser, visitors: &'a [Box<NodeVisitor>]) -> NodeTraverser<'a> {
+        let mut traverser = NodeTraverser {
+            parser: parser,
+            visitors: Vec::new(),
+        };
+
+        for visitor in visitors {
+            traverser.add_visitor(&**visitor);
+        }
+
+        traverser
+    }
+
+    /// Adds the visitor - after all visitors with the same or lower priority.
+    pub fn add_visitor(&mut self, visitor: &'a NodeVisitor) -> &mut NodeTraverser<'a> {
+        let index = self.visitors
+                        .iter()
+                        .position(|x| x.priority() > visitor.priority())
+                        .unwrap_or(self.visitors.len());
+        self.visitors.insert(index, visitor);
+
+        self
+    }
+
+    /// Traverses the node tree - returns `None` if a visitor removed the node.
+    pub fn traverse(&self, node: Box<Node>) -> Option<Box<Node>> {
+        let mut node = Some(node);
+
+        for visitor in &self.visitors {
+            node = match node {
+                Some(node) => self.traverse_for_visitor(*visitor, node),
+                None => return None,
+            }
+        }
+
+        node
+    }
+
+    /// Traverses the node tree of a template.
+    ///
+    /// # Failures
+    /// * When a visitor removes or replaces the module node itself
+    pub fn traverse_module(&self, module: Module) -> Result<Module, Traced<ParserError>> {
+        let name = module.filename().to_string();
+
+        match self.traverse(Box::new(module)).map(node::downcast::<Module>) {
+            Some(Ok(module)) => Ok(*module),
+            _ => traced_err!(ParserError::InvalidModule { name: name }),
+        }
+    }
+
+    fn traverse_for_visitor(&self, visitor: &NodeVisitor, node: Box<Node>) -> Option<Box<Node>> {
+        let mut node = visitor.enter_node(node, self.parser);
+
+        let children = mem::take(node.children_mut());
+        *node.children_mut() = children.into_iter()
+                                       .filter_map(|x| self.traverse_for_visitor(visitor, x))
+                                       .collect();
+
+        visitor.leave_node(node, self.parser)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+    use engine::{Engine, Setup, Node};
+    use engine::node::{self, Text, Body};
+    use engine::parser::Parser;
+    use engine::parser::token::stream::Position;
+    use extension::api::NodeVisitor;
+    use runtime::Runtime;
+
+    /// Renames text nodes `from` to `to`, removes text nodes `remove` and logs
+    /// the visited text nodes.
+    #[derive(Debug)]
+    struct Visitor {
+        from: &'static str,
+        to: &'static str,
+        remove: &'static str,
+        priority: i32,
+        log: Mutex<Vec<String>>,
+    }
+
+    impl NodeVisitor for Visitor {
+        fn enter_node(&self, node: Box<Node>, _parser: &Parser) -> Box<Node> {
+            match node::downcast::<Text>(node) {
+                Ok(text) => {
+                    self.log.lock().unwrap().push(text.text().to_string());
+                    if text.text() == self.from {
+                        return Text::boxed(self.to.to_string(), text.position());
+                    }
+                    text
+                }
+                Err(node) => node,
+            }
+        }
+
+        fn leave_node(&self, node: Box<Node>, _parser: &Parser) -> Option<Box<Node>> {
+            match node.as_any().downcast_ref::<Text>() {
+                Some(text) if text.text() == self.remove => return None,
+                _ => {}
+            }
+
+            Some(node)
+        }
+
+        fn priority(&self) -> i32 {
+            self.priority
+        }
+    }
+
+    fn visitor(from: &'static str, to: &'static str, remove: &'static str, priority: i32) -> Visitor {
+        Visitor {
+            from: from,
+            to: to,
+            remove: remove,
+            priority: priority,
+            log: Mutex::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    pub fn traverse() {
+        let engine = Engine::new(Setup::default()).unwrap();
+        let parser = Parser::new(&engine).unwrap();
+        let (a, b) = (visitor("z", "w", "", 5), visitor("x", "z", "y", -5));
+
+        let mut traverser = NodeTraverser::new(&parser, &[]);
+        traverser.add_visitor(&a).add_visitor(&b);
+
+        let position = Position::default();
+        let body = Body::boxed(vec![Text::boxed("x".to_string(), &position),
+                                    Text::boxed("y".to_string(), &position)]);
+        let body = traverser.traverse(body).unwrap();
+
+        assert_eq!(*b.log.lock().unwrap(), vec!["x", "y"]);
+        assert_eq!(*a.log.lock().unwrap(), vec!["z"]);
+        assert_eq!(Runtime::default().run(&*body).unwrap(), "w");
+    }
+}
@@ -6,7 +6,8 @@
 //! Text Node.
 
 use super::GenericNode;
-use runtime::{Runtime, Execute, Job};
+use runtime::{Runtime, Execute, Job, RuntimeError};
+use api::error::Traced;
 use engine::parser::token::stream::Position;
 use std::clone::Clone;
 
@@ -22,10 +23,12 @@ impl Virtual {
 }
 
 impl Execute for Virtual {
-    fn execute(&self, runtime: &Runtime, job: &mut Job) {
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
         for node in &self.nodes {
-            node.execute(runtime, job)
+            try_traced!(node.execute(runtime, job));
         }
+
+        Ok(())
     }
 }
 
@@ -51,6 +54,6 @@ mod test {
             children.push(node_world);
         }
 
-        assert_eq!(rt.run(&node_virtual), "Hello world!");
+        assert_eq!(rt.run(&node_virtual).unwrap(), "Hello world!");
     }
 }
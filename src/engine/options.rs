@@ -11,6 +11,7 @@ use extension::optimizer;
 
 pub type Autoescape = escaper::Mode;
 pub type Optimizations = optimizer::Mode;
+pub type Escapers = escaper::Escapers;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Charset {
@@ -29,9 +30,14 @@ pub struct Options {
     pub charset: Charset,
     pub strict_variables: bool,
     pub autoescape: Autoescape,
+    pub escapers: Escapers,
     pub cache: Option<PathBuf>,
     pub auto_reload: Option<bool>, // defaults to `self.debug` if set to `None`
     pub optimizations: Optimizations,
+    pub number_format: (usize, char, char),
+    pub date_format: String,
+    pub timezone: Option<String>,
+    pub random_seed: Option<u64>,
 }
 
 impl Default for Options {
@@ -41,9 +47,14 @@ impl Default for Options {
             charset: Charset::default(),
             strict_variables: false,
             autoescape: escaper::Mode::default(),
+            escapers: Escapers::new(),
             cache: None,
             auto_reload: None,
             optimizations: optimizer::Mode::default(),
+            number_format: (0, '.', ','),
+            date_format: "F j, Y H:i".to_string(),
+            timezone: None,
+            random_seed: None,
         }
     }
 }
@@ -61,8 +72,12 @@ impl Options {
         self.strict_variables
     }
 
-    pub fn autoescape(&self) -> Autoescape {
-        self.autoescape
+    pub fn autoescape(&self) -> &Autoescape {
+        &self.autoescape
+    }
+
+    pub fn escapers(&self) -> &Escapers {
+        &self.escapers
     }
 
     pub fn cache(&self) -> Option<&Path> {
@@ -81,4 +96,24 @@ impl Options {
     pub fn optimizations(&self) -> Optimizations {
         self.optimizations
     }
+
+    /// The defaults `(decimals, decimal_point, thousands_sep)` of the `number_format` filter.
+    pub fn number_format(&self) -> (usize, char, char) {
+        self.number_format
+    }
+
+    /// The default format of the `date` filter.
+    pub fn date_format(&self) -> &str {
+        &self.date_format
+    }
+
+    /// The default timezone of dates - UTC if unset.
+    pub fn timezone(&self) -> Option<&str> {
+        self.timezone.as_ref().map(|x| x.as_ref())
+    }
+
+    /// The seed of the `random()` function - random if unset.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
 }
@@ -11,6 +11,7 @@ use std::error::Error;
 use engine::parser::token::TokenError;
 use engine::parser::job::{self, cursor};
 use engine::parser::token;
+use extension::sandbox::SecurityError;
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -40,6 +41,19 @@ pub enum ParserError {
         name: String,
         job: job::JobDump,
     },
+    UnknownFilter {
+        name: String,
+        position: token::stream::Position,
+    },
+    UnknownFunction {
+        name: String,
+        position: token::stream::Position,
+    },
+    InvalidCall {
+        name: String,
+        reason: String,
+        position: token::stream::Position,
+    },
     UnexpectedToken {
         reason: Option<&'static str>,
         expected: token::PatternDump,
@@ -50,6 +64,16 @@ pub enum ParserError {
         expected: Option<token::PatternDump>,
         cursor: cursor::CursorDump,
     },
+    Security(SecurityError),
+    InvalidModule {
+        name: String,
+    },
+}
+
+impl From<SecurityError> for ParserError {
+    fn from(err: SecurityError) -> ParserError {
+        ParserError::Security(err)
+    }
 }
 
 impl From<TokenError> for ParserError {
@@ -73,8 +97,13 @@ impl Error for ParserError {
             ParserError::SemanticError => "Semantic error.",
             ParserError::NoTagHandler{..} => "There is no registered tag handler for named block.",
             ParserError::UnexpectedBinaryOperator{..} => "Unexpected Binary Operator.",
+            ParserError::UnknownFilter{..} => "Unknown filter.",
+            ParserError::UnknownFunction{..} => "Unknown function.",
+            ParserError::InvalidCall{..} => "Invalid function call.",
             ParserError::UnexpectedToken{..} => "Unexpected Token.",
             ParserError::UnexpectedEof{..} => "Unexpected end of token stream.",
+            ParserError::Security(..) => "Security error.",
+            ParserError::InvalidModule{..} => "A node visitor removed or replaced the module node.",
         }
     }
 }
@@ -125,6 +154,15 @@ impl Display for ParserError {
                        name = n,
                        job = j)
             }
+            ParserError::UnknownFilter {
+                ref name, ref position
+            } => write!(f, " The filter {:?} at {} does not exist.", name, position),
+            ParserError::UnknownFunction {
+                ref name, ref position
+            } => write!(f, " The function {:?} at {} does not exist.", name, position),
+            ParserError::InvalidCall {
+                ref name, ref reason, ref position
+            } => write!(f, " The call of {:?} at {} is invalid: {}.", name, position, reason),
             ParserError::UnexpectedToken {
                 reason: r, expected: ref x, found: ref i
             } => {
@@ -153,6 +191,10 @@ impl Display for ParserError {
 
                 write!(f, " For {}.", cursor)
             }
+            ParserError::Security(ref e) => write!(f, " {}", e),
+            ParserError::InvalidModule {
+                ref name
+            } => write!(f, " For template {:?}.", name),
         }
     }
 }
@@ -167,6 +209,11 @@ pub enum NodeError {
         key: String,
         node_tag: String,
     },
+    NotCompilable {
+        template: String,
+        position: token::stream::Position,
+        reason: String,
+    },
 }
 
 impl Error for NodeError {
@@ -176,6 +223,7 @@ impl Error for NodeError {
                 "Unexptected node error (please report as bug with details)."
             }
             NodeError::AttributeNotFound{..} => "Attribute not found.",
+            NodeError::NotCompilable{..} => "Node cannot be compiled to Rust.",
         }
     }
 }
@@ -196,6 +244,15 @@ impl Display for NodeError {
                        key = key,
                        node = node_tag)
             }
+            NodeError::NotCompilable {
+                ref template, ref position, ref reason
+            } => {
+                write!(f,
+                       " In template {template:?} at {position}: {reason}.",
+                       template = template,
+                       position = position,
+                       reason = reason)
+            }
         }
     }
 }
@@ -9,11 +9,12 @@ use engine::node;
 use engine::Node;
 use engine::parser::job::Job;
 use engine::parser::ParserError;
-use extension::api::BinaryOperator;
-use extension::api::op::Precedence;
+use extension::api::op::{Precedence, Assoc};
 use engine::ExtensionRegistry;
-use engine::parser::token::{Token, Punctuation, BracketType};
-use std::rc::Rc;
+use engine::parser::token::{Token, Type, Punctuation, BracketType};
+use engine::parser::token::stream::{Item, Position};
+use runtime::Value;
+use std::sync::Arc;
 use api::error::{Traced, Dump, ErrorExt};
 
 #[derive(Debug)]
@@ -22,11 +23,11 @@ pub struct Expression; // dummy
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct ExpressionParser {
-    ext: Rc<ExtensionRegistry>,
+    ext: Arc<ExtensionRegistry>,
 }
 
 impl ExpressionParser {
-    pub fn new(ext: &Rc<ExtensionRegistry>) -> ExpressionParser {
+    pub fn new(ext: &Arc<ExtensionRegistry>) -> ExpressionParser {
         ExpressionParser { ext: ext.clone() }
     }
 
@@ -35,9 +36,9 @@ impl ExpressionParser {
                  job: &mut Job,
                  precedence: Precedence)
                  -> Result<Box<Node>, Traced<ParserError>> {
-        let expr = try_traced!(self.primary(job));
+        let mut expr = try_traced!(self.primary(job));
 
-        let _token = try_traced!(job.mut_cursor().peek_token().ok_or_else(|| {
+        try_traced!(job.mut_cursor().peek_token().ok_or_else(|| {
             ParserError::UnexpectedEof {
                 expected: None,
                 reason: Some("Found unclosed expression"),
@@ -46,27 +47,18 @@ impl ExpressionParser {
             .at(loc!())
         }));
 
-        // while self.is_binary(token) {
-        //     let operator = try_traced!(self.binary_operator(token));
-        //
-        //     if operator.prec >= precedence {
-        //         unimplemented!();//self.parser.stream().next();
-        //
-        //         // if operator.is_callable() {
-        //         //     expr = operator.call(self.parser, expr);
-        //         // } else {
-        //         //     // let rec_precedence = match operator.associativity {
-        //         //     //     Assoc::Left => operator.precedence + 1,
-        //         //     //     Assoc::Right => operator.precedence,
-        //         //     // }
-        //         //     // let rec_expr = self.parse(self);
-        //         //     // let class = operator.class();
-        //         //     unimplemented!() // orig: new $class()
-        //         // }
-        //         //
-        //         // token = self.parser.current_token();
-        //     }
-        // }
+        while let Some((operator, prec, assoc, position)) = self.binary_operator(job) {
+            if prec < precedence {
+                break;
+            }
+
+            job.mut_cursor().next_token();
+            let prec = match assoc {
+                Assoc::Left => Precedence(prec.0 + 1),
+                Assoc::Right => prec,
+            };
+            expr = try_traced!(self.binary(job, operator, expr, prec, &position));
+        }
 
         return Ok(match precedence {
             // Precedence(0) => self.parse_conditional_expression(&expr),
@@ -74,7 +66,88 @@ impl ExpressionParser {
         });
     }
 
+    /// Returns the binary operator of the next token, its precedence,
+    /// associativity and position - or `None`, if the next token is no operator.
+    ///
+    /// The range operator `..` is built in - `a..b` is a shortcut for `range(a, b)`.
+    fn binary_operator(&self, job: &mut Job) -> Option<(String, Precedence, Assoc, Position)> {
+        let item = match job.mut_cursor().peek() {
+            Some(item) => item,
+            None => return None,
+        };
+
+        let repr = match *item.token() {
+            Token::Operator(ref x) | Token::Name(ref x) => x,
+            _ => return None,
+        };
+
+        if *item.token() == Token::Operator("..".to_string()) {
+            return Some((repr.clone(), Precedence(25), Assoc::Left, item.position().clone()));
+        }
+
+        self.ext.operators_binary().get(repr).map(|operator| {
+            (repr.clone(), operator.prec.clone(), operator.assoc.clone(), item.position().clone())
+        })
+    }
+
+    /// Returns the unary operator of the token and its precedence - or `None`,
+    /// if the token is no unary operator.
+    fn unary_operator(&self, item: &Item) -> Option<(String, Precedence)> {
+        match *item.token() {
+            Token::Operator(ref x) | Token::Name(ref x) => {
+                let operator = self.ext.operators_unary().get(x);
+
+                operator.map(|operator| (x.clone(), operator.prec.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses the right operand with at least `precedence` and returns the
+    /// binary expression.
+    fn binary(&self,
+              job: &mut Job,
+              operator: String,
+              left: Box<Node>,
+              precedence: Precedence,
+              position: &Position)
+              -> Result<Box<Node>, Traced<ParserError>> {
+        if operator == ".." {
+            if let Some(item) = job.mut_cursor().peek() {
+                match *item.token() {
+                    Token::Operator(_) |
+                    Token::Punctuation(Punctuation::ClosingBracket(_)) |
+                    Token::Punctuation(Punctuation::Comma) |
+                    Token::Punctuation(Punctuation::Colon) |
+                    Token::BlockEnd |
+                    Token::ExpressionEnd => {
+                        return traced_err!(ParserError::UnexpectedToken {
+                            reason: Some("The range operator (..) needs an upper bound"),
+                            expected: "an expression".to_string(),
+                            found: item.dump(),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let right = try_traced!(self.parse(job, precedence));
+
+        if operator == ".." {
+            return Ok(node::expression::Function::boxed("range".to_string(),
+                                                        vec![left, right],
+                                                        position));
+        }
+
+        Ok(node::expression::Binary::boxed(operator, left, right, position))
+    }
+
     pub fn primary(&self, job: &mut Job) -> Result<Box<Node>, Traced<ParserError>> {
+        if let Some(arrow) = try_traced!(self.parse_arrow(job)) {
+            return Ok(arrow);
+        }
+
         match *try_traced!(job.mut_cursor().peek_token().ok_or_else(|| {
             ParserError::UnexpectedEof {
                 expected: None,
@@ -83,41 +156,86 @@ impl ExpressionParser {
             }
             .at(loc!())
         })) {
-            Token::Operator(ref _op) => unimplemented!(),
-            Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round)) => unimplemented!(),
+            Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round)) => {
+                job.mut_cursor().next_token();
+                let expr = try_traced!(self.parse(job, Precedence(0)));
+                try_traced!(job.mut_cursor()
+                               .next_expect(Token::Punctuation(Punctuation::ClosingBracket(BracketType::Round)),
+                                            Some("An opened parenthesis is not properly closed")));
+
+                return self.parse_postfix_expression(job, expr);
+            }
             _ => {}
         }
 
         return self.parse_primary_expression(job);
     }
 
-    pub fn is_binary(&self, _token: &Token) -> bool {
-        // TODO: refactor
-        // a) move this to token
-        // b) or merge with binary_operator() below to `binary_operator -> Result<Option<>,>`, so `try_traced!(self.binary_operator(token))` will yield an Option, which we can use in loop `while let Some(binary) = try_traced!(self.binary_operator(token))`
-        unimplemented!()
-    }
+    /// Parses an arrow function, e.g. `v => v|upper` or `(v, k) => k`.
+    ///
+    /// Returns `None` without consuming any token, if the next tokens do not
+    /// form an arrow function.
+    fn parse_arrow(&self, job: &mut Job) -> Result<Option<Box<Node>>, Traced<ParserError>> {
+        let arrow = Token::Operator("=>".to_string());
+        let comma = Token::Punctuation(Punctuation::Comma);
+        let round_open = Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round));
+        let round_close = Token::Punctuation(Punctuation::ClosingBracket(BracketType::Round));
 
-    pub fn binary_operator(&self, _token: &Token) -> Result<&BinaryOperator, Traced<ParserError>> {
-        // TODO refactor
-        // merge with `is_binary` above - we may then get rid of these errors (!)
-        unimplemented!()
-        // let name = try_traced!(token.value().ok_or_else(|| {
-        //     ParserError::Unreachable {
-        //         reason: format!("Could not parse binary operator, because the token type {type:?} has no value",
-        //         type = token.get_type()),
-        //         job: ()
-        //     }.at(loc!())
-        // }));
-        //
-        // let operator = try_traced!(self.ext.operators_binary().get(&name).ok_or_else(|| {
-        //     ParserError::UnexpectedBinaryOperator {
-        //         name: name.to_string(),
-        //         job: unimplemented!()
-        //     }.at(loc!())
-        // }));
-        //
-        // Ok(operator)
+        // first, determine if we are parsing an arrow function by finding `=>`
+        let short = match job.mut_cursor().look(0) {
+            Some(&Token::Name(_)) => {
+                if job.mut_cursor().look(1) != Some(&arrow) {
+                    return Ok(None);
+                }
+                true
+            }
+            Some(token) if *token == round_open => {
+                let mut i = 2;
+                while job.mut_cursor().look(i) == Some(&comma) {
+                    i += 2;
+                }
+                if job.mut_cursor().look(i) != Some(&round_close) ||
+                   job.mut_cursor().look(i + 1) != Some(&arrow) {
+                    return Ok(None);
+                }
+                false
+            }
+            _ => return Ok(None),
+        };
+
+        let position = match job.mut_cursor().peek() {
+            Some(item) => item.position().clone(),
+            None => return Ok(None),
+        };
+
+        if !short {
+            job.mut_cursor().next_token();
+        }
+
+        let mut params = Vec::new();
+        loop {
+            let item = try_traced!(job.mut_cursor()
+                                      .next_expect(Type::Name,
+                                                   Some("An arrow function argument must be a \
+                                                         name")));
+            params.push(item.token().value_as_str().unwrap_or_default().to_string());
+
+            if short || job.mut_cursor().peek_token() != Some(&comma) {
+                break;
+            }
+            job.mut_cursor().next_token();
+        }
+
+        if !short {
+            try_traced!(job.mut_cursor().next_expect(round_close,
+                                                     Some("An arrow function argument list \
+                                                           must be closed by a parenthesis")));
+        }
+        try_traced!(job.mut_cursor().next_expect(arrow, Some("Expected an arrow function")));
+
+        let body = try_traced!(self.parse(job, Precedence(0)));
+
+        Ok(Some(node::expression::Arrow::boxed(params, body, &position)))
     }
 
     pub fn parse_conditional_expression(&self, _expr: &Box<Node>) -> Box<Node> {
@@ -140,40 +258,159 @@ impl ExpressionParser {
             .at(loc!())
         }));
 
+        if let Some((operator, prec)) = self.unary_operator(item) {
+            job.mut_cursor().next_token();
+            let operand = try_traced!(self.parse(job, prec));
+            let unary = node::expression::Unary::boxed(operator, operand, item.position());
+
+            return self.parse_postfix_expression(job, unary);
+        }
+
         let node: Box<Node> = match *item.token() {
             Token::Name(ref value) => {
                 job.mut_cursor().next_token();
 
                 match value.as_ref() {
-                    "true" | "TRUE" => unimplemented!(),
-                    "false" | "FALSE" => unimplemented!(),
-                    "none" | "NONE" | "null" | "NULL" => unimplemented!(),
+                    "true" | "TRUE" => self.constant(Value::Boolean(true), item.position()),
+                    "false" | "FALSE" => self.constant(Value::Boolean(false), item.position()),
+                    "none" | "NONE" | "null" | "NULL" => self.constant(Value::Null, item.position()),
                     _ => if job.mut_cursor().peek_token() ==
                         Some(&Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round))) {
-                            unimplemented!()
+                            try_traced!(self.parse_function_expression(job, value, item.position()))
                         } else {
                             node::expression::Name::boxed(value.clone(), item.position())
                         },
                 }
             }
-            Token::IntegerNumber(_) => unimplemented!(),
-            Token::FloatingNumber(_) => unimplemented!(),
-            Token::String(_) => unimplemented!(),
-            Token::_InterpolationStart => unimplemented!(),
-            Token::Operator(_) => unimplemented!(),
-            Token::Punctuation(_) => unimplemented!(),
+            Token::IntegerNumber(x) => {
+                job.mut_cursor().next_token();
+                self.constant(Value::Integer(x as i64), item.position())
+            }
+            Token::FloatingNumber(x) => {
+                job.mut_cursor().next_token();
+                self.constant(Value::Float(x), item.position())
+            }
+            Token::String(ref x) => {
+                job.mut_cursor().next_token();
+                self.constant(Value::String(x.clone()), item.position())
+            }
+            Token::Punctuation(Punctuation::OpeningBracket(BracketType::Square)) => {
+                try_traced!(self.parse_array_expression(job))
+            }
+            Token::Punctuation(Punctuation::OpeningBracket(BracketType::Curly)) => {
+                try_traced!(self.parse_hash_expression(job))
+            }
+            Token::Operator(ref x) if x == ".." => {
+                return traced_err!(ParserError::UnexpectedToken {
+                    reason: Some("The range operator (..) needs a lower bound"),
+                    expected: "an expression".to_string(),
+                    found: item.dump(),
+                })
+            }
+            Token::_InterpolationStart |
+            Token::Operator(_) |
+            Token::Punctuation(_) |
             Token::_Eof |
             Token::Text(_) |
             Token::_InterpolationEnd |
             Token::BlockStart |
             Token::ExpressionStart |
             Token::BlockEnd |
-            Token::ExpressionEnd => unimplemented!(),
+            Token::ExpressionEnd => {
+                return traced_err!(ParserError::UnexpectedToken {
+                    reason: Some("Expected to find an expression"),
+                    expected: "an expression".to_string(),
+                    found: item.dump(),
+                })
+            }
         };
 
         self.parse_postfix_expression(job, node)
     }
 
+    /// Parses a sequence literal, e.g. `[1, 'two', three]`.
+    fn parse_array_expression(&self, job: &mut Job) -> Result<Box<Node>, Traced<ParserError>> {
+        let square_open = Token::Punctuation(Punctuation::OpeningBracket(BracketType::Square));
+        let square_close = Token::Punctuation(Punctuation::ClosingBracket(BracketType::Square));
+
+        let item = try_traced!(job.mut_cursor()
+                                  .next_expect(square_open,
+                                               Some("A sequence element was expected")));
+
+        let mut values = Vec::new();
+        while job.mut_cursor().peek_token() != Some(&square_close) {
+            if !values.is_empty() {
+                try_traced!(job.mut_cursor()
+                               .next_expect(Token::Punctuation(Punctuation::Comma),
+                                            Some("A sequence element must be followed by a \
+                                                  comma")));
+
+                // trailing comma
+                if job.mut_cursor().peek_token() == Some(&square_close) {
+                    break;
+                }
+            }
+
+            values.push(try_traced!(self.parse(job, Precedence(0))));
+        }
+
+        try_traced!(job.mut_cursor().next_expect(square_close,
+                                                 Some("An opened sequence is not properly \
+                                                       closed")));
+
+        Ok(node::expression::Array::sequence(values, item.position()))
+    }
+
+    /// Parses a hash literal, e.g. `{foo: 1, 'bar': 2, 3: baz}`.
+    ///
+    /// Keys may be names, strings or numbers - names are taken literally.
+    fn parse_hash_expression(&self, job: &mut Job) -> Result<Box<Node>, Traced<ParserError>> {
+        let curly_open = Token::Punctuation(Punctuation::OpeningBracket(BracketType::Curly));
+        let curly_close = Token::Punctuation(Punctuation::ClosingBracket(BracketType::Curly));
+
+        let item = try_traced!(job.mut_cursor()
+                                  .next_expect(curly_open, Some("A hash element was expected")));
+
+        let mut pairs = Vec::new();
+        while job.mut_cursor().peek_token() != Some(&curly_close) {
+            if !pairs.is_empty() {
+                try_traced!(job.mut_cursor()
+                               .next_expect(Token::Punctuation(Punctuation::Comma),
+                                            Some("A hash value must be followed by a comma")));
+
+                // trailing comma
+                if job.mut_cursor().peek_token() == Some(&curly_close) {
+                    break;
+                }
+            }
+
+            let key = match job.mut_cursor().peek() {
+                Some(key) => {
+                    match *key.token() {
+                        Token::Name(ref name) => {
+                            job.mut_cursor().next_token();
+                            self.constant(Value::String(name.clone()), key.position())
+                        }
+                        _ => try_traced!(self.parse_primary_expression(job)),
+                    }
+                }
+                None => try_traced!(self.parse_primary_expression(job)),
+            };
+
+            try_traced!(job.mut_cursor()
+                           .next_expect(Token::Punctuation(Punctuation::Colon),
+                                        Some("A hash key must be followed by a colon (:)")));
+
+            pairs.push(key);
+            pairs.push(try_traced!(self.parse(job, Precedence(0))));
+        }
+
+        try_traced!(job.mut_cursor().next_expect(curly_close,
+                                                 Some("An opened hash is not properly closed")));
+
+        Ok(node::expression::Array::hash(pairs, item.position()))
+    }
+
     fn parse_postfix_expression(&self,
                                 job: &mut Job,
                                 node: Box<Node>)
@@ -203,17 +440,284 @@ impl ExpressionParser {
         return Ok(node);
     }
 
+    /// Parses an attribute access - `a.b`, `a.b(args)` or `a[b]`.
     fn parse_subscript_expression(&self,
-                                  _job: &mut Job,
-                                  _node: Box<Node>)
+                                  job: &mut Job,
+                                  node: Box<Node>)
                                   -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+        use engine::node::expression::get_attr::Kind;
+
+        let item = try_traced!(job.mut_cursor().next().ok_or_else(|| {
+            ParserError::UnexpectedEof {
+                expected: None,
+                reason: Some("Expected an attribute"),
+                cursor: job.mut_cursor().dump(),
+            }
+            .at(loc!())
+        }));
+        let round_open = Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round));
+
+        if *item.token() == Token::Punctuation(Punctuation::Dot) {
+            let attribute = try_traced!(job.mut_cursor().next().ok_or_else(|| {
+                ParserError::UnexpectedEof {
+                    expected: None,
+                    reason: Some("Expected an attribute name after \".\""),
+                    cursor: job.mut_cursor().dump(),
+                }
+                .at(loc!())
+            }));
+            let name = match *attribute.token() {
+                Token::Name(ref x) => Value::String(x.clone()),
+                Token::IntegerNumber(x) => Value::Integer(x as i64),
+                _ => {
+                    return traced_err!(ParserError::UnexpectedToken {
+                        reason: Some("Expected an attribute name after \".\""),
+                        expected: format!("{:?}", Type::Name),
+                        found: attribute.dump(),
+                    })
+                }
+            };
+            let method = name.as_str().map(|x| x.to_string());
+            let name = self.constant(name, attribute.position());
+
+            if job.mut_cursor().peek_token() == Some(&round_open) {
+                let args = try_traced!(self.parse_arguments(job));
+
+                if let (Some(template), Some(method)) = (imported_template(job, &*node), method) {
+                    return Ok(self.method_call(template, method, args, item.position()));
+                }
+
+                return Ok(node::expression::GetAttr::boxed(node,
+                                                           name,
+                                                           args,
+                                                           Kind::Method,
+                                                           item.position()));
+            }
+
+            return Ok(node::expression::GetAttr::boxed(node, name, vec![], Kind::Any, item.position()));
+        }
+
+        let attribute = try_traced!(self.parse(job, Precedence(0)));
+        try_traced!(job.mut_cursor()
+                       .next_expect(Token::Punctuation(Punctuation::ClosingBracket(BracketType::Square)),
+                                    Some("An opened bracket must be closed")));
+
+        Ok(node::expression::GetAttr::boxed(node, attribute, vec![], Kind::Array, item.position()))
     }
 
     fn parse_filter_expression(&self,
-                               _job: &mut Job,
-                               _node: Box<Node>)
+                               job: &mut Job,
+                               node: Box<Node>)
                                -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+        let mut node = node;
+
+        while let Some(&Token::Punctuation(Punctuation::VerticalBar)) = job.mut_cursor()
+                                                                           .peek_token() {
+            job.mut_cursor().next_token();
+
+            let item = try_traced!(job.mut_cursor()
+                                      .next_expect(Type::Name, Some("Expected a filter name")));
+            let name = item.token().value_as_str().unwrap_or_default();
+
+            let filter = try_traced!(self.ext.filters().get(name).ok_or_else(|| {
+                ParserError::UnknownFilter {
+                    name: name.to_string(),
+                    position: item.position().clone(),
+                }
+                .at(loc!())
+            }));
+
+            try_traced!(job.check_filter(name, item.position()));
+
+            let args = if job.mut_cursor().peek_token() ==
+                          Some(&Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round))) {
+                try_traced!(self.parse_arguments(job))
+            } else {
+                Vec::new()
+            };
+
+            if let Some(strategy) = filter.pre_escape() {
+                if !node::expression::is_safe_for(&*node, strategy) {
+                    let strategy = try_traced!(job.parser().strategy(strategy).ok_or_else(|| {
+                        ParserError::Unreachable {
+                            reason: format!("Unknown escaping strategy {:?} for filter {:?}",
+                                            strategy,
+                                            name),
+                            job: job.dump(),
+                        }
+                        .at(loc!())
+                    }));
+                    node = node::expression::Escape::boxed(node, strategy, item.position());
+                }
+            }
+
+            let safe_for = if filter.preserves_safety() {
+                node::expression::safe_for(&*node)
+            } else {
+                filter.is_safe(&constants(&args))
+            };
+
+            node = node::expression::Filter::boxed(name.to_string(), node, args, item.position());
+            node::expression::set_safe_for(&mut *node, &safe_for);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_function_expression(&self,
+                                 job: &mut Job,
+                                 name: &str,
+                                 position: &Position)
+                                 -> Result<Box<Node>, Traced<ParserError>> {
+        match name {
+            "parent" => return self.parse_parent_expression(job, position),
+            "block" => return self.parse_block_reference_expression(job, position),
+            _ => {}
+        }
+
+        if let Some((template, method)) = job.imported_macro(name)
+                                              .map(|(x, y)| (x.to_string(), y.to_string())) {
+            let args = try_traced!(self.parse_arguments(job));
+            return Ok(self.method_call(template, method, args, position));
+        }
+
+        let function = try_traced!(self.ext.functions().get(name).ok_or_else(|| {
+            ParserError::UnknownFunction {
+                name: name.to_string(),
+                position: position.clone(),
+            }
+            .at(loc!())
+        }));
+
+        try_traced!(job.check_function(name, position));
+
+        let args = try_traced!(self.parse_arguments(job));
+        let safe_for = function.is_safe(&constants(&args));
+
+        let mut node: Box<Node> = node::expression::Function::boxed(name.to_string(), args, position);
+        node::expression::set_safe_for(&mut *node, &safe_for);
+
+        Ok(node)
+    }
+
+    /// Parses `parent()` - the parent definition of the enclosing block.
+    fn parse_parent_expression(&self,
+                               job: &mut Job,
+                               position: &Position)
+                               -> Result<Box<Node>, Traced<ParserError>> {
+        let args = try_traced!(self.parse_arguments(job));
+
+        let reason = match job.peek_block_stack() {
+            _ if !args.is_empty() => "it takes no arguments",
+            None => "it must be called within a block",
+            Some(_) if !job.has_parent() => {
+                "it must be called in a template, which extends another one"
+            }
+            Some(block) => {
+                let mut node: Box<Node> = node::expression::Parent::boxed(block.to_string(),
+                                                                         position);
+                node.set_attribute(node::expression::SAFE, "all");
+                return Ok(node);
+            }
+        };
+
+        traced_err!(ParserError::InvalidCall {
+            name: "parent".to_string(),
+            reason: reason.to_string(),
+            position: position.clone(),
+        })
+    }
+
+    /// Parses `block(name)` - the output of the block.
+    fn parse_block_reference_expression(&self,
+                                        job: &mut Job,
+                                        position: &Position)
+                                        -> Result<Box<Node>, Traced<ParserError>> {
+        let mut args = try_traced!(self.parse_arguments(job));
+        if args.len() != 1 {
+            return traced_err!(ParserError::InvalidCall {
+                name: "block".to_string(),
+                reason: "it takes the name of the block as argument".to_string(),
+                position: position.clone(),
+            });
+        }
+
+        let mut node: Box<Node> = node::expression::BlockReference::boxed(args.remove(0),
+                                                                          position);
+        node.set_attribute(node::expression::SAFE, "all");
+
+        Ok(node)
+    }
+
+    /// Returns the call of an imported macro - the output is safe.
+    fn method_call(&self,
+                   template: String,
+                   method: String,
+                   args: Vec<Box<Node>>,
+                   position: &Position)
+                   -> Box<Node> {
+        let template = self.constant(Value::String(template), position);
+
+        let mut node: Box<Node> = node::expression::MethodCall::boxed(template,
+                                                                      method,
+                                                                      args,
+                                                                      position);
+        node.set_attribute(node::expression::SAFE, "all");
+
+        node
+    }
+
+    /// Parses a parenthesized, comma separated list of expressions.
+    pub fn parse_arguments(&self, job: &mut Job) -> Result<Vec<Box<Node>>, Traced<ParserError>> {
+        let round_open = Token::Punctuation(Punctuation::OpeningBracket(BracketType::Round));
+        let round_close = Token::Punctuation(Punctuation::ClosingBracket(BracketType::Round));
+
+        try_traced!(job.mut_cursor().next_expect(round_open,
+                                                 Some("A list of arguments must begin with an \
+                                                       opening parenthesis")));
+
+        let mut args = Vec::new();
+        while job.mut_cursor().peek_token() != Some(&round_close) {
+            if !args.is_empty() {
+                try_traced!(job.mut_cursor()
+                               .next_expect(Token::Punctuation(Punctuation::Comma),
+                                            Some("Arguments must be separated by a comma")));
+            }
+
+            args.push(try_traced!(self.parse(job, Precedence(0))));
+        }
+
+        try_traced!(job.mut_cursor().next_expect(round_close,
+                                                 Some("A list of arguments must be closed by a \
+                                                       parenthesis")));
+
+        Ok(args)
+    }
+
+    fn constant(&self, value: Value, position: &Position) -> Box<Node> {
+        let mut node: Box<Node> = node::expression::Constant::boxed(value, position);
+        node.set_attribute(node::expression::SAFE, "all");
+
+        node
     }
 }
+
+/// Returns the values of constant arguments, e.g. for `Filter::is_safe()`.
+fn constants(args: &[Box<Node>]) -> Vec<Option<Value>> {
+    args.iter().map(|arg| arg.constant().cloned()).collect()
+}
+
+/// Returns the name of the template imported as `node`, e.g. `forms` of
+/// `{% import 'forms.html' as forms %}` or `_self`.
+fn imported_template(job: &Job, node: &Node) -> Option<String> {
+    let name = match node.as_any().downcast_ref::<node::expression::Name>() {
+        Some(name) => name.key(),
+        None => return None,
+    };
+
+    if name == "_self" {
+        return Some(job.template_name().to_string());
+    }
+
+    job.imported_template(name).map(|x| x.to_string())
+}
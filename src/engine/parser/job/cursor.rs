@@ -81,6 +81,13 @@ impl<'stream> Cursor<'stream> {
         self.peek().map(|item| item.token())
     }
 
+    /// Returns the token `offset` positions after the next one, i.e. `look(0) == peek_token()`.
+    ///
+    /// orig: look
+    pub fn look(&self, offset: usize) -> Option<&'stream Token> {
+        self.stream.as_vec().get(self.next + offset).map(|item| item.token())
+    }
+
     pub fn peek_pos(&self) -> Option<&'stream stream::Position> {
         self.peek().map(|item| item.position())
     }
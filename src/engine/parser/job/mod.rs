@@ -13,8 +13,13 @@ use engine::node;
 use extension::api::op::Precedence;
 use extension::api::token_parser::{Test, TestResult};
 use std::iter;
+use std::mem;
+use std::collections::HashMap;
 use template;
 use engine::Node;
+use extension::escaper::Strategy;
+use extension::sandbox::SecurityPolicy;
+use std::sync::Arc;
 
 pub mod cursor;
 pub use self::cursor::Cursor;
@@ -34,17 +39,22 @@ pub struct Job<'p, 'stream> {
     state: State,
     stack: Vec<State>,
     template: &'stream template::Raw,
+    autoescape: Vec<Option<Strategy>>,
+    sandboxed: Vec<bool>,
 }
 
 #[allow(dead_code)]
 #[derive(Default, Debug)]
 pub struct State {
-    parent: Option<()>,
-    blocks: Vec<()>,
-    block_stack: Vec<()>,
-    macros: Vec<()>,
+    parent: Option<Box<Node>>,
+    blocks: Vec<Box<Node>>,
+    block_stack: Vec<String>,
+    macros: Vec<Box<Node>>,
     reserved_macro_names: Vec<()>,
-    imported_symbols: Vec<Vec<()>>,
+    /// Aliases of `{% import %}` - the names of the templates.
+    imported_templates: HashMap<String, String>,
+    /// Aliases of `{% from %}` - the names of the templates and macros.
+    imported_macros: HashMap<String, (String, String)>,
     traits: Vec<()>,
     embedded_templates: Vec<()>,
 }
@@ -59,6 +69,10 @@ impl<'p, 'stream> Job<'p, 'stream> {
             state: State::default(),
             stack: Vec::new(),
             template: tokens.template(),
+            autoescape: vec![parser.autoescape()
+                                   .strategy(tokens.template().name(), parser.escapers())],
+            sandboxed: vec![parser.sandbox()
+                                  .is_some_and(|x| x.is_sandboxed(tokens.template().name()))],
         }
     }
 
@@ -70,44 +84,85 @@ impl<'p, 'stream> Job<'p, 'stream> {
         self.do_parse(Some(test))
     }
 
+    /// Parses the module - without applying the node visitors.
+    pub fn parse_module(mut self) -> Result<node::Module, Traced<ParserError>> {
+        self.do_parse_module(None)
+    }
+
     #[allow(unused_mut)]
     #[allow(dead_code)] // TODO: testcase
     fn do_parse(mut self, test: Option<&Test>) -> Result<template::Compiled, Traced<ParserError>> {
+        let module = try_traced!(self.do_parse_module(test));
+        let policy = self.policy().cloned();
+
+        self.parser.compile(module, policy)
+    }
+
+    fn do_parse_module(&mut self,
+                       test: Option<&Test>)
+                       -> Result<node::Module, Traced<ParserError>> {
 
         // NOTE: try to move this to other point
         //  - to avoid very first redundant push?
         //  - Better have unit tests forst
-        self.stack.push(self.state);
-        self.state = State::default();
+        let state = mem::take(&mut self.state);
+        self.stack.push(state);
 
         let nodes = match self.do_sub_parse(test) {
             Err(e) => return Err(e),
             Ok(nodes) => {
                 if self.state.parent.is_some() {
-                    unimplemented!()
-                    // self.filter_body_nodes(body).unwrap_or_else(|| node::Virtual::boxed())
+                    try_traced!(self.filter_body_nodes(nodes))
                 } else {
                     nodes
                 }
             }
         };
 
+        let state = mem::replace(&mut self.state, self.stack.pop().unwrap());
         let module = node::Module::new(node::Body::boxed(nodes),
-                                       self.state.parent,
-                                       self.state.blocks, // as nodes?
-                                       self.state.macros, // as nodes?
-                                       self.state.traits, // as nodes?
-                                       self.state.embedded_templates,
+                                       state.parent,
+                                       state.blocks,
+                                       state.macros,
+                                       state.traits, // as nodes?
+                                       state.embedded_templates,
                                        self.template.name());
-        self.state = self.stack.pop().unwrap();
 
-        // *IMPORTANT TODO*: move initialisation somewhere else(!)
-        // let traverser = NodeTraverser::new(engine, self.visitors);
-        // module = traverser.traverse(module);
+        return Ok(module);
+    }
 
-        let compiled = template::Compiled::new(module);
+    /// Removes the output of a template, which extends another one - only its
+    /// blocks are displayed.
+    ///
+    /// # Failures
+    /// * When there is text outside of blocks
+    fn filter_body_nodes(&mut self,
+                         nodes: Vec<Box<Node>>)
+                         -> Result<Vec<Box<Node>>, Traced<ParserError>> {
+        let mut filtered = Vec::new();
+        for node in nodes {
+            let output = if let Some(text) = node.as_any().downcast_ref::<node::Text>() {
+                if !text.text().trim().is_empty() {
+                    return traced_err!(ParserError::TokenParserError {
+                        tag: "extends",
+                        error: format!("A template that extends another one cannot include \
+                                        content outside Twig blocks at {}. Did you forget to \
+                                        put the content inside a {{% block %}} tag?",
+                                       text.position()),
+                        job: self.dump(),
+                    });
+                }
+                true
+            } else {
+                node.as_any().is::<node::Print>() || node.as_any().is::<node::BlockReference>()
+            };
 
-        return Ok(compiled);
+            if !output {
+                filtered.push(node);
+            }
+        }
+
+        Ok(filtered)
     }
 
     pub fn sub_parse(&mut self) -> Result<Vec<Box<Node>>, Traced<ParserError>> {
@@ -131,6 +186,7 @@ impl<'p, 'stream> Job<'p, 'stream> {
                     let node = try_traced!(self.parse_expression(Precedence(0)));
                     try_traced!(self.cursor.next_expect(Token::ExpressionEnd, None));
 
+                    let node = self.escape(node, item.position());
                     nodes.push(node::Print::boxed(node, item.position()));
                 }
                 Token::BlockStart => {
@@ -167,6 +223,7 @@ impl<'p, 'stream> Job<'p, 'stream> {
                             unreachable!()
                         };
 
+                        try_traced!(self.check_tag(tag, item.position()));
                         try_traced!(self.parser
                                         .tag_handler(tag)
                                         .ok_or_else(|| {
@@ -218,6 +275,165 @@ impl<'p, 'stream> Job<'p, 'stream> {
         // }
     }
 
+    /// Returns the active escaping strategy.
+    pub fn autoescape(&self) -> Option<&Strategy> {
+        self.autoescape.last().and_then(|x| x.as_ref())
+    }
+
+    /// Sets the escaping strategy for a nested section, e.g. `{% autoescape 'js' %}`.
+    pub fn push_autoescape(&mut self, strategy: Option<Strategy>) {
+        self.autoescape.push(strategy)
+    }
+
+    /// Restores the escaping strategy of the enclosing section.
+    pub fn pop_autoescape(&mut self) -> Option<Strategy> {
+        self.autoescape.pop().and_then(|x| x)
+    }
+
+    /// Wraps the expression with an escape node according to the active strategy.
+    ///
+    /// Expressions which are known to be safe at compile time are not wrapped.
+    pub fn escape(&self, expr: Box<Node>, position: &token::stream::Position) -> Box<Node> {
+        match self.autoescape() {
+            Some(strategy) if !node::expression::is_safe_for(&*expr, strategy.name()) => {
+                node::expression::Escape::boxed(expr, strategy.clone(), position)
+            }
+            _ => expr,
+        }
+    }
+
+    /// Returns the security policy, if the active section is sandboxed.
+    pub fn policy(&self) -> Option<&Arc<SecurityPolicy>> {
+        match self.sandboxed.last() {
+            Some(&true) => self.parser.sandbox().map(|x| x.policy()),
+            _ => None,
+        }
+    }
+
+    /// Sandboxes a nested section, e.g. `{% sandbox %}`.
+    pub fn push_sandbox(&mut self, sandboxed: bool) {
+        self.sandboxed.push(sandboxed)
+    }
+
+    /// Restores the sandbox mode of the enclosing section.
+    pub fn pop_sandbox(&mut self) -> bool {
+        self.sandboxed.pop().unwrap_or(false)
+    }
+
+    /// Checks the tag against the security policy, if the section is sandboxed.
+    pub fn check_tag(&self,
+                     tag: &str,
+                     position: &token::stream::Position)
+                     -> Result<(), Traced<ParserError>> {
+        if let Some(policy) = self.policy() {
+            try_traced!(policy.check_tag(tag, position));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the filter against the security policy, if the section is sandboxed.
+    pub fn check_filter(&self,
+                        filter: &str,
+                        position: &token::stream::Position)
+                        -> Result<(), Traced<ParserError>> {
+        if let Some(policy) = self.policy() {
+            try_traced!(policy.check_filter(filter, position));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the function against the security policy, if the section is sandboxed.
+    pub fn check_function(&self,
+                          function: &str,
+                          position: &token::stream::Position)
+                          -> Result<(), Traced<ParserError>> {
+        if let Some(policy) = self.policy() {
+            try_traced!(policy.check_function(function, position));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name of the template being parsed.
+    pub fn template_name(&self) -> &str {
+        self.template.name()
+    }
+
+    /// Sets the parent of the template, i.e. `{% extends %}`.
+    ///
+    /// Returns false if the template already has a parent.
+    pub fn set_parent(&mut self, parent: Box<Node>) -> bool {
+        if self.state.parent.is_some() {
+            return false;
+        }
+
+        self.state.parent = Some(parent);
+        true
+    }
+
+    pub fn has_parent(&self) -> bool {
+        self.state.parent.is_some()
+    }
+
+    /// Adds the definition of a block - see `node::Block`.
+    pub fn add_block(&mut self, block: Box<node::Block>) {
+        self.state.blocks.push(block)
+    }
+
+    /// Returns the position of the block `name`, if it is defined.
+    pub fn block_position(&self, name: &str) -> Option<&token::stream::Position> {
+        self.state
+            .blocks
+            .iter()
+            .filter_map(|x| x.as_any().downcast_ref::<node::Block>())
+            .find(|x| x.name() == name)
+            .map(|x| x.position())
+    }
+
+    /// Enters the block `name`.
+    pub fn push_block_stack(&mut self, name: &str) {
+        self.state.block_stack.push(name.to_string())
+    }
+
+    /// Leaves the innermost block.
+    pub fn pop_block_stack(&mut self) -> Option<String> {
+        self.state.block_stack.pop()
+    }
+
+    /// Returns the name of the innermost block, if any.
+    pub fn peek_block_stack(&self) -> Option<&str> {
+        self.state.block_stack.last().map(|x| &x[..])
+    }
+
+    /// Adds the definition of a macro - see `node::Macro`.
+    pub fn add_macro(&mut self, macro_: Box<node::Macro>) {
+        self.state.macros.push(macro_)
+    }
+
+    /// Makes the macros of the template available as `alias.macro()`.
+    pub fn add_imported_template(&mut self, alias: &str, template: &str) {
+        self.state.imported_templates.insert(alias.to_string(), template.to_string());
+    }
+
+    /// Returns the name of the template imported as `alias`.
+    pub fn imported_template(&self, alias: &str) -> Option<&str> {
+        self.state.imported_templates.get(alias).map(|x| &x[..])
+    }
+
+    /// Makes the macro of the template available as `alias()`.
+    pub fn add_imported_macro(&mut self, alias: &str, template: &str, name: &str) {
+        self.state
+            .imported_macros
+            .insert(alias.to_string(), (template.to_string(), name.to_string()));
+    }
+
+    /// Returns the names of the template and the macro imported as `alias`.
+    pub fn imported_macro(&self, alias: &str) -> Option<(&str, &str)> {
+        self.state.imported_macros.get(alias).map(|&(ref x, ref y)| (&x[..], &y[..]))
+    }
+
     pub fn parser(&self) -> &Parser {
         self.parser
     }
@@ -7,6 +7,7 @@
 
 use std::fmt;
 use template;
+use engine::parser::token::stream;
 use api::error::Dump;
 
 pub type Position = usize;
@@ -104,6 +105,11 @@ impl<'a> Cursor<'a> {
         self.pos
     }
 
+    /// Returns true if `pos` is at the beginning of a line.
+    pub fn is_line_start(&self, pos: Position) -> bool {
+        pos == 0 || self.template.code[..pos].ends_with('\n')
+    }
+
     pub fn line(&self) -> usize {
         self.line
         // Alternative: self.template.code[0..self.pos].chars().filter(|c| c == '\n').count() + 1;
@@ -118,6 +124,14 @@ impl<'a> Cursor<'a> {
     pub fn set_line(&mut self, line: usize) {
         self.line = line;
     }
+
+    /// Returns line and column of the cursor as token stream position.
+    pub fn stream_position(&self) -> stream::Position {
+        stream::Position {
+            line: self.line(),
+            column: self.column(),
+        }
+    }
 }
 
 impl<'a> fmt::Display for Cursor<'a> {
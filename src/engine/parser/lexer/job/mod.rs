@@ -81,6 +81,14 @@ impl<'c, 't> Job<'c, 't> {
         self.tokens.push(token, &self.cursor);
     }
 
+    /// Pushes a token at a previously recorded position.
+    ///
+    /// Text tokens are recorded where they begin - not where the cursor ends up
+    /// after the text (and any trimmed whitespace) has been consumed.
+    pub fn push_token_at(&mut self, token: token::Token, position: token::stream::Position) {
+        self.tokens.push_at(token, position);
+    }
+
     pub fn cursor(&self) -> &Cursor<'t> {
         &self.cursor
     }
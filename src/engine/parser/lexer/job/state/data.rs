@@ -9,7 +9,7 @@ use super::{TokenizeState, Code};
 use engine::parser::lexer::job::state;
 use engine::parser::lexer::job::Job;
 use engine::parser::token::Token;
-use engine::parser::lexer::patterns::{token_start, verbatim_start, Extract};
+use engine::parser::lexer::patterns::{self, token_start, verbatim_start, Extract};
 use engine::parser::lexer::{LexerError, SyntaxError};
 use api::error::{Traced, Dump};
 
@@ -22,24 +22,24 @@ impl TokenizeState for Data {
     }
 
     fn tokenize<'a>(job: &'a mut Job) -> Result<(), Traced<LexerError>> {
+        let position = job.cursor.stream_position();
+
         let capture = match Self::next_token_start_after_cursor(job) {
             Some(capture) => capture,
             None => {
                 let slice = job.cursor.slice_to_end();
-                job.push_token(Token::Text(slice.to_string()));
+                job.push_token_at(Token::Text(slice.to_string()), position);
 
                 return state::Final::tokenize(job);
             }
         };
 
-        let mut slice = job.cursor.slice_to(capture.position.0);
-
-        if capture.whitespace_trim {
-            slice = slice.trim_right();
-        }
+        let slice = Self::trim_text(job.cursor.slice_to(capture.position.0),
+                                    capture.whitespace_trim,
+                                    capture.whitespace_line_trim);
 
         job.cursor.move_to(capture.position.1);
-        job.push_token(Token::Text(slice.to_string()));
+        job.push_token_at(Token::Text(slice.to_string()), position);
 
         match capture.tag {
             token_start::Tag::Comment => {
@@ -84,6 +84,19 @@ impl TokenizeState for Data {
                 job.push_token(Token::ExpressionStart);
                 try_traced!(state::Expression::tokenize(job));
 
+                return Self::tokenize(job);
+            }
+            token_start::Tag::LineStatement => {
+                job.current_exp_block_line = job.cursor.line();
+                job.push_token(Token::BlockStart);
+                try_traced!(state::LineStatement::tokenize(job));
+
+                return Self::tokenize(job);
+            }
+            token_start::Tag::LineComment => {
+                let whole_line = job.cursor.is_line_start(capture.position.0);
+                Self::lex_line_comment(job, whole_line);
+
                 return Self::tokenize(job);
             }
         }
@@ -99,6 +112,20 @@ impl<'a> Data {
         Ok(())
     }
 
+    /// Skips the rest of the line - including the newline if the comment
+    /// occupies the whole line.
+    fn lex_line_comment(job: &'a mut Job, whole_line: bool) {
+        let len = match job.cursor.tail().find('\n') {
+            Some(n) if whole_line => n + 1,
+            Some(n) => n,
+            None => job.cursor.tail().len(),
+        };
+
+        if len > 0 {
+            job.cursor.move_by(len);
+        }
+    }
+
     fn lex_verbatim_data(job: &'a mut Job,
                          tag: verbatim_start::Tag)
                          -> Result<(), Traced<SyntaxError>> {
@@ -115,17 +142,30 @@ impl<'a> Data {
             }
         };
 
-        let mut slice = job.cursor.slice_by(capture.position.0);
+        let position = job.cursor.stream_position();
+        let slice = Self::trim_text(job.cursor.slice_by(capture.position.0),
+                                    capture.whitespace_trim,
+                                    capture.whitespace_line_trim);
         job.cursor.move_by(capture.position.1 - capture.position.0);
 
-        if capture.whitespace_trim {
-            slice = slice.trim_right();
-        }
-
-        job.push_token(Token::Text(slice.to_string()));
+        job.push_token_at(Token::Text(slice.to_string()), position);
         Ok(())
     }
 
+    /// Trim text in front of a tag with whitespace control modifier
+    ///
+    /// * `whitespace_trim` (`{{-`, `{%-`, `{#-`) trims all whitespace
+    /// * `whitespace_line_trim` (`{{~`, `{%~`, `{#~`) trims whitespace except newlines
+    fn trim_text(slice: &'a str, whitespace_trim: bool, whitespace_line_trim: bool) -> &'a str {
+        if whitespace_trim {
+            patterns::_php_trim_right(slice)
+        } else if whitespace_line_trim {
+            patterns::_php_trim_right_line(slice)
+        } else {
+            slice
+        }
+    }
+
     /// Find the first token after the current cursor
     fn next_token_start_after_cursor(job: &'a mut Job) -> Option<token_start::ItemData> {
         let position = job.cursor.position();
@@ -139,8 +179,24 @@ impl<'a> Data {
 mod test {
     use engine::parser::lexer::test::tokenize_err;
     use engine::parser::lexer::test::assert_tokenize;
-    use engine::parser::token::Token;
-    use engine::parser::lexer::{LexerError, SyntaxError};
+    use engine::parser::token::{Token, Type, Punctuation, BracketType};
+    use engine::parser::lexer::{Lexer, LexerError, SyntaxError, Options};
+    use engine::{Engine, ExtensionRegistry};
+    use template::Raw;
+
+    fn tokenize_line_prefixed(code: &str) -> Vec<Token> {
+        let mut engine = Engine::default();
+        engine.set_extensions(ExtensionRegistry::default());
+        let options = Options {
+            line_statement_prefix: Some("%%".into()),
+            line_comment_prefix: Some("##".into()),
+            ..Options::default()
+        };
+        let lexer = Lexer::new(&engine, options).unwrap();
+        let tpl = Raw::new(code, "test-example");
+
+        lexer.tokenize(&tpl).unwrap().into_iter().map(|i| i.into()).collect()
+    }
 
     #[test]
     pub fn no_more_tokens() {
@@ -206,6 +262,66 @@ mod test {
             ]);
     }
 
+    #[test]
+    pub fn block_whitespace_line_trim() {
+        assert_tokenize(" To \t\n be \t{%~ something ~%} \t\n or not",
+                        vec![
+                Token::Text(" To \t\n be".to_string()),
+                Token::BlockStart,
+                Token::Name("something".to_string()),
+                Token::BlockEnd,
+                Token::Text("\n or not".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn var_whitespace_trim() {
+        assert_tokenize(" foo \n {{- x -}} \n bar {{~ y ~}} \n baz",
+                        vec![
+                Token::Text(" foo".to_string()),
+                Token::ExpressionStart,
+                Token::Name("x".to_string()),
+                Token::ExpressionEnd,
+                Token::Text("bar".to_string()),
+                Token::ExpressionStart,
+                Token::Name("y".to_string()),
+                Token::ExpressionEnd,
+                Token::Text("\n baz".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn comment_whitespace_line_trim() {
+        assert_tokenize(" Hello \n {#~ World ~#} \n!",
+                        vec![
+                Token::Text(" Hello \n".to_string()),
+                Token::Text("\n!".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn block_verbatim_whitespace_trim() {
+        assert_tokenize("{% verbatim -%}\n  {{ x }} \n{%~ endverbatim %}",
+                        vec![
+                Token::Text("".to_string()),
+                Token::Text("{{ x }} \n".to_string()),
+                Token::Text("".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn text_position() {
+        let tpl = Raw::new("a \n  {{- x -}}  \n\n b {#~ c #}\nd", "test-example");
+        let stream = Lexer::default().tokenize(&tpl).unwrap();
+
+        let positions = stream.iter()
+                              .filter(|item| item.token().is_type(Type::Text))
+                              .map(|item| (item.position().line, item.position().column))
+                              .collect::<Vec<_>>();
+
+        assert_eq!(positions, vec![(1, 1), (4, 2), (5, 1)]);
+    }
+
     #[test]
     pub fn unclosed_block() {
         let cursor_dump = "`test-example` line 2 column 10".to_string();
@@ -243,4 +359,61 @@ mod test {
                 err,
                 expect);
     }
+
+    #[test]
+    pub fn line_statement() {
+        assert_eq!(tokenize_line_prefixed("a\n  %% if x ## note\nb\n%% endif"),
+                   vec![
+                Token::Text("a\n".to_string()),
+                Token::BlockStart,
+                Token::Name("if".to_string()),
+                Token::Name("x".to_string()),
+                Token::BlockEnd,
+                Token::Text("b\n".to_string()),
+                Token::BlockStart,
+                Token::Name("endif".to_string()),
+                Token::BlockEnd,
+                Token::Text("".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn line_statement_open_brackets() {
+        assert_eq!(tokenize_line_prefixed("%% set x = [1,\n 2]\n"),
+                   vec![
+                Token::Text("".to_string()),
+                Token::BlockStart,
+                Token::Name("set".to_string()),
+                Token::Name("x".to_string()),
+                Token::Operator("=".to_string()),
+                Token::Punctuation(Punctuation::OpeningBracket(BracketType::Square)),
+                Token::IntegerNumber(1),
+                Token::Punctuation(Punctuation::Comma),
+                Token::IntegerNumber(2),
+                Token::Punctuation(Punctuation::ClosingBracket(BracketType::Square)),
+                Token::BlockEnd,
+                Token::Text("".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn line_comment() {
+        assert_eq!(tokenize_line_prefixed("a = 1  ## one\n  ## skipped\nb {{ x }}## two"),
+                   vec![
+                Token::Text("a = 1".to_string()),
+                Token::Text("\n".to_string()),
+                Token::Text("b ".to_string()),
+                Token::ExpressionStart,
+                Token::Name("x".to_string()),
+                Token::ExpressionEnd,
+                Token::Text("".to_string()),
+                Token::Text("".to_string()),
+            ]);
+    }
+
+    #[test]
+    pub fn line_prefixes_disabled() {
+        assert_tokenize("%% if x ## y",
+                        vec![Token::Text("%% if x ## y".to_string())]);
+    }
 }
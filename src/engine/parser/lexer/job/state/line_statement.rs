@@ -0,0 +1,42 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Line statement state of the lexer.
+
+use super::{TokenizeState, Code};
+use engine::parser::lexer::job::Job;
+use engine::parser::token::Token;
+use engine::parser::lexer::patterns::Extract;
+use engine::parser::lexer::LexerError;
+use super::shared_traits::LexExpression;
+use api::error::Traced;
+
+/// Lexes a line statement like `%% for x in y` as if it was a block tag.
+///
+/// The statement ends with the line, unless there are open brackets.
+pub struct LineStatement;
+
+impl TokenizeState for LineStatement {
+    fn state() -> Code {
+        Code::LineStatement
+    }
+
+    fn tokenize(job: &mut Job) -> Result<(), Traced<LexerError>> {
+        if job.brackets.is_empty() {
+            if let Some(item) = job.patterns.line_statement_end.extract(job.cursor.tail()) {
+                if item.position.1 > 0 {
+                    job.cursor.move_by(item.position.1);
+                }
+                job.push_token(Token::BlockEnd);
+
+                return Ok(());
+            }
+        };
+
+        return Self::lex_expression(job);
+    }
+}
+
+impl LexExpression for LineStatement {}
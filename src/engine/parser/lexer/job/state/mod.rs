@@ -13,6 +13,7 @@ pub mod shared_traits;
 pub mod initial;
 pub mod data;
 pub mod block;
+pub mod line_statement;
 pub mod expression;
 pub mod string;
 pub mod interpolation;
@@ -20,6 +21,7 @@ pub mod final_;
 pub use self::initial::Initial;
 pub use self::data::Data;
 pub use self::block::Block;
+pub use self::line_statement::LineStatement;
 pub use self::expression::Expression;
 pub use self::string::String;
 pub use self::interpolation::Interpolation;
@@ -45,6 +47,7 @@ pub enum Code {
     Expression = 2, // orig: var
     String = 3,
     Interpolation = 4,
+    LineStatement = 5,
     Initial = -1, // orig: implicit sub-state of Data
     Final = -2, // orig: implicit sub-state
 }
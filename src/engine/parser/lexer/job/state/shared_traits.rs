@@ -25,7 +25,9 @@ pub trait LexExpression where
 
         if job.cursor.is_eof() {
             let error_code = match Self::state() {
-                state::Code::Block => SyntaxError::UnclosedBlock { cursor: job.cursor.dump() },
+                state::Code::Block | state::Code::LineStatement => {
+                    SyntaxError::UnclosedBlock { cursor: job.cursor.dump() }
+                }
                 state::Code::Expression => {
                     SyntaxError::UnclosedVariable { cursor: job.cursor.dump() }
                 }
@@ -87,9 +89,6 @@ pub trait LexExpression where
                                     cursor: job.cursor.dump(),
                                 }));
                             }
-
-                            let bracket = (b.clone(), job.cursor.line());
-                            job.push_bracket(bracket);
                         }
                     }
                 }
@@ -5,7 +5,7 @@
 
 //! Lexes a template string.
 
-use std::rc::Rc;
+use std::sync::Arc;
 #[cfg(test)]
 mod test;
 use template;
@@ -24,19 +24,28 @@ pub use self::error::{LexerError, SyntaxError};
 #[derive(PartialEq, Debug)]
 pub struct Lexer {
     patterns: Patterns,
+    options: Arc<Options>,
 }
 
 impl Lexer {
     pub fn new(twig: &Engine, opt: Options) -> Result<Lexer, Traced<LexerError>> {
-        let ref opt = Rc::new(opt); // TODO: -> switch to &Options (!?)
+        let opt = Arc::new(opt); // TODO: -> switch to &Options (!?)
         let ext = match twig.extensions() {
             Err(_) => return traced_err!(LexerError::MissingExtensions),
             Ok(ext) => ext,
         };
 
-        let p = try_traced!(Patterns::new(opt, ext));
+        let p = try_traced!(Patterns::new(&opt, &ext));
 
-        Ok(Lexer { patterns: p })
+        Ok(Lexer {
+            patterns: p,
+            options: opt,
+        })
+    }
+
+    /// Returns the delimiters, whitespace modifiers and line prefixes.
+    pub fn options(&self) -> &Options {
+        &self.options
     }
 
     #[allow(dead_code)] // TODO: testcase
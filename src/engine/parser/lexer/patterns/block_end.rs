@@ -7,10 +7,10 @@
 ///
 /// Written as regular expressions (perl-style).
 
-use super::Options;
+use super::{Options, WHITESPACE_LINE_CHARS};
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -18,7 +18,7 @@ pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -27,14 +27,16 @@ pub struct ItemData {
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
         Ok(Pattern {
-            regex: try_new_regex!(format!(r"\A\s*(?:{ws}{b1}\s*|{b1})\n?",
+            regex: try_new_regex!(format!(r"\A\s*(?:{ws}{b1}\s*|{wl}{b1}{lc}*|{b1}\n?)",
                                           ws = opt.whitespace_trim.quoted(),
+                                          wl = opt.whitespace_line_trim.quoted(),
+                                          lc = WHITESPACE_LINE_CHARS,
                                           b1 = opt.tag_block_end.quoted())),
             options: (*opt).clone(),
         })
-    }   // orig: '/\s*(?:'.$whitespace_trim.$tag_block[1].'\s*|\s*'.$tag_block[1].')\n?/A'
+    }   // orig: '/\s*(?:'.$whitespace_trim.$tag_block[1].'\s*|'.$whitespace_line_trim.$tag_block[1].'['.$whitespace_line_chars.']*|'.$tag_block[1].'\n?)/A'
 }
 
 impl<'t> super::Extract<'t> for Pattern {
@@ -63,19 +65,20 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
-        assert_eq!(pattern.as_str(), r"\A\s*(?:-%\}\s*|%\})\n?");
+        assert_eq!(pattern.as_str(),
+                   r"\A\s*(?:-%\}\s*|~%\}[ \t\x00\x0B]*|%\}\n?)");
     }
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.extract(&r"Lorem Ipsum -%}"), None);
@@ -85,5 +88,11 @@ mod test {
 
         assert_eq!(pattern.extract(&r"%}Lorem Ipsum").unwrap(),
                    ItemData { position: (0, 2) });
+
+        assert_eq!(pattern.extract(&"%}\n Lorem Ipsum").unwrap(),
+                   ItemData { position: (0, 3) });
+
+        assert_eq!(pattern.extract(&" ~%} \t\n Lorem Ipsum").unwrap(),
+                   ItemData { position: (0, 6) });
     }
 }
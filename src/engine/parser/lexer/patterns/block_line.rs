@@ -7,10 +7,10 @@
 ///
 /// Written as regular expressions (perl-style).
 
-use super::Options;
+use super::{Options, WHITESPACE_LINE_CHARS};
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::{Traced, ErrorExt};
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -20,7 +20,7 @@ pub use engine::parser::lexer::LexerError;
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -30,10 +30,12 @@ pub struct ItemData {
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
         Ok(Pattern {
-            regex: try_new_regex!(format!(r"\A\s*line\s+(\d+)\s*(?:{ws}{b1}\s*|{b1})",
+            regex: try_new_regex!(format!(r"\A\s*line\s+(\d+)\s*(?:{ws}{b1}\s*|{wl}{b1}{lc}*|{b1})",
                                           ws = opt.whitespace_trim.quoted(),
+                                          wl = opt.whitespace_line_trim.quoted(),
+                                          lc = WHITESPACE_LINE_CHARS,
                                           b1 = opt.tag_block_end.quoted())),
             options: (*opt).clone(),
         })
@@ -78,19 +80,20 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
-        assert_eq!(pattern.as_str(), r"\A\s*line\s+(\d+)\s*(?:-%\}\s*|%\})");
+        assert_eq!(pattern.as_str(),
+                   r"\A\s*line\s+(\d+)\s*(?:-%\}\s*|~%\}[ \t\x00\x0B]*|%\})");
     }
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(// u64::max_value() == 18446744073709551615
@@ -104,7 +107,7 @@ mod test {
 
     #[test]
     pub fn extract_max_int() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         // u64::max_value() == 18446744073709551615
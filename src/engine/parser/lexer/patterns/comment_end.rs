@@ -7,10 +7,10 @@
 ///
 /// Written as regular expressions (perl-style).
 
-use super::Options;
+use super::{Options, WHITESPACE_LINE_CHARS};
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -18,7 +18,7 @@ pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -27,14 +27,16 @@ pub struct ItemData {
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
         Ok(Pattern {
-            regex: try_new_regex!(format!(r"(?:{ws}{c1}\s*|{c1})\n?",
+            regex: try_new_regex!(format!(r"(?:{ws}{c1}\s*|{wl}{c1}{lc}*|{c1}\n?)",
                                           ws = opt.whitespace_trim.quoted(),
+                                          wl = opt.whitespace_line_trim.quoted(),
+                                          lc = WHITESPACE_LINE_CHARS,
                                           c1 = opt.tag_comment_end.quoted())),
             options: (*opt).clone(),
         })
-    }   // orig: '/(?:'.$whitespace_trim.$tag_comment[1].'\s*|'.$tag_comment[1].')\n?/s'
+    }   // orig: '/(?:'.$whitespace_trim.$tag_comment[1].'\s*\n?|'.$whitespace_line_trim.$tag_comment[1].'['.$whitespace_line_chars.']*|'.$tag_comment[1].'\n?)/s'
 }
 
 impl<'t> super::Extract<'t> for Pattern {
@@ -63,19 +65,20 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
-        assert_eq!(pattern.as_str(), r"(?:-\#\}\s*|\#\})\n?");
+        assert_eq!(pattern.as_str(),
+                   r"(?:-\#\}\s*|~\#\}[ \t\x00\x0B]*|\#\}\n?)");
     }
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.extract(&r"Lorem Ipsum #}").unwrap(),
@@ -86,5 +89,8 @@ mod test {
 
         assert_eq!(pattern.extract(&r"Lorem -#} Ipsum").unwrap(),
                    ItemData { position: (6, 10) });
+
+        assert_eq!(pattern.extract(&"Lorem ~#} \nIpsum").unwrap(),
+                   ItemData { position: (6, 10) });
     }
 }
@@ -7,10 +7,10 @@
 ///
 /// Written as regular expressions (perl-style).
 
-use super::Options;
+use super::{Options, WHITESPACE_LINE_CHARS};
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -18,7 +18,7 @@ pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -27,15 +27,17 @@ pub struct ItemData {
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
         Ok(Pattern {
-            regex: try_new_regex!(format!(r"\A\s*(?:{ws}{v1}\s*|{v1})",
+            regex: try_new_regex!(format!(r"\A\s*(?:{ws}{v1}\s*|{wl}{v1}{lc}*|{v1})",
                                           ws = opt.whitespace_trim.quoted(),
+                                          wl = opt.whitespace_line_trim.quoted(),
+                                          lc = WHITESPACE_LINE_CHARS,
                                           v1 = opt.tag_variable_end.quoted())),
             options: opt.clone(),
         })
     }
-}   // orig: '/\s*'.$whitespace_trim.$tag_variable[1].'\s*|\s*'.$tag_variable[1].'/A'
+}   // orig: '/\s*(?:'.$whitespace_trim.$tag_variable[1].'\s*|'.$whitespace_line_trim.$tag_variable[1].'['.$whitespace_line_chars.']*|'.$tag_variable[1].')/A'
 
 impl<'t> super::Extract<'t> for Pattern {
     type Item = ItemData;
@@ -63,19 +65,20 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
-        assert_eq!(pattern.as_str(), r"\A\s*(?:-\}\}\s*|\}\})");
+        assert_eq!(pattern.as_str(),
+                   r"\A\s*(?:-\}\}\s*|~\}\}[ \t\x00\x0B]*|\}\})");
     }
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.extract(&r"Lorem Ipsum }}"), None);
@@ -85,5 +88,8 @@ mod test {
 
         assert_eq!(pattern.extract(&r" -}} Lorem Ipsum").unwrap(),
                    ItemData { position: (0, 5) });
+
+        assert_eq!(pattern.extract(&" ~}} \n Lorem Ipsum").unwrap(),
+                   ItemData { position: (0, 5) });
     }
 }
@@ -10,7 +10,7 @@
 use super::Options;
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -18,7 +18,7 @@ pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -27,7 +27,7 @@ pub struct ItemData {
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
         Ok(Pattern {
             regex: try_new_regex!(format!(r"\A\s*{i1}", i1 = opt.interpolation_end.quoted())),
             options: (*opt).clone(),
@@ -61,11 +61,11 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.as_str(), r"\A\s*\}");
@@ -73,7 +73,7 @@ mod test {
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.extract(&r"Lorem Ipsum}"), None);
@@ -0,0 +1,88 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The `line statement end` pattern used by the lexer to tokenize the templates.
+//!
+//! Written as regular expressions (perl-style).
+
+use super::Options;
+use regex;
+use regex::Error as regexError;
+use std::sync::Arc;
+use api::error::Traced;
+
+pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
+
+#[derive(Debug, PartialEq)]
+pub struct Pattern {
+    regex: regex::Regex,
+    options: Arc<Options>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ItemData {
+    pub position: (usize, usize),
+}
+
+impl Pattern {
+    /// A line statement ends with the line - optionally followed by a line comment.
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
+        let comment = match opt.line_comment_prefix {
+            Some(ref prefix) => format!(r"(?:{}[^\n]*)?", prefix.quoted()),
+            None => String::new(),
+        };
+
+        Ok(Pattern {
+            regex: try_new_regex!(format!(r"\A[ \t]*{lc}(?:\n|\z)", lc = comment)),
+            options: (*opt).clone(),
+        })
+    }
+}
+
+impl<'t> super::Extract<'t> for Pattern {
+    type Item = ItemData;
+
+    fn regex(&self) -> &regex::Regex {
+        &self.regex
+    }
+
+    fn item_from_captures(&self, captures: &regex::Captures) -> ItemData {
+        ItemData {
+            position: match captures.pos(0) {
+                Some(position) => position,
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use engine::parser::lexer::patterns::{Options, Extract};
+    use std::sync::Arc;
+
+    #[test]
+    pub fn as_str() {
+        let ref options = Arc::new(Options {
+            line_comment_prefix: Some("##".into()),
+            ..Options::default()
+        });
+        let pattern = Pattern::new(options).unwrap();
+
+        assert_eq!(pattern.as_str(), r"\A[ \t]*(?:\#\#[^\n]*)?(?:\n|\z)");
+    }
+
+    #[test]
+    pub fn extract() {
+        let ref options = Arc::<Options>::default();
+        let pattern = Pattern::new(options).unwrap();
+
+        assert_eq!(pattern.extract(" x\n"), None);
+        assert_eq!(pattern.extract(" \t\nLorem"),
+                   Some(ItemData { position: (0, 3) }));
+        assert_eq!(pattern.extract("  "), Some(ItemData { position: (0, 2) }));
+    }
+}
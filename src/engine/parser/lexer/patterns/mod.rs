@@ -8,7 +8,7 @@
 /// Written as regular expressions (perl-style).
 
 use std::iter::Iterator;
-use std::rc::Rc;
+use std::sync::Arc;
 use regex;
 use regex::Error as regexError;
 use engine::ExtensionRegistry;
@@ -24,6 +24,7 @@ pub mod block_end;
 pub mod block_line;
 pub mod verbatim_start;
 pub mod verbatim_end;
+pub mod line_statement_end;
 pub mod interpolation_start;
 pub mod interpolation_end;
 pub mod operator;
@@ -35,12 +36,18 @@ pub mod string_dq_delim;
 pub mod string_dq_part;
 pub use self::options::Options;
 
+/// Whitespace characters trimmed by the `whitespace_line_trim` modifier, as regex character class.
+///
+/// orig: $this->options['whitespace_line_chars'] = ' \t\0\x0B'
+pub const WHITESPACE_LINE_CHARS: &'static str = r"[ \t\x00\x0B]";
+
 // #[derive(PartialEq)]
 #[derive(Debug, PartialEq)]
 pub struct Patterns {
     pub expression_end: expression_end::Pattern,
     pub block_end: block_end::Pattern,
     pub verbatim_end: verbatim_end::Pattern,
+    pub line_statement_end: line_statement_end::Pattern,
     pub operator: operator::Pattern,
     pub comment_end: comment_end::Pattern,
     pub verbatim_start: verbatim_start::Pattern,
@@ -58,12 +65,13 @@ pub struct Patterns {
 
 #[allow(unused_variables)]
 impl Patterns {
-    pub fn new(opt: &Rc<Options>,
-               ext: &Rc<ExtensionRegistry>)
+    pub fn new(opt: &Arc<Options>,
+               ext: &Arc<ExtensionRegistry>)
                -> Result<Patterns, Traced<regexError>> {
         Ok(Patterns {
             expression_end: try_traced!(expression_end::Pattern::new(opt)),
             verbatim_end: try_traced!(verbatim_end::Pattern::new(opt)),
+            line_statement_end: try_traced!(line_statement_end::Pattern::new(opt)),
             operator: try_traced!(operator::Pattern::new(ext)),
             block_end: try_traced!(block_end::Pattern::new(opt)),
             comment_end: try_traced!(comment_end::Pattern::new(opt)),
@@ -84,8 +92,8 @@ impl Patterns {
 
 impl<'a> Default for Patterns {
     fn default() -> Patterns {
-        let ref ext = Rc::new(ExtensionRegistry::default());
-        let ref opt = Rc::new(Options::default());
+        let ext = &Arc::new(ExtensionRegistry::default());
+        let opt = &Arc::new(Options::default());
 
         Patterns::new(opt, ext).unwrap()
     }
@@ -217,6 +225,16 @@ pub fn _php_trim_right(slice: &str) -> &str {
     slice.trim_right_matches(ws)
 }
 
+/// Trim whitespace except newlines in a *php-compatible* manner
+///
+/// orig: rtrim($text, " \t\0\x0B")
+/// trims the same characters as `_php_trim_right`, but keeps "\n" and "\r".
+/// This is what the `whitespace_line_trim` modifier (`~`) does.
+pub fn _php_trim_right_line(slice: &str) -> &str {
+    let ws: &[_] = &[' ', '\t', '\0', '\x0B'];
+    slice.trim_end_matches(ws)
+}
+
 fn to_hex(c: &char) -> Option<u32> {
     match *c {
         '0'...'9' => Some(*c as u32 - '0' as u32),
@@ -417,4 +435,12 @@ mod test {
         assert_eq!("trim me RUST! \0 \t \n \r \x0B \n ".trim_right(),
                    "trim me RUST! \0");
     }
+
+    #[test]
+    pub fn php_trim_right_line() {
+        assert_eq!(_php_trim_right_line("trim me PHP! \n\0 \t \x0B "),
+                   "trim me PHP! \n");
+
+        assert_eq!(_php_trim_right_line("keep\r\n"), "keep\r\n");
+    }
 }
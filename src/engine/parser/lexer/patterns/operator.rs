@@ -12,7 +12,7 @@ use regex::Error as regexError;
 use extension::api::{UnaryOperator, BinaryOperator};
 use engine::ExtensionRegistry;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -45,12 +45,15 @@ impl Builder {
         let mut operators: Vec<(usize, &str)> = Vec::with_capacity(1 + unary.len() + binary.len());
 
         operators.push(("=".len(), "="));
-
-        for (ref op_repr, _) in unary.iter() {
-            operators.push((op_repr.len(), op_repr))
-        }
-        for (ref op_repr, _) in binary.iter() {
-            operators.push((op_repr.len(), op_repr))
+        operators.push(("=>".len(), "=>")); // arrow functions
+        operators.push(("..".len(), "..")); // range(), e.g. `1..5`
+
+        // operators like `and` are lexed as names, because the regex can't
+        // check that they are followed by a whitespace or a parenthesis
+        for op_repr in unary.keys().chain(binary.keys()) {
+            if !op_repr.chars().last().is_some_and(char::is_alphabetic) {
+                operators.push((op_repr.len(), op_repr))
+            }
         }
 
         // sort operators by length in reverse order (i.e. descending): 10,9,8,7,6 ..
@@ -87,7 +90,7 @@ impl Builder {
 
 #[allow(dead_code, unused_variables)]
 impl Pattern {
-    pub fn new(ext: &Rc<ExtensionRegistry>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(ext: &Arc<ExtensionRegistry>) -> Result<Pattern, Traced<regexError>> {
         let unary = ext.operators_unary();
         let binary = ext.operators_binary();
 
@@ -136,11 +139,11 @@ impl PartialEq for Pattern {
 //     use super::*;
 //     use environment::Environment;
 //     use engine::parser::lexer::patterns::Extract;
-//     use std::rc::Rc;
+//     use std::sync::Arc;
 //
 //     #[test]
 //     pub fn as_str() {
-//         let environment = Rc::<Environment>::default();
+//         let environment = Arc::<Environment>::default();
 //         let pattern = Pattern::new(environment).unwrap();
 //
 //         assert_eq!(
@@ -151,7 +154,7 @@ impl PartialEq for Pattern {
 //
 //     #[test]
 //     pub fn extract() {
-//         let environment = Rc::<Environment>::default();
+//         let environment = Arc::<Environment>::default();
 //         let pattern = Pattern::new(environment).unwrap();
 //
 //         assert_eq!(
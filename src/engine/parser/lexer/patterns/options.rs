@@ -24,6 +24,11 @@ pub struct Options {
     pub tag_expression_start: OptionData,
     pub tag_variable_end: OptionData,
     pub whitespace_trim: OptionData,
+    pub whitespace_line_trim: OptionData,
+    /// Prefix turning a whole line into a block tag, e.g. `%%` for `%% for x in y`.
+    pub line_statement_prefix: Option<OptionData>,
+    /// Prefix of a comment ranging to the end of the line, e.g. `##`.
+    pub line_comment_prefix: Option<OptionData>,
 }
 
 impl<'a> Into<OptionData> for &'a str {
@@ -57,6 +62,9 @@ impl Default for Options {
             tag_expression_start: "{{".into(),
             tag_variable_end: "}}".into(),
             whitespace_trim: "-".into(),
+            whitespace_line_trim: "~".into(),
+            line_statement_prefix: None,
+            line_comment_prefix: None,
         }
     }
 }
@@ -105,6 +113,12 @@ mod test {
                 raw: "-".into(),
                 quoted: r"-".into(),
             },
+            whitespace_line_trim: OptionData {
+                raw: "~".into(),
+                quoted: r"~".into(),
+            },
+            line_statement_prefix: None,
+            line_comment_prefix: None,
         };
 
         assert_eq!(opt_o, opt_x);
@@ -51,7 +51,8 @@ impl<'t> super::Extract<'t> for Pattern {
                 Some(position) => position,
                 _ => unreachable!(),
             },
-            escaped_string: match captures.at(0) {
+            // without the enclosing quotes - orig: substr($match[0], 1, -1)
+            escaped_string: match captures.at(1).or(captures.at(2)) {
                 Some(ref val) => val,
                 _ => unreachable!(),
             },
@@ -63,7 +64,7 @@ impl<'t> super::Extract<'t> for Pattern {
         self.find(text).map(|position| {
             ItemData {
                 position: position,
-                escaped_string: &text[position.0..position.1],
+                escaped_string: &text[position.0 + 1..position.1 - 1],
             }
         })
     }
@@ -83,13 +84,13 @@ mod test {
         assert_eq!(pattern.extract(&r##""123\.abc"def"##),
                    Some(ItemData {
                        position: (0, 10),
-                       escaped_string: r##""123\.abc""##,
+                       escaped_string: r##"123\.abc"##,
                    }));
 
         assert_eq!(pattern.extract(&r"'Lorem' Ipsum"),
                    Some(ItemData {
                        position: (0, 7),
-                       escaped_string: "'Lorem'",
+                       escaped_string: "Lorem",
                    }));
     }
 }
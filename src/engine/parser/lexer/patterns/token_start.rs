@@ -10,7 +10,7 @@
 use super::Options;
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'pattern, 'tpl> = super::ExtractIter<'pattern, 'tpl, Pattern>;
@@ -18,13 +18,14 @@ pub type ExtractIter<'pattern, 'tpl> = super::ExtractIter<'pattern, 'tpl, Patter
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ItemData {
     pub position: (usize, usize),
     pub whitespace_trim: bool,
+    pub whitespace_line_trim: bool,
     pub tag: Tag,
 }
 
@@ -33,19 +34,34 @@ pub enum Tag {
     Block,
     Comment,
     Expression,
+    LineStatement,
+    LineComment,
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
+        let mut regex = format!(r"({v0}|{b0}|{c0})({ws}|{wl})?",
+                                ws = opt.whitespace_trim.quoted(),
+                                wl = opt.whitespace_line_trim.quoted(),
+                                b0 = opt.tag_block_start.quoted(),
+                                c0 = opt.tag_comment_start.quoted(),
+                                v0 = opt.tag_expression_start.quoted());
+
+        // line statements must start the line - apart from indentation
+        if let Some(ref prefix) = opt.line_statement_prefix {
+            regex.push_str(&format!(r"|(?m:^)[ \t]*(?P<ls>{})", prefix.quoted()));
+        }
+
+        // line comments swallow the horizontal whitespace in front of them
+        if let Some(ref prefix) = opt.line_comment_prefix {
+            regex.push_str(&format!(r"|[ \t]*(?P<lc>{})", prefix.quoted()));
+        }
+
         Ok(Pattern {
-            regex: try_new_regex!(format!(r"({v0}|{b0}|{c0})({ws})?",
-                                          ws = opt.whitespace_trim.quoted(),
-                                          b0 = opt.tag_block_start.quoted(),
-                                          c0 = opt.tag_comment_start.quoted(),
-                                          v0 = opt.tag_expression_start.quoted())),
+            regex: try_new_regex!(regex),
             options: (*opt).clone(),
         })
-    }   // orig: '/('.$tag_variable[0].'|'.$tag_block[0].'|'.$tag_comment[0].')('.$whitespace_trim.')?/s'
+    }   // orig: '/('.$tag_variable[0].'|'.$tag_block[0].'|'.$tag_comment[0].')('.$whitespace_trim.'|'.$whitespace_line_trim.')?/s'
 }
 
 impl<'t> super::Extract<'t> for Pattern {
@@ -62,10 +78,16 @@ impl<'t> super::Extract<'t> for Pattern {
                 _ => unreachable!(),
             },
             whitespace_trim: match captures.at(2) {
-                Some(_) => true,
+                Some(x) => x == self.options.whitespace_trim.raw(),
+                None => false,
+            },
+            whitespace_line_trim: match captures.at(2) {
+                Some(x) => x == self.options.whitespace_line_trim.raw(),
                 None => false,
             },
             tag: match captures.at(1) {
+                None if captures.name("ls").is_some() => Tag::LineStatement,
+                None if captures.name("lc").is_some() => Tag::LineComment,
                 Some(x) if x == self.options.tag_block_start.raw() => Tag::Block,
                 Some(x) if x == self.options.tag_comment_start.raw() => Tag::Comment,
                 Some(x) if x == self.options.tag_expression_start.raw() => Tag::Expression,
@@ -79,19 +101,19 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
-        assert_eq!(pattern.as_str(), r"(\{\{|\{%|\{\#)(-)?");
+        assert_eq!(pattern.as_str(), r"(\{\{|\{%|\{\#)(-|~)?");
     }
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.extract(&r"{-{"), None);
@@ -100,6 +122,7 @@ mod test {
                    Some(ItemData {
                        position: (0, 3),
                        whitespace_trim: true,
+                       whitespace_line_trim: false,
                        tag: Tag::Expression,
                    }));
 
@@ -107,7 +130,34 @@ mod test {
                    Some(ItemData {
                        position: (0, 2),
                        whitespace_trim: false,
+                       whitespace_line_trim: false,
                        tag: Tag::Expression,
                    }));
+
+        assert_eq!(pattern.extract(&r"{%~"),
+                   Some(ItemData {
+                       position: (0, 3),
+                       whitespace_trim: false,
+                       whitespace_line_trim: true,
+                       tag: Tag::Block,
+                   }));
+    }
+
+    #[test]
+    pub fn line_prefixes() {
+        let ref options = Arc::new(Options {
+            line_statement_prefix: Some("%%".into()),
+            line_comment_prefix: Some("##".into()),
+            ..Options::default()
+        });
+        let pattern = Pattern::new(options).unwrap();
+
+        assert_eq!(pattern.as_str(),
+                   r"(\{\{|\{%|\{\#)(-|~)?|(?m:^)[ \t]*(?P<ls>%%)|[ \t]*(?P<lc>\#\#)");
+
+        assert_eq!(pattern.extract_iter("a\n  %% if x\nb %% c ## d")
+                          .map(|x| (x.position, x.tag))
+                          .collect::<Vec<_>>(),
+                   vec![((2, 6), Tag::LineStatement), ((18, 21), Tag::LineComment)]);
     }
 }
@@ -7,10 +7,10 @@
 ///
 /// Written as regular expressions (perl-style).
 
-use super::Options;
+use super::{Options, WHITESPACE_LINE_CHARS};
 use regex;
 use regex::Error as regexError;
-use std::rc::Rc;
+use std::sync::Arc;
 use api::error::Traced;
 
 pub type ExtractIter<'a, 'b> = super::ExtractIter<'a, 'b, Pattern>;
@@ -19,26 +19,29 @@ pub use super::verbatim_start::Tag; // enum {Raw, Verbatim}
 #[derive(Debug, PartialEq)]
 pub struct Pattern {
     regex: regex::Regex,
-    options: Rc<Options>,
+    options: Arc<Options>,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ItemData {
     pub position: (usize, usize),
     pub whitespace_trim: bool,
+    pub whitespace_line_trim: bool,
     pub tag: Tag,
 }
 
 impl Pattern {
-    pub fn new(opt: &Rc<Options>) -> Result<Pattern, Traced<regexError>> {
+    pub fn new(opt: &Arc<Options>) -> Result<Pattern, Traced<regexError>> {
         Ok(Pattern {
-            regex: try_new_regex!(format!(r"{b0}({ws})?\s*(?:end(raw|verbatim))\s*(?:{ws}{b1}\s*|{b1})",
+            regex: try_new_regex!(format!(r"{b0}({ws}|{wl})?\s*(?:end(raw|verbatim))\s*(?:{ws}{b1}\s*|{wl}{b1}{lc}*|{b1})",
                 ws = opt.whitespace_trim.quoted(),
+                wl = opt.whitespace_line_trim.quoted(),
+                lc = WHITESPACE_LINE_CHARS,
                 b0 = opt.tag_block_start.quoted(),
                 b1 = opt.tag_block_end.quoted())),
             options: opt.clone(),
         })
-    }   // orig: '/('.$tag_block[0].$whitespace_trim.'|'.$tag_block[0].')\s*(?:end%s)\s*(?:'.$whitespace_trim.$tag_block[1].'\s*|\s*'.$tag_block[1].')/s'
+    }   // orig: '/'.$tag_block[0].'('.$whitespace_trim.'|'.$whitespace_line_trim.')?\s*(?:end%s)\s*(?:'.$whitespace_trim.$tag_block[1].'\s*|'.$whitespace_line_trim.$tag_block[1].'['.$whitespace_line_chars.']*|'.$tag_block[1].')/s'
 }
 
 impl<'t> super::Extract<'t> for Pattern {
@@ -55,7 +58,11 @@ impl<'t> super::Extract<'t> for Pattern {
                 _ => unreachable!(),
             },
             whitespace_trim: match captures.at(1) {
-                Some(_) => true,
+                Some(x) => x == self.options.whitespace_trim.raw(),
+                None => false,
+            },
+            whitespace_line_trim: match captures.at(1) {
+                Some(x) => x == self.options.whitespace_line_trim.raw(),
                 None => false,
             },
             tag: match captures.at(2) {
@@ -71,20 +78,20 @@ impl<'t> super::Extract<'t> for Pattern {
 mod test {
     use super::*;
     use engine::parser::lexer::patterns::{Options, Extract};
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     pub fn as_str() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.as_str(),
-                   r"\{%(-)?\s*(?:end(raw|verbatim))\s*(?:-%\}\s*|%\})");
+                   r"\{%(-|~)?\s*(?:end(raw|verbatim))\s*(?:-%\}\s*|~%\}[ \t\x00\x0B]*|%\})");
     }
 
     #[test]
     pub fn extract() {
-        let ref options = Rc::<Options>::default();
+        let ref options = Arc::<Options>::default();
         let pattern = Pattern::new(options).unwrap();
 
         assert_eq!(pattern.extract(&r"Lorem Ipsum"), None);
@@ -93,6 +100,7 @@ mod test {
                    ItemData {
                        position: (12, 24),
                        whitespace_trim: false,
+                       whitespace_line_trim: false,
                        tag: Tag::Raw,
                    });
 
@@ -101,6 +109,7 @@ mod test {
                    ItemData {
                        position: (27, 53),
                        whitespace_trim: true,
+                       whitespace_line_trim: false,
                        tag: Tag::Verbatim,
                    });
     }
@@ -7,7 +7,7 @@
 
 use engine::parser::lexer::Lexer;
 use template::raw::Raw;
-use std::rc::Rc;
+use std::sync::Arc;
 use engine::parser::token::Token;
 use engine::parser::token::Punctuation;
 use engine::parser::lexer::LexerError;
@@ -17,7 +17,7 @@ use api::error::Traced;
 static TWIG_TEMPLATE_CODE: &'static str = include_str!("twig.template.html");
 
 pub fn tokenize_err<'a>(code: &'a str) -> Traced<LexerError> {
-    let tpl = Rc::new(Raw::new(code, "test-example"));
+    let tpl = Arc::new(Raw::new(code, "test-example"));
     let lxr = Lexer::default();
 
     println!("Template Code: {:?}", tpl.code);
@@ -28,7 +28,7 @@ pub fn tokenize_err<'a>(code: &'a str) -> Traced<LexerError> {
 /// #panics
 /// when the tokenstream does not equal `tokens`
 pub fn assert_tokenize(code: &str, tokens: Vec<Token>) {
-    let tpl = Rc::new(Raw::new(code, "test-example"));
+    let tpl = Arc::new(Raw::new(code, "test-example"));
     let lxr = Lexer::default();
 
     println!("Template Code: {:?}", tpl.code);
@@ -6,9 +6,13 @@
 //! Parser
 
 use engine::{Engine, ExtensionRegistry};
+use engine::node;
 use extension;
 use extension::api::TokenParser;
-use std::rc::Rc;
+use extension::escaper;
+use extension::Sandbox;
+use extension::sandbox::SecurityPolicy;
+use std::sync::Arc;
 use template;
 use std::collections::HashMap;
 use api::error::Traced;
@@ -27,8 +31,11 @@ pub use self::expression_parser::ExpressionParser;
 
 #[derive(Debug)]
 pub struct Parser {
-    ext: Rc<ExtensionRegistry>,
+    ext: Arc<ExtensionRegistry>,
     expression_parser: ExpressionParser,
+    autoescape: escaper::Mode,
+    escapers: escaper::Escapers,
+    sandbox: Option<Sandbox>,
 } // avoid a circular reference to the engine!
 
 impl Parser {
@@ -38,9 +45,19 @@ impl Parser {
             Ok(ext) => ext,
         };
 
+        // autoescaping is only available with the escaper extension
+        let autoescape = if ext.has("escaper") {
+            twig.options().autoescape().clone()
+        } else {
+            escaper::Mode::Disabled
+        };
+
         Ok(Parser {
-            ext: (*ext).clone(),
-            expression_parser: ExpressionParser::new(ext),
+            ext: ext.clone(),
+            expression_parser: ExpressionParser::new(&ext),
+            autoescape: autoescape,
+            escapers: twig.options().escapers().clone(),
+            sandbox: twig.sandbox().cloned(),
         })
     }
 
@@ -55,9 +72,79 @@ impl Parser {
         job.parse()
     }
 
+    /// Parses the token stream - sandboxed if `sandboxed` is true or the
+    /// sandbox mode says so, e.g. for templates included in sandboxed templates.
+    pub fn parse_sandboxed<'a, 't>(&'a self,
+                                   stream: &'t token::Stream<'t>,
+                                   sandboxed: bool)
+                                   -> Result<template::Compiled, Traced<ParserError>>
+        where 't: 'a
+    {
+        let mut job = Job::new(stream, self);
+        if sandboxed {
+            job.push_sandbox(true);
+        }
+
+        job.parse()
+    }
+
+    /// Parses the token stream - without applying the node visitors, e.g. to
+    /// store the module in the filesystem cache.
+    ///
+    /// Note: Templates in sandbox mode must be parsed with `parse_sandboxed()`.
+    pub fn parse_module<'a, 't>(&'a self,
+                                stream: &'t token::Stream<'t>)
+                                -> Result<node::Module, Traced<ParserError>>
+        where 't: 'a
+    {
+        Job::new(stream, self).parse_module()
+    }
+
+    /// Applies the node visitors to a module returned by `parse_module()`.
+    pub fn compile_module(&self,
+                          module: node::Module)
+                          -> Result<template::Compiled, Traced<ParserError>> {
+        self.compile(module, None)
+    }
+
+    /// Applies the node visitors - the template is sandboxed at runtime, if
+    /// there is a security policy.
+    fn compile(&self,
+               module: node::Module,
+               policy: Option<Arc<SecurityPolicy>>)
+               -> Result<template::Compiled, Traced<ParserError>> {
+        let traverser = node::NodeTraverser::new(self, self.visitors());
+        let module = try_traced!(traverser.traverse_module(module));
+
+        let mut compiled = template::Compiled::new(module, self.ext.clone());
+        compiled.set_policy(policy);
+
+        Ok(compiled)
+    }
+
     /// Returns the engine extensions.
-    pub fn extensions(&self) -> &ExtensionRegistry {
-        &*self.ext
+    pub fn extensions(&self) -> &Arc<ExtensionRegistry> {
+        &self.ext
+    }
+
+    /// Returns the autoescape mode of the engine.
+    pub fn autoescape(&self) -> &escaper::Mode {
+        &self.autoescape
+    }
+
+    /// Returns the custom escaping strategies of the engine.
+    pub fn escapers(&self) -> &escaper::Escapers {
+        &self.escapers
+    }
+
+    /// Returns the built-in or custom escaping strategy named `name`.
+    pub fn strategy(&self, name: &str) -> Option<escaper::Strategy> {
+        escaper::resolve(name, &self.escapers)
+    }
+
+    /// Returns the sandbox settings - if the sandbox extension is registered.
+    pub fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_ref()
     }
 
     /// Returns the token parser registered for `tag`
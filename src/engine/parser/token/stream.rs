@@ -126,12 +126,14 @@ impl<'a> Stream<'a> {
     }
 
     pub fn push(&mut self, token: Token, cursor: &Cursor) {
+        self.push_at(token, cursor.stream_position());
+    }
+
+    /// Pushes a token with an explicit position, e.g. where a text token began.
+    pub fn push_at(&mut self, token: Token, position: Position) {
         self.items.push(Item {
             token: token,
-            position: Position {
-                line: cursor.line(),
-                column: cursor.column(),
-            },
+            position: position,
         });
     }
 
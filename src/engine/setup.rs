@@ -6,10 +6,12 @@
 //! Stores the Twig configuration.
 
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::{Arc, RwLock};
 use extension;
 use extension::api::Extension;
+use extension::escaper;
 use engine::{Engine, options, Options, extension_registry, ExtensionRegistry};
+use engine::template_cache::Cache;
 use engine::error::TwigError;
 use api::error::Traced;
 
@@ -24,12 +26,9 @@ pub struct Setup {
 
 impl Default for Setup {
     fn default() -> Setup {
-        let mut ext = ExtensionRegistry::default();
-        ext.push(extension::Core::new()).unwrap(); // core extension
-
         Setup {
             opt: Options::default(),
-            ext: ext,
+            ext: ExtensionRegistry::default(),
         }
     }
 }
@@ -68,18 +67,22 @@ impl Setup {
         let o = self.opt;
 
         // add default extensions
-        try_traced!(self.ext.push(extension::Escaper::new(o.autoescape)));
+        try_traced!(self.ext.push(extension::Core::with_options(&o)));
+        try_traced!(self.ext.push(extension::Escaper::new(o.autoescape.clone(),
+                                                          o.escapers.clone())));
         try_traced!(self.ext.push(extension::Optimizer::new(o.optimizations)));
 
+        c.cache = Arc::new(RwLock::new(Cache::new(o.auto_reload())));
+        c.cache.write().unwrap().set_filecache(o.cache());
+        c.template_class_prefix = "__TwigTemplate_".to_string();
+        c.options = o;
+
         // init extensions
         try_traced!(self.ext.init(&mut c));
-        c.ext = Some(Rc::new(self.ext));
+        c.set_extensions(self.ext);
 
-        // TODO: register staging extension (!)
-        // // init staging extension
-        // let staging = ext::Staging::new();
-        // try_traced!(c.init_extension(&*staging));
-        // c.ext_staging = Some(staging);
+        // collects `Engine::add_*()` until the engine compiles templates
+        c.state_mut().ext_staging = Some(extension::Staging::new());
 
         return Ok(c);
     }
@@ -115,17 +118,60 @@ impl Setup {
     }
 
     /// Whether to enable auto-escaping (default to html):
-    ///     * false: disable auto-escaping
-    ///     * true: equivalent to html
-    ///     * html, js: set the autoescaping to one of the supported strategies
-    ///     * filename: set the autoescaping strategy based on the template filename extension
-    ///     * PHP callback: a PHP callback that returns an escaping strategy based on the template "filename"
+    ///     * Disabled: disable auto-escaping
+    ///     * Enabled(strategy): set the autoescaping to one of the supported strategies
+    ///     * Filename: set the autoescaping strategy based on the template filename extension
+    ///     * Callback: a closure that returns an escaping strategy based on the template "filename"
     pub fn set_autoescape(mut self, autoescape: options::Autoescape) -> Self {
         self.opt.autoescape = autoescape;
 
         self
     }
 
+    /// Registers a custom escaping strategy, e.g. `latex`.
+    ///
+    /// It can be used like the built-in strategies with `|escape('latex')`,
+    /// `{% autoescape 'latex' %}` and the filename strategy (`*.latex.twig`).
+    pub fn add_escaper<F>(mut self, name: &str, f: F) -> Self
+        where F: Fn(&str) -> String + Send + Sync + 'static
+    {
+        self.opt.escapers.insert(name.to_string(), escaper::Strategy::custom(name, f));
+
+        self
+    }
+
+    /// The defaults of the `number_format` filter (default to `(0, '.', ',')`):
+    ///     * number of decimals
+    ///     * decimal point
+    ///     * thousands separator
+    pub fn set_number_format(mut self, decimals: usize, decimal_point: char, thousands_sep: char) -> Self {
+        self.opt.number_format = (decimals, decimal_point, thousands_sep);
+
+        self
+    }
+
+    /// The default format of the `date` filter (default to "F j, Y H:i")
+    pub fn set_date_format(mut self, date_format: &str) -> Self {
+        self.opt.date_format = date_format.to_string();
+
+        self
+    }
+
+    /// The default timezone of dates, e.g. "Europe/Paris" (default to UTC)
+    pub fn set_timezone(mut self, timezone: Option<&str>) -> Self {
+        self.opt.timezone = timezone.map(|x| x.to_string());
+
+        self
+    }
+
+    /// The seed of the `random()` function, e.g. for reproducible tests
+    ///     (default to a random seed)
+    pub fn set_random_seed(mut self, seed: Option<u64>) -> Self {
+        self.opt.random_seed = seed;
+
+        self
+    }
+
     /// An absolute path where to store the compiled templates (optional)
     pub fn set_cache(mut self, cache: Option<&Path>) -> Self {
         self.opt.cache = cache.map(|reference| reference.to_owned());
@@ -3,28 +3,96 @@
 // For the copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-//! Twig filecache for compiled templates.
+//! Twig cache for compiled templates.
 
 use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use engine::TwigError;
+use template;
+use api::error::Traced;
 
-#[allow(dead_code)]
+/// Extension of the cache files.
+const EXTENSION: &'static str = ".twig";
+
+/// Distinguishes temporary files of the same process.
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Compiled templates by name and cache key of the loader.
+///
+/// The name is part of the key, because it determines e.g. the escaping strategy.
+#[derive(Debug, Default)]
 pub struct Cache {
-    loaded_templates: HashMap<String, String>,
+    loaded_templates: HashMap<(String, String, bool), Entry>,
     filecache: Option<PathBuf>,
-    _auto_reload: bool,
+    auto_reload: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    compiled: Arc<template::Compiled>,
+    time: i64,
 }
 
 #[allow(dead_code)]
 impl Cache {
+    pub fn new(auto_reload: bool) -> Cache {
+        Cache { auto_reload: auto_reload, ..Cache::default() }
+    }
+
+    /// Returns true if cached templates must be checked with `Loader::is_fresh()`.
+    pub fn auto_reload(&self) -> bool {
+        self.auto_reload
+    }
+
     /// Clears the internal template cache.
     pub fn clear(&mut self) {
         self.loaded_templates.clear()
     }
 
-    /// Loads a template by name.
-    pub fn load(_name: &str) -> &str {
-        unimplemented!()
+    /// Returns the number of cached templates.
+    pub fn len(&self) -> usize {
+        self.loaded_templates.len()
+    }
+
+    /// Returns the compiled template and its compile time (seconds since the epoch).
+    pub fn get(&self,
+               name: &str,
+               key: &str,
+               sandboxed: bool)
+               -> Option<(Arc<template::Compiled>, i64)> {
+        self.loaded_templates
+            .get(&(name.to_string(), key.to_string(), sandboxed))
+            .map(|x| (x.compiled.clone(), x.time))
+    }
+
+    /// Stores the compiled template, compiled just now - replacing outdated versions.
+    pub fn insert(&mut self,
+                  name: &str,
+                  key: &str,
+                  sandboxed: bool,
+                  compiled: Arc<template::Compiled>) {
+        self.loaded_templates.retain(|&(ref x, _, y), _| x != name || y != sandboxed);
+        self.loaded_templates.insert((name.to_string(), key.to_string(), sandboxed),
+                                     Entry {
+                                         compiled: compiled,
+                                         time: now(),
+                                     });
+    }
+
+    /// Removes all compiled versions of the template `name`.
+    ///
+    /// Returns true if the template was cached.
+    pub fn evict(&mut self, name: &str) -> bool {
+        let len = self.loaded_templates.len();
+        self.loaded_templates.retain(|&(ref x, _, _), _| x != name);
+
+        self.loaded_templates.len() != len
     }
 
     /// Sets the cache directory or None if filecache is disabled.
@@ -32,7 +100,7 @@ impl Cache {
         match filecache {
             None => self.filecache = None,
             Some(path) => {
-                if path.file_name().is_some() {
+                if path.is_file() {
                     panic!("path must be a directory")
                 }
 
@@ -50,14 +118,143 @@ impl Cache {
     }
 
     /// Clears the template cache files on the filesystem.
-    pub fn clear_filecache(&self) {
-        if let Some(ref _path) = self.filecache {
-            unimplemented!()
+    ///
+    /// Other files in the cache directory are kept.
+    pub fn clear_filecache(&self) -> Result<(), Traced<TwigError>> {
+        let dir = match self.filecache {
+            Some(ref dir) if dir.is_dir() => dir,
+            _ => return Ok(()),
+        };
+
+        let entries = try_untraced!(fs::read_dir(dir).map_err(|e| cache_error(dir, e)));
+        for entry in entries {
+            let path = try_untraced!(entry.map_err(|e| cache_error(dir, e))).path();
+            let is_cache_file = match path.file_name().and_then(|x| x.to_str()) {
+                Some(name) => is_cache_file(name),
+                None => false,
+            };
+
+            if is_cache_file {
+                try_untraced!(fs::remove_file(&path).map_err(|e| cache_error(&path, e)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the cache filename for a given template - or None if filecache is disabled.
+    ///
+    /// The `id` identifies the template, e.g. by the cache key of the loader,
+    /// and the engine settings which affect compilation.
+    pub fn get_cache_filename(&self, id: &str) -> Option<PathBuf> {
+        self.filecache.as_ref().map(|dir| dir.join(format!("{:016x}{}", hash(id), EXTENSION)))
+    }
+
+    /// Returns the data of the cache file and its modification time (seconds
+    /// since the epoch).
+    ///
+    /// Returns None if the file is missing or belongs to another `id`.
+    pub fn read_file(&self, id: &str) -> Option<(String, i64)> {
+        let path = match self.get_cache_filename(id) {
+            Some(path) => path,
+            None => return None,
+        };
+
+        let mut file = match File::open(&path) {
+            Ok(file) => file,
+            Err(_) => return None,
+        };
+        let time = match file.metadata().and_then(|x| x.modified()) {
+            Ok(time) => seconds(time),
+            Err(_) => return None,
+        };
+        let mut data = String::new();
+        if file.read_to_string(&mut data).is_err() {
+            return None;
         }
+
+        let header = header(id);
+        if !data.starts_with(&header) {
+            return None;
+        }
+
+        Some((data[header.len()..].to_string(), time))
+    }
+
+    /// Writes the data of the cache file.
+    ///
+    /// The data is written to a temporary file first, which replaces the cache
+    /// file afterwards - i.e. concurrent readers never see partial data.
+    pub fn write_file(&self, id: &str, data: &str) -> Result<(), Traced<TwigError>> {
+        let path = match self.get_cache_filename(id) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            try_untraced!(fs::create_dir_all(dir).map_err(|e| cache_error(dir, e)));
+        }
+
+        let tmp = path.with_extension(format!("twig.{}.{}.tmp",
+                                              process::id(),
+                                              TMP_COUNTER.fetch_add(1, Ordering::SeqCst)));
+        let written = File::create(&tmp).and_then(|mut file| {
+            try!(file.write_all(header(id).as_bytes()));
+            try!(file.write_all(data.as_bytes()));
+            file.sync_all()
+        });
+
+        if let Err(e) = written.and_then(|_| fs::rename(&tmp, &path)) {
+            let _ = fs::remove_file(&tmp);
+            return traced_err!(cache_error(&path, e));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the header of the cache file, which identifies the template.
+fn header(id: &str) -> String {
+    format!("{}:{}", id.len(), id)
+}
+
+/// Returns true for cache files and their temporary files, e.g.
+/// `0123456789abcdef.twig`.
+fn is_cache_file(name: &str) -> bool {
+    let bytes = name.as_bytes();
+
+    bytes.len() >= 16 + EXTENSION.len() && bytes[..16].iter().all(u8::is_ascii_hexdigit) &&
+    name[16..].starts_with(EXTENSION)
+}
+
+/// 64-bit FNV-1a hash - stable across platforms and compiler versions, unlike
+/// `std::hash`.
+pub fn hash(data: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
     }
 
-    /// Gets the cache filename for a given template.
-    pub fn get_cache_filename(&self, _name: &str) -> String {
-        unimplemented!()
+    hash
+}
+
+fn cache_error(path: &Path, error: io::Error) -> TwigError {
+    TwigError::Cache {
+        path: path.to_path_buf(),
+        reason: error.to_string(),
+    }
+}
+
+/// Returns the seconds since the epoch - like the modification times checked
+/// by `Loader::is_fresh()`.
+fn now() -> i64 {
+    seconds(SystemTime::now())
+}
+
+fn seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(_) => 0,
     }
 }
@@ -0,0 +1,236 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Access to templates while rendering, e.g. for `source()` and `template_from_string()`.
+
+use std::sync::{Arc, Weak, RwLock};
+use loader::Loader;
+use engine::{Lexer, Parser, TwigError};
+use engine::template_cache::{self, Cache};
+use engine::node::{self, codec};
+use template;
+use api::error::Traced;
+
+/// Loads and compiles templates on behalf of running templates.
+///
+/// The engine shares its loader, lexer and parser with every job it renders -
+/// across threads.
+#[derive(Debug)]
+pub struct Templates {
+    loader: Option<Arc<Loader>>,
+    lexer: Arc<Lexer>,
+    parser: Arc<Parser>,
+    cache: Weak<RwLock<Cache>>, // the engine owns the cache
+    precompiled: Option<Arc<template::Precompiled>>,
+    settings: String,
+}
+
+impl Templates {
+    pub fn new(loader: Option<Arc<Loader>>,
+               lexer: Arc<Lexer>,
+               parser: Arc<Parser>,
+               cache: Weak<RwLock<Cache>>,
+               precompiled: Option<Arc<template::Precompiled>>)
+               -> Templates {
+        let settings = settings(&lexer, &parser);
+
+        Templates {
+            loader: loader,
+            lexer: lexer,
+            parser: parser,
+            cache: cache,
+            precompiled: precompiled,
+            settings: settings,
+        }
+    }
+
+    /// Returns the engine settings, which affect lexing and parsing - the lexer
+    /// options, the extensions, the autoescape mode and the escapers.
+    pub fn settings(&self) -> &str {
+        &self.settings
+    }
+
+    /// Returns the hash of the settings - e.g. to check precompiled templates.
+    pub fn fingerprint(&self) -> u64 {
+        template_cache::hash(&self.settings)
+    }
+
+    /// Loads and compiles the template `name` - or returns the cached template.
+    ///
+    /// Precompiled templates take precedence over the templates of the loader.
+    /// They must be built with the same settings as the engine.
+    ///
+    /// Cached templates are recompiled, if `auto_reload` is enabled and the
+    /// loader reports changes. Threads loading the same template concurrently
+    /// all get the template which was cached first. With a cache directory the parsed templates are
+    /// stored on the filesystem, too - e.g. for other processes.
+    ///
+    /// # Failures
+    /// * When the template cannot be found
+    /// * When the template was precompiled with other settings
+    /// * When an error occurred during compilation
+    pub fn load(templates: &Arc<Templates>,
+                name: &str,
+                sandboxed: bool)
+                -> Result<Arc<template::Compiled>, Traced<TwigError>> {
+        let sandboxed = templates.is_sandboxed(name, sandboxed);
+        // precompiled templates never change - sandboxed templates are
+        // compiled from source, to apply the security policy
+        let precompiled = match templates.precompiled {
+            Some(ref precompiled) if !sandboxed && precompiled.has(name) => Some(precompiled),
+            _ => None,
+        };
+        if precompiled.and_then(|x| x.fingerprint()).is_some_and(|x| x != templates.fingerprint()) {
+            return traced_err!(TwigError::Precompiled { name: name.to_string() });
+        }
+
+        let key = match (precompiled, &templates.loader) {
+            (Some(_), _) => String::new(),
+            (None, &Some(ref loader)) => {
+                try_traced!(loader.cache_key(name)).into_owned()
+            }
+            (None, &None) => return traced_err!(TwigError::LoaderNotInitialized),
+        };
+
+        let mut outdated = None;
+        if let Some(cache) = templates.cache.upgrade() {
+            let cache = cache.read().unwrap();
+
+            if let Some((compiled, time)) = cache.get(name, &key, sandboxed) {
+                if precompiled.is_some() || !cache.auto_reload() || templates.is_fresh(name, time) {
+                    return Ok(compiled);
+                }
+                outdated = Some(compiled);
+            }
+        }
+
+        let mut compiled = match precompiled.and_then(|x| x.module(name)) {
+            Some(module) => try_traced!(templates.parser.compile_module(module)),
+            None => try_traced!(templates.compile_cached(name, &key, sandboxed)),
+        };
+        compiled.set_templates(templates.clone());
+        let compiled = Arc::new(compiled);
+
+        if let Some(cache) = templates.cache.upgrade() {
+            let mut cache = cache.write().unwrap();
+
+            // another thread may have cached the template in the meantime
+            match (cache.get(name, &key, sandboxed), outdated) {
+                (Some((ref cached, _)), Some(ref outdated)) if Arc::ptr_eq(cached, outdated) => {}
+                (Some((cached, _)), _) => return Ok(cached),
+                (None, _) => {}
+            }
+            cache.insert(name, &key, sandboxed, compiled.clone());
+        }
+
+        Ok(compiled)
+    }
+
+    /// Compiles the template `name` - with the parsed module from the cache
+    /// directory, if it is fresh.
+    ///
+    /// Sandboxed templates are always compiled from source, to apply the
+    /// current security policy.
+    fn compile_cached(&self,
+                      name: &str,
+                      key: &str,
+                      sandboxed: bool)
+                      -> Result<template::Compiled, Traced<TwigError>> {
+        let cache = match self.cache.upgrade() {
+            Some(cache) => cache,
+            None => return self.compile(&try_traced!(self.source(name)), name, sandboxed),
+        };
+        if sandboxed || cache.read().unwrap().filecache().is_none() {
+            return self.compile(&try_traced!(self.source(name)), name, sandboxed);
+        }
+
+        let id = self.cache_id(name, key);
+        let cached = cache.read().unwrap().read_file(&id);
+
+        if let Some((data, time)) = cached {
+            if !cache.read().unwrap().auto_reload() || self.is_fresh(name, time) {
+                if let Some(module) = codec::decode(&data, &self.parser) {
+                    return Ok(try_traced!(self.parser.compile_module(module)));
+                }
+            }
+        }
+
+        let module = try_traced!(self.parse_module(&try_traced!(self.source(name)), name));
+
+        if let Some(data) = codec::encode(&module) {
+            try_traced!(cache.read().unwrap().write_file(&id, &data));
+        }
+
+        Ok(try_traced!(self.parser.compile_module(module)))
+    }
+
+    /// Identifies the template in the cache directory - including the engine
+    /// settings, which affect lexing and parsing.
+    fn cache_id(&self, name: &str, key: &str) -> String {
+        format!("twig {} {}\n{}\n{}\n{}",
+                env!("CARGO_PKG_VERSION"),
+                codec::VERSION,
+                self.settings,
+                name,
+                key)
+    }
+
+    /// Returns true if the template is sandboxed - explicitly or by the sandbox mode.
+    fn is_sandboxed(&self, name: &str, sandboxed: bool) -> bool {
+        sandboxed || self.parser.sandbox().is_some_and(|x| x.is_sandboxed(name))
+    }
+
+    /// Returns true if the template `name` did not change since `time`.
+    fn is_fresh(&self, name: &str, time: i64) -> bool {
+        match self.loader {
+            Some(ref loader) => loader.is_fresh(name, time),
+            None => false,
+        }
+    }
+
+    /// Returns the source of the template `name`.
+    pub fn source(&self, name: &str) -> Result<String, Traced<TwigError>> {
+        match self.loader {
+            Some(ref loader) => Ok(try_traced!(loader.source(name)).into_owned()),
+            None => traced_err!(TwigError::LoaderNotInitialized),
+        }
+    }
+
+    /// Parses a template from its source - without applying the node visitors,
+    /// see `Parser::parse_module()`.
+    pub fn parse_module(&self, source: &str, name: &str) -> Result<node::Module, Traced<TwigError>> {
+        let template = template::Raw::new(source, name);
+        let tokenstream = try_traced!(self.lexer.tokenize(&template));
+
+        Ok(try_traced!(self.parser.parse_module(&tokenstream)))
+    }
+
+    /// Compiles a template from its source - sandboxed if `sandboxed` is true
+    /// or the sandbox mode says so.
+    pub fn compile(&self,
+                   source: &str,
+                   name: &str,
+                   sandboxed: bool)
+                   -> Result<template::Compiled, Traced<TwigError>> {
+        let template = template::Raw::new(source, name);
+        let tokenstream = try_traced!(self.lexer.tokenize(&template));
+
+        Ok(try_traced!(self.parser.parse_sandboxed(&tokenstream, sandboxed)))
+    }
+}
+
+/// Describes the settings of the lexer and parser, see `Templates::settings()`.
+fn settings(lexer: &Lexer, parser: &Parser) -> String {
+    let mut extensions: Vec<&str> = parser.extensions().iter().map(|x| x.name()).collect();
+    extensions.sort();
+    let mut escapers: Vec<&String> = parser.escapers().keys().collect();
+    escapers.sort();
+
+    format!("{:?}\n{:?} {:?} {:?}",
+            lexer.options(),
+            extensions,
+            parser.autoescape(),
+            escapers)
+}
@@ -0,0 +1,142 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Filters defined by Twig extensions.
+
+use std::fmt;
+use runtime::{Runtime, Job, Value, RuntimeError};
+use api::error::Traced;
+
+/// Filter defined by Twig extensions, e.g. `{{ name|upper }}`.
+pub trait Filter : fmt::Debug + Send + Sync {
+    /// Applies the filter to `input`.
+    fn apply(&self,
+             runtime: &Runtime,
+             job: &mut Job,
+             input: Value,
+             args: Vec<Value>)
+             -> Result<Value, Traced<RuntimeError>>;
+
+    /// Returns the escaping strategies the output is safe for, e.g. `["html"]`.
+    ///
+    /// `"all"` marks the output as safe for any strategy. Arguments are only
+    /// known if they are constant expressions.
+    fn is_safe(&self, _args: &[Option<Value>]) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns true if the output is as safe as the input.
+    fn preserves_safety(&self) -> bool {
+        false
+    }
+
+    /// Returns the escaping strategy to apply to the input first, e.g. "html".
+    fn pre_escape(&self) -> Option<&str> {
+        None
+    }
+}
+
+pub type FilterCallable = Fn(&Runtime, &mut Job, Value, Vec<Value>)
+                             -> Result<Value, Traced<RuntimeError>>
+                             + Send + Sync;
+
+/// Filter backed by a closure.
+///
+/// # Examples
+///
+/// ```
+/// use twig::extension::api::SimpleFilter;
+/// use twig::runtime::Value;
+///
+/// let filter = SimpleFilter::new("shout", |input, _args| {
+///     Ok(Value::from(input.to_string().to_uppercase() + "!"))
+/// }).preserves_safety();
+/// ```
+pub struct SimpleFilter {
+    name: String,
+    callable: Box<FilterCallable>,
+    is_safe: Vec<String>,
+    preserves_safety: bool,
+    pre_escape: Option<String>,
+}
+
+impl SimpleFilter {
+    pub fn new<F>(name: &str, f: F) -> SimpleFilter
+        where F: Fn(Value, Vec<Value>) -> Result<Value, Traced<RuntimeError>>,
+              F: Send + Sync + 'static
+    {
+        SimpleFilter::with_job(name, move |_runtime, _job, input, args| f(input, args))
+    }
+
+    /// Creates a filter with access to the runtime and the current job.
+    pub fn with_job<F>(name: &str, f: F) -> SimpleFilter
+        where F: Fn(&Runtime, &mut Job, Value, Vec<Value>) -> Result<Value, Traced<RuntimeError>>,
+              F: Send + Sync + 'static
+    {
+        SimpleFilter {
+            name: name.to_string(),
+            callable: Box::new(f),
+            is_safe: Vec::new(),
+            preserves_safety: false,
+            pre_escape: None,
+        }
+    }
+
+    /// Marks the output as safe for the given escaping strategies.
+    pub fn safe_for(mut self, strategies: &[&str]) -> SimpleFilter {
+        self.is_safe = strategies.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Marks the output as safe as the input.
+    pub fn preserves_safety(mut self) -> SimpleFilter {
+        self.preserves_safety = true;
+        self
+    }
+
+    /// Escapes the input with `strategy`, unless it is safe already.
+    pub fn pre_escape(mut self, strategy: &str) -> SimpleFilter {
+        self.pre_escape = Some(strategy.to_string());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the filter as boxed trait object, ready for registration.
+    pub fn boxed(self) -> Box<Filter> {
+        Box::new(self)
+    }
+}
+
+impl Filter for SimpleFilter {
+    fn apply(&self,
+             runtime: &Runtime,
+             job: &mut Job,
+             input: Value,
+             args: Vec<Value>)
+             -> Result<Value, Traced<RuntimeError>> {
+        (self.callable)(runtime, job, input, args)
+    }
+
+    fn is_safe(&self, _args: &[Option<Value>]) -> Vec<String> {
+        self.is_safe.clone()
+    }
+
+    fn preserves_safety(&self) -> bool {
+        self.preserves_safety
+    }
+
+    fn pre_escape(&self) -> Option<&str> {
+        self.pre_escape.as_deref()
+    }
+}
+
+impl fmt::Debug for SimpleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SimpleFilter({:?})", self.name)
+    }
+}
@@ -0,0 +1,108 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Functions defined by Twig extensions.
+
+use std::fmt;
+use runtime::{Runtime, Job, Value, RuntimeError};
+use api::error::Traced;
+
+/// Function defined by Twig extensions, e.g. `{{ range(1, 3) }}`.
+pub trait Function : fmt::Debug + Send + Sync {
+    fn call(&self,
+            runtime: &Runtime,
+            job: &mut Job,
+            args: Vec<Value>)
+            -> Result<Value, Traced<RuntimeError>>;
+
+    /// Returns the escaping strategies the output is safe for, see `Filter::is_safe()`.
+    fn is_safe(&self, _args: &[Option<Value>]) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns true if the function reads the context, e.g. `dump()` - the
+    /// optimizer keeps the `loop` variable of surrounding for-loops then.
+    fn needs_context(&self) -> bool {
+        false
+    }
+}
+
+pub type FunctionCallable = Fn(&Runtime, &mut Job, Vec<Value>)
+                               -> Result<Value, Traced<RuntimeError>>
+                               + Send + Sync;
+
+/// Function backed by a closure.
+pub struct SimpleFunction {
+    name: String,
+    callable: Box<FunctionCallable>,
+    is_safe: Vec<String>,
+    needs_context: bool,
+}
+
+impl SimpleFunction {
+    pub fn new<F>(name: &str, f: F) -> SimpleFunction
+        where F: Fn(Vec<Value>) -> Result<Value, Traced<RuntimeError>> + Send + Sync + 'static
+    {
+        SimpleFunction::with_job(name, move |_runtime, _job, args| f(args))
+    }
+
+    /// Creates a function with access to the runtime and the current job.
+    pub fn with_job<F>(name: &str, f: F) -> SimpleFunction
+        where F: Fn(&Runtime, &mut Job, Vec<Value>) -> Result<Value, Traced<RuntimeError>>,
+              F: Send + Sync + 'static
+    {
+        SimpleFunction {
+            name: name.to_string(),
+            callable: Box::new(f),
+            is_safe: Vec::new(),
+            needs_context: false,
+        }
+    }
+
+    /// Marks the output as safe for the given escaping strategies.
+    pub fn safe_for(mut self, strategies: &[&str]) -> SimpleFunction {
+        self.is_safe = strategies.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// Marks the function as reading the context, see `Function::needs_context()`.
+    pub fn needs_context(mut self) -> SimpleFunction {
+        self.needs_context = true;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the function as boxed trait object, ready for registration.
+    pub fn boxed(self) -> Box<Function> {
+        Box::new(self)
+    }
+}
+
+impl Function for SimpleFunction {
+    fn call(&self,
+            runtime: &Runtime,
+            job: &mut Job,
+            args: Vec<Value>)
+            -> Result<Value, Traced<RuntimeError>> {
+        (self.callable)(runtime, job, args)
+    }
+
+    fn is_safe(&self, _args: &[Option<Value>]) -> Vec<String> {
+        self.is_safe.clone()
+    }
+
+    fn needs_context(&self) -> bool {
+        self.needs_context
+    }
+}
+
+impl fmt::Debug for SimpleFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SimpleFunction({:?})", self.name)
+    }
+}
@@ -0,0 +1,111 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Globals defined by Twig extensions.
+
+use std::sync::Mutex;
+use std::fmt;
+use runtime::Value;
+
+/// Global variable defined by Twig extensions, e.g. `{{ site_name }}`.
+///
+/// Globals are visible in every template - variables of the context take precedence.
+pub trait Global : fmt::Debug + Send + Sync {
+    fn name(&self) -> &str;
+
+    fn value(&self) -> Value;
+}
+
+pub type GlobalCallable = Fn() -> Value + Send + Sync;
+
+/// Global with a static value, or computed by a closure on first use.
+///
+/// # Examples
+///
+/// ```
+/// use twig::extension::api::SimpleGlobal;
+///
+/// let site_name = SimpleGlobal::new("site_name", "Example");
+/// let year = SimpleGlobal::lazy("year", || 2015.into());
+/// ```
+pub struct SimpleGlobal {
+    name: String,
+    value: Mutex<Option<Value>>,
+    callable: Option<Box<GlobalCallable>>,
+}
+
+impl SimpleGlobal {
+    pub fn new<V>(name: &str, value: V) -> SimpleGlobal
+        where V: Into<Value>
+    {
+        SimpleGlobal {
+            name: name.to_string(),
+            value: Mutex::new(Some(value.into())),
+            callable: None,
+        }
+    }
+
+    /// Creates a global, which is computed by the closure on first use.
+    pub fn lazy<F>(name: &str, f: F) -> SimpleGlobal
+        where F: Fn() -> Value + Send + Sync + 'static
+    {
+        SimpleGlobal {
+            name: name.to_string(),
+            value: Mutex::new(None),
+            callable: Some(Box::new(f)),
+        }
+    }
+
+    /// Returns the global as boxed trait object, ready for registration.
+    pub fn boxed(self) -> Box<Global> {
+        Box::new(self)
+    }
+}
+
+impl Global for SimpleGlobal {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn value(&self) -> Value {
+        if let Some(ref value) = *self.value.lock().unwrap() {
+            return value.clone();
+        }
+
+        // computed without holding the lock - concurrent calls keep the first value
+        let value = self.callable.as_ref().map(|f| f()).unwrap_or_default();
+
+        self.value.lock().unwrap().get_or_insert(value).clone()
+    }
+}
+
+impl fmt::Debug for SimpleGlobal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SimpleGlobal({:?})", self.name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use runtime::Value;
+
+    #[test]
+    pub fn lazy() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let global = SimpleGlobal::lazy("answer", move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Value::Integer(42)
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(global.value(), Value::Integer(42));
+        assert_eq!(global.value(), Value::Integer(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
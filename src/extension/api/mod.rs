@@ -0,0 +1,251 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Twig Extension API
+
+use std::fmt;
+use std::collections::HashMap;
+use engine::{self, Node};
+use engine::parser::{Parser, Job, ParserError};
+use engine::parser::token::stream::Item;
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+
+pub mod filter;
+pub mod function;
+pub mod global;
+pub use self::filter::{Filter, SimpleFilter};
+pub use self::function::{Function, SimpleFunction};
+pub use self::global::{Global, SimpleGlobal};
+
+/// Extends the Twig Engine with new behaviour.
+pub trait Extension : fmt::Debug + Send + Sync {
+    /// Get the name of the extension.
+    fn name(&self) -> &'static str;
+
+    /// Initialize the engine.
+    /// This is where you can load some file that contains filter functions for instance.
+    fn init(&self, _engine: &mut engine::Engine) {} // TODO: add error handling ???
+
+    /// Get the token parser instances to register with the engine.
+    fn token_parsers(&self) -> HashMap<String, Box<TokenParser>> {
+        HashMap::new()
+    }
+
+    /// Get the node visitor instances to register with the engine.
+    fn node_visitors(&self) -> Vec<Box<NodeVisitor>> {
+        Vec::new()
+    }
+
+    /// Get the filters to register with the engine.
+    fn filters(&self) -> HashMap<String, Box<Filter>> {
+        HashMap::new()
+    }
+
+    /// Get the tests to register with the engine.
+    fn tests(&self) -> HashMap<String, Box<Test>> {
+        HashMap::new()
+    }
+
+    /// Get the functions to register with the engine.
+    fn functions(&self) -> HashMap<String, Box<Function>> {
+        HashMap::new()
+    }
+
+    /// Get the unary operators to register with the engine.
+    fn operators_unary(&self) -> Vec<UnaryOperator> {
+        Vec::new()
+    }
+
+    /// Get the binary operators to register with the engine.
+    fn operators_binary(&self) -> Vec<BinaryOperator> {
+        Vec::new()
+    }
+
+    /// Get the global variables to register with the engine.
+    fn globals(&self) -> Vec<Box<Global>> {
+        Vec::new()
+    }
+}
+
+// Abstract extension traits + structs - TODO: check what needs to be trait / can be struct
+
+/// Node visitor defined by Twig extensions.
+///
+/// Visits the nodes of each compiled template - see `engine::node::NodeTraverser`.
+pub trait NodeVisitor : fmt::Debug + Send + Sync {
+    /// Called before the child nodes are visited - returns the (replaced) node.
+    fn enter_node(&self, node: Box<Node>, _parser: &Parser) -> Box<Node> {
+        node
+    }
+
+    /// Called after the child nodes are visited - returns the (replaced) node,
+    /// or `None` to remove it.
+    fn leave_node(&self, node: Box<Node>, _parser: &Parser) -> Option<Box<Node>> {
+        Some(node)
+    }
+
+    /// Returns the priority between -10 and 10 - visitors with a lower priority
+    /// run first, visitors with the same priority in order of registration.
+    fn priority(&self) -> i32 {
+        0
+    }
+}
+
+/// Test defined by Twig extensions.
+pub trait Test : fmt::Debug + Send + Sync {}
+
+/// Token Parser defined by Twig extensions.
+///
+/// Also called 'tag handler' by twig parser.
+pub trait TokenParser : fmt::Debug + Send + Sync {
+    fn tag(&self) -> &'static str;
+
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>>;
+}
+
+pub mod token_parser {
+    use engine::parser::token::stream::Item;
+
+    pub type Test = Fn(&Item) -> TestResult;
+
+    #[derive(Debug)]
+    pub enum TestResult {
+        Continue, // orig: no_match
+        KeepToken, // orig: is_match + dropNeedle == false
+        DropToken, // orig: is_match + dropNeedle == true
+    }
+}
+
+/// Unary operator defined by Twig extensions, e.g. `{{ -a }}`.
+///
+/// Operators which end with a letter, like `not`, are lexed as names.
+#[derive(Debug)]
+pub struct UnaryOperator {
+    pub repr: String, // token representation like "-"
+    pub prec: op::Precedence,
+    pub op: op::Unary,
+}
+
+impl UnaryOperator {
+    /// Creates an operator, which applies `f` to the operand.
+    pub fn new<F>(repr: &str, prec: usize, f: F) -> UnaryOperator
+        where F: Fn(&Value) -> Result<Value, Traced<RuntimeError>> + Send + Sync + 'static
+    {
+        UnaryOperator {
+            repr: repr.to_string(),
+            prec: op::Precedence(prec),
+            op: op::Unary::Callable(Box::new(f)),
+        }
+    }
+}
+
+/// Binary operator defined by Twig extensions, e.g. `{{ a + b }}`.
+///
+/// Operators which end with a letter, like `and`, are lexed as names.
+#[derive(Debug)]
+pub struct BinaryOperator {
+    pub repr: String, // token representation like "!="
+    pub prec: op::Precedence,
+    pub op: op::Binary,
+    pub assoc: op::Assoc,
+}
+
+impl BinaryOperator {
+    /// Creates an operator, which applies `f` to both operands.
+    pub fn new<F>(repr: &str, prec: usize, assoc: op::Assoc, f: F) -> BinaryOperator
+        where F: Fn(&Value, &Value) -> Result<Value, Traced<RuntimeError>> + Send + Sync + 'static
+    {
+        BinaryOperator {
+            repr: repr.to_string(),
+            prec: op::Precedence(prec),
+            op: op::Binary::Callable(Box::new(f)),
+            assoc: assoc,
+        }
+    }
+
+    /// Creates a boolean operator, which skips the right operand if the left
+    /// one is `value` - e.g. `or` with `true`.
+    pub fn short_circuit(repr: &str, prec: usize, value: bool) -> BinaryOperator {
+        BinaryOperator {
+            repr: repr.to_string(),
+            prec: op::Precedence(prec),
+            op: op::Binary::ShortCircuit(value),
+            assoc: op::Assoc::Left,
+        }
+    }
+}
+
+pub mod op {
+    use std::fmt;
+    use runtime::{Value, RuntimeError};
+    use api::error::Traced;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Extension(String); // might switch to ID for faster lookups
+
+    #[derive(Clone, Debug, PartialEq, PartialOrd)]
+    pub struct Precedence(pub usize);
+
+    #[derive(Debug, PartialEq)]
+    pub enum Operation {
+        Class(Class),
+        Callable(Function),
+    }
+
+    pub type UnaryCallable = Fn(&Value) -> Result<Value, Traced<RuntimeError>> + Send + Sync;
+
+    /// Evaluation of a unary operator.
+    pub enum Unary {
+        /// Applies the callable to the operand.
+        Callable(Box<UnaryCallable>),
+    }
+
+    impl fmt::Debug for Unary {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Unary::Callable(_) => write!(f, "Callable"),
+            }
+        }
+    }
+
+    pub type BinaryCallable = Fn(&Value, &Value) -> Result<Value, Traced<RuntimeError>>
+                                  + Send + Sync;
+
+    /// Evaluation of a binary operator.
+    pub enum Binary {
+        /// Applies the callable to both operands.
+        Callable(Box<BinaryCallable>),
+        /// Returns the boolean, if the left operand is true resp. false - or
+        /// else the right operand as boolean.
+        ShortCircuit(bool),
+    }
+
+    impl fmt::Debug for Binary {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match *self {
+                Binary::Callable(_) => write!(f, "Callable"),
+                Binary::ShortCircuit(x) => write!(f, "ShortCircuit({:?})", x),
+            }
+        }
+    }
+
+    /// Associativity
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Assoc {
+        Left,
+        Right,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Function {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Class {
+        name: String,
+    }
+}
@@ -0,0 +1,164 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Formats dates with PHP format characters, e.g. "F j, Y H:i".
+
+use std::fmt::Write;
+use chrono::{Datelike, Timelike, NaiveDate};
+use runtime::value::Date;
+use super::{zone_of, local, format_offset};
+
+const DAYS: [&'static str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday",
+                                 "Saturday", "Sunday"];
+
+const MONTHS: [&'static str; 12] = ["January", "February", "March", "April", "May", "June",
+                                    "July", "August", "September", "October", "November",
+                                    "December"];
+
+/// Formats the date - a backslash escapes the next character.
+///
+/// orig: $date->format($format)
+pub fn format(date: &Date, format: &str) -> String {
+    let (offset, abbreviation, dst) = zone_of(date).info(date.timestamp());
+    let local = local(date);
+    let weekday = local.weekday().num_days_from_monday() as usize;
+    let month = local.month0() as usize;
+    let hour12 = match local.hour() % 12 {
+        0 => 12,
+        x => x,
+    };
+
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        // writing to a string never fails
+        let _ = match c {
+            // day
+            'd' => write!(out, "{:02}", local.day()),
+            'D' => write!(out, "{}", &DAYS[weekday][..3]),
+            'j' => write!(out, "{}", local.day()),
+            'l' => write!(out, "{}", DAYS[weekday]),
+            'N' => write!(out, "{}", weekday + 1),
+            'S' => write!(out, "{}", ordinal_suffix(local.day())),
+            'w' => write!(out, "{}", local.weekday().num_days_from_sunday()),
+            'z' => write!(out, "{}", local.ordinal0()),
+            // week
+            'W' => write!(out, "{:02}", local.iso_week().week()),
+            // month
+            'F' => write!(out, "{}", MONTHS[month]),
+            'm' => write!(out, "{:02}", local.month()),
+            'M' => write!(out, "{}", &MONTHS[month][..3]),
+            'n' => write!(out, "{}", local.month()),
+            't' => write!(out, "{}", days_in_month(local.year(), local.month())),
+            // year
+            'L' => write!(out, "{}", is_leap_year(local.year()) as u8),
+            'o' => write!(out, "{}", local.iso_week().year()),
+            'Y' => write!(out, "{}", year(local.year())),
+            'y' => write!(out, "{:02}", local.year().rem_euclid(100)),
+            // time
+            'a' => write!(out, "{}", if local.hour() < 12 { "am" } else { "pm" }),
+            'A' => write!(out, "{}", if local.hour() < 12 { "AM" } else { "PM" }),
+            'B' => {
+                let beats = (date.timestamp() + 3600).rem_euclid(86400) * 10 / 864;
+                write!(out, "{:03}", beats)
+            }
+            'g' => write!(out, "{}", hour12),
+            'G' => write!(out, "{}", local.hour()),
+            'h' => write!(out, "{:02}", hour12),
+            'H' => write!(out, "{:02}", local.hour()),
+            'i' => write!(out, "{:02}", local.minute()),
+            's' => write!(out, "{:02}", local.second()),
+            'u' => write!(out, "{:06}", date.micros()),
+            'v' => write!(out, "{:03}", date.micros() / 1000),
+            // timezone
+            'e' => write!(out, "{}", date.timezone()),
+            'I' => write!(out, "{}", dst as u8),
+            'O' => write!(out, "{}", format_offset(offset, false)),
+            'P' => write!(out, "{}", format_offset(offset, true)),
+            'p' if offset == 0 => write!(out, "Z"),
+            'p' => write!(out, "{}", format_offset(offset, true)),
+            'T' => write!(out, "{}", abbreviation),
+            'Z' => write!(out, "{}", offset),
+            // full date/time
+            'c' => write!(out, "{}", self::format(date, "Y-m-d\\TH:i:sP")),
+            'r' => write!(out, "{}", self::format(date, "D, d M Y H:i:s O")),
+            'U' => write!(out, "{}", date.timestamp()),
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    out.push(c);
+                }
+                Ok(())
+            }
+            c => {
+                out.push(c);
+                Ok(())
+            }
+        };
+    }
+
+    out
+}
+
+/// Returns the year with at least four digits, e.g. "0099" or "-0055".
+fn year(year: i32) -> String {
+    if year < 0 {
+        format!("-{:04}", -year)
+    } else {
+        format!("{:04}", year)
+    }
+}
+
+/// Returns the English ordinal suffix of a day, e.g. "st" for 1.
+fn ordinal_suffix(day: u32) -> &'static str {
+    match (day % 10, day % 100) {
+        (_, 11) | (_, 12) | (_, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    NaiveDate::from_ymd_opt(year, 2, 29).is_some()
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::value::Date;
+
+    #[test]
+    pub fn _format() {
+        // Thursday, 12th February 2004, 15:19:21.5 UTC
+        let date = Date::new(1076599161, 500000, 3600, "Europe/Paris");
+
+        assert_eq!(format(&date, "F j, Y H:i"), "February 12, 2004 16:19");
+        assert_eq!(format(&date, "D, d M y \\a\\t g:i:s.v A T (e)"),
+                   "Thu, 12 Feb 04 at 4:19:21.500 PM CET (Europe/Paris)");
+        assert_eq!(format(&date, "N w z W t L o jS"), "4 4 42 07 29 1 2004 12th");
+        assert_eq!(format(&date, "c|r|U|Z|O|I|B"),
+                   "2004-02-12T16:19:21+01:00|Thu, 12 Feb 2004 16:19:21 +0100|1076599161|3600|\
+                    +0100|0|680");
+        assert_eq!(format(&Date::new(0, 0, 0, "+00:00"), "e T P p"), "+00:00 +00:00 +00:00 Z");
+    }
+
+    #[test]
+    pub fn _ordinal_suffix() {
+        assert_eq!(ordinal_suffix(1), "st");
+        assert_eq!(ordinal_suffix(12), "th");
+        assert_eq!(ordinal_suffix(22), "nd");
+        assert_eq!(ordinal_suffix(23), "rd");
+    }
+}
@@ -0,0 +1,306 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Date handling of the core extension.
+//!
+//! Dates are converted, parsed and formatted like PHP's `\DateTime`. The
+//! timezone database is provided by `chrono-tz`.
+
+use std::str::FromStr;
+use chrono::{Duration, FixedOffset, NaiveDateTime, DateTime, LocalResult, Offset, TimeZone, Timelike,
+             Utc};
+use chrono_tz::{Tz, OffsetComponents, OffsetName, TZ_VARIANTS};
+use runtime::{Value, RuntimeError};
+use runtime::value::Date;
+use api::error::Traced;
+
+pub mod format;
+pub mod parse;
+
+/// Timezone of a date - from the timezone database or a fixed offset.
+///
+/// orig: \DateTimeZone
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Parses identifiers like "Europe/Paris" (case insensitive) and offsets like "+02:00".
+    pub fn parse(name: &str) -> Option<Zone> {
+        if let Some(offset) = parse_offset(name) {
+            return Some(Zone::Fixed(offset));
+        }
+
+        if name.eq_ignore_ascii_case("z") {
+            return Some(Zone::Named(Tz::UTC));
+        }
+
+        match Tz::from_str(name) {
+            Ok(tz) => Some(Zone::Named(tz)),
+            Err(_) => {
+                TZ_VARIANTS.iter()
+                           .find(|tz| tz.name().eq_ignore_ascii_case(name))
+                           .map(|tz| Zone::Named(*tz))
+            }
+        }
+    }
+
+    /// Returns the identifier, e.g. "Europe/Paris" or "+02:00".
+    pub fn name(&self) -> String {
+        match *self {
+            Zone::Named(tz) => tz.name().to_string(),
+            Zone::Fixed(offset) => format_offset(offset.local_minus_utc(), true),
+        }
+    }
+
+    /// Returns the offset in seconds, the abbreviation and the daylight saving
+    /// time flag at `timestamp`.
+    pub fn info(&self, timestamp: i64) -> (i32, String, bool) {
+        match *self {
+            Zone::Named(tz) => {
+                let offset = tz.offset_from_utc_datetime(&naive_utc(timestamp));
+                let seconds = offset.fix().local_minus_utc();
+                let abbreviation = match offset.abbreviation() {
+                    Some(x) => x.to_string(),
+                    None => format_offset(seconds, true),
+                };
+
+                (seconds, abbreviation, offset.dst_offset().num_seconds() != 0)
+            }
+            Zone::Fixed(offset) => {
+                let seconds = offset.local_minus_utc();
+
+                (seconds, format_offset(seconds, true), false)
+            }
+        }
+    }
+
+    /// Returns the timestamp of the local (wall clock) time.
+    ///
+    /// Ambiguous times are resolved to the earlier time and times skipped by
+    /// a transition use the offset before the transition, like PHP.
+    pub fn timestamp(&self, local: &NaiveDateTime) -> i64 {
+        let offset = match *self {
+            Zone::Named(tz) => {
+                match tz.from_local_datetime(local) {
+                    LocalResult::Single(x) | LocalResult::Ambiguous(x, _) => return x.timestamp(),
+                    LocalResult::None => {
+                        tz.offset_from_utc_datetime(&(*local - Duration::days(1)))
+                          .fix()
+                          .local_minus_utc()
+                    }
+                }
+            }
+            Zone::Fixed(offset) => offset.local_minus_utc(),
+        };
+
+        local.and_utc().timestamp() - offset as i64
+    }
+
+    /// Returns the date at `timestamp` in this timezone.
+    pub fn date(&self, timestamp: i64, micros: u32) -> Date {
+        let (offset, _, _) = self.info(timestamp);
+
+        Date::new(timestamp, micros, offset, &self.name())
+    }
+
+    /// Returns the date of the local (wall clock) time in this timezone.
+    pub fn date_from_local(&self, local: &NaiveDateTime) -> Date {
+        self.date(self.timestamp(local), local.nanosecond() / 1000)
+    }
+}
+
+/// Returns the default timezone of the engine - UTC, unless configured.
+pub fn default_zone(timezone: Option<&str>) -> Result<Zone, Traced<RuntimeError>> {
+    match timezone {
+        Some(name) => zone(name),
+        None => Ok(Zone::Named(Tz::UTC)),
+    }
+}
+
+/// orig: new \DateTimeZone($timezone)
+pub fn zone(name: &str) -> Result<Zone, Traced<RuntimeError>> {
+    match Zone::parse(name) {
+        Some(zone) => Ok(zone),
+        None => {
+            traced_err!(RuntimeError::InvalidArgument {
+                reason: format!("Unknown or bad timezone ({})", name),
+            })
+        }
+    }
+}
+
+/// Returns the timezone of a date.
+pub fn zone_of(date: &Date) -> Zone {
+    Zone::parse(date.timezone()).unwrap_or(Zone::Fixed(utc_offset()))
+}
+
+/// Returns the local (wall clock) time of a date.
+pub fn local(date: &Date) -> NaiveDateTime {
+    let local = naive_utc(date.timestamp() + date.offset() as i64);
+
+    local.with_nanosecond(date.micros() * 1000).unwrap_or(local)
+}
+
+/// Returns the current date.
+pub fn now(zone: Zone) -> Date {
+    let now = Utc::now();
+
+    zone.date(now.timestamp(), now.timestamp_subsec_micros())
+}
+
+/// Converts a value to a date in `timezone` - a date, a timestamp or a date string.
+///
+/// The timezone may be null for the default timezone or false to keep the
+/// timezone of the date.
+///
+/// orig: twig_date_converter($env, $date = null, $timezone = null)
+pub fn convert(value: &Value, timezone: &Value, default: Zone) -> Result<Date, Traced<RuntimeError>> {
+    let zone = match *timezone {
+        Value::Boolean(false) => None,
+        Value::Null => Some(default),
+        ref x => Some(try_traced!(self::zone(&x.to_string()))),
+    };
+
+    let date = match *value {
+        Value::Date(ref date) => date.clone(),
+        Value::Null => return Ok(now(zone.unwrap_or(default))),
+        ref x => {
+            let text = x.to_string();
+            let is_digits = |x: &str| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit());
+
+            if text == "now" {
+                return Ok(now(zone.unwrap_or(default)));
+            } else if is_digits(&text) || (text.starts_with('-') && is_digits(&text[1..])) {
+                try_traced!(parse_date(&format!("@{}", text), default))
+            } else {
+                try_traced!(parse_date(&text, default))
+            }
+        }
+    };
+
+    Ok(match zone {
+        Some(zone) => zone.date(date.timestamp(), date.micros()),
+        None => date,
+    })
+}
+
+/// orig: new \DateTime($text, $zone)
+pub fn parse_date(text: &str, zone: Zone) -> Result<Date, Traced<RuntimeError>> {
+    match parse::parse(text, &now(zone)) {
+        Some(date) => Ok(date),
+        None => parse_error(text),
+    }
+}
+
+/// Alters the date with a modifier like "+1 day".
+///
+/// orig: $date->modify($modifier)
+pub fn modify(date: &Date, modifier: &str) -> Result<Date, Traced<RuntimeError>> {
+    match parse::parse(modifier, date) {
+        Some(date) => Ok(date),
+        None => parse_error(modifier),
+    }
+}
+
+fn parse_error<T>(text: &str) -> Result<T, Traced<RuntimeError>> {
+    traced_err!(RuntimeError::InvalidArgument {
+        reason: format!("Failed to parse time string ({})", text),
+    })
+}
+
+/// Returns the UTC time at `timestamp`.
+pub fn naive_utc(timestamp: i64) -> NaiveDateTime {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(x) => x.naive_utc(),
+        None => NaiveDateTime::default(),
+    }
+}
+
+fn utc_offset() -> FixedOffset {
+    FixedOffset::east_opt(0).unwrap()
+}
+
+/// Parses offsets like "+02:00", "+0200" or "-05".
+fn parse_offset(x: &str) -> Option<FixedOffset> {
+    let sign = match x.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None,
+    };
+    let digits = x[1..].replace(":", "");
+    if digits.is_empty() || digits.len() > 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let (hours, minutes) = if digits.len() <= 2 {
+        (digits.parse::<i32>().unwrap(), 0)
+    } else {
+        let (hours, minutes) = digits.split_at(digits.len() - 2);
+        (hours.parse::<i32>().unwrap(), minutes.parse::<i32>().unwrap())
+    };
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Formats an offset in seconds like "+02:00" or "+0200".
+pub fn format_offset(seconds: i32, colon: bool) -> String {
+    let abs = seconds.abs();
+
+    format!("{}{:02}{}{:02}",
+            if seconds < 0 { '-' } else { '+' },
+            abs / 3600,
+            if colon { ":" } else { "" },
+            abs / 60 % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn _zone() {
+        assert_eq!(Zone::parse("europe/paris").map(|x| x.name()),
+                   Some("Europe/Paris".to_string()));
+        assert_eq!(Zone::parse("-0530").map(|x| x.name()), Some("-05:30".to_string()));
+        assert_eq!(Zone::parse("Mars/Olympus"), None);
+    }
+
+    #[test]
+    pub fn _convert() {
+        let paris = Zone::parse("Europe/Paris").unwrap();
+        let utc = Zone::parse("UTC").unwrap();
+
+        let date = convert(&Value::from(1000000000), &Value::Null, paris).unwrap();
+        assert_eq!(date.to_string(), "2001-09-09T03:46:40+02:00");
+
+        let date = convert(&Value::from("1000000000"), &Value::from(false), paris).unwrap();
+        assert_eq!(date.to_string(), "2001-09-09T01:46:40+00:00");
+
+        let date = convert(&Value::from("2020-07-01 12:00"), &Value::from("UTC"), paris).unwrap();
+        assert_eq!(date.to_string(), "2020-07-01T10:00:00+00:00");
+
+        let date = convert(&Value::Date(date), &Value::from("Europe/Paris"), utc).unwrap();
+        assert_eq!(date.timezone(), "Europe/Paris");
+
+        assert!(convert(&Value::from("2020-07-01"), &Value::from("Mars/Olympus"), utc).is_err());
+        assert!(convert(&Value::from("not a date"), &Value::Null, utc).is_err());
+    }
+
+    #[test]
+    pub fn _timestamp() {
+        let paris = Zone::parse("Europe/Paris").unwrap();
+        let local = |x: &str| NaiveDateTime::parse_from_str(x, "%Y-%m-%d %H:%M").unwrap();
+
+        // skipped and repeated by the daylight saving time transitions
+        assert_eq!(paris.date_from_local(&local("2021-03-28 02:30")).to_string(),
+                   "2021-03-28T03:30:00+02:00");
+        assert_eq!(paris.date_from_local(&local("2021-10-31 02:30")).to_string(),
+                   "2021-10-31T02:30:00+02:00");
+    }
+}
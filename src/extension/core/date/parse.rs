@@ -0,0 +1,460 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Parses date strings and modifiers, e.g. "2020-01-31 10:00", "@1580464800",
+//! "June 20, 1971", "+1 day", "next monday" or "first day of next month".
+//!
+//! Absolute parts are applied first, then weekdays and relative parts.
+//!
+//! orig: strtotime()
+
+use regex::{Regex, Captures};
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Weekday};
+use runtime::value::Date;
+use super::{Zone, zone_of, local, naive_utc};
+use super::format::days_in_month;
+
+/// Returns `None` early - like `try!` for results.
+macro_rules! try_opt {
+    ($e:expr) => (match $e {
+        Some(x) => x,
+        None => return None,
+    })
+}
+
+const MONTH: &'static str = "(jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|\
+                             aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|\
+                             dec(?:ember)?)";
+
+const WEEKDAY: &'static str = "(mon(?:day)?|tue(?:s(?:day)?)?|wed(?:nesday)?|thu(?:rs(?:day)?)?|\
+                               fri(?:day)?|sat(?:urday)?|sun(?:day)?)";
+
+const UNIT: &'static str = "(usec|microsecond|msec|millisecond|sec|second|min|minute|hour|day|\
+                            week|fortnight|month|year)s?";
+
+const TIME: &'static str = r"(\d{1,2}):(\d{2})(?::(\d{2})(?:[.,](\d+))?)?";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rule {
+    Separator,
+    Timestamp,
+    IsoDateTime,
+    IsoDate,
+    SlashDate,
+    AmericanDate,
+    EuropeanDate,
+    MonthYear,
+    MonthDay,
+    Time,
+    HourMeridian,
+    FirstLastDayOf,
+    Relative,
+    RelativeUnit,
+    RelativeWeekday,
+    DayMonth,
+    Weekday,
+    Keyword,
+    Ago,
+    Utc,
+    Offset,
+    Identifier,
+}
+
+struct Patterns {
+    rules: Vec<(Rule, Regex)>,
+}
+
+impl Patterns {
+    fn new() -> Patterns {
+        let rules = vec![(Rule::Separator, r"[\s,]+".to_string()),
+                         (Rule::Timestamp, r"@(-?\d+)".to_string()),
+                         (Rule::IsoDateTime, format!(r"(\d{{4}})-(\d{{1,2}})-(\d{{1,2}})t{}", TIME)),
+                         (Rule::IsoDate, r"(\d{4})-(\d{1,2})-(\d{1,2})\b".to_string()),
+                         (Rule::SlashDate, r"(\d{4})/(\d{1,2})/(\d{1,2})\b".to_string()),
+                         (Rule::AmericanDate, r"(\d{1,2})/(\d{1,2})/(\d{4})\b".to_string()),
+                         (Rule::EuropeanDate, r"(\d{1,2})[.-](\d{1,2})[.-](\d{4})\b".to_string()),
+                         (Rule::MonthYear, format!(r"{}[\s,.-]+(\d{{4}})\b", MONTH)),
+                         (Rule::MonthDay,
+                          format!(r"{}[\s.-]*(\d{{1,2}})(?:st|nd|rd|th)?(?:[\s,.-]+(\d{{4}}))?\b",
+                                  MONTH)),
+                         (Rule::Time, format!(r"{}(?:\s*([ap])\.?m\.?\b)?", TIME)),
+                         (Rule::HourMeridian, r"(\d{1,2})\s*([ap])\.?m\.?\b".to_string()),
+                         (Rule::FirstLastDayOf, r"(first|last) day of\b".to_string()),
+                         (Rule::Relative, format!(r"([+-]?)\s*(\d+)\s*{}\b", UNIT)),
+                         (Rule::RelativeUnit, format!(r"(next|last|previous|this)\s+{}\b", UNIT)),
+                         (Rule::RelativeWeekday,
+                          format!(r"(next|last|previous|this)\s+{}\b", WEEKDAY)),
+                         (Rule::DayMonth,
+                          format!(r"(\d{{1,2}})(?:st|nd|rd|th)?[\s.-]*{}(?:[\s,.-]+(\d{{4}}))?\b",
+                                  MONTH)),
+                         (Rule::Weekday, format!(r"{}\b", WEEKDAY)),
+                         (Rule::Keyword, r"(now|today|midnight|noon|tomorrow|yesterday)\b".to_string()),
+                         (Rule::Ago, r"ago\b".to_string()),
+                         (Rule::Utc, r"(?:z|utc|gmt)\b".to_string()),
+                         (Rule::Offset, r"(?:utc|gmt)?([+-]\d{1,2}(?::?\d{2})?)\b".to_string()),
+                         (Rule::Identifier, r"([a-z]+(?:/[a-z0-9_+-]+)+)".to_string())];
+
+        Patterns {
+            rules: rules.into_iter()
+                        .map(|(rule, pattern)| {
+                            (rule, Regex::new(&format!(r"\A(?:{})", pattern)).unwrap())
+                        })
+                        .collect(),
+        }
+    }
+}
+
+thread_local!(static PATTERNS: Patterns = Patterns::new());
+
+/// Weekday relative to the current day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Relative {
+    /// today or the next one
+    This,
+    /// the next one after today
+    Next,
+    /// the last one before today
+    Last,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    timestamp: Option<i64>,
+    date: Option<(Option<i32>, u32, u32)>,
+    time: Option<(u32, u32, u32, u32)>,
+    reset_time: bool,
+    weekday: Option<(Weekday, Relative)>,
+    first_last_day_of: Option<bool>,
+    // years, months, days, hours, minutes, seconds, microseconds
+    relative: [i64; 7],
+    zone: Option<Zone>,
+}
+
+/// Parses `text` relative to `base` - returns `None` if it is invalid.
+///
+/// The result is in the timezone given by `text` or the timezone of `base`.
+pub fn parse(text: &str, base: &Date) -> Option<Date> {
+    let text = text.trim().to_lowercase();
+    let mut state = State::default();
+
+    let mut rest = &text[..];
+    while !rest.is_empty() {
+        let matched = PATTERNS.with(|patterns| {
+            for &(rule, ref regex) in &patterns.rules {
+                if let Some(captures) = regex.captures(rest) {
+                    let length = captures.pos(0).map(|x| x.1).unwrap_or(0);
+                    if length > 0 && apply(&mut state, rule, &captures).is_some() {
+                        return Some(length);
+                    }
+                }
+            }
+            None
+        });
+
+        match matched {
+            Some(length) => rest = &rest[length..],
+            None => return None,
+        }
+    }
+
+    resolve(&state, base)
+}
+
+/// Records a matched rule - returns `None` if the values are out of range.
+fn apply(state: &mut State, rule: Rule, captures: &Captures) -> Option<()> {
+    let number = |i: usize| -> Option<i64> { captures.at(i).and_then(|x| x.parse().ok()) };
+    let text = |i: usize| -> &str { captures.at(i).unwrap_or("") };
+
+    match rule {
+        Rule::Separator => {}
+        Rule::Timestamp => {
+            state.timestamp = Some(try_opt!(number(1)));
+            state.zone = Some(Zone::Fixed(utc()));
+        }
+        Rule::IsoDateTime => {
+            try_opt!(set_date(state, number(1), number(2), number(3)));
+            try_opt!(set_time(state, number(4), number(5), number(6), captures.at(7), None));
+        }
+        Rule::IsoDate | Rule::SlashDate => return set_date(state, number(1), number(2), number(3)),
+        Rule::AmericanDate => return set_date(state, number(3), number(1), number(2)),
+        Rule::EuropeanDate => return set_date(state, number(3), number(2), number(1)),
+        Rule::MonthYear => return set_date(state, number(2), Some(month(text(1))), Some(1)),
+        Rule::MonthDay => return set_date(state, number(3), Some(month(text(1))), number(2)),
+        Rule::DayMonth => return set_date(state, number(3), Some(month(text(2))), number(1)),
+        Rule::Time => {
+            return set_time(state, number(1), number(2), number(3), captures.at(4), captures.at(5))
+        }
+        Rule::HourMeridian => return set_time(state, number(1), Some(0), None, None, captures.at(2)),
+        Rule::FirstLastDayOf => state.first_last_day_of = Some(text(1) == "first"),
+        Rule::Relative => {
+            let amount = try_opt!(number(2));
+            let sign = if text(1) == "-" { -1 } else { 1 };
+            add_relative(state, sign * amount, text(3));
+        }
+        Rule::RelativeUnit => {
+            let amount = match text(1) {
+                "next" => 1,
+                "this" => 0,
+                _ => -1,
+            };
+            add_relative(state, amount, text(2));
+        }
+        Rule::RelativeWeekday => {
+            let relative = match text(1) {
+                "next" => Relative::Next,
+                "this" => Relative::This,
+                _ => Relative::Last,
+            };
+            state.weekday = Some((weekday(text(2)), relative));
+        }
+        Rule::Weekday => state.weekday = Some((weekday(text(1)), Relative::This)),
+        Rule::Keyword => {
+            match text(1) {
+                "now" => {}
+                "today" | "midnight" => state.reset_time = true,
+                "noon" => return set_time(state, Some(12), Some(0), Some(0), None, None),
+                "tomorrow" => {
+                    state.reset_time = true;
+                    state.relative[2] += 1;
+                }
+                _ => {
+                    state.reset_time = true;
+                    state.relative[2] -= 1;
+                }
+            }
+        }
+        Rule::Ago => {
+            for x in state.relative.iter_mut() {
+                *x = -*x;
+            }
+        }
+        Rule::Utc => state.zone = Some(Zone::Fixed(utc())),
+        Rule::Offset | Rule::Identifier => state.zone = Some(try_opt!(Zone::parse(text(1)))),
+    }
+
+    Some(())
+}
+
+fn utc() -> FixedOffset {
+    FixedOffset::east_opt(0).unwrap()
+}
+
+fn set_date(state: &mut State, year: Option<i64>, month: Option<i64>, day: Option<i64>) -> Option<()> {
+    let month = try_opt!(month) as u32;
+    let day = try_opt!(day) as u32;
+    if !(1..=12).contains(&month) || day > 31 {
+        return None;
+    }
+
+    state.date = Some((year.map(|x| x as i32), month, day));
+    state.reset_time = true;
+
+    Some(())
+}
+
+fn set_time(state: &mut State,
+            hour: Option<i64>,
+            minute: Option<i64>,
+            second: Option<i64>,
+            fraction: Option<&str>,
+            meridian: Option<&str>)
+            -> Option<()> {
+    let mut hour = try_opt!(hour) as u32;
+    let minute = try_opt!(minute) as u32;
+    let second = second.unwrap_or(0) as u32;
+    let micros = match fraction {
+        Some(x) => try_opt!(format!("{:0<6}", &x[..::std::cmp::min(x.len(), 6)]).parse().ok()),
+        None => 0,
+    };
+
+    match meridian {
+        Some(x) => {
+            if !(1..=12).contains(&hour) {
+                return None;
+            }
+            hour = hour % 12 + if x == "p" { 12 } else { 0 };
+        }
+        None if hour > 23 => return None,
+        None => {}
+    }
+    if minute > 59 || second > 59 {
+        return None;
+    }
+
+    state.time = Some((hour, minute, second, micros));
+
+    Some(())
+}
+
+fn add_relative(state: &mut State, amount: i64, unit: &str) {
+    let (index, factor) = match unit {
+        "year" => (0, 1),
+        "month" => (1, 1),
+        "fortnight" => (2, 14),
+        "week" => (2, 7),
+        "day" => (2, 1),
+        "hour" => (3, 1),
+        "min" | "minute" => (4, 1),
+        "sec" | "second" => (5, 1),
+        "msec" | "millisecond" => (6, 1000),
+        _ => (6, 1),
+    };
+
+    state.relative[index] += amount * factor;
+}
+
+fn month(name: &str) -> i64 {
+    match &name[..3] {
+        "jan" => 1,
+        "feb" => 2,
+        "mar" => 3,
+        "apr" => 4,
+        "may" => 5,
+        "jun" => 6,
+        "jul" => 7,
+        "aug" => 8,
+        "sep" => 9,
+        "oct" => 10,
+        "nov" => 11,
+        _ => 12,
+    }
+}
+
+fn weekday(name: &str) -> Weekday {
+    match &name[..3] {
+        "mon" => Weekday::Mon,
+        "tue" => Weekday::Tue,
+        "wed" => Weekday::Wed,
+        "thu" => Weekday::Thu,
+        "fri" => Weekday::Fri,
+        "sat" => Weekday::Sat,
+        _ => Weekday::Sun,
+    }
+}
+
+/// Returns the date - days beyond the end of the month overflow into the next month.
+fn ymd(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    let first = try_opt!(NaiveDate::from_ymd_opt(year, month, 1));
+
+    first.checked_add_signed(Duration::days(day as i64 - 1))
+}
+
+fn resolve(state: &State, base: &Date) -> Option<Date> {
+    let zone = state.zone.unwrap_or_else(|| zone_of(base));
+    let mut local = match state.timestamp {
+        Some(timestamp) => naive_utc(timestamp),
+        None => local(&zone.date(base.timestamp(), base.micros())),
+    };
+
+    if let Some((year, month, day)) = state.date {
+        local = try_opt!(ymd(year.unwrap_or(local.year()), month, day)).and_time(local.time());
+    }
+    if let Some((hour, minute, second, micros)) = state.time {
+        local = try_opt!(local.date().and_hms_micro_opt(hour, minute, second, micros));
+    } else if state.reset_time || state.weekday.is_some() {
+        local = try_opt!(local.date().and_hms_opt(0, 0, 0));
+    }
+
+    if let Some((weekday, relative)) = state.weekday {
+        let current = local.weekday().num_days_from_monday() as i64;
+        let target = weekday.num_days_from_monday() as i64;
+        let days = match relative {
+            Relative::This => (target - current).rem_euclid(7),
+            Relative::Next => (target - current - 1).rem_euclid(7) + 1,
+            Relative::Last => -((current - target - 1).rem_euclid(7) + 1),
+        };
+        local = try_opt!(local.checked_add_signed(Duration::days(days)));
+    }
+
+    let months = state.relative[0] * 12 + state.relative[1];
+    if months != 0 {
+        let total = local.year() as i64 * 12 + local.month0() as i64 + months;
+        let date = try_opt!(ymd(total.div_euclid(12) as i32,
+                                total.rem_euclid(12) as u32 + 1,
+                                local.day()));
+        local = date.and_time(local.time());
+    }
+
+    match state.first_last_day_of {
+        Some(true) => local = try_opt!(local.with_day(1)),
+        Some(false) => {
+            let last = days_in_month(local.year(), local.month());
+            local = try_opt!(local.with_day(last));
+        }
+        None => {}
+    }
+
+    let duration = Duration::days(state.relative[2]) + Duration::hours(state.relative[3]) +
+                   Duration::minutes(state.relative[4]) +
+                   Duration::seconds(state.relative[5]) +
+                   Duration::microseconds(state.relative[6]);
+    let local: NaiveDateTime = try_opt!(local.checked_add_signed(duration));
+
+    Some(zone.date_from_local(&local))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::value::Date;
+    use extension::core::date::format::format;
+
+    /// Wednesday, 2020-01-15 10:30:00.25 in Paris
+    fn base() -> Date {
+        Date::new(1579080600, 250000, 3600, "Europe/Paris")
+    }
+
+    fn parse(text: &str) -> String {
+        match super::parse(text, &base()) {
+            Some(date) => format(&date, "Y-m-d H:i:s.u P"),
+            None => "invalid".to_string(),
+        }
+    }
+
+    #[test]
+    pub fn absolute() {
+        assert_eq!(parse("now"), "2020-01-15 10:30:00.250000 +01:00");
+        assert_eq!(parse("2020-07-01"), "2020-07-01 00:00:00.000000 +02:00");
+        assert_eq!(parse("2020-07-01T12:34:56.5+00:00"), "2020-07-01 12:34:56.500000 +00:00");
+        assert_eq!(parse("2020-02-30 8pm"), "2020-03-01 20:00:00.000000 +01:00");
+        assert_eq!(parse("12/24/2020 UTC"), "2020-12-24 00:00:00.000000 +00:00");
+        assert_eq!(parse("24.12.2020 11:15 America/New_York"),
+                   "2020-12-24 11:15:00.000000 -05:00");
+        assert_eq!(parse("June 20, 1971"), "1971-06-20 00:00:00.000000 +01:00");
+        assert_eq!(parse("1st march"), "2020-03-01 00:00:00.000000 +01:00");
+        assert_eq!(parse("@86400"), "1970-01-02 00:00:00.000000 +00:00");
+        assert_eq!(parse("13:00"), "2020-01-15 13:00:00.000000 +01:00");
+    }
+
+    #[test]
+    pub fn relative() {
+        assert_eq!(parse("+1 day"), "2020-01-16 10:30:00.250000 +01:00");
+        assert_eq!(parse("-2 weeks 3 hours"), "2020-01-01 13:30:00.250000 +01:00");
+        assert_eq!(parse("2 days ago"), "2020-01-13 10:30:00.250000 +01:00");
+        assert_eq!(parse("tomorrow noon"), "2020-01-16 12:00:00.000000 +01:00");
+        assert_eq!(parse("yesterday"), "2020-01-14 00:00:00.000000 +01:00");
+        assert_eq!(parse("next month"), "2020-02-15 10:30:00.250000 +01:00");
+        assert_eq!(parse("2020-01-31 +1 month"), "2020-03-02 00:00:00.000000 +01:00");
+        assert_eq!(parse("last day of next month"), "2020-02-29 10:30:00.250000 +01:00");
+        assert_eq!(parse("first day of this month midnight"),
+                   "2020-01-01 00:00:00.000000 +01:00");
+    }
+
+    #[test]
+    pub fn weekdays() {
+        assert_eq!(parse("wednesday"), "2020-01-15 00:00:00.000000 +01:00");
+        assert_eq!(parse("next wednesday"), "2020-01-22 00:00:00.000000 +01:00");
+        assert_eq!(parse("last wednesday"), "2020-01-08 00:00:00.000000 +01:00");
+        assert_eq!(parse("monday 9:00"), "2020-01-20 09:00:00.000000 +01:00");
+        assert_eq!(parse("last friday"), "2020-01-10 00:00:00.000000 +01:00");
+    }
+
+    #[test]
+    pub fn invalid() {
+        assert_eq!(parse("not a date"), "invalid");
+        assert_eq!(parse("2020-13-01"), "invalid");
+        assert_eq!(parse("25:00"), "invalid");
+    }
+}
@@ -0,0 +1,629 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Collection filters.
+//!
+//! Sequences and maps are treated like PHP arrays, i.e. a sequence is a map
+//! with the keys 0, 1, 2, .. - strings are treated as sequences of characters.
+
+use std::cmp::{self, Ordering};
+use runtime::{Runtime, Job, Value, RuntimeError};
+use runtime::value::{Map, Key, Closure};
+use api::error::Traced;
+use extension::core::{arg, opt_arg, invalid_argument};
+
+/// Returns the number of elements or characters.
+///
+/// orig: twig_length_filter
+pub fn length(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let length = match input {
+        Value::Null => 0,
+        Value::Sequence(ref x) => x.len(),
+        Value::Map(ref x) => x.len(),
+        ref x => x.to_string().chars().count(),
+    };
+
+    Ok(Value::Integer(length as i64))
+}
+
+/// orig: twig_first
+pub fn first(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(match input {
+        Value::Sequence(x) => x.into_iter().next().unwrap_or_default(),
+        Value::Map(x) => x.into_iter().next().map(|entry| entry.1).unwrap_or_default(),
+        Value::Null => Value::Null,
+        x => Value::from(x.to_string().chars().take(1).collect::<String>()),
+    })
+}
+
+/// orig: twig_last
+pub fn last(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(match input {
+        Value::Sequence(x) => x.into_iter().last().unwrap_or_default(),
+        Value::Map(x) => x.into_iter().last().map(|entry| entry.1).unwrap_or_default(),
+        Value::Null => Value::Null,
+        x => Value::from(x.to_string().chars().last().map(|c| c.to_string()).unwrap_or_default()),
+    })
+}
+
+/// Extracts a slice of a sequence, map or string.
+///
+/// `{{ items|slice(1, 2) }}`, `{{ items|slice(-2) }}`, `{{ items|slice(0, -1, true) }}`
+///
+/// orig: twig_slice($env, $item, $start, $length = null, $preserveKeys = false)
+pub fn slice(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let start = arg(&args, 0).to_int();
+    let length = opt_arg(&args, 1).map(|x| x.to_int());
+    let preserve_keys = arg(&args, 2).is_true();
+
+    match to_map(&input) {
+        Some(map) => {
+            let (from, to) = range(map.len(), start, length);
+            let entries = map.into_iter().skip(from).take(to - from).collect();
+
+            Ok(renumber(entries, preserve_keys).into_value())
+        }
+        None => {
+            let chars: Vec<char> = input.to_string().chars().collect();
+            let (from, to) = range(chars.len(), start, length);
+
+            Ok(Value::from(chars[from..to].iter().cloned().collect::<String>()))
+        }
+    }
+}
+
+/// Returns the range of `len` elements like `array_slice()`.
+fn range(len: usize, start: i64, length: Option<i64>) -> (usize, usize) {
+    let len = len as i64;
+    let from = if start < 0 {
+        cmp::max(len + start, 0)
+    } else {
+        cmp::min(start, len)
+    };
+    let to = match length {
+        None => len,
+        Some(length) if length < 0 => cmp::max(len + length, from),
+        Some(length) => cmp::min(from + length, len),
+    };
+
+    (from as usize, to as usize)
+}
+
+/// Merges two sequences or maps.
+///
+/// Integer keys are appended, string keys are overwritten.
+///
+/// orig: twig_array_merge($arr1, $arr2)
+pub fn merge(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let mut merged = match to_map(&input) {
+        Some(map) => renumber(map.into_iter().collect(), false),
+        None => return merge_error(&input, 1),
+    };
+
+    match to_map(arg(&args, 0)) {
+        Some(map) => append(&mut merged, map),
+        None => return merge_error(arg(&args, 0), 2),
+    }
+
+    Ok(merged.into_value())
+}
+
+fn merge_error(value: &Value, argument: usize) -> Result<Value, Traced<RuntimeError>> {
+    invalid_argument(format!("The merge filter only works with sequences or mappings, got \
+                              \"{}\" for argument {}",
+                             value.type_name(),
+                             argument))
+}
+
+/// orig: array_keys($array)
+pub fn keys(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(match to_map(&input) {
+        Some(map) => Value::Sequence(map.into_iter().map(|entry| Value::from(entry.0)).collect()),
+        None => Value::Sequence(Vec::new()),
+    })
+}
+
+/// Joins the elements with `glue` - the last two with `and`, if given.
+///
+/// `{{ [1, 2, 3]|join(', ', ' and ') }}` gives "1, 2 and 3".
+///
+/// orig: twig_join_filter($value, $glue = '', $and = null)
+pub fn join(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let values: Vec<String> = match input {
+        Value::Null => Vec::new(),
+        Value::Sequence(x) => x.iter().map(|x| x.to_string()).collect(),
+        Value::Map(x) => x.values().map(|x| x.to_string()).collect(),
+        x => vec![x.to_string()],
+    };
+    let glue = arg(&args, 0).to_string();
+
+    let joined = match (opt_arg(&args, 1), values.split_last()) {
+        (Some(and), Some((last, init))) if !init.is_empty() => {
+            format!("{}{}{}", init.join(&glue), and, last)
+        }
+        _ => values.join(&glue),
+    };
+
+    Ok(Value::String(joined))
+}
+
+/// Splits the elements into sequences of `size` elements.
+///
+/// The last batch is completed with `fill`, if given.
+///
+/// orig: twig_array_batch($items, $size, $fill = null, $preserveKeys = true)
+pub fn batch(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let size = arg(&args, 0).to_float().ceil();
+    if size < 1.0 {
+        return invalid_argument("The batch size must be greater than 0".to_string());
+    }
+    let size = size as usize;
+    let preserve_keys = opt_arg(&args, 2).map(|x| x.is_true()).unwrap_or(true);
+
+    let entries: Vec<(Key, Value)> = match to_map(&input) {
+        Some(map) => map.into_iter().collect(),
+        None => return Ok(Value::Sequence(Vec::new())),
+    };
+
+    let mut batches: Vec<Map> = entries.chunks(size)
+                                       .map(|chunk| {
+                                           if preserve_keys {
+                                               chunk.iter().cloned().collect()
+                                           } else {
+                                               Map::from_sequence(chunk.iter()
+                                                                       .map(|x| x.1.clone())
+                                                                       .collect())
+                                           }
+                                       })
+                                       .collect();
+
+    if let Some(fill) = opt_arg(&args, 1) {
+        if let Some(last) = batches.last_mut() {
+            for _ in last.len()..size {
+                last.push(fill.clone());
+            }
+        }
+    }
+
+    Ok(Value::Sequence(batches.into_iter().map(|x| x.into_value()).collect()))
+}
+
+/// Reverses a sequence, map or string.
+///
+/// orig: twig_reverse_filter($env, $item, $preserveKeys = false)
+pub fn reverse(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let preserve_keys = arg(&args, 0).is_true();
+
+    match to_map(&input) {
+        Some(map) => {
+            let mut entries: Vec<(Key, Value)> = map.into_iter().collect();
+            entries.reverse();
+
+            Ok(renumber(entries, preserve_keys).into_value())
+        }
+        None => Ok(Value::from(input.to_string().chars().rev().collect::<String>())),
+    }
+}
+
+/// Sorts the values - keys are preserved.
+///
+/// The optional arrow function compares two values like `(a, b) => a <=> b`.
+///
+/// orig: twig_sort_filter($env, $array, $arrow = null)
+pub fn sort(_runtime: &Runtime,
+            job: &mut Job,
+            input: Value,
+            args: Vec<Value>)
+            -> Result<Value, Traced<RuntimeError>> {
+    let mut entries: Vec<(Key, Value)> = try_traced!(expect_map(&input, "sort")).into_iter().collect();
+
+    match opt_arg(&args, 0) {
+        None => entries = merge_sort(entries, &mut |a, b| a.1.compare(&b.1)),
+        Some(arrow) => {
+            let arrow = try_traced!(expect_closure(arrow, "sort"));
+            let mut error = None;
+
+            entries = merge_sort(entries, &mut |a, b| {
+                if error.is_some() {
+                    return Ordering::Equal;
+                }
+
+                match arrow.call(job, vec![a.1.clone(), b.1.clone()]) {
+                    Ok(x) => x.to_int().cmp(&0),
+                    Err(e) => {
+                        error = Some(e);
+                        Ordering::Equal
+                    }
+                }
+            });
+
+            if let Some(e) = error {
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(entries.into_iter().collect::<Map>().into_value())
+}
+
+/// Stable merge sort - unlike `slice::sort_by()` it does not panic, if the
+/// comparisons are inconsistent, e.g. PHP-like comparisons of strings and numbers.
+fn merge_sort<T, F>(mut items: Vec<T>, compare: &mut F) -> Vec<T>
+    where F: FnMut(&T, &T) -> Ordering
+{
+    if items.len() <= 1 {
+        return items;
+    }
+
+    let right = items.split_off(items.len() / 2);
+    let mut left = merge_sort(items, compare).into_iter().peekable();
+    let mut right = merge_sort(right, compare).into_iter().peekable();
+
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    loop {
+        let next = match (left.peek(), right.peek()) {
+            (Some(a), Some(b)) if compare(a, b) == Ordering::Greater => right.next(),
+            (Some(_), _) => left.next(),
+            (None, _) => right.next(),
+        };
+
+        match next {
+            Some(item) => merged.push(item),
+            None => return merged,
+        }
+    }
+}
+
+/// Applies the arrow function to each element - keys are preserved.
+///
+/// `{{ items|map((v, k) => k ~ v)|join }}`
+///
+/// orig: twig_array_map($array, $arrow)
+pub fn map(_runtime: &Runtime,
+           job: &mut Job,
+           input: Value,
+           args: Vec<Value>)
+           -> Result<Value, Traced<RuntimeError>> {
+    let entries = try_traced!(expect_map(&input, "map"));
+    let arrow = try_traced!(expect_closure(arg(&args, 0), "map"));
+
+    let mut mapped = Map::new();
+    for (key, value) in entries {
+        let value = try_traced!(arrow.call(job, vec![value, Value::from(key.clone())]));
+        mapped.insert(key, value);
+    }
+
+    Ok(mapped.into_value())
+}
+
+/// Keeps the elements for which the arrow function is true - keys are preserved.
+///
+/// orig: twig_array_filter($env, $array, $arrow)
+pub fn filter(_runtime: &Runtime,
+              job: &mut Job,
+              input: Value,
+              args: Vec<Value>)
+              -> Result<Value, Traced<RuntimeError>> {
+    let entries = try_traced!(expect_map(&input, "filter"));
+    let arrow = try_traced!(expect_closure(arg(&args, 0), "filter"));
+
+    let mut filtered = Map::new();
+    for (key, value) in entries {
+        if try_traced!(arrow.call(job, vec![value.clone(), Value::from(key.clone())])).is_true() {
+            filtered.insert(key, value);
+        }
+    }
+
+    Ok(filtered.into_value())
+}
+
+/// Reduces the elements to a single value, starting with `initial`.
+///
+/// `{{ numbers|reduce((carry, v) => carry|merge([v]), []) }}`
+///
+/// orig: twig_array_reduce($env, $array, $arrow, $initial = null)
+pub fn reduce(_runtime: &Runtime,
+              job: &mut Job,
+              input: Value,
+              args: Vec<Value>)
+              -> Result<Value, Traced<RuntimeError>> {
+    let entries = try_traced!(expect_map(&input, "reduce"));
+    let arrow = try_traced!(expect_closure(arg(&args, 0), "reduce"));
+
+    let mut carry = arg(&args, 1).clone();
+    for (key, value) in entries {
+        carry = try_traced!(arrow.call(job, vec![carry, value, Value::from(key)]));
+    }
+
+    Ok(carry)
+}
+
+/// Returns the first element for which the arrow function is true, or null.
+///
+/// orig: twig_array_find($env, $array, $arrow)
+pub fn find(_runtime: &Runtime,
+            job: &mut Job,
+            input: Value,
+            args: Vec<Value>)
+            -> Result<Value, Traced<RuntimeError>> {
+    let entries = try_traced!(expect_map(&input, "find"));
+    let arrow = try_traced!(expect_closure(arg(&args, 0), "find"));
+
+    for (key, value) in entries {
+        if try_traced!(arrow.call(job, vec![value.clone(), Value::from(key)])).is_true() {
+            return Ok(value);
+        }
+    }
+
+    Ok(Value::Null)
+}
+
+/// Returns the values of the column `name` - keyed by the column `index`, if given.
+///
+/// orig: array_column($array, $name, $index)
+pub fn column(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let rows = try_traced!(expect_map(&input, "column"));
+    let name = Key::from(arg(&args, 0));
+    let index = opt_arg(&args, 1).map(Key::from);
+
+    let mut column = Map::new();
+    for row in rows.values().filter_map(to_map) {
+        let value = match row.get(name.clone()) {
+            Some(value) => value.clone(),
+            None => continue,
+        };
+
+        match index.as_ref().and_then(|index| row.get(index.clone())) {
+            Some(key) => {
+                column.insert(Key::from(key), value);
+            }
+            None => column.push(value),
+        }
+    }
+
+    Ok(column.into_value())
+}
+
+/// Returns sequences and maps as map.
+fn to_map(value: &Value) -> Option<Map> {
+    match *value {
+        Value::Sequence(ref x) => Some(Map::from_sequence(x.clone())),
+        Value::Map(ref x) => Some(x.clone()),
+        _ => None,
+    }
+}
+
+/// Returns sequences and maps as map - or an error for other values.
+fn expect_map(value: &Value, filter: &str) -> Result<Map, Traced<RuntimeError>> {
+    match to_map(value) {
+        Some(map) => Ok(map),
+        None => {
+            invalid_argument(format!("The {} filter only works with sequences or mappings, \
+                                      got \"{}\"",
+                                     filter,
+                                     value.type_name()))
+        }
+    }
+}
+
+/// Returns the arrow function argument of a filter.
+fn expect_closure<'a>(value: &'a Value, filter: &str) -> Result<&'a Closure, Traced<RuntimeError>> {
+    match *value {
+        Value::Closure(ref closure) => Ok(closure),
+        _ => {
+            invalid_argument(format!("The {} filter expects an arrow function, got \"{}\"",
+                                     filter,
+                                     value.type_name()))
+        }
+    }
+}
+
+/// Collects the entries - integer keys are renumbered, unless they are preserved.
+fn renumber(entries: Vec<(Key, Value)>, preserve_keys: bool) -> Map {
+    if preserve_keys {
+        return entries.into_iter().collect();
+    }
+
+    let mut map = Map::new();
+    append(&mut map, entries);
+
+    map
+}
+
+/// Appends integer keys and overwrites string keys.
+fn append<I>(map: &mut Map, entries: I)
+    where I: IntoIterator<Item = (Key, Value)>
+{
+    for (key, value) in entries {
+        match key {
+            Key::Integer(_) => map.push(value),
+            key => {
+                map.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::{Runtime, Job, Value};
+    use runtime::value::Map;
+
+    fn seq(values: &[i32]) -> Value {
+        Value::from(values.to_vec())
+    }
+
+    fn map(entries: Vec<(&str, i32)>) -> Value {
+        Value::Map(entries.into_iter().collect())
+    }
+
+    #[test]
+    pub fn _length() {
+        assert_eq!(length(Value::from("héllo"), vec![]).unwrap(), Value::from(5));
+        assert_eq!(length(seq(&[1, 2]), vec![]).unwrap(), Value::from(2));
+        assert_eq!(length(map(vec![("a", 1)]), vec![]).unwrap(), Value::from(1));
+        assert_eq!(length(Value::Null, vec![]).unwrap(), Value::from(0));
+    }
+
+    #[test]
+    pub fn first_last() {
+        assert_eq!(first(seq(&[1, 2, 3]), vec![]).unwrap(), Value::from(1));
+        assert_eq!(last(map(vec![("a", 1), ("b", 2)]), vec![]).unwrap(), Value::from(2));
+        assert_eq!(first(Value::from("élan"), vec![]).unwrap(), Value::from("é"));
+        assert_eq!(last(seq(&[]), vec![]).unwrap(), Value::Null);
+    }
+
+    #[test]
+    pub fn _slice() {
+        let items = seq(&[1, 2, 3, 4, 5]);
+        let slice = |args| slice(items.clone(), args).unwrap();
+
+        assert_eq!(slice(vec![Value::from(1), Value::from(2)]), seq(&[2, 3]));
+        assert_eq!(slice(vec![Value::from(-2)]), seq(&[4, 5]));
+        assert_eq!(slice(vec![Value::from(1), Value::from(-2)]), seq(&[2, 3]));
+        assert_eq!(slice(vec![Value::from(3), Value::Null, Value::from(true)]),
+                   Value::Map(vec![(3, 4), (4, 5)].into_iter().collect()));
+        assert_eq!(super::slice(Value::from("héllo"), vec![Value::from(-4), Value::from(2)])
+                       .unwrap(),
+                   Value::from("él"));
+    }
+
+    #[test]
+    pub fn _merge() {
+        assert_eq!(merge(seq(&[1, 2]), vec![seq(&[3])]).unwrap(), seq(&[1, 2, 3]));
+        assert_eq!(merge(map(vec![("a", 1), ("b", 2)]), vec![map(vec![("b", 3), ("c", 4)])])
+                       .unwrap(),
+                   map(vec![("a", 1), ("b", 3), ("c", 4)]));
+
+        let mut mixed = Map::new();
+        mixed.insert(5, "x");
+        mixed.insert("a", "y");
+        let mut expected = Map::new();
+        expected.insert(0, "x");
+        expected.insert("a", "y");
+        expected.insert(1, 1);
+        assert_eq!(merge(Value::Map(mixed), vec![seq(&[1])]).unwrap(),
+                   Value::Map(expected));
+
+        assert!(merge(seq(&[1]), vec![Value::from("x")]).is_err());
+    }
+
+    #[test]
+    pub fn _keys() {
+        assert_eq!(keys(map(vec![("a", 1), ("7", 2)]), vec![]).unwrap(),
+                   Value::from(vec![Value::from("a"), Value::from(7)]));
+        assert_eq!(keys(Value::from("x"), vec![]).unwrap(), Value::Sequence(vec![]));
+    }
+
+    #[test]
+    pub fn _join() {
+        let items = seq(&[1, 2, 3]);
+
+        assert_eq!(join(items.clone(), vec![]).unwrap(), Value::from("123"));
+        assert_eq!(join(items.clone(), vec![Value::from(", "), Value::from(" and ")]).unwrap(),
+                   Value::from("1, 2 and 3"));
+        assert_eq!(join(seq(&[1]), vec![Value::from(", "), Value::from(" and ")]).unwrap(),
+                   Value::from("1"));
+    }
+
+    #[test]
+    pub fn _batch() {
+        let items = seq(&[1, 2, 3, 4, 5]);
+
+        assert_eq!(batch(items.clone(), vec![Value::from(2), Value::from(0)]).unwrap(),
+                   Value::from(vec![seq(&[1, 2]),
+                                    Value::Map(vec![(2, 3), (3, 4)].into_iter().collect()),
+                                    Value::Map(vec![(4, 5), (5, 0)].into_iter().collect())]));
+        assert_eq!(batch(items.clone(),
+                         vec![Value::from(3), Value::Null, Value::from(false)])
+                       .unwrap(),
+                   Value::from(vec![seq(&[1, 2, 3]), seq(&[4, 5])]));
+        assert!(batch(items, vec![Value::from(0)]).is_err());
+    }
+
+    #[test]
+    pub fn _reverse() {
+        assert_eq!(reverse(seq(&[1, 2, 3]), vec![]).unwrap(), seq(&[3, 2, 1]));
+        assert_eq!(reverse(seq(&[1, 2]), vec![Value::from(true)]).unwrap(),
+                   Value::Map(vec![(1, 2), (0, 1)].into_iter().collect()));
+        assert_eq!(reverse(Value::from("äbc"), vec![]).unwrap(), Value::from("cbä"));
+    }
+
+    #[test]
+    pub fn _sort() {
+        let sort = |input, args| super::sort(&Runtime::default(), &mut Job::new(), input, args);
+
+        assert_eq!(sort(Value::from(vec!["10", "9", "b", "a"]), vec![]).unwrap(),
+                   Value::Map(vec![(1, "9"), (0, "10"), (3, "a"), (2, "b")]
+                                  .into_iter()
+                                  .collect()));
+        assert_eq!(sort(seq(&[1, 2]), vec![]).unwrap(), seq(&[1, 2]));
+        assert!(sort(Value::from(1), vec![]).is_err());
+    }
+
+    #[test]
+    pub fn sort_mixed() {
+        // PHP-like comparisons of strings and numbers are not a total order
+        let pool = vec![Value::from("0abc"), Value::from(1), Value::from("98"), Value::from("abc"),
+                        Value::from("10"), Value::from(9), Value::from("9a"), Value::from("1e1"),
+                        Value::from(" 5"), Value::from(2.5), Value::from("-1"), Value::from("")];
+        let mut state: u64 = 1;
+        let input: Vec<Value> = (0..64)
+                                    .map(|_| {
+                                        state = state.wrapping_mul(6364136223846793005)
+                                                     .wrapping_add(1442695040888963407);
+                                        pool[(state >> 33) as usize % pool.len()].clone()
+                                    })
+                                    .collect();
+
+        let sorted = super::sort(&Runtime::default(), &mut Job::new(), Value::from(input), vec![])
+                         .unwrap();
+
+        match sorted {
+            Value::Map(ref map) => assert_eq!(map.len(), 64),
+            ref x => panic!("unexpected {:?}", x),
+        }
+    }
+
+    #[test]
+    pub fn sort_inconsistent_arrow() {
+        use std::sync::Arc;
+        use engine::node::Node;
+        use engine::node::expression::Constant;
+        use engine::parser::token::stream::Position;
+
+        // `(a, b) => 1`
+        let position = Position { line: 1, column: 1 };
+        let body: Box<Node> = Constant::boxed(Value::from(1), &position);
+        let arrow = Closure::new(vec!["a".to_string(), "b".to_string()],
+                                 Arc::from(body),
+                                 Default::default());
+        let input: Vec<i32> = (0..64).collect();
+
+        let sorted = super::sort(&Runtime::default(),
+                                 &mut Job::new(),
+                                 Value::from(input),
+                                 vec![Value::Closure(arrow)])
+                         .unwrap();
+
+        match sorted {
+            Value::Map(ref map) => assert_eq!(map.len(), 64),
+            ref x => panic!("unexpected {:?}", x),
+        }
+    }
+
+    #[test]
+    pub fn _column() {
+        let rows = Value::from(vec![map(vec![("id", 3), ("n", 30)]),
+                                    map(vec![("id", 5)]),
+                                    map(vec![("id", 7), ("n", 70)])]);
+
+        assert_eq!(column(rows.clone(), vec![Value::from("n")]).unwrap(),
+                   seq(&[30, 70]));
+        assert_eq!(column(rows, vec![Value::from("n"), Value::from("id")]).unwrap(),
+                   Value::Map(vec![(3, 30), (7, 70)].into_iter().collect()));
+    }
+}
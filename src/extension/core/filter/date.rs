@@ -0,0 +1,82 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Date filters.
+
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::date::{self, format};
+use extension::core::{arg, opt_arg};
+
+/// Formats a date with PHP format characters in the given timezone.
+///
+/// The format defaults to the engine-wide date format and the timezone to the
+/// engine-wide default timezone.
+///
+/// orig: twig_date_format_filter($env, $date, $format = null, $timezone = null)
+pub fn date(input: Value,
+            args: Vec<Value>,
+            default_format: &str,
+            default_timezone: Option<&str>)
+            -> Result<Value, Traced<RuntimeError>> {
+    let zone = try_traced!(date::default_zone(default_timezone));
+    let date = try_traced!(date::convert(&input, arg(&args, 1), zone));
+
+    Ok(Value::String(match opt_arg(&args, 0) {
+        Some(x) => format::format(&date, &x.to_string()),
+        None => format::format(&date, default_format),
+    }))
+}
+
+/// Returns a new date modified by a relative modifier like "+1 day".
+///
+/// orig: twig_date_modify_filter($env, $date, $modifier)
+pub fn date_modify(input: Value,
+                   args: Vec<Value>,
+                   default_timezone: Option<&str>)
+                   -> Result<Value, Traced<RuntimeError>> {
+    let zone = try_traced!(date::default_zone(default_timezone));
+    let date = try_traced!(date::convert(&input, &Value::Boolean(false), zone));
+
+    Ok(Value::Date(try_traced!(date::modify(&date, &arg(&args, 0).to_string()))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn _date() {
+        let input = Value::from(1076599161);
+        let paris = Some("Europe/Paris");
+
+        assert_eq!(date(input.clone(), vec![], "F j, Y H:i", None).unwrap(),
+                   Value::from("February 12, 2004 15:19"));
+        assert_eq!(date(input.clone(), vec![], "F j, Y H:i", paris).unwrap(),
+                   Value::from("February 12, 2004 16:19"));
+        assert_eq!(date(input.clone(),
+                        vec![Value::from("d/m/Y H:i T"), Value::from("America/New_York")],
+                        "F j, Y H:i",
+                        paris)
+                       .unwrap(),
+                   Value::from("12/02/2004 10:19 EST"));
+        assert_eq!(date(Value::from("2004-02-12 15:19"), vec![Value::from("U")], "", paris)
+                       .unwrap(),
+                   Value::from("1076595540"));
+        assert!(date(input, vec![Value::Null, Value::from("Mars/Olympus")], "", None).is_err());
+    }
+
+    #[test]
+    pub fn _date_modify() {
+        let modified = date_modify(Value::from("2004-02-12 15:19 Europe/Paris"),
+                                   vec![Value::from("+1 month")],
+                                   None)
+                           .unwrap();
+
+        assert_eq!(modified.to_string(), "2004-03-12T15:19:00+01:00");
+        assert!(date_modify(modified, vec![Value::from("whenever")], None).is_err());
+    }
+}
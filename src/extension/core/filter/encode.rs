@@ -0,0 +1,342 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Encoding filters - JSON and URLs.
+//!
+//! Output is byte-compatible with PHP's `json_encode()`, `rawurlencode()` and
+//! `http_build_query()`.
+
+use std::fmt::Write;
+use runtime::{Value, RuntimeError};
+use runtime::value::Key;
+use api::error::Traced;
+use extension::core::arg;
+
+/// orig: JSON_HEX_TAG - encodes `<` and `>` as `\u003C` and `\u003E`
+pub const JSON_HEX_TAG: i64 = 1;
+/// orig: JSON_HEX_AMP - encodes `&` as `\u0026`
+pub const JSON_HEX_AMP: i64 = 2;
+/// orig: JSON_HEX_APOS - encodes `'` as `\u0027`
+pub const JSON_HEX_APOS: i64 = 4;
+/// orig: JSON_HEX_QUOT - encodes `"` as `\u0022`
+pub const JSON_HEX_QUOT: i64 = 8;
+/// orig: JSON_FORCE_OBJECT - encodes sequences as objects
+pub const JSON_FORCE_OBJECT: i64 = 16;
+/// orig: JSON_UNESCAPED_SLASHES - does not escape `/`
+pub const JSON_UNESCAPED_SLASHES: i64 = 64;
+/// orig: JSON_PRETTY_PRINT - indents with four spaces
+pub const JSON_PRETTY_PRINT: i64 = 128;
+/// orig: JSON_UNESCAPED_UNICODE - does not escape multibyte characters
+pub const JSON_UNESCAPED_UNICODE: i64 = 256;
+/// orig: JSON_PRESERVE_ZERO_FRACTION - encodes `10.0` as `10.0` instead of `10`
+pub const JSON_PRESERVE_ZERO_FRACTION: i64 = 1024;
+
+/// Returns the JSON representation of a value.
+///
+/// The options are a bitmask of the `JSON_*` constants, e.g.
+/// `{{ data|json_encode(192) }}` for `JSON_PRETTY_PRINT | JSON_UNESCAPED_SLASHES`.
+/// Like PHP it gives `false` for `NAN` and `INF`.
+///
+/// orig: json_encode($value, $options = 0)
+pub fn json_encode(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let options = arg(&args, 0).to_int();
+    let mut out = String::new();
+
+    match encode_json(&mut out, &input, options, 0) {
+        Some(()) => Ok(Value::String(out)),
+        None => Ok(Value::Boolean(false)),
+    }
+}
+
+/// URL-encodes a string (RFC 3986) or builds a query string from a mapping.
+///
+/// `{{ {'q': 'a b', 'page': 2}|url_encode }}` gives "q=a%20b&page=2".
+///
+/// orig: twig_urlencode_filter($url)
+pub fn url_encode(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let query = match input {
+        Value::Sequence(_) | Value::Map(_) => {
+            let mut pairs = Vec::new();
+            build_query(&mut pairs, &input, None);
+            pairs.join("&")
+        }
+        x => raw_url_encode(&x.to_string()),
+    };
+
+    Ok(Value::String(query))
+}
+
+/// Returns `None` if the value contains non-finite floats.
+fn encode_json(out: &mut String, value: &Value, options: i64, depth: usize) -> Option<()> {
+    match *value {
+        Value::Null => out.push_str("null"),
+        Value::Boolean(x) => out.push_str(if x { "true" } else { "false" }),
+        Value::Integer(x) => {
+            let _ = write!(out, "{}", x);
+        }
+        Value::Float(x) => {
+            if !x.is_finite() {
+                return None;
+            }
+            out.push_str(&json_float(x, options & JSON_PRESERVE_ZERO_FRACTION != 0));
+        }
+        Value::String(ref x) | Value::Markup(ref x, _) => encode_json_string(out, x, options),
+        Value::Sequence(ref x) => {
+            if options & JSON_FORCE_OBJECT != 0 {
+                return encode_json_object(out, &sequence_entries(x), options, depth);
+            }
+            return encode_json_array(out, &x.iter().collect::<Vec<_>>(), options, depth);
+        }
+        Value::Map(ref x) => {
+            if x.is_list() && options & JSON_FORCE_OBJECT == 0 {
+                return encode_json_array(out, &x.values().collect::<Vec<_>>(), options, depth);
+            }
+            let entries: Vec<_> = x.iter().map(|&(ref k, ref v)| (k.clone(), v)).collect();
+            return encode_json_object(out, &entries, options, depth);
+        }
+        Value::Closure(_) => out.push_str("{}"),
+        Value::Date(ref x) => return encode_json(out, &x.properties().into_value(), options, depth),
+    }
+
+    Some(())
+}
+
+fn encode_json_array(out: &mut String, values: &[&Value], options: i64, depth: usize) -> Option<()> {
+    if values.is_empty() {
+        out.push_str("[]");
+        return Some(());
+    }
+
+    out.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json_newline(out, options, depth + 1);
+        if encode_json(out, value, options, depth + 1).is_none() {
+            return None;
+        }
+    }
+    json_newline(out, options, depth);
+    out.push(']');
+
+    Some(())
+}
+
+fn encode_json_object(out: &mut String,
+                      entries: &[(Key, &Value)],
+                      options: i64,
+                      depth: usize)
+                      -> Option<()> {
+    if entries.is_empty() {
+        out.push_str("{}");
+        return Some(());
+    }
+
+    out.push('{');
+    for (i, &(ref key, value)) in entries.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        json_newline(out, options, depth + 1);
+        encode_json_string(out, &key.to_string(), options);
+        out.push_str(if options & JSON_PRETTY_PRINT != 0 { ": " } else { ":" });
+        if encode_json(out, value, options, depth + 1).is_none() {
+            return None;
+        }
+    }
+    json_newline(out, options, depth);
+    out.push('}');
+
+    Some(())
+}
+
+fn json_newline(out: &mut String, options: i64, depth: usize) {
+    if options & JSON_PRETTY_PRINT != 0 {
+        out.push('\n');
+        out.push_str(&"    ".repeat(depth));
+    }
+}
+
+fn encode_json_string(out: &mut String, text: &str, options: i64) {
+    out.push('"');
+    for c in text.chars() {
+        let _ = match c {
+            '"' if options & JSON_HEX_QUOT != 0 => write!(out, "\\u0022"),
+            '"' => write!(out, "\\\""),
+            '\\' => write!(out, "\\\\"),
+            '/' if options & JSON_UNESCAPED_SLASHES == 0 => write!(out, "\\/"),
+            '\u{8}' => write!(out, "\\b"),
+            '\u{c}' => write!(out, "\\f"),
+            '\n' => write!(out, "\\n"),
+            '\r' => write!(out, "\\r"),
+            '\t' => write!(out, "\\t"),
+            '<' if options & JSON_HEX_TAG != 0 => write!(out, "\\u003C"),
+            '>' if options & JSON_HEX_TAG != 0 => write!(out, "\\u003E"),
+            '&' if options & JSON_HEX_AMP != 0 => write!(out, "\\u0026"),
+            '\'' if options & JSON_HEX_APOS != 0 => write!(out, "\\u0027"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32),
+            // line terminators are always escaped, because they break javascript
+            '\u{2028}' | '\u{2029}' => write!(out, "\\u{:04x}", c as u32),
+            c if c.is_ascii() || options & JSON_UNESCAPED_UNICODE != 0 => {
+                out.push(c);
+                Ok(())
+            }
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units).iter() {
+                    let _ = write!(out, "\\u{:04x}", unit);
+                }
+                Ok(())
+            }
+        };
+    }
+    out.push('"');
+}
+
+/// Returns the values with their indices as keys.
+fn sequence_entries(values: &[Value]) -> Vec<(Key, &Value)> {
+    values.iter().enumerate().map(|(k, v)| (Key::from(k as i64), v)).collect()
+}
+
+/// Formats a float with the shortest representation that round-trips.
+///
+/// orig: serialize_precision = -1
+fn json_float(x: f64, preserve_zero_fraction: bool) -> String {
+    let repr = format!("{:e}", x); // e.g. "-1.234e-5"
+    let (mantissa, exponent) = repr.split_at(repr.find('e').unwrap());
+    let exponent: i32 = exponent[1..].parse().unwrap();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace(".", "");
+
+    if x != 0.0 && !(-4..17).contains(&exponent) {
+        let (first, rest) = digits.split_at(1);
+        format!("{}{}.{}e{}{}",
+                sign,
+                first,
+                if rest.is_empty() { "0" } else { rest },
+                if exponent < 0 { '-' } else { '+' },
+                exponent.abs())
+    } else if exponent < 0 {
+        format!("{}0.{}{}", sign, "0".repeat((-exponent - 1) as usize), digits)
+    } else {
+        let point = exponent as usize + 1;
+        if digits.len() > point {
+            format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+        } else if preserve_zero_fraction {
+            format!("{}{}{}.0", sign, digits, "0".repeat(point - digits.len()))
+        } else {
+            format!("{}{}{}", sign, digits, "0".repeat(point - digits.len()))
+        }
+    }
+}
+
+/// orig: rawurlencode($string)
+fn raw_url_encode(text: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in text.bytes() {
+        match byte as char {
+            c if c.is_ascii_alphanumeric() => encoded.push(c),
+            c @ '-' | c @ '_' | c @ '.' | c @ '~' => encoded.push(c),
+            _ => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Appends the `key=value` pairs of an array - nested arrays use `key[sub]=value`.
+///
+/// orig: http_build_query($data, '', '&', PHP_QUERY_RFC3986)
+fn build_query(pairs: &mut Vec<String>, value: &Value, prefix: Option<&str>) {
+    let entries: Vec<(Key, &Value)> = match *value {
+        Value::Sequence(ref x) => sequence_entries(x),
+        Value::Map(ref x) => x.iter().map(|&(ref k, ref v)| (k.clone(), v)).collect(),
+        _ => return,
+    };
+
+    for (key, value) in entries {
+        let key = match prefix {
+            Some(prefix) => format!("{}%5B{}%5D", prefix, raw_url_encode(&key.to_string())),
+            None => raw_url_encode(&key.to_string()),
+        };
+
+        match *value {
+            Value::Null | Value::Closure(_) | Value::Date(_) => {}
+            Value::Sequence(_) | Value::Map(_) => build_query(pairs, value, Some(&key)),
+            Value::Boolean(x) => pairs.push(format!("{}={}", key, x as u8)),
+            ref x => pairs.push(format!("{}={}", key, raw_url_encode(&x.to_string()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+    use runtime::value::{Map, Date};
+
+    fn json(value: Value, options: i64) -> String {
+        json_encode(value, vec![Value::from(options)]).unwrap().to_string()
+    }
+
+    #[test]
+    pub fn _json_encode() {
+        let mut map = Map::new();
+        map.insert("url", "http://example.com/ä?<b>");
+        map.insert("list", vec![Value::from(1), Value::from(2.5), Value::Null]);
+        map.insert("empty", Map::new());
+        map.insert("ok", true);
+
+        assert_eq!(json(map.clone().into_value(), 0),
+                   r#"{"url":"http:\/\/example.com\/\u00e4?<b>","list":[1,2.5,null],"empty":[],"ok":true}"#);
+        assert_eq!(json(map.into_value(), JSON_PRETTY_PRINT | JSON_UNESCAPED_SLASHES | JSON_HEX_TAG),
+                   "{\n    \"url\": \"http://example.com/\\u00e4?\\u003Cb\\u003E\",\n    \"list\": [\n        \
+                    1,\n        2.5,\n        null\n    ],\n    \"empty\": [],\n    \"ok\": true\n}");
+        assert_eq!(json(Value::from(vec![Value::from("a")]), JSON_FORCE_OBJECT), r#"{"0":"a"}"#);
+        assert_eq!(json(Value::from("😀\t\u{1}'"), JSON_HEX_APOS),
+                   r#""\ud83d\ude00\t\u0001\u0027""#);
+        assert_eq!(json(Value::from("é"), JSON_UNESCAPED_UNICODE), "\"é\"");
+        assert_eq!(json(Value::Date(Date::new(1076599161, 0, 3600, "Europe/Paris")), 0),
+                   r#"{"date":"2004-02-12 16:19:21.000000","timezone_type":3,"timezone":"Europe\/Paris"}"#);
+        assert_eq!(json_encode(Value::Float(::std::f64::NAN), vec![]).unwrap(),
+                   Value::Boolean(false));
+    }
+
+    #[test]
+    pub fn _json_float() {
+        assert_eq!(json_float(10.0, false), "10");
+        assert_eq!(json_float(10.0, true), "10.0");
+        assert_eq!(json_float(0.1, false), "0.1");
+        assert_eq!(json_float(-0.00001, false), "-1.0e-5");
+        assert_eq!(json_float(1.0 / 3.0, false), "0.3333333333333333");
+        assert_eq!(json_float(1e15, false), "1000000000000000");
+        assert_eq!(json_float(1.5e25, false), "1.5e+25");
+    }
+
+    #[test]
+    pub fn _url_encode() {
+        let mut nested = Map::new();
+        nested.insert("a b", "x&y");
+        nested.insert("list", vec![Value::from(1), Value::from(true), Value::Null]);
+
+        let mut map = Map::new();
+        map.insert("q", "café au lait");
+        map.insert("tags", nested);
+        map.insert("page", 2);
+
+        assert_eq!(url_encode(map.into_value(), vec![]).unwrap(),
+                   Value::from("q=caf%C3%A9%20au%20lait&tags%5Ba%20b%5D=x%26y&\
+                                tags%5Blist%5D%5B0%5D=1&tags%5Blist%5D%5B1%5D=1&page=2"));
+        assert_eq!(url_encode(Value::from("a+b/c ~"), vec![]).unwrap(),
+                   Value::from("a%2Bb%2Fc%20~"));
+    }
+}
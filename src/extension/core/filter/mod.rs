@@ -0,0 +1,84 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Core filters.
+
+use std::collections::HashMap;
+use extension::api::{Filter, SimpleFilter};
+use super::Core;
+
+pub mod string;
+pub mod collection;
+pub mod number;
+pub mod encode;
+#[cfg(feature = "date")]
+pub mod date;
+
+/// Returns the core filters by name.
+pub fn filters(core: &Core) -> HashMap<String, Box<Filter>> {
+    let number_format = core.number_format();
+    let filters = vec![SimpleFilter::new("upper", string::upper),
+                       SimpleFilter::new("lower", string::lower),
+                       SimpleFilter::new("title", string::title),
+                       SimpleFilter::new("capitalize", string::capitalize),
+                       SimpleFilter::new("trim", string::trim),
+                       SimpleFilter::new("striptags", string::striptags),
+                       SimpleFilter::new("nl2br", string::nl2br)
+                           .pre_escape("html")
+                           .safe_for(&["html"]),
+                       SimpleFilter::new("replace", string::replace),
+                       SimpleFilter::new("split", string::split),
+                       SimpleFilter::new("format", string::format),
+                       SimpleFilter::new("truncate", string::truncate),
+                       SimpleFilter::new("spaceless", string::spaceless).safe_for(&["html"]),
+                       SimpleFilter::new("raw", string::raw).safe_for(&["all"]),
+                       SimpleFilter::new("length", collection::length),
+                       SimpleFilter::new("first", collection::first),
+                       SimpleFilter::new("last", collection::last),
+                       SimpleFilter::new("slice", collection::slice),
+                       SimpleFilter::new("merge", collection::merge),
+                       SimpleFilter::new("keys", collection::keys),
+                       SimpleFilter::new("join", collection::join),
+                       SimpleFilter::new("batch", collection::batch),
+                       SimpleFilter::new("reverse", collection::reverse),
+                       SimpleFilter::with_job("sort", collection::sort),
+                       SimpleFilter::with_job("map", collection::map),
+                       SimpleFilter::with_job("filter", collection::filter),
+                       SimpleFilter::with_job("reduce", collection::reduce),
+                       SimpleFilter::with_job("find", collection::find),
+                       SimpleFilter::new("number_format", move |input, args| {
+                           number::number_format(input, args, number_format)
+                       }),
+                       SimpleFilter::new("round", number::round),
+                       SimpleFilter::new("abs", number::abs),
+                       SimpleFilter::new("format_number", number::format_number),
+                       SimpleFilter::new("column", collection::column),
+                       SimpleFilter::new("json_encode", encode::json_encode).safe_for(&["js"]),
+                       SimpleFilter::new("url_encode", encode::url_encode)];
+
+    filters.into_iter()
+           .chain(date_filters(core))
+           .map(|filter| (filter.name().to_string(), filter.boxed()))
+           .collect()
+}
+
+/// Returns the date filters, which need the `date` feature.
+#[cfg(feature = "date")]
+fn date_filters(core: &Core) -> Vec<SimpleFilter> {
+    let (date_format, timezone) = (core.date_format().to_string(), core.timezone().map(|x| x.to_string()));
+    let modify_timezone = timezone.clone();
+
+    vec![SimpleFilter::new("date", move |input, args| {
+             date::date(input, args, &date_format, timezone.as_ref().map(|x| x.as_ref()))
+         }),
+         SimpleFilter::new("date_modify", move |input, args| {
+             date::date_modify(input, args, modify_timezone.as_ref().map(|x| x.as_ref()))
+         })]
+}
+
+#[cfg(not(feature = "date"))]
+fn date_filters(_core: &Core) -> Vec<SimpleFilter> {
+    Vec::new()
+}
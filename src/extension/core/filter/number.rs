@@ -0,0 +1,293 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Number filters.
+
+use std::cmp;
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::{arg, opt_arg, invalid_argument};
+
+/// Formats a number with grouped thousands.
+///
+/// Missing arguments default to the engine-wide `(decimals, decimal_point, thousands_sep)`.
+///
+/// orig: twig_number_format_filter($env, $number, $decimal = null, $decimalPoint = null, $thousandSep = null)
+pub fn number_format(input: Value,
+                     args: Vec<Value>,
+                     defaults: (usize, char, char))
+                     -> Result<Value, Traced<RuntimeError>> {
+    let decimals = match opt_arg(&args, 0) {
+        Some(x) => cmp::max(x.to_int(), 0) as usize,
+        None => defaults.0,
+    };
+    let decimal_point = match opt_arg(&args, 1) {
+        Some(x) => x.to_string(),
+        None => defaults.1.to_string(),
+    };
+    let thousands_sep = match opt_arg(&args, 2) {
+        Some(x) => x.to_string(),
+        None => defaults.2.to_string(),
+    };
+
+    Ok(Value::String(php_number_format(input.to_float(),
+                                       decimals,
+                                       &decimal_point,
+                                       &thousands_sep)))
+}
+
+/// Rounds a number with the `common`, `floor` or `ceil` method.
+///
+/// orig: twig_round($value, $precision = 0, $method = 'common')
+pub fn round(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let value = input.to_float();
+    let precision = arg(&args, 0).to_int().clamp(-308, 308) as i32;
+    let method = match opt_arg(&args, 1) {
+        Some(x) => x.to_string(),
+        None => "common".to_string(),
+    };
+
+    let rounded = match method.as_ref() {
+        "common" => php_round(value, precision),
+        "floor" => (value * 10f64.powi(precision)).floor() / 10f64.powi(precision),
+        "ceil" => (value * 10f64.powi(precision)).ceil() / 10f64.powi(precision),
+        _ => {
+            return invalid_argument("The round filter only supports the \"common\", \"ceil\", \
+                                     and \"floor\" methods."
+                                        .to_string())
+        }
+    };
+
+    Ok(Value::Float(rounded))
+}
+
+/// orig: abs($number)
+pub fn abs(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(match input.to_number() {
+        Value::Integer(x) => {
+            match x.checked_abs() {
+                Some(x) => Value::Integer(x),
+                None => Value::Float((x as f64).abs()),
+            }
+        }
+        x => Value::Float(x.to_float().abs()),
+    })
+}
+
+/// Formats a number like the intl extension with the `en` locale.
+///
+/// The styles are `decimal` and `percent`. The attributes `fraction_digit`,
+/// `min_fraction_digit`, `max_fraction_digit` and `grouping_used` are supported.
+///
+/// `{{ 1234.5|format_number({fraction_digit: 2}) }}` gives "1,234.50".
+///
+/// orig: twig_format_number($number, array $attrs = [], $style = 'decimal', $type = 'default', $locale = null)
+pub fn format_number(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let style = match opt_arg(&args, 1) {
+        Some(x) => x.to_string(),
+        None => "decimal".to_string(),
+    };
+    let (value, mut min, mut max, suffix) = match style.as_ref() {
+        "decimal" => (input.to_float(), 0, 3, ""),
+        "percent" => (input.to_float() * 100.0, 0, 0, "%"),
+        _ => {
+            return invalid_argument(format!("The style \"{}\" does not exist. Known styles are: \
+                                             \"decimal\", \"percent\".",
+                                            style))
+        }
+    };
+    let mut grouping = true;
+
+    match *arg(&args, 0) {
+        Value::Null => {}
+        Value::Sequence(ref x) if x.is_empty() => {}
+        Value::Map(ref attrs) => {
+            for &(ref name, ref x) in attrs.iter() {
+                let digits = cmp::max(x.to_int(), 0) as usize;
+
+                match name.to_string().as_ref() {
+                    "fraction_digit" => {
+                        min = digits;
+                        max = digits;
+                    }
+                    "min_fraction_digit" => {
+                        min = digits;
+                        max = cmp::max(max, digits);
+                    }
+                    "max_fraction_digit" => {
+                        max = digits;
+                        min = cmp::min(min, digits);
+                    }
+                    "grouping_used" => grouping = x.is_true(),
+                    name => {
+                        return invalid_argument(format!("The number formatter attribute \"{}\" \
+                                                         does not exist. Known attributes are: \
+                                                         \"fraction_digit\", \
+                                                         \"min_fraction_digit\", \
+                                                         \"max_fraction_digit\", \
+                                                         \"grouping_used\".",
+                                                        name))
+                    }
+                }
+            }
+        }
+        ref x => {
+            return invalid_argument(format!("The format_number filter expects a mapping of \
+                                             attributes, got \"{}\"",
+                                            x.type_name()))
+        }
+    }
+
+    // rounds half to even like ICU
+    let mut formatted = format_decimal(value, max, ".", if grouping { "," } else { "" });
+    if max > min {
+        let point = formatted.len() - max - 1;
+        let trimmed = cmp::max(formatted.trim_end_matches('0').len(), point + 1 + min);
+        formatted.truncate(trimmed);
+        if formatted.ends_with('.') {
+            formatted.pop();
+        }
+    }
+
+    Ok(Value::String(formatted + suffix))
+}
+
+/// Rounds half away from zero - after pre-rounding to 15 significant digits,
+/// such that `php_round(1.005, 2) == 1.01`.
+///
+/// orig: round($value, $precision)
+pub fn php_round(value: f64, precision: i32) -> f64 {
+    if !value.is_finite() || value == 0.0 {
+        return value;
+    }
+
+    let factor = 10f64.powi(precision.abs());
+    let scaled = if precision >= 0 {
+        value * factor
+    } else {
+        value / factor
+    };
+    if !scaled.is_finite() {
+        return value;
+    }
+
+    let pre_rounded: f64 = format!("{:.14e}", scaled).parse().unwrap_or(scaled);
+    let rounded = pre_rounded.round();
+    let result = if precision >= 0 {
+        rounded / factor
+    } else {
+        rounded * factor
+    };
+
+    if result.is_finite() { result } else { value }
+}
+
+/// orig: number_format($num, $decimals, $dec_point, $thousands_sep)
+pub fn php_number_format(number: f64,
+                         decimals: usize,
+                         decimal_point: &str,
+                         thousands_sep: &str)
+                         -> String {
+    format_decimal(php_round(number, decimals as i32),
+                   decimals,
+                   decimal_point,
+                   thousands_sep)
+}
+
+/// Formats a number with exactly `decimals` decimals and grouped thousands.
+///
+/// A negative sign is omitted, if the formatted number is zero.
+fn format_decimal(number: f64, decimals: usize, decimal_point: &str, thousands_sep: &str) -> String {
+    let digits = format!("{:.*}", decimals, number.abs());
+    let (integer, fraction) = match digits.find('.') {
+        Some(i) => (&digits[..i], &digits[i + 1..]),
+        None => (&digits[..], ""),
+    };
+
+    let mut formatted = String::new();
+    if number.is_sign_negative() && digits.chars().any(|c| c != '0' && c != '.') {
+        formatted.push('-');
+    }
+    for (i, c) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            formatted.push_str(thousands_sep);
+        }
+        formatted.push(c);
+    }
+    if decimals > 0 {
+        formatted.push_str(decimal_point);
+        formatted.push_str(fraction);
+    }
+
+    formatted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    const DEFAULTS: (usize, char, char) = (0, '.', ',');
+
+    #[test]
+    pub fn _number_format() {
+        let format = |input, args| number_format(input, args, DEFAULTS).unwrap();
+
+        assert_eq!(format(Value::from(1234567.891), vec![]), Value::from("1,234,568"));
+        assert_eq!(format(Value::from("1234.5"), vec![Value::from(2)]),
+                   Value::from("1,234.50"));
+        assert_eq!(format(Value::from(-1234.567),
+                          vec![Value::from(2), Value::from(","), Value::from(".")]),
+                   Value::from("-1.234,57"));
+        assert_eq!(format(Value::from(-0.4), vec![]), Value::from("0"));
+        assert_eq!(number_format(Value::from(1000), vec![], (3, ',', ' ')).unwrap(),
+                   Value::from("1 000,000"));
+    }
+
+    #[test]
+    pub fn _php_round() {
+        assert_eq!(php_round(2.5, 0), 3.0);
+        assert_eq!(php_round(-2.5, 0), -3.0);
+        assert_eq!(php_round(1.005, 2), 1.01);
+        assert_eq!(php_round(1234.5678, -2), 1200.0);
+    }
+
+    #[test]
+    pub fn _round() {
+        let round = |input: f64, args| super::round(Value::from(input), args).unwrap();
+
+        assert_eq!(round(42.55, vec![]), Value::from(43.0));
+        assert_eq!(round(42.55, vec![Value::from(1), Value::from("floor")]),
+                   Value::from(42.5));
+        assert_eq!(round(42.51, vec![Value::from(0), Value::from("ceil")]),
+                   Value::from(43.0));
+        assert!(super::round(Value::from(1), vec![Value::from(0), Value::from("x")]).is_err());
+    }
+
+    #[test]
+    pub fn _abs() {
+        assert_eq!(abs(Value::from(-5), vec![]).unwrap(), Value::from(5));
+        assert_eq!(abs(Value::from("-1.5"), vec![]).unwrap(), Value::from(1.5));
+        assert_eq!(abs(Value::from(::std::i64::MIN), vec![]).unwrap(),
+                   Value::from(9223372036854775808.0));
+    }
+
+    #[test]
+    pub fn _format_number() {
+        let format = |input: f64, args| format_number(Value::from(input), args).unwrap();
+
+        assert_eq!(format(1234.5678, vec![]), Value::from("1,234.568"));
+        assert_eq!(format(2.5, vec![]), Value::from("2.5"));
+        assert_eq!(format(0.256, vec![Value::Null, Value::from("percent")]),
+                   Value::from("26%"));
+
+        let mut attrs = ::runtime::value::Map::new();
+        attrs.insert("fraction_digit", 2);
+        attrs.insert("grouping_used", false);
+        assert_eq!(format(1234.5, vec![Value::Map(attrs)]), Value::from("1234.50"));
+
+        assert!(format_number(Value::from(1), vec![Value::Null, Value::from("x")]).is_err());
+    }
+}
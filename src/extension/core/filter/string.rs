@@ -0,0 +1,616 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! String filters.
+//!
+//! Semantics follow Twig-PHP, e.g. `{{ 'hello world'|title }}` gives "Hello World".
+
+use std::cmp;
+use std::fmt::Write;
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::{arg, opt_arg, invalid_argument};
+
+/// orig: mb_strtoupper($string)
+pub fn upper(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(Value::String(input.to_string().to_uppercase()))
+}
+
+/// orig: mb_strtolower($string)
+pub fn lower(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(Value::String(input.to_string().to_lowercase()))
+}
+
+/// Uppercases the first letter of each word - all other letters are lowercased.
+///
+/// orig: mb_convert_case($string, MB_CASE_TITLE)
+pub fn title(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let mut titled = String::new();
+    let mut word = false;
+
+    for c in input.to_string().chars() {
+        if word {
+            titled.extend(c.to_lowercase());
+        } else {
+            titled.extend(c.to_uppercase());
+        }
+        word = c.is_alphanumeric() || c == '\'';
+    }
+
+    Ok(Value::String(titled))
+}
+
+/// Uppercases the first character - all others are lowercased.
+pub fn capitalize(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let text = input.to_string();
+    let mut chars = text.chars();
+
+    let capitalized = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.as_str().to_lowercase().chars()).collect(),
+        None => String::new(),
+    };
+
+    Ok(Value::String(capitalized))
+}
+
+/// Strips characters from the beginning and/or end of a string.
+///
+/// `{{ text|trim }}`, `{{ text|trim('.') }}`, `{{ text|trim('.', 'left') }}`
+///
+/// orig: twig_trim_filter($string, $characterMask = null, $side = 'both')
+pub fn trim(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let text = input.to_string();
+    let mask: Vec<char> = match opt_arg(&args, 0) {
+        Some(mask) => mask.to_string().chars().collect(),
+        None => " \t\n\r\0\x0B".chars().collect(),
+    };
+    let is_masked = |c: char| mask.contains(&c);
+
+    let trimmed = match opt_arg(&args, 1).map(|x| x.to_string()) {
+        None => text.trim_matches(is_masked),
+        Some(ref side) if side == "both" => text.trim_matches(is_masked),
+        Some(ref side) if side == "left" => text.trim_start_matches(is_masked),
+        Some(ref side) if side == "right" => text.trim_end_matches(is_masked),
+        Some(_) => {
+            return invalid_argument("Trimming side must be \"left\", \"right\" or \"both\""
+                                        .to_string())
+        }
+    };
+
+    Ok(Value::String(trimmed.to_string()))
+}
+
+/// Strips HTML tags, except for the allowed ones, e.g. `striptags('<p><br>')`.
+///
+/// orig: strip_tags($string, $allowable_tags)
+pub fn striptags(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let allowed: Vec<String> = match opt_arg(&args, 0) {
+        Some(&Value::Sequence(ref names)) => {
+            names.iter().map(|x| x.to_string().to_lowercase()).collect()
+        }
+        Some(tags) => {
+            tags.to_string()
+                .split(['<', '>'])
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_lowercase())
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    let text = input.to_string();
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = &text[..];
+
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // a `<` followed by whitespace is no tag
+        match rest[1..].chars().next() {
+            Some(c) if !c.is_whitespace() => {}
+            _ => {
+                stripped.push('<');
+                rest = &rest[1..];
+                continue;
+            }
+        }
+
+        if rest.starts_with("<!--") {
+            rest = match rest.find("-->") {
+                Some(end) => &rest[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        if !allowed.is_empty() && allowed.contains(&tag_name(tag)) {
+            stripped.push_str(tag);
+        }
+        rest = &rest[end..];
+    }
+    stripped.push_str(rest);
+
+    Ok(Value::String(stripped))
+}
+
+/// Returns the byte position after the closing `>` of the tag - quotes are respected.
+fn tag_end(tag: &str) -> usize {
+    let mut quote = None;
+
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+
+    tag.len() // unclosed tags are stripped until the end
+}
+
+/// Returns the lowercase name of a tag like `</a href="#">`, i.e. "a".
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches(['<', '/'])
+       .chars()
+       .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == ':')
+       .collect::<String>()
+       .to_lowercase()
+}
+
+/// Inserts HTML line breaks before all newlines.
+///
+/// orig: nl2br($string)
+pub fn nl2br(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let text = input.to_string();
+    let mut converted = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' | '\n' => {
+                converted.push_str("<br />");
+                converted.push(c);
+
+                let pair = if c == '\r' { '\n' } else { '\r' };
+                if chars.peek() == Some(&pair) {
+                    converted.push(pair);
+                    chars.next();
+                }
+            }
+            _ => converted.push(c),
+        }
+    }
+
+    Ok(Value::String(converted))
+}
+
+/// Replaces placeholders, e.g. `{{ "I like %this%."|replace({'%this%': fruit}) }}`.
+///
+/// Longer placeholders are replaced first and replacements are not replaced again.
+///
+/// orig: strtr($string, $from)
+pub fn replace(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let pairs: Vec<(String, String)> = match *arg(&args, 0) {
+        Value::Map(ref map) => {
+            map.iter().map(|&(ref k, ref v)| (k.to_string(), v.to_string())).collect()
+        }
+        Value::Sequence(ref seq) => {
+            seq.iter().enumerate().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        }
+        ref x => {
+            return invalid_argument(format!("The \"replace\" filter expects a mapping as \
+                                             replace values, got \"{}\"",
+                                            x.type_name()))
+        }
+    };
+    let pairs: Vec<_> = pairs.into_iter().filter(|pair| !pair.0.is_empty()).collect();
+
+    let text = input.to_string();
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = &text[..];
+
+    'outer: while !rest.is_empty() {
+        let longest = pairs.iter()
+                           .filter(|pair| rest.starts_with(&pair.0[..]))
+                           .max_by_key(|pair| pair.0.len());
+
+        if let Some(&(ref from, ref to)) = longest {
+            replaced.push_str(to);
+            rest = &rest[from.len()..];
+            continue 'outer;
+        }
+
+        let c = rest.chars().next().unwrap();
+        replaced.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Ok(Value::String(replaced))
+}
+
+/// Splits a string by the given delimiter.
+///
+/// * a positive limit returns at most `limit` elements, the last one contains the rest
+/// * a negative limit drops the last `-limit` elements
+/// * an empty delimiter splits the string into chunks of `limit` characters
+///
+/// orig: twig_split_filter($env, $value, $delimiter, $limit = null)
+pub fn split(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let text = input.to_string();
+    let delimiter = arg(&args, 0).to_string();
+    let limit = opt_arg(&args, 1).map(|x| x.to_int());
+
+    let parts: Vec<String> = if delimiter.is_empty() {
+        let size = cmp::max(limit.unwrap_or(1), 1) as usize;
+        let chars: Vec<char> = text.chars().collect();
+
+        chars.chunks(size).map(|chunk| chunk.iter().cloned().collect()).collect()
+    } else {
+        match limit {
+            None => text.split(&delimiter[..]).map(|x| x.to_string()).collect(),
+            Some(limit) if limit >= 0 => {
+                text.splitn(cmp::max(limit, 1) as usize, &delimiter[..])
+                    .map(|x| x.to_string())
+                    .collect()
+            }
+            Some(limit) => {
+                let mut parts: Vec<String> = text.split(&delimiter[..])
+                                                 .map(|x| x.to_string())
+                                                 .collect();
+                let len = parts.len().saturating_sub((-limit) as usize);
+                parts.truncate(len);
+                parts
+            }
+        }
+    };
+
+    Ok(Value::from(parts))
+}
+
+/// Formats a string like `sprintf`, e.g. `{{ "%s has %05.1f%%"|format(name, x) }}`.
+pub fn format(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    Ok(Value::String(try_traced!(sprintf(&input.to_string(), &args))))
+}
+
+/// Supports `%[argnum$][flags][width][.precision]specifier` with the specifiers
+/// `b, c, d, e, E, f, F, o, s, u, x, X` and the flags `-, +, space, 0, 'char`.
+///
+/// orig: sprintf($format, ...$values)
+pub fn sprintf(format: &str, args: &[Value]) -> Result<String, Traced<RuntimeError>> {
+    let format: Vec<char> = format.chars().collect();
+    let mut output = String::with_capacity(format.len());
+    let mut next_arg = 0;
+    let mut i = 0;
+
+    let digits = |i: &mut usize| -> Option<usize> {
+        let start = *i;
+        while *i < format.len() && format[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        format[start..*i].iter().cloned().collect::<String>().parse().ok()
+    };
+
+    while i < format.len() {
+        let c = format[i];
+        i += 1;
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        if i < format.len() && format[i] == '%' {
+            output.push('%');
+            i += 1;
+            continue;
+        }
+
+        // argnum
+        let mut argnum = None;
+        let start = i;
+        if let Some(n) = digits(&mut i) {
+            if i < format.len() && format[i] == '$' && n > 0 {
+                argnum = Some(n - 1);
+                i += 1;
+            } else {
+                i = start;
+            }
+        }
+
+        // flags
+        let (mut left, mut plus, mut pad) = (false, false, ' ');
+        while i < format.len() {
+            match format[i] {
+                '-' => left = true,
+                '+' => plus = true,
+                '0' => pad = '0',
+                ' ' => pad = ' ',
+                '\'' if i + 1 < format.len() => {
+                    i += 1;
+                    pad = format[i];
+                }
+                _ => break,
+            }
+            i += 1;
+        }
+
+        let width = digits(&mut i).unwrap_or(0);
+        let precision = if i < format.len() && format[i] == '.' {
+            i += 1;
+            Some(digits(&mut i).unwrap_or(0))
+        } else {
+            None
+        };
+
+        let specifier = match format.get(i) {
+            Some(c) => *c,
+            None => return invalid_argument("Missing format specifier at end of string".to_string()),
+        };
+        i += 1;
+
+        let index = argnum.unwrap_or_else(|| {
+            next_arg += 1;
+            next_arg - 1
+        });
+        let value = match args.get(index) {
+            Some(value) => value,
+            None => {
+                return invalid_argument(format!("{} arguments are required, {} given",
+                                                index + 2,
+                                                args.len() + 1))
+            }
+        };
+
+        let (sign, text) = match specifier {
+            's' => {
+                let text = value.to_string();
+                match precision {
+                    Some(p) => ("", text.chars().take(p).collect()),
+                    None => ("", text),
+                }
+            }
+            'd' => {
+                let x = value.to_int();
+                (sign(x < 0, plus), x.abs().to_string())
+            }
+            'u' => ("", (value.to_int() as u64).to_string()),
+            'c' => {
+                output.push(::std::char::from_u32(value.to_int() as u32).unwrap_or('\u{FFFD}'));
+                continue; // no padding
+            }
+            'f' | 'F' => {
+                let x = value.to_float();
+                (sign(x < 0.0, plus), format!("{:.*}", precision.unwrap_or(6), x.abs()))
+            }
+            'e' | 'E' => {
+                let x = value.to_float();
+                let repr = format!("{:.*e}", precision.unwrap_or(6), x.abs());
+                let (mantissa, exponent) = repr.split_at(repr.find('e').unwrap());
+                let exponent: i32 = exponent[1..].parse().unwrap();
+                let text = format!("{}{}{}{}",
+                                   mantissa,
+                                   specifier,
+                                   if exponent < 0 { '-' } else { '+' },
+                                   exponent.abs());
+                (sign(x < 0.0, plus), text)
+            }
+            'x' => ("", format!("{:x}", value.to_int() as u64)),
+            'X' => ("", format!("{:X}", value.to_int() as u64)),
+            'o' => ("", format!("{:o}", value.to_int() as u64)),
+            'b' => ("", format!("{:b}", value.to_int() as u64)),
+            x => return invalid_argument(format!("Unknown format specifier \"{}\"", x)),
+        };
+
+        let len = sign.chars().count() + text.chars().count();
+        let padding: String = (len..width).map(|_| pad).collect();
+        if left {
+            write!(output, "{}{}{}", sign, text, padding).unwrap();
+        } else if pad == '0' {
+            write!(output, "{}{}{}", sign, padding, text).unwrap();
+        } else {
+            write!(output, "{}{}{}", padding, sign, text).unwrap();
+        }
+    }
+
+    Ok(output)
+}
+
+fn sign(negative: bool, plus: bool) -> &'static str {
+    match (negative, plus) {
+        (true, _) => "-",
+        (false, true) => "+",
+        (false, false) => "",
+    }
+}
+
+/// Truncates a string to `length` characters, including the optional ellipsis.
+///
+/// `{{ text|truncate(10, '…') }}`, `{{ text|truncate(10, '…', false) }}`
+///
+/// Without `cut` the last word is kept completely.
+///
+/// orig: AbstractString::truncate($length, $ellipsis = '', $cut = true)
+pub fn truncate(input: Value, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let text = input.to_string();
+    let chars: Vec<char> = text.chars().collect();
+
+    let mut length = match opt_arg(&args, 0) {
+        Some(x) => cmp::max(x.to_int(), 0) as usize,
+        None => return invalid_argument("The \"truncate\" filter expects a length".to_string()),
+    };
+    let ellipsis = arg(&args, 1).to_string();
+    let cut = opt_arg(&args, 2).map(|x| x.is_true()).unwrap_or(true);
+
+    if chars.len() <= length {
+        return Ok(Value::String(text));
+    }
+
+    let mut ellipsis_len = ellipsis.chars().count();
+    if length < ellipsis_len {
+        ellipsis_len = 0;
+    }
+
+    if !cut {
+        let from = cmp::max(length, 1) - 1;
+        match chars[from..].iter().position(|c| " \r\n\t".contains(*c)) {
+            Some(i) => length = from + i + ellipsis_len,
+            None => return Ok(Value::String(text)),
+        }
+    }
+
+    let truncated: String = chars[..length - ellipsis_len].iter().cloned().collect();
+    if ellipsis_len > 0 {
+        Ok(Value::String(truncated.trim_end().to_string() + &ellipsis))
+    } else {
+        Ok(Value::String(truncated))
+    }
+}
+
+/// Removes whitespace between HTML tags.
+///
+/// orig: trim(preg_replace('/>\s+</', '><', $content))
+pub fn spaceless(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let text = input.to_string();
+    let text = text.trim_matches(is_space);
+    let mut compact = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find('>') {
+        compact.push_str(&rest[..i + 1]);
+        rest = &rest[i + 1..];
+
+        let trimmed = rest.trim_start_matches(is_space);
+        if trimmed.len() < rest.len() && trimmed.starts_with('<') {
+            rest = trimmed;
+        }
+    }
+    compact.push_str(rest);
+
+    Ok(Value::String(compact))
+}
+
+/// orig: PCRE `\s`
+fn is_space(c: char) -> bool {
+    c == ' ' || ('\t'..='\r').contains(&c)
+}
+
+/// Marks the value as safe, i.e. it will not be escaped.
+///
+/// orig: twig_raw_filter
+pub fn raw(input: Value, _args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    match input {
+        Value::String(x) | Value::Markup(x, _) => Ok(Value::Markup(x, "all".to_string())),
+        input => Ok(Value::Markup(input.to_string(), "all".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+    use runtime::value::Map;
+
+    fn apply<F>(f: F, input: &str, args: Vec<Value>) -> String
+        where F: Fn(Value, Vec<Value>) -> Result<Value, Traced<RuntimeError>>
+    {
+        f(Value::from(input), args).unwrap().to_string()
+    }
+
+    #[test]
+    pub fn case() {
+        assert_eq!(apply(upper, "straße ä", vec![]), "STRASSE Ä");
+        assert_eq!(apply(lower, "ÄÖÜ ΣΑΣ", vec![]), "äöü σας");
+        assert_eq!(apply(title, "hello wORLD o'neil-ümit", vec![]),
+                   "Hello World O'neil-Ümit");
+        assert_eq!(apply(capitalize, "éCOLE normale", vec![]), "École normale");
+    }
+
+    #[test]
+    pub fn _trim() {
+        assert_eq!(apply(trim, "  x \n", vec![]), "x");
+        assert_eq!(apply(trim, "..x..", vec![Value::from(".")]), "x");
+        assert_eq!(apply(trim, "  x  ", vec![Value::Null, Value::from("left")]), "x  ");
+        assert_eq!(apply(trim, "  x  ", vec![Value::Null, Value::from("right")]), "  x");
+        assert!(trim(Value::from("x"), vec![Value::Null, Value::from("middle")]).is_err());
+    }
+
+    #[test]
+    pub fn _striptags() {
+        let html = "<p class=\"a>b\">Hi <b>you</b><br/>!</p><!-- x --> a < b";
+
+        assert_eq!(apply(striptags, html, vec![]), "Hi you! a < b");
+        assert_eq!(apply(striptags, html, vec![Value::from("<b><br>")]),
+                   "Hi <b>you</b><br/>! a < b");
+    }
+
+    #[test]
+    pub fn _nl2br() {
+        assert_eq!(apply(nl2br, "a\nb\r\nc", vec![]), "a<br />\nb<br />\r\nc");
+    }
+
+    #[test]
+    pub fn _replace() {
+        let mut map = Map::new();
+        map.insert("%a%", "%b%");
+        map.insert("%b%", "B");
+        map.insert("%a", "x");
+
+        assert_eq!(apply(replace, "%a% %b% %a", vec![Value::Map(map)]), "%b% B x");
+        assert!(replace(Value::from("x"), vec![Value::from("y")]).is_err());
+    }
+
+    #[test]
+    pub fn _split() {
+        let split = |input, args| split(Value::from(input), args).unwrap();
+
+        assert_eq!(split("a,b,c", vec![Value::from(",")]),
+                   Value::from(vec!["a", "b", "c"]));
+        assert_eq!(split("a,b,c", vec![Value::from(","), Value::from(2)]),
+                   Value::from(vec!["a", "b,c"]));
+        assert_eq!(split("a,b,c", vec![Value::from(","), Value::from(-1)]),
+                   Value::from(vec!["a", "b"]));
+        assert_eq!(split("abcde", vec![Value::from(""), Value::from(2)]),
+                   Value::from(vec!["ab", "cd", "e"]));
+    }
+
+    #[test]
+    pub fn _sprintf() {
+        let args = vec![Value::from("Tom"), Value::from(3.14159), Value::from(-42)];
+
+        assert_eq!(sprintf("%s: %.2f, %d%%", &args).unwrap(), "Tom: 3.14, -42%");
+        assert_eq!(sprintf("[%1$5s|%1$-5s|%1$'*5s]", &args).unwrap(), "[  Tom|Tom  |**Tom]");
+        assert_eq!(sprintf("%3$05d %1$.1s %2$+.1e", &args).unwrap(), "-0042 T +3.1e+0");
+        assert_eq!(sprintf("%x %X %o %b %c", &[Value::from(255),
+                                                Value::from(255),
+                                                Value::from(8),
+                                                Value::from(5),
+                                                Value::from(65)])
+                       .unwrap(),
+                   "ff FF 10 101 A");
+        assert!(sprintf("%s %s", &[Value::from(1)]).is_err());
+    }
+
+    #[test]
+    pub fn _truncate() {
+        let text = "Lorem ipsum dolor sit amet";
+
+        assert_eq!(apply(truncate, text, vec![Value::from(8)]), "Lorem ip");
+        assert_eq!(apply(truncate, text, vec![Value::from(8), Value::from("…")]), "Lorem i…");
+        assert_eq!(apply(truncate, text, vec![Value::from(7), Value::from("…")]), "Lorem…");
+        assert_eq!(apply(truncate,
+                         text,
+                         vec![Value::from(8), Value::from("…"), Value::from(false)]),
+                   "Lorem ipsum…");
+        assert_eq!(apply(truncate, "short", vec![Value::from(8)]), "short");
+    }
+
+    #[test]
+    pub fn _spaceless() {
+        assert_eq!(apply(spaceless, " <div>\n  <b> x </b>\n</div> ", vec![]),
+                   "<div><b> x </b></div>");
+    }
+}
@@ -0,0 +1,205 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Collection functions.
+
+use std::cmp::{self, Ordering};
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::{arg, opt_arg, invalid_argument};
+
+/// Returns the numbers or letters from `low` to `high`, both inclusive.
+///
+/// `{{ range(0, 10, 2) }}`, `{{ range('a', 'e') }}` - `{{ 1..5 }}` is a shortcut
+/// for `{{ range(1, 5) }}`.
+///
+/// orig: range($low, $high, $step = 1)
+pub fn range(args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let (low, high) = (arg(&args, 0), arg(&args, 1));
+    let step = match opt_arg(&args, 2) {
+        Some(x) => x.to_number(),
+        None => Value::Integer(1),
+    };
+    if step.to_float() == 0.0 {
+        return invalid_argument("range(): Argument #3 ($step) cannot be 0".to_string());
+    }
+
+    if let (Some(low), Some(high)) = (letter(low), letter(high)) {
+        let step = cmp::max(step.to_int().abs(), 1) as usize;
+        let letters: Vec<u8> = if low <= high {
+            (low..=high).step_by(step).collect()
+        } else {
+            (high..=low).rev().step_by(step).collect()
+        };
+
+        return Ok(Value::Sequence(letters.into_iter()
+                                         .map(|x| Value::String((x as char).to_string()))
+                                         .collect()));
+    }
+
+    let (low, high) = (low.to_number(), high.to_number());
+    let is_float = |x: &Value| matches!(*x, Value::Float(_));
+
+    if is_float(&low) || is_float(&high) || step.to_float().fract() != 0.0 {
+        let (low, high, step) = (low.to_float(), high.to_float(), step.to_float().abs());
+        let count = ((high - low).abs() / step + 1e-9).floor() as usize + 1;
+        let sign = if low <= high { 1.0 } else { -1.0 };
+
+        return Ok(Value::Sequence((0..count)
+                                      .map(|i| Value::Float(low + sign * step * i as f64))
+                                      .collect()));
+    }
+
+    let (low, high, step) = (low.to_int(), high.to_int(), step.to_int().unsigned_abs() as usize);
+    let numbers: Vec<i64> = if low <= high {
+        (low..=high).step_by(step).collect()
+    } else {
+        (high..=low).rev().step_by(step).collect()
+    };
+
+    Ok(Value::Sequence(numbers.into_iter().map(Value::Integer).collect()))
+}
+
+/// Returns the first byte of non-numeric strings, e.g. `b'a'` for "a".
+fn letter(value: &Value) -> Option<u8> {
+    match value.as_str() {
+        Some(x) if !x.is_empty() && x.trim().parse::<f64>().is_err() => Some(x.as_bytes()[0]),
+        _ => None,
+    }
+}
+
+/// Returns the value at `position`, starting over at the end.
+///
+/// `{{ cycle(['odd', 'even'], loop.index0) }}`
+///
+/// orig: twig_cycle($values, $position)
+pub fn cycle(args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let values = match *arg(&args, 0) {
+        Value::Sequence(ref x) => x.clone(),
+        Value::Map(ref x) => x.values().cloned().collect(),
+        ref x => return Ok(x.clone()),
+    };
+    if values.is_empty() {
+        return invalid_argument("The \"cycle\" function does not work on empty arrays".to_string());
+    }
+
+    let position = arg(&args, 1).to_int().rem_euclid(values.len() as i64);
+
+    Ok(values[position as usize].clone())
+}
+
+/// Returns the lowest value of the arguments or of a single sequence argument.
+///
+/// orig: min(...$values)
+pub fn min(args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    extremum("min", args, Ordering::Less)
+}
+
+/// Returns the highest value of the arguments or of a single sequence argument.
+///
+/// orig: max(...$values)
+pub fn max(args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    extremum("max", args, Ordering::Greater)
+}
+
+/// Returns the first value, which is not `ordering` to another.
+fn extremum(name: &str, args: Vec<Value>, ordering: Ordering) -> Result<Value, Traced<RuntimeError>> {
+    let values = if args.len() == 1 {
+        match args.into_iter().next() {
+            Some(Value::Sequence(x)) => x,
+            Some(Value::Map(x)) => x.into_iter().map(|entry| entry.1).collect(),
+            _ => {
+                return invalid_argument(format!("{}(): Argument #1 ($value) must be of type array",
+                                                name))
+            }
+        }
+    } else {
+        args
+    };
+
+    let mut values = values.into_iter();
+    let mut extremum = match values.next() {
+        Some(x) => x,
+        None => {
+            return invalid_argument(format!("{}(): Argument #1 ($value) must contain at least \
+                                             one element",
+                                            name))
+        }
+    };
+    for value in values {
+        if value.compare(&extremum) == ordering {
+            extremum = value;
+        }
+    }
+
+    Ok(extremum)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+    use runtime::value::Map;
+
+    fn sequence(values: &[Value]) -> Value {
+        Value::Sequence(values.to_vec())
+    }
+
+    #[test]
+    pub fn _range() {
+        let range = |args: &[Value]| super::range(args.to_vec()).unwrap();
+
+        assert_eq!(range(&[Value::from(1), Value::from(4)]),
+                   sequence(&[Value::from(1), Value::from(2), Value::from(3), Value::from(4)]));
+        assert_eq!(range(&[Value::from(10), Value::from(0), Value::from(-5)]),
+                   sequence(&[Value::from(10), Value::from(5), Value::from(0)]));
+        assert_eq!(range(&[Value::from("a"), Value::from("e"), Value::from(2)]),
+                   sequence(&[Value::from("a"), Value::from("c"), Value::from("e")]));
+        assert_eq!(range(&[Value::from("c"), Value::from("a")]),
+                   sequence(&[Value::from("c"), Value::from("b"), Value::from("a")]));
+        assert_eq!(range(&[Value::from(0), Value::from(1), Value::from(0.25)]),
+                   sequence(&[Value::from(0.0),
+                              Value::from(0.25),
+                              Value::from(0.5),
+                              Value::from(0.75),
+                              Value::from(1.0)]));
+        assert_eq!(range(&[Value::from("2"), Value::from("3")]),
+                   sequence(&[Value::from(2), Value::from(3)]));
+        assert_eq!(range(&[Value::from(1), Value::from(2), Value::from(5)]),
+                   sequence(&[Value::from(1)]));
+        assert_eq!(range(&[Value::from(i64::MAX - 1), Value::from(i64::MAX)]),
+                   sequence(&[Value::from(i64::MAX - 1), Value::from(i64::MAX)]));
+        assert_eq!(range(&[Value::from(i64::MAX), Value::from(i64::MAX - 1)]),
+                   sequence(&[Value::from(i64::MAX), Value::from(i64::MAX - 1)]));
+        assert!(super::range(vec![Value::from(1), Value::from(2), Value::from(0)]).is_err());
+    }
+
+    #[test]
+    pub fn _cycle() {
+        let values = sequence(&[Value::from("odd"), Value::from("even")]);
+
+        assert_eq!(cycle(vec![values.clone(), Value::from(3)]).unwrap(), Value::from("even"));
+        assert_eq!(cycle(vec![values, Value::from(-2)]).unwrap(), Value::from("odd"));
+        assert_eq!(cycle(vec![Value::from("x"), Value::from(1)]).unwrap(), Value::from("x"));
+        assert!(cycle(vec![sequence(&[]), Value::from(0)]).is_err());
+    }
+
+    #[test]
+    pub fn min_max() {
+        let mut map = Map::new();
+        map.insert("a", 3);
+        map.insert("b", 12);
+
+        assert_eq!(min(vec![Value::from(2), Value::from("10"), Value::from(1.5)]).unwrap(),
+                   Value::from(1.5));
+        assert_eq!(max(vec![Value::from(2), Value::from("10"), Value::from(1.5)]).unwrap(),
+                   Value::from("10"));
+        assert_eq!(max(vec![map.into_value()]).unwrap(), Value::from(12));
+        assert_eq!(min(vec![sequence(&[Value::from("b"), Value::from("a")])]).unwrap(),
+                   Value::from("a"));
+        assert!(max(vec![sequence(&[])]).is_err());
+        assert!(min(vec![]).is_err());
+    }
+}
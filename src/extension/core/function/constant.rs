@@ -0,0 +1,76 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The `constant()` function.
+//!
+//! There are no PHP constants in Rust - this provides the ones templates use
+//! most, e.g. `{{ data|json_encode(constant('JSON_PRETTY_PRINT')) }}`.
+
+use std::f64::consts;
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::{arg, invalid_argument};
+use extension::core::filter::encode;
+
+/// Returns the value of a constant.
+///
+/// orig: twig_constant($constant, $object = null)
+pub fn constant(args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let name = arg(&args, 0).to_string();
+
+    match lookup(&name) {
+        Some(value) => Ok(value),
+        None => invalid_argument(format!("Undefined constant \"{}\"", name)),
+    }
+}
+
+fn lookup(name: &str) -> Option<Value> {
+    Some(match name {
+        "PHP_EOL" => Value::from("\n"),
+        "PHP_INT_MAX" => Value::from(i64::MAX),
+        "PHP_INT_MIN" => Value::from(i64::MIN),
+        "PHP_INT_SIZE" => Value::from(8),
+        "PHP_FLOAT_EPSILON" => Value::from(f64::EPSILON),
+        "PHP_FLOAT_MAX" => Value::from(f64::MAX),
+        "PHP_FLOAT_MIN" => Value::from(f64::MIN_POSITIVE),
+        "PHP_FLOAT_DIG" => Value::from(15),
+        "M_PI" => Value::from(consts::PI),
+        "M_E" => Value::from(consts::E),
+        "M_SQRT2" => Value::from(consts::SQRT_2),
+        "NAN" => Value::from(f64::NAN),
+        "INF" => Value::from(f64::INFINITY),
+        "JSON_HEX_TAG" => Value::from(encode::JSON_HEX_TAG),
+        "JSON_HEX_AMP" => Value::from(encode::JSON_HEX_AMP),
+        "JSON_HEX_APOS" => Value::from(encode::JSON_HEX_APOS),
+        "JSON_HEX_QUOT" => Value::from(encode::JSON_HEX_QUOT),
+        "JSON_FORCE_OBJECT" => Value::from(encode::JSON_FORCE_OBJECT),
+        "JSON_UNESCAPED_SLASHES" => Value::from(encode::JSON_UNESCAPED_SLASHES),
+        "JSON_PRETTY_PRINT" => Value::from(encode::JSON_PRETTY_PRINT),
+        "JSON_UNESCAPED_UNICODE" => Value::from(encode::JSON_UNESCAPED_UNICODE),
+        "JSON_PRESERVE_ZERO_FRACTION" => Value::from(encode::JSON_PRESERVE_ZERO_FRACTION),
+        "DATE_ATOM" | "DATE_RFC3339" | "DATE_W3C" => Value::from("Y-m-d\\TH:i:sP"),
+        "DATE_COOKIE" => Value::from("l, d-M-Y H:i:s T"),
+        "DATE_ISO8601" => Value::from("Y-m-d\\TH:i:sO"),
+        "DATE_RFC822" | "DATE_RFC1036" => Value::from("D, d M y H:i:s O"),
+        "DATE_RFC850" => Value::from("l, d-M-y H:i:s T"),
+        "DATE_RFC1123" | "DATE_RFC2822" => Value::from("D, d M Y H:i:s O"),
+        "DATE_RFC7231" => Value::from("D, d M Y H:i:s \\G\\M\\T"),
+        "DATE_RSS" => Value::from("D, d M Y H:i:s O"),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn _constant() {
+        assert_eq!(constant(vec![Value::from("JSON_PRETTY_PRINT")]).unwrap(), Value::from(128));
+        assert_eq!(constant(vec![Value::from("PHP_EOL")]).unwrap(), Value::from("\n"));
+        assert!(constant(vec![Value::from("App\\Entity::STATUS")]).is_err());
+    }
+}
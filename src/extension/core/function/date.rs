@@ -0,0 +1,40 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Date functions.
+
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::date;
+
+/// Converts a date string, a timestamp or null (now) to a date in the given timezone.
+///
+/// orig: twig_date_converter($env, $date = null, $timezone = null)
+pub fn date(args: Vec<Value>, default_timezone: Option<&str>) -> Result<Value, Traced<RuntimeError>> {
+    const NULL: &'static Value = &Value::Null;
+
+    let zone = try_traced!(date::default_zone(default_timezone));
+    let input = args.first().unwrap_or(NULL);
+    let timezone = args.get(1).unwrap_or(NULL);
+
+    Ok(Value::Date(try_traced!(date::convert(input, timezone, zone))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn _date() {
+        let date = |args: Vec<Value>| super::date(args, Some("Europe/Paris")).map(|x| x.to_string());
+
+        assert_eq!(date(vec![Value::from("2004-02-12 15:19")]).unwrap(),
+                   "2004-02-12T15:19:00+01:00");
+        assert_eq!(date(vec![Value::from(0), Value::from("UTC")]).unwrap(),
+                   "1970-01-01T00:00:00+00:00");
+        assert!(date(vec![Value::from("yesterday-ish")]).is_err());
+    }
+}
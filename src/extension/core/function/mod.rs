@@ -0,0 +1,49 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Core functions.
+
+use std::collections::HashMap;
+use extension::api::{Function, SimpleFunction};
+use super::Core;
+
+pub mod collection;
+pub mod random;
+pub mod constant;
+pub mod template;
+#[cfg(feature = "date")]
+pub mod date;
+
+/// Returns the core functions by name.
+pub fn functions(core: &Core) -> HashMap<String, Box<Function>> {
+    let rng = random::Random::new(core.random_seed());
+    let functions = vec![SimpleFunction::new("range", collection::range),
+                         SimpleFunction::new("cycle", collection::cycle),
+                         SimpleFunction::new("min", collection::min),
+                         SimpleFunction::new("max", collection::max),
+                         SimpleFunction::new("random", move |args| random::random(&rng, args)),
+                         SimpleFunction::new("constant", constant::constant),
+                         SimpleFunction::with_job("source", template::source).safe_for(&["all"])];
+
+    functions.into_iter()
+             .chain(date_functions(core))
+             .map(|function| (function.name().to_string(), function.boxed()))
+             .collect()
+}
+
+/// Returns the date functions, which need the `date` feature.
+#[cfg(feature = "date")]
+fn date_functions(core: &Core) -> Vec<SimpleFunction> {
+    let timezone = core.timezone().map(|x| x.to_string());
+
+    vec![SimpleFunction::new("date", move |args| {
+             date::date(args, timezone.as_ref().map(|x| x.as_ref()))
+         })]
+}
+
+#[cfg(not(feature = "date"))]
+fn date_functions(_core: &Core) -> Vec<SimpleFunction> {
+    Vec::new()
+}
@@ -0,0 +1,151 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The `random()` function and its random number generator.
+
+use std::cmp;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::core::{arg, opt_arg, invalid_argument};
+
+/// orig: mt_getrandmax()
+const RAND_MAX: i64 = 2147483647;
+
+/// Increment of the splitmix64 state.
+const GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+/// Pseudo random number generator (splitmix64).
+///
+/// It is seeded from the operating system, unless a seed is configured via
+/// `Setup::set_random_seed()` - e.g. for reproducible tests.
+#[derive(Debug)]
+pub struct Random {
+    state: AtomicU64,
+}
+
+impl Random {
+    pub fn new(seed: Option<u64>) -> Random {
+        let seed = match seed {
+            Some(seed) => seed,
+            None => RandomState::new().build_hasher().finish(),
+        };
+
+        Random { state: AtomicU64::new(seed) }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.fetch_add(GAMMA, Ordering::Relaxed).wrapping_add(GAMMA);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number between `min` and `max`, both inclusive.
+    pub fn range(&self, min: i64, max: i64) -> i64 {
+        let span = max.wrapping_sub(min) as u64;
+        if span == u64::MAX {
+            return self.next_u64() as i64;
+        }
+
+        min.wrapping_add((self.next_u64() % (span + 1)) as i64)
+    }
+}
+
+/// Returns a random value depending on the argument:
+///
+/// * null: an integer between 0 and 2147483647
+/// * an integer: an integer between 0 and the integer (or `max`, if given)
+/// * a string: a random character
+/// * a sequence or mapping: a random value
+///
+/// orig: twig_random($env, $values = null, $max = null)
+pub fn random(random: &Random, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+    let max = opt_arg(&args, 1).map(|x| x.to_int());
+
+    let values = match *arg(&args, 0) {
+        Value::Null => {
+            return Ok(Value::Integer(match max {
+                Some(max) => try_traced!(random_int(random, 0, max)),
+                None => random.range(0, RAND_MAX),
+            }))
+        }
+        Value::Integer(_) | Value::Float(_) => {
+            let value = arg(&args, 0).to_int();
+            let (min, max) = match max {
+                Some(max) => (value, max),
+                None => (cmp::min(value, 0), cmp::max(value, 0)),
+            };
+            return Ok(Value::Integer(try_traced!(random_int(random, min, max))));
+        }
+        Value::String(ref x) | Value::Markup(ref x, _) => {
+            if x.is_empty() {
+                return Ok(Value::from(""));
+            }
+            x.chars().map(|c| Value::String(c.to_string())).collect::<Vec<_>>()
+        }
+        Value::Sequence(ref x) => x.clone(),
+        Value::Map(ref x) => x.values().cloned().collect(),
+        ref x => return Ok(x.clone()),
+    };
+
+    if values.is_empty() {
+        return invalid_argument("The random function cannot pick from an empty array".to_string());
+    }
+
+    let index = random.range(0, values.len() as i64 - 1);
+    Ok(values[index as usize].clone())
+}
+
+/// orig: mt_rand($min, $max)
+fn random_int(random: &Random, min: i64, max: i64) -> Result<i64, Traced<RuntimeError>> {
+    if max < min {
+        return invalid_argument("random(): Argument #2 ($max) must be greater than or equal \
+                                 to argument #1 ($min)"
+                                    .to_string());
+    }
+
+    Ok(random.range(min, max))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn seed() {
+        let (a, b) = (Random::new(Some(42)), Random::new(Some(42)));
+
+        assert_eq!(a.next_u64(), b.next_u64());
+        assert!(a.next_u64() != Random::new(Some(43)).next_u64());
+    }
+
+    #[test]
+    pub fn _random() {
+        let rng = Random::new(Some(7));
+        let random = |args: Vec<Value>| super::random(&rng, args).unwrap();
+
+        for _ in 0..100 {
+            let x = random(vec![Value::from(-3)]).to_int();
+            assert!(x >= -3 && x <= 0);
+
+            let x = random(vec![Value::from(5), Value::from(6)]).to_int();
+            assert!(x == 5 || x == 6);
+
+            let x = random(vec![Value::from("ab")]);
+            assert!(x == Value::from("a") || x == Value::from("b"));
+        }
+
+        assert_eq!(random(vec![Value::Sequence(vec![Value::from(true)])]), Value::from(true));
+        assert_eq!(random(vec![Value::from("")]), Value::from(""));
+        assert!(super::random(&rng, vec![Value::Sequence(vec![])]).is_err());
+        assert!(super::random(&rng, vec![Value::from(5), Value::from(1)]).is_err());
+    }
+}
@@ -0,0 +1,39 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Template functions.
+
+use runtime::{Runtime, Job, Value, RuntimeError};
+use api::error::Traced;
+use extension::core::arg;
+
+/// Returns the raw source of a template - without rendering it.
+///
+/// Missing templates give an empty string, if `ignore_missing` is true.
+///
+/// orig: twig_source($env, $name, $ignore_missing = false)
+pub fn source(_runtime: &Runtime,
+              job: &mut Job,
+              args: Vec<Value>)
+              -> Result<Value, Traced<RuntimeError>> {
+    let name = arg(&args, 0).to_string();
+    let ignore_missing = arg(&args, 1).is_true();
+
+    let result = match job.templates() {
+        Some(templates) => templates.source(&name).map_err(|e| e.error().to_string()),
+        None => Err("templates are only available when rendering with an engine".to_string()),
+    };
+
+    match result {
+        Ok(source) => Ok(Value::String(source)),
+        Err(_) if ignore_missing => Ok(Value::from("")),
+        Err(reason) => {
+            traced_err!(RuntimeError::Template {
+                name: name,
+                reason: reason,
+            })
+        }
+    }
+}
@@ -8,17 +8,36 @@
 use engine;
 use extension::api;
 use std::collections::HashMap;
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
 
 pub mod token_parser;
+pub mod filter;
+pub mod function;
+pub mod operator;
+#[cfg(feature = "date")]
+pub mod date;
 
 
-#[derive(Default, Debug, PartialEq)]
+#[derive(Debug, PartialEq)]
 pub struct Core {
     fmt_date: String, // "F j, Y H:i"
     fmt_date_interval: String, // "%d days"
     fmt_format: (usize, char, char), // (0, '.', ',')
-    timezone: Option<String>, // type?
-    escapers: Vec<()>, // ??
+    timezone: Option<String>, // e.g. "Europe/Paris"
+    random_seed: Option<u64>,
+}
+
+impl Default for Core {
+    fn default() -> Core {
+        Core {
+            fmt_date: "F j, Y H:i".to_string(),
+            fmt_date_interval: "%d days".to_string(),
+            fmt_format: (0, '.', ','),
+            timezone: None,
+            random_seed: None,
+        }
+    }
 }
 
 impl api::Extension for Core {
@@ -62,34 +81,85 @@ impl api::Extension for Core {
         return p;
     }
 
-    // /// Get the filters to register with the engine.
-    // fn filters(&self) -> HashMap<String, Box<api::Filter>> {
-    //     unimplemented!()
-    // }
-    //
+    /// Get the filters to register with the engine.
+    fn filters(&self) -> HashMap<String, Box<api::Filter>> {
+        filter::filters(self)
+    }
+
+    /// Get the functions to register with the engine.
+    fn functions(&self) -> HashMap<String, Box<api::Function>> {
+        function::functions(self)
+    }
+
     // /// Get the tests to register with the engine.
     // fn tests(&self) -> HashMap<String, Box<api::Test>> {
     //     unimplemented!()
     // }
-    //
-    // /// Get the functions to register with the engine.
-    // fn functions(&self) -> HashMap<String, Box<api::Function>> {
-    //     unimplemented!()
-    // }
-    //
-    // /// Get the unary operators to register with the engine.
-    // fn operators_unary(&self) -> Vec<api::UnaryOperator> {
-    //     unimplemented!()
-    // }
-    //
-    // /// Get the binary operators to register with the engine.
-    // fn operators_binary(&self) -> Vec<api::BinaryOperator> {
-    //     unimplemented!()
-    // }
+
+    /// Get the unary operators to register with the engine.
+    fn operators_unary(&self) -> Vec<api::UnaryOperator> {
+        operator::operators_unary()
+    }
+
+    /// Get the binary operators to register with the engine.
+    fn operators_binary(&self) -> Vec<api::BinaryOperator> {
+        operator::operators_binary()
+    }
 }
 
 impl Core {
     pub fn new() -> Box<Core> {
         Box::new(Core::default())
     }
+
+    /// Creates the core extension with the defaults of the `number_format` and `date`
+    /// filters, the default timezone and the random seed from the engine options.
+    pub fn with_options(options: &engine::Options) -> Box<Core> {
+        Box::new(Core {
+            fmt_date: options.date_format().to_string(),
+            fmt_format: options.number_format(),
+            timezone: options.timezone().map(|x| x.to_string()),
+            random_seed: options.random_seed(),
+            ..Core::default()
+        })
+    }
+
+    /// Returns the default `(decimals, decimal_point, thousands_sep)` of the `number_format` filter.
+    pub fn number_format(&self) -> (usize, char, char) {
+        self.fmt_format
+    }
+
+    /// Returns the default format of the `date` filter.
+    pub fn date_format(&self) -> &str {
+        &self.fmt_date
+    }
+
+    /// Returns the default timezone - UTC if unset.
+    pub fn timezone(&self) -> Option<&str> {
+        self.timezone.as_ref().map(|x| x.as_ref())
+    }
+
+    /// Returns the seed of the `random()` function - random if unset.
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+}
+
+/// Returns the argument at `index` or `Value::Null`.
+fn arg(args: &[Value], index: usize) -> &Value {
+    const NULL: &'static Value = &Value::Null;
+
+    args.get(index).unwrap_or(NULL)
+}
+
+/// Returns the argument at `index`, unless it is missing or null.
+fn opt_arg(args: &[Value], index: usize) -> Option<&Value> {
+    match args.get(index) {
+        None | Some(&Value::Null) => None,
+        Some(value) => Some(value),
+    }
+}
+
+fn invalid_argument<T>(reason: String) -> Result<T, Traced<RuntimeError>> {
+    traced_err!(RuntimeError::InvalidArgument { reason: reason })
 }
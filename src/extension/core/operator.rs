@@ -0,0 +1,222 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Core operators.
+//!
+//! Numbers are computed like PHP - integers overflow to floats.
+
+use std::cmp::Ordering;
+use runtime::{Value, RuntimeError};
+use api::error::Traced;
+use extension::api::{UnaryOperator, BinaryOperator};
+use extension::api::op::Assoc;
+use extension::core::invalid_argument;
+
+/// Returns the core unary operators.
+///
+/// They bind stronger than binary operators, except `not` (50), e.g. `-a ~ b` is `(-a) ~ b`.
+pub fn operators_unary() -> Vec<UnaryOperator> {
+    vec![UnaryOperator::new("not", 50, |a| Ok(Value::Boolean(!a.is_true()))),
+         UnaryOperator::new("-", 500, neg),
+         UnaryOperator::new("+", 500, pos)]
+}
+
+/// Returns the core binary operators.
+///
+/// The precedence follows Twig, e.g. `or` (10) binds weaker than `+` (30).
+/// The range operator `..` (25) is built into the parser.
+pub fn operators_binary() -> Vec<BinaryOperator> {
+    vec![BinaryOperator::short_circuit("or", 10, true),
+         BinaryOperator::short_circuit("and", 15, false),
+         comparison("==", |x| x == Ordering::Equal),
+         comparison("!=", |x| x != Ordering::Equal),
+         comparison("<", |x| x == Ordering::Less),
+         comparison(">", |x| x == Ordering::Greater),
+         comparison("<=", |x| x != Ordering::Greater),
+         comparison(">=", |x| x != Ordering::Less),
+         BinaryOperator::new("<=>", 20, Assoc::Left, |a, b| {
+             Ok(Value::Integer(a.compare(b) as i64))
+         }),
+         BinaryOperator::new("+", 30, Assoc::Left, add),
+         BinaryOperator::new("-", 30, Assoc::Left, sub),
+         BinaryOperator::new("~", 40, Assoc::Left, concat),
+         BinaryOperator::new("*", 60, Assoc::Left, mul),
+         BinaryOperator::new("/", 60, Assoc::Left, div),
+         BinaryOperator::new("//", 60, Assoc::Left, floor_div),
+         BinaryOperator::new("%", 60, Assoc::Left, rem),
+         BinaryOperator::new("**", 200, Assoc::Right, pow)]
+}
+
+fn comparison<F>(repr: &str, f: F) -> BinaryOperator
+    where F: Fn(Ordering) -> bool + Send + Sync + 'static
+{
+    BinaryOperator::new(repr,
+                        20,
+                        Assoc::Left,
+                        move |a, b| Ok(Value::Boolean(f(a.compare(b)))))
+}
+
+/// orig: $a + $b
+pub fn add(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    arithmetic("+", a, b, i64::checked_add, |a, b| a + b)
+}
+
+/// orig: $a - $b
+pub fn sub(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    arithmetic("-", a, b, i64::checked_sub, |a, b| a - b)
+}
+
+/// orig: $a * $b
+pub fn mul(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    arithmetic("*", a, b, i64::checked_mul, |a, b| a * b)
+}
+
+/// Divides the numbers - the result is an integer, if the division has no remainder.
+///
+/// orig: $a / $b
+pub fn div(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    if b.to_float() == 0.0 {
+        return invalid_argument("Division by zero".to_string());
+    }
+
+    arithmetic("/",
+               a,
+               b,
+               |a, b| if a.checked_rem(b) == Some(0) { a.checked_div(b) } else { None },
+               |a, b| a / b)
+}
+
+/// Divides the numbers and rounds the result down to an integer.
+///
+/// orig: (int) floor($a / $b)
+pub fn floor_div(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    match try_traced!(div(a, b)) {
+        Value::Float(x) => Ok(Value::Integer(x.floor() as i64)),
+        x => Ok(x),
+    }
+}
+
+/// Returns the remainder of the integer division.
+///
+/// orig: $a % $b
+pub fn rem(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    try_traced!(check_operands("%", a, b));
+
+    match a.to_int().checked_rem(b.to_int()) {
+        Some(x) => Ok(Value::Integer(x)),
+        None if b.to_int() == 0 => invalid_argument("Modulo by zero".to_string()),
+        None => Ok(Value::Integer(0)), // i64::MIN % -1
+    }
+}
+
+/// orig: $a ** $b
+pub fn pow(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    arithmetic("**", a, b, checked_pow, f64::powf)
+}
+
+/// Negates the number.
+///
+/// orig: -$a
+pub fn neg(a: &Value) -> Result<Value, Traced<RuntimeError>> {
+    mul(a, &Value::Integer(-1))
+}
+
+/// Converts the value to a number.
+///
+/// orig: +$a
+pub fn pos(a: &Value) -> Result<Value, Traced<RuntimeError>> {
+    mul(a, &Value::Integer(1))
+}
+
+/// Concatenates the values as strings.
+///
+/// orig: $a . $b
+pub fn concat(a: &Value, b: &Value) -> Result<Value, Traced<RuntimeError>> {
+    Ok(Value::String(format!("{}{}", a, b)))
+}
+
+/// Applies `int` to integers, or `float` if one value is a float or `int` overflows.
+fn arithmetic<I, F>(operator: &str,
+                    a: &Value,
+                    b: &Value,
+                    int: I,
+                    float: F)
+                    -> Result<Value, Traced<RuntimeError>>
+    where I: Fn(i64, i64) -> Option<i64>,
+          F: Fn(f64, f64) -> f64
+{
+    try_traced!(check_operands(operator, a, b));
+
+    Ok(match (a.to_number(), b.to_number()) {
+        (Value::Integer(a), Value::Integer(b)) => {
+            match int(a, b) {
+                Some(x) => Value::Integer(x),
+                None => Value::Float(float(a as f64, b as f64)),
+            }
+        }
+        (a, b) => Value::Float(float(a.to_float(), b.to_float())),
+    })
+}
+
+/// Returns `a ** b` - or `None` for negative exponents and overflows.
+fn checked_pow(a: i64, b: i64) -> Option<i64> {
+    if b < 0 || b > u32::MAX as i64 {
+        return None;
+    }
+
+    a.checked_pow(b as u32)
+}
+
+/// Rejects sequences and maps, like PHP 8.
+fn check_operands(operator: &str, a: &Value, b: &Value) -> Result<(), Traced<RuntimeError>> {
+    match (a, b) {
+        (&Value::Sequence(_), _) | (&Value::Map(_), _) | (_, &Value::Sequence(_)) |
+        (_, &Value::Map(_)) => {
+            invalid_argument(format!("Unsupported operand types: {} {} {}",
+                                     a.type_name(),
+                                     operator,
+                                     b.type_name()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn _arithmetic() {
+        assert_eq!(add(&Value::from(1), &Value::from(2)).unwrap(), Value::from(3));
+        assert_eq!(add(&Value::from("1.5"), &Value::from(2)).unwrap(), Value::from(3.5));
+        assert_eq!(sub(&Value::from(1), &Value::from(2)).unwrap(), Value::from(-1));
+        assert_eq!(mul(&Value::from(i64::MAX), &Value::from(2)).unwrap(),
+                   Value::from(i64::MAX as f64 * 2.0));
+        assert_eq!(div(&Value::from(6), &Value::from(3)).unwrap(), Value::from(2));
+        assert_eq!(div(&Value::from(7), &Value::from(2)).unwrap(), Value::from(3.5));
+        assert_eq!(floor_div(&Value::from(-7), &Value::from(2)).unwrap(), Value::from(-4));
+        assert_eq!(rem(&Value::from(7), &Value::from(-3)).unwrap(), Value::from(1));
+        assert_eq!(pow(&Value::from(2), &Value::from(10)).unwrap(), Value::from(1024));
+        assert_eq!(pow(&Value::from(2), &Value::from(-1)).unwrap(), Value::from(0.5));
+        assert_eq!(concat(&Value::from(1), &Value::from("a")).unwrap(), Value::from("1a"));
+    }
+
+    #[test]
+    pub fn _neg() {
+        assert_eq!(neg(&Value::from(2)).unwrap(), Value::from(-2));
+        assert_eq!(neg(&Value::from("1.5")).unwrap(), Value::from(-1.5));
+        assert_eq!(neg(&Value::from(i64::MIN)).unwrap(), Value::from(-(i64::MIN as f64)));
+        assert_eq!(pos(&Value::from("3")).unwrap(), Value::from(3));
+        assert!(neg(&Value::from(vec![1])).is_err());
+    }
+
+    #[test]
+    pub fn invalid_operands() {
+        assert!(div(&Value::from(1), &Value::from(0)).is_err());
+        assert!(rem(&Value::from(1), &Value::from("0")).is_err());
+        assert!(add(&Value::from(vec![1]), &Value::from(1)).is_err());
+    }
+}
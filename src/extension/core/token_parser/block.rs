@@ -4,11 +4,23 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::api::op::Precedence;
+use extension::api::token_parser::TestResult;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
-use api::error::Traced;
+use engine::parser::token::{Token, Type};
+use api::error::{Traced, Dump};
 
+/// Defines a block, which templates extending this one can override.
+///
+/// ```text
+/// {% block title %}Index{% endblock %}
+/// {% block content %}...{% endblock content %}
+/// {% block title page.title|title %}
+/// ```
+///
+/// orig: Twig_TokenParser_Block
 #[derive(Debug, Default)]
 pub struct Block;
 
@@ -17,7 +29,63 @@ impl TokenParser for Block {
         "block"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let name = try_traced!(job.mut_cursor()
+                                  .next_expect(Type::Name, Some("A block must have a name")));
+        let name = name.token().value_as_str().unwrap_or_default().to_string();
+
+        if let Some(position) = job.block_position(&name).cloned() {
+            return traced_err!(ParserError::TokenParserError {
+                tag: self.tag(),
+                error: format!("The block {:?} has already been defined at {}", name, position),
+                job: job.dump(),
+            });
+        }
+
+        job.push_block_stack(&name);
+        let body = parse_body(job, &name);
+        job.pop_block_stack();
+
+        job.add_block(node::Block::boxed(name.clone(), try_traced!(body), item.position()));
+
+        Ok(node::BlockReference::boxed(name, item.position()))
+    }
+}
+
+/// Parses `%}...{% endblock [name] %}` or the short form ` expr %}`.
+fn parse_body(job: &mut Job, name: &str) -> Result<Vec<Box<Node>>, Traced<ParserError>> {
+    if job.mut_cursor().peek_token() != Some(&Token::BlockEnd) {
+        let position = job.mut_cursor().peek_pos().cloned().unwrap_or_default();
+        let expr = try_traced!(job.parse_expression(Precedence(0)));
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("block-Block must be closed")));
+
+        let expr = job.escape(expr, &position);
+        return Ok(vec![node::Print::boxed(expr, &position)]);
+    }
+
+    job.mut_cursor().next_token();
+    let body = try_traced!(job.sub_parse_until(&is_block_end));
+
+    if let Some(&Token::Name(ref end)) = job.mut_cursor().peek_token() {
+        if end != name {
+            return traced_err!(ParserError::TokenParserError {
+                tag: "block",
+                error: format!("Expected endblock for block {:?} (but {:?} given)", name, end),
+                job: job.dump(),
+            });
+        }
+        job.mut_cursor().next_token();
+    }
+    try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                             Some("endblock-Block must be closed")));
+
+    Ok(body)
+}
+
+pub fn is_block_end(item: &Item) -> TestResult {
+    match item.token().value_as_str() {
+        Some("endblock") => TestResult::DropToken,
+        _ => TestResult::Continue,
     }
 }
@@ -4,11 +4,22 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::api::op::Precedence;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
-use api::error::Traced;
+use engine::parser::token::Token;
+use api::error::{Traced, Dump};
 
+/// Extends another template - the blocks of this template override the blocks
+/// of the parent.
+///
+/// ```text
+/// {% extends 'layout.html' %}
+/// {% block content %}Hello{% endblock %}
+/// ```
+///
+/// orig: Twig_TokenParser_Extends
 #[derive(Debug, Default)]
 pub struct Extends;
 
@@ -17,7 +28,27 @@ impl TokenParser for Extends {
         "extends"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        if job.peek_block_stack().is_some() {
+            return traced_err!(ParserError::TokenParserError {
+                tag: self.tag(),
+                error: "Cannot use \"extends\" in a block".to_string(),
+                job: job.dump(),
+            });
+        }
+
+        let parent = try_traced!(job.parse_expression(Precedence(0)));
+        if !job.set_parent(parent) {
+            return traced_err!(ParserError::TokenParserError {
+                tag: self.tag(),
+                error: "Multiple extends tags are forbidden".to_string(),
+                job: job.dump(),
+            });
+        }
+
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("extends-Block must be closed")));
+
+        Ok(node::Virtual::boxed(item.position()))
     }
 }
@@ -4,11 +4,22 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::api::op::Precedence;
+use extension::api::token_parser::TestResult;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
-use api::error::Traced;
+use engine::parser::token::{Token, Type, Punctuation};
+use api::error::{Traced, Dump};
 
+/// Loops over the items of a sequence or map.
+///
+/// ```text
+/// {% for user in users %}{{ loop.index }}. {{ user.name }}{% endfor %}
+/// {% for key, value in map if value %}...{% else %}empty{% endfor %}
+/// ```
+///
+/// orig: Twig_TokenParser_For
 #[derive(Debug, Default)]
 pub struct For;
 
@@ -17,7 +28,97 @@ impl TokenParser for For {
         "for"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let (key_target, value_target) = try_traced!(parse_targets(job));
+
+        match job.mut_cursor().next_token() {
+            Some(&Token::Name(ref x)) |
+            Some(&Token::Operator(ref x)) if x == "in" => {}
+            _ => {
+                return traced_err!(ParserError::TokenParserError {
+                    tag: self.tag(),
+                    error: "Expected \"in\" after the loop variables".to_string(),
+                    job: job.dump(),
+                })
+            }
+        }
+
+        let seq = try_traced!(job.parse_expression(Precedence(0)));
+
+        let condition = if job.mut_cursor().peek_token() == Some(&Token::Name("if".to_string())) {
+            job.mut_cursor().next_token();
+            Some(try_traced!(job.parse_expression(Precedence(0))))
+        } else {
+            None
+        };
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("for-Block must be closed")));
+
+        let body = try_traced!(job.sub_parse_until(&is_for_fork));
+        let else_ = if job.mut_cursor().peek_token() == Some(&Token::Name("else".to_string())) {
+            job.mut_cursor().next_token();
+            try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                     Some("else-Block must be closed")));
+
+            try_traced!(job.sub_parse_until(&is_for_end))
+        } else {
+            Vec::new()
+        };
+
+        try_traced!(job.mut_cursor().next_expect(Token::Name("endfor".to_string()),
+                                                 Some("for-Block must be ended by endfor")));
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("endfor-Block must be closed")));
+
+        Ok(node::For::boxed(key_target,
+                            value_target,
+                            seq,
+                            condition,
+                            body,
+                            else_,
+                            item.position()))
+    }
+}
+
+/// Parses `value` or `key, value` - the key defaults to `_key`.
+fn parse_targets(job: &mut Job) -> Result<(String, String), Traced<ParserError>> {
+    let mut targets = Vec::new();
+    loop {
+        let target = try_traced!(job.mut_cursor()
+                                    .next_expect(Type::Name,
+                                                 Some("Only variables can be assigned to")));
+        targets.push(target.token().value_as_str().unwrap_or_default().to_string());
+
+        if job.mut_cursor().peek_token() != Some(&Token::Punctuation(Punctuation::Comma)) {
+            break;
+        }
+        job.mut_cursor().next_token();
+    }
+
+    let value = targets.pop().unwrap_or_default();
+    match targets.pop() {
+        Some(key) if targets.is_empty() => Ok((key, value)),
+        None => Ok(("_key".to_string(), value)),
+        Some(_) => {
+            traced_err!(ParserError::TokenParserError {
+                tag: "for",
+                error: "A for loop takes at most a key and a value variable".to_string(),
+                job: job.dump(),
+            })
+        }
+    }
+}
+
+pub fn is_for_fork(item: &Item) -> TestResult {
+    match item.token().value_as_str() {
+        Some("else") | Some("endfor") => TestResult::KeepToken,
+        _ => TestResult::Continue,
+    }
+}
+
+pub fn is_for_end(item: &Item) -> TestResult {
+    match item.token().value_as_str() {
+        Some("endfor") => TestResult::KeepToken,
+        _ => TestResult::Continue,
     }
 }
@@ -4,11 +4,22 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::core::token_parser::import::template_name;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
+use engine::parser::token::{Token, Type, Punctuation};
 use api::error::Traced;
 
+/// Imports macros of a template as functions.
+///
+/// ```text
+/// {% from 'forms.html' import input as field, textarea %}
+///
+/// {{ field('email') }}
+/// ```
+///
+/// orig: Twig_TokenParser_From
 #[derive(Debug, Default)]
 pub struct From;
 
@@ -17,7 +28,37 @@ impl TokenParser for From {
         "from"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let template = try_traced!(template_name(job, self.tag()));
+        try_traced!(job.mut_cursor().next_expect(Token::Name("import".to_string()),
+                                                 Some("Expected \"import\" after the template")));
+
+        loop {
+            let name = try_traced!(job.mut_cursor()
+                                      .next_expect(Type::Name, Some("Expected a macro name")));
+            let name = name.token().value_as_str().unwrap_or_default().to_string();
+
+            let alias = if job.mut_cursor().peek_token() == Some(&Token::Name("as".to_string())) {
+                job.mut_cursor().next_token();
+                let alias = try_traced!(job.mut_cursor()
+                                           .next_expect(Type::Name,
+                                                        Some("Expected an alias name")));
+                alias.token().value_as_str().unwrap_or_default().to_string()
+            } else {
+                name.clone()
+            };
+
+            job.add_imported_macro(&alias, &template, &name);
+
+            if job.mut_cursor().peek_token() != Some(&Token::Punctuation(Punctuation::Comma)) {
+                break;
+            }
+            job.mut_cursor().next_token();
+        }
+
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("from-Block must be closed")));
+
+        Ok(node::Virtual::boxed(item.position()))
     }
 }
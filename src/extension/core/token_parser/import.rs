@@ -4,11 +4,23 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::api::op::Precedence;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
-use api::error::Traced;
+use engine::parser::token::{Token, Type};
+use api::error::{Traced, Dump};
 
+/// Imports the macros of a template.
+///
+/// ```text
+/// {% import 'forms.html' as forms %}
+/// {% import _self as forms %}
+///
+/// {{ forms.input('email') }}
+/// ```
+///
+/// orig: Twig_TokenParser_Import
 #[derive(Debug, Default)]
 pub struct Import;
 
@@ -17,7 +29,40 @@ impl TokenParser for Import {
         "import"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let template = try_traced!(template_name(job, self.tag()));
+        try_traced!(job.mut_cursor().next_expect(Token::Name("as".to_string()),
+                                                 Some("Expected \"as\" after the template")));
+        let alias = try_traced!(job.mut_cursor()
+                                   .next_expect(Type::Name, Some("Expected an alias name")));
+        let alias = alias.token().value_as_str().unwrap_or_default().to_string();
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("import-Block must be closed")));
+
+        job.add_imported_template(&alias, &template);
+
+        Ok(node::Virtual::boxed(item.position()))
+    }
+}
+
+/// Parses the name of the imported template - a string or `_self`.
+pub fn template_name(job: &mut Job, tag: &'static str) -> Result<String, Traced<ParserError>> {
+    let expr = try_traced!(job.parse_expression(Precedence(0)));
+
+    if let Some(name) = expr.as_any().downcast_ref::<node::expression::Name>() {
+        if name.key() == "_self" {
+            return Ok(job.template_name().to_string());
+        }
+    }
+
+    match expr.constant().and_then(|x| x.as_str()) {
+        Some(name) => Ok(name.to_string()),
+        None => {
+            traced_err!(ParserError::TokenParserError {
+                tag: tag,
+                error: "Only template names and _self can be imported".to_string(),
+                job: job.dump(),
+            })
+        }
     }
 }
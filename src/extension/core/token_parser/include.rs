@@ -4,11 +4,20 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::api::op::Precedence;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
+use engine::parser::token::Token;
 use api::error::Traced;
 
+/// Renders another template with the current context.
+///
+/// ```text
+/// {% include 'header.html' %}
+/// {% include 'sidebar.html' ignore missing %}
+/// {% include 'user.html' with {'user': admin} only %}
+/// ```
 #[derive(Debug, Default)]
 pub struct Include;
 
@@ -17,7 +26,40 @@ impl TokenParser for Include {
         "include"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let name = try_traced!(job.parse_expression(Precedence(0)));
+
+        let ignore_missing = is_name(job, "ignore");
+        if ignore_missing {
+            job.mut_cursor().next_token();
+            try_traced!(job.mut_cursor().next_expect(Token::Name("missing".to_string()),
+                                                     Some("Expected \"missing\" after \
+                                                           \"ignore\"")));
+        }
+
+        let variables = if is_name(job, "with") {
+            job.mut_cursor().next_token();
+            Some(try_traced!(job.parse_expression(Precedence(0))))
+        } else {
+            None
+        };
+
+        let only = is_name(job, "only");
+        if only {
+            job.mut_cursor().next_token();
+        }
+
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("include-Block must be closed")));
+
+        Ok(node::Include::boxed(name, variables, ignore_missing, only, item.position()))
+    }
+}
+
+/// Returns true if the next token is the name `name`.
+fn is_name(job: &mut Job, name: &str) -> bool {
+    match job.mut_cursor().peek_token() {
+        Some(&Token::Name(ref x)) => x == name,
+        _ => false,
     }
 }
@@ -4,11 +4,26 @@
 // file that was distributed with this source code.
 
 use extension::api::TokenParser;
-use engine::Node;
+use extension::api::op::Precedence;
+use extension::api::token_parser::TestResult;
+use engine::{node, Node};
 use engine::parser::{Job, ParserError};
 use engine::parser::token::stream::Item;
-use api::error::Traced;
+use engine::parser::token::{Token, Type, Punctuation, BracketType};
+use runtime::Value;
+use api::error::{Traced, Dump};
 
+/// Defines a macro - a reusable snippet, which is called like a function.
+///
+/// ```text
+/// {% macro input(name, type = 'text') %}
+///     <input type="{{ type }}" name="{{ name }}">
+/// {% endmacro %}
+///
+/// {{ _self.input('email') }}
+/// ```
+///
+/// orig: Twig_TokenParser_Macro
 #[derive(Debug, Default)]
 pub struct Macro;
 
@@ -17,7 +32,105 @@ impl TokenParser for Macro {
         "macro"
     }
 
-    fn parse(&self, _job: &mut Job, _item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
-        unimplemented!()
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let name = try_traced!(job.mut_cursor()
+                                  .next_expect(Type::Name, Some("A macro must have a name")));
+        let name = name.token().value_as_str().unwrap_or_default().to_string();
+
+        let arguments = try_traced!(parse_arguments(job));
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("macro-Block must be closed")));
+
+        let body = try_traced!(job.sub_parse_until(&is_macro_end));
+
+        if let Some(&Token::Name(ref end)) = job.mut_cursor().peek_token() {
+            if *end != name {
+                return traced_err!(ParserError::TokenParserError {
+                    tag: self.tag(),
+                    error: format!("Expected endmacro for macro {:?} (but {:?} given)",
+                                   name,
+                                   end),
+                    job: job.dump(),
+                });
+            }
+            job.mut_cursor().next_token();
+        }
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("endmacro-Block must be closed")));
+
+        job.add_macro(node::Macro::boxed(name, arguments, body, item.position()));
+
+        Ok(node::Virtual::boxed(item.position()))
+    }
+}
+
+/// Parses the argument names and default values, e.g. `(name, type = 'text')`.
+fn parse_arguments(job: &mut Job) -> Result<Box<Node>, Traced<ParserError>> {
+    let round_close = Token::Punctuation(Punctuation::ClosingBracket(BracketType::Round));
+
+    let item = try_traced!(job.mut_cursor()
+                              .next_expect(Token::Punctuation(Punctuation::OpeningBracket(
+                                               BracketType::Round)),
+                                           Some("A list of arguments must begin with an \
+                                                 opening parenthesis")));
+
+    let mut pairs: Vec<Box<Node>> = Vec::new();
+    while job.mut_cursor().peek_token() != Some(&round_close) {
+        if !pairs.is_empty() {
+            try_traced!(job.mut_cursor()
+                           .next_expect(Token::Punctuation(Punctuation::Comma),
+                                        Some("Arguments must be separated by a comma")));
+        }
+
+        let name = try_traced!(job.mut_cursor()
+                                  .next_expect(Type::Name, Some("An argument must be a name")));
+        let key = node::expression::Constant::boxed(
+            Value::String(name.token().value_as_str().unwrap_or_default().to_string()),
+            name.position());
+
+        let default: Box<Node> = if job.mut_cursor().peek_token() ==
+                                    Some(&Token::Operator("=".to_string())) {
+            job.mut_cursor().next_token();
+            let default = try_traced!(job.parse_expression(Precedence(0)));
+            if !is_constant(&*default) {
+                return traced_err!(ParserError::TokenParserError {
+                    tag: "macro",
+                    error: "A default value for an argument must be a constant (a boolean, a \
+                            string, a number, or an array)"
+                               .to_string(),
+                    job: job.dump(),
+                });
+            }
+            default
+        } else {
+            node::expression::Constant::boxed(Value::Null, name.position())
+        };
+
+        pairs.push(key);
+        pairs.push(default);
+    }
+
+    try_traced!(job.mut_cursor().next_expect(round_close,
+                                             Some("A list of arguments must be closed by a \
+                                                   parenthesis")));
+
+    Ok(node::expression::Array::hash(pairs, item.position()))
+}
+
+/// Returns true for literals, e.g. `-1` or `['a', 'b']`.
+fn is_constant(node: &Node) -> bool {
+    if node.constant().is_some() {
+        return true;
+    }
+
+    let any = node.as_any();
+    (any.is::<node::expression::Array>() || any.is::<node::expression::Unary>()) &&
+    node.children().iter().all(|x| is_constant(&**x))
+}
+
+pub fn is_macro_end(item: &Item) -> TestResult {
+    match item.token().value_as_str() {
+        Some("endmacro") => TestResult::DropToken,
+        _ => TestResult::Continue,
     }
 }
@@ -5,21 +5,149 @@
 
 //! Debug Extension.
 
-use super::api::Extension;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use super::api::{Extension, Function, SimpleFunction};
+use engine::Engine;
+use runtime::{Runtime, Job, Value, RuntimeError};
+use runtime::value::{Map, Key, float_to_string};
+use api::error::Traced;
 
-#[allow(dead_code)]
-// dummy
-#[derive(Default, Debug, PartialEq)]
-pub struct Debug;
+/// Adds the `dump()` function, which is only active in debug mode.
+#[derive(Default, Debug)]
+pub struct Debug {
+    debug: AtomicBool,
+}
 
 impl Extension for Debug {
     fn name(&self) -> &'static str {
         "debug"
     }
+
+    fn init(&self, engine: &mut Engine) {
+        self.debug.store(engine.options().debug(), Ordering::Relaxed);
+    }
+
+    fn functions(&self) -> HashMap<String, Box<Function>> {
+        let debug = self.debug.load(Ordering::Relaxed);
+        let function = SimpleFunction::with_job("dump", move |runtime, job, args| {
+            dump(runtime, job, args, debug)
+        })
+        .needs_context();
+
+        let mut functions = HashMap::new();
+        functions.insert(function.name().to_string(), function.boxed());
+        functions
+    }
 }
 
 impl Debug {
     pub fn new() -> Box<Debug> {
-        Box::new(Debug)
+        Box::new(Debug::default())
+    }
+}
+
+/// Dumps the arguments with their types - or the whole context without arguments.
+///
+/// Outputs nothing, unless `debug` is enabled.
+///
+/// orig: twig_var_dump($env, $context, ...$vars)
+pub fn dump(runtime: &Runtime,
+            job: &mut Job,
+            args: Vec<Value>,
+            debug: bool)
+            -> Result<Value, Traced<RuntimeError>> {
+    if !debug {
+        return Ok(Value::Null);
+    }
+
+    let mut output = String::new();
+
+    if args.is_empty() {
+        let ext = job.extensions().clone();
+        let variables = runtime.variables();
+        let mut names: Vec<&String> = variables.keys().chain(ext.globals().keys()).collect();
+        names.sort();
+        names.dedup();
+
+        let mut context = Map::new();
+        for name in names {
+            let value = match variables.get(name) {
+                Some(value) => value.clone(),
+                None => ext.globals()[name].value(),
+            };
+            context.insert(name.as_str(), value);
+        }
+        var_dump(&mut output, &context.into_value(), 0);
+    } else {
+        for value in args.iter() {
+            var_dump(&mut output, value, 0);
+        }
+    }
+
+    Ok(Value::String(output))
+}
+
+/// Writes a value like PHP `var_dump()`, e.g. `string(5) "hello"`.
+fn var_dump(output: &mut String, value: &Value, depth: usize) {
+    let indent = "  ".repeat(depth);
+
+    let _ = match *value {
+        Value::Null => writeln!(output, "{}NULL", indent),
+        Value::Boolean(x) => writeln!(output, "{}bool({})", indent, x),
+        Value::Integer(x) => writeln!(output, "{}int({})", indent, x),
+        Value::Float(x) => writeln!(output, "{}float({})", indent, float_to_string(x)),
+        Value::String(ref x) => writeln!(output, "{}string({}) \"{}\"", indent, x.len(), x),
+        Value::Markup(ref x, _) => writeln!(output, "{}markup({}) \"{}\"", indent, x.len(), x),
+        Value::Closure(ref x) => writeln!(output, "{}closure({})", indent, x.params().join(", ")),
+        Value::Date(ref x) => writeln!(output, "{}date({})", indent, x),
+        Value::Sequence(ref x) => {
+            let _ = writeln!(output, "{}array({}) {{", indent, x.len());
+            for (i, value) in x.iter().enumerate() {
+                let _ = writeln!(output, "{}  [{}]=>", indent, i);
+                var_dump(output, value, depth + 1);
+            }
+            writeln!(output, "{}}}", indent)
+        }
+        Value::Map(ref x) => {
+            let _ = writeln!(output, "{}array({}) {{", indent, x.len());
+            for &(ref key, ref value) in x.iter() {
+                let _ = match *key {
+                    Key::Integer(i) => writeln!(output, "{}  [{}]=>", indent, i),
+                    Key::String(ref s) => writeln!(output, "{}  [\"{}\"]=>", indent, s),
+                };
+                var_dump(output, value, depth + 1);
+            }
+            writeln!(output, "{}}}", indent)
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::{Runtime, Job, Value};
+    use runtime::value::Map;
+
+    #[test]
+    pub fn _dump() {
+        let mut runtime = Runtime::default();
+        runtime.set("name", "Jo");
+        runtime.set("age", 42);
+        let dump = |args: Vec<Value>, debug| {
+            super::dump(&runtime, &mut Job::new(), args, debug).unwrap().to_string()
+        };
+
+        let mut map = Map::new();
+        map.insert("a", 1.5);
+        map.insert("b", vec![true]);
+
+        assert_eq!(dump(vec![map.into_value(), Value::Null], true),
+                   "array(2) {\n  [\"a\"]=>\n  float(1.5)\n  [\"b\"]=>\n  array(1) {\n    [0]=>\n    \
+                    bool(true)\n  }\n}\nNULL\n");
+        assert_eq!(dump(vec![], true),
+                   "array(2) {\n  [\"age\"]=>\n  int(42)\n  [\"name\"]=>\n  string(2) \"Jo\"\n}\n");
+        assert_eq!(dump(vec![Value::from("x")], false), "");
     }
 }
@@ -0,0 +1,110 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Escaper filters.
+
+use extension::api::Filter;
+use extension::escaper::{self, Strategy, Escapers};
+use runtime::{Runtime, Job, Value, RuntimeError};
+use api::error::{Traced, ErrorExt};
+
+/// Escapes a string for the strategy given as first argument (default: html).
+///
+/// ```text
+/// {{ user.name|escape }}
+/// {{ user.name|e('js') }}
+/// ```
+///
+/// orig: twig_escape_filter
+#[derive(Debug, Default)]
+pub struct Escape {
+    escapers: Escapers,
+}
+
+impl Escape {
+    pub fn new(escapers: Escapers) -> Escape {
+        Escape { escapers: escapers }
+    }
+}
+
+impl Filter for Escape {
+    fn apply(&self,
+             _runtime: &Runtime,
+             _job: &mut Job,
+             input: Value,
+             args: Vec<Value>)
+             -> Result<Value, Traced<RuntimeError>> {
+        let strategy = match args.first() {
+            None | Some(&Value::Null) => Strategy::Html,
+            Some(name) => {
+                let name = name.to_string();
+                try_traced!(escaper::resolve(&name, &self.escapers).ok_or_else(|| {
+                    let mut valid = vec!["html", "js", "url", "css", "html_attr"];
+                    valid.extend(self.escapers.keys().map(|x| x.as_str()));
+
+                    RuntimeError::InvalidArgument {
+                        reason: format!("Invalid escaping strategy {:?} (valid ones: {})",
+                                        name,
+                                        valid.join(", ")),
+                    }
+                    .at(loc!())
+                }))
+            }
+        };
+
+        // unlike autoescaping, the filter escapes markup, too
+        Ok(Value::Markup(strategy.escape(&input.to_string()), strategy.name().to_string()))
+    }
+
+    /// orig: twig_escape_filter_is_safe
+    fn is_safe(&self, args: &[Option<Value>]) -> Vec<String> {
+        match args.first() {
+            None => vec![Strategy::Html.name().to_string()],
+            Some(&Some(ref name)) => vec![name.to_string()],
+            Some(&None) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use extension::api::Filter;
+    use extension::escaper::{Strategy, Escapers};
+    use runtime::{Runtime, Job, Value};
+
+    #[test]
+    fn escape() {
+        let rt = Runtime::default();
+        let mut job = Job::new();
+        let mut escapers = Escapers::new();
+        escapers.insert("latex".to_string(),
+                        Strategy::custom("latex", |text| text.replace("&", r"\&")));
+        let filter = Escape::new(escapers);
+
+        assert_eq!(filter.apply(&rt, &mut job, Value::from("<b>"), vec![]).unwrap(),
+                   Value::Markup("&lt;b&gt;".to_string(), "html".to_string()));
+        assert_eq!(filter.apply(&rt, &mut job, Value::from("a b"), vec![Value::from("url")])
+                         .unwrap(),
+                   Value::Markup("a%20b".to_string(), "url".to_string()));
+        let markup = Value::Markup("a&amp;b".to_string(), "html".to_string());
+        assert_eq!(filter.apply(&rt, &mut job, markup, vec![Value::from("latex")]).unwrap(),
+                   Value::Markup(r"a\&amp;b".to_string(), "latex".to_string()));
+        assert_eq!(filter.apply(&rt, &mut job, Value::from("a&b"), vec![Value::from("latex")])
+                         .unwrap(),
+                   Value::Markup(r"a\&b".to_string(), "latex".to_string()));
+        assert!(filter.apply(&rt, &mut job, Value::from("x"), vec![Value::from("csv")])
+                      .is_err());
+    }
+
+    #[test]
+    fn is_safe() {
+        let filter = Escape::default();
+
+        assert_eq!(filter.is_safe(&[]), vec!["html"]);
+        assert_eq!(filter.is_safe(&[Some(Value::from("js"))]), vec!["js"]);
+        assert_eq!(filter.is_safe(&[None]), Vec::<String>::new());
+    }
+}
@@ -4,26 +4,149 @@
 // file that was distributed with this source code.
 
 //! Escaper Extension
+//!
+//! Escapes the output of `{{ ... }}` according to the active strategy, which
+//! can be changed locally with the `autoescape` tag:
+//!
+//! ```text
+//! {% autoescape 'js' %}var x = "{{ x }}";{% endautoescape %}
+//! {% autoescape false %}{{ trusted_html }}{% endautoescape %}
+//! ```
+//!
+//! Expressions which are known to be safe are not escaped (again), e.g.
+//! `{{ html|raw }}`, `{{ x|escape }}` or `{{ 'constant' }}`.
+//!
+//! Custom strategies can be registered with `Setup::add_escaper()`.
 
-use super::api::Extension;
+use super::api::{self, Extension};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+pub mod strategy;
+pub mod token_parser;
+pub mod filter;
+pub use self::strategy::Strategy;
 
 #[derive(Default, Debug, PartialEq)]
 pub struct Escaper {
     mode: Mode,
+    escapers: Escapers,
+}
+
+/// Custom escaping strategies by name.
+pub type Escapers = HashMap<String, Strategy>;
+
+/// Returns the built-in or custom strategy named `name`.
+///
+/// Built-in strategies take precedence.
+pub fn resolve(name: &str, escapers: &Escapers) -> Option<Strategy> {
+    Strategy::from_name(name).or_else(|| escapers.get(name).cloned())
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Maps a template name to an escaping strategy.
+pub type Callback = Arc<Fn(&str) -> Option<Strategy> + Send + Sync>;
+
+#[derive(Clone)]
 pub enum Mode {
-    Html,
-    _Enabled,
-    _Disabled,
-    _Filename,
-    _Callback,
+    Enabled(Strategy),
+    Disabled,
+    /// Guess the strategy from the template name, see `Mode::guess()`.
+    Filename,
+    Callback(Callback),
 }
 
 impl Default for Mode {
     fn default() -> Mode {
-        Mode::Html
+        Mode::Enabled(Strategy::Html)
+    }
+}
+
+impl Mode {
+    /// Creates a mode, where the strategy is selected by a user closure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twig::extension::escaper::{Mode, Strategy};
+    ///
+    /// let mode = Mode::callback(|name| {
+    ///     if name.ends_with(".json") { Some(Strategy::Js) } else { Mode::guess(name) }
+    /// });
+    /// ```
+    pub fn callback<F>(f: F) -> Mode
+        where F: Fn(&str) -> Option<Strategy> + Send + Sync + 'static
+    {
+        Mode::Callback(Arc::new(f))
+    }
+
+    /// Returns the initial escaping strategy for the template `name`.
+    ///
+    /// In filename mode custom strategies are selected by extension, e.g. a
+    /// `csv` strategy for `report.csv.twig`.
+    pub fn strategy(&self, name: &str, escapers: &Escapers) -> Option<Strategy> {
+        match *self {
+            Mode::Enabled(ref strategy) => Some(strategy.clone()),
+            Mode::Disabled => None,
+            Mode::Filename => {
+                match escapers.get(extension(name)) {
+                    Some(strategy) => Some(strategy.clone()),
+                    None => Mode::guess(name),
+                }
+            }
+            Mode::Callback(ref f) => f(name),
+        }
+    }
+
+    /// Guesses the escaping strategy from the extension of the template name.
+    ///
+    /// A trailing `.twig` is stripped first, so `page.css.twig` gives css.
+    /// orig: Twig_FileExtensionEscapingStrategy::guess()
+    pub fn guess(name: &str) -> Option<Strategy> {
+        if name.ends_with('/') || name.ends_with('\\') {
+            return Some(Strategy::Html); // directories
+        }
+
+        match extension(name) {
+            "js" | "json" => Some(Strategy::Js),
+            "css" => Some(Strategy::Css),
+            "txt" => None,
+            _ => Some(Strategy::Html),
+        }
+    }
+}
+
+/// Returns the file extension of a template name, ignoring a trailing `.twig`.
+fn extension(name: &str) -> &str {
+    let name = name.strip_suffix(".twig").unwrap_or(name);
+
+    let basename = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    match basename.rfind('.') {
+        Some(i) => &basename[i + 1..],
+        None => "",
+    }
+}
+
+impl fmt::Debug for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Mode::Enabled(ref strategy) => write!(f, "Enabled({:?})", strategy),
+            Mode::Disabled => write!(f, "Disabled"),
+            Mode::Filename => write!(f, "Filename"),
+            Mode::Callback(_) => write!(f, "Callback(..)"),
+        }
+    }
+}
+
+impl PartialEq for Mode {
+    fn eq(&self, other: &Mode) -> bool {
+        match (self, other) {
+            (&Mode::Enabled(ref a), &Mode::Enabled(ref b)) => a == b,
+            (&Mode::Disabled, &Mode::Disabled) => true,
+            (&Mode::Filename, &Mode::Filename) => true,
+            (&Mode::Callback(ref a), &Mode::Callback(ref b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
     }
 }
 
@@ -31,10 +154,90 @@ impl Extension for Escaper {
     fn name(&self) -> &'static str {
         "escaper"
     }
+
+    fn token_parsers(&self) -> HashMap<String, Box<api::TokenParser>> {
+        let mut p: HashMap<String, Box<api::TokenParser>> = HashMap::new();
+        p.insert("autoescape".to_string(), Box::new(token_parser::AutoEscape));
+
+        return p;
+    }
+
+    fn filters(&self) -> HashMap<String, Box<api::Filter>> {
+        let mut f: HashMap<String, Box<api::Filter>> = HashMap::new();
+        f.insert("escape".to_string(),
+                 Box::new(filter::Escape::new(self.escapers.clone())));
+        f.insert("e".to_string(),
+                 Box::new(filter::Escape::new(self.escapers.clone())));
+
+        return f;
+    }
 }
 
 impl Escaper {
-    pub fn new(mode: Mode) -> Box<Escaper> {
-        Box::new(Escaper { mode: mode })
+    pub fn new(mode: Mode, escapers: Escapers) -> Box<Escaper> {
+        Box::new(Escaper {
+            mode: mode,
+            escapers: escapers,
+        })
+    }
+
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    /// Registers a custom escaping strategy, e.g. `latex`.
+    pub fn set_escaper<F>(&mut self, name: &str, f: F) -> &mut Escaper
+        where F: Fn(&str) -> String + Send + Sync + 'static
+    {
+        self.escapers.insert(name.to_string(), Strategy::custom(name, f));
+
+        self
+    }
+
+    /// Returns the custom escaping strategies.
+    pub fn escapers(&self) -> &Escapers {
+        &self.escapers
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn guess() {
+        assert_eq!(Mode::guess("index.html"), Some(Strategy::Html));
+        assert_eq!(Mode::guess("index.html.twig"), Some(Strategy::Html));
+        assert_eq!(Mode::guess("app/config.js.twig"), Some(Strategy::Js));
+        assert_eq!(Mode::guess("data.json"), Some(Strategy::Js));
+        assert_eq!(Mode::guess("@theme/style.css"), Some(Strategy::Css));
+        assert_eq!(Mode::guess("mail.txt.twig"), None);
+        assert_eq!(Mode::guess("v1.0/layout"), Some(Strategy::Html));
+        assert_eq!(Mode::guess("partials/"), Some(Strategy::Html));
+    }
+
+    #[test]
+    pub fn strategy() {
+        let none = Escapers::new();
+        let mode = Mode::callback(|name| if name == "raw" { None } else { Some(Strategy::Url) });
+
+        assert_eq!(mode.strategy("raw", &none), None);
+        assert_eq!(mode.strategy("link", &none), Some(Strategy::Url));
+        assert_eq!(Mode::Filename.strategy("x.css", &none), Some(Strategy::Css));
+        assert_eq!(Mode::Disabled.strategy("x.css", &none), None);
+        assert_eq!(Mode::default().strategy("x.css", &none), Some(Strategy::Html));
+    }
+
+    #[test]
+    pub fn custom_strategy() {
+        let mut escaper = Escaper::default();
+        escaper.set_escaper("csv", |text| format!("\"{}\"", text.replace("\"", "\"\"")));
+        let escapers = escaper.escapers();
+
+        assert_eq!(resolve("csv", escapers).unwrap().escape("a\"b"), "\"a\"\"b\"");
+        assert_eq!(resolve("js", escapers), Some(Strategy::Js));
+        assert_eq!(resolve("latex", escapers), None);
+        assert_eq!(Mode::Filename.strategy("report.csv.twig", escapers).map(|x| x.escape("x")),
+                   Some("\"x\"".to_string()));
     }
 }
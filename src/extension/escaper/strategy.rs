@@ -0,0 +1,255 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Escaping strategies.
+//!
+//! The escaping tables follow Twig-PHP (`twig_escape_filter`), so templates
+//! render byte-identical output.
+
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+/// Escaping function of custom strategies.
+pub type EscaperFn = Arc<Fn(&str) -> String + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub enum Strategy {
+    #[default]
+    Html,
+    Js,
+    Css,
+    Url,
+    HtmlAttr,
+    /// Strategy registered by the user, e.g. `latex`.
+    Custom(String, EscaperFn),
+}
+
+impl Strategy {
+    /// Creates a custom strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use twig::extension::escaper::Strategy;
+    ///
+    /// let csv = Strategy::custom("csv", |text| format!("\"{}\"", text.replace("\"", "\"\"")));
+    /// assert_eq!(csv.escape("a \"b\""), "\"a \"\"b\"\"\"");
+    /// ```
+    pub fn custom<F>(name: &str, f: F) -> Strategy
+        where F: Fn(&str) -> String + Send + Sync + 'static
+    {
+        Strategy::Custom(name.to_string(), Arc::new(f))
+    }
+
+    /// Returns the built-in strategy named `name`, e.g. "html_attr".
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        match name {
+            "html" => Some(Strategy::Html),
+            "js" => Some(Strategy::Js),
+            "css" => Some(Strategy::Css),
+            "url" => Some(Strategy::Url),
+            "html_attr" => Some(Strategy::HtmlAttr),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match *self {
+            Strategy::Html => "html",
+            Strategy::Js => "js",
+            Strategy::Css => "css",
+            Strategy::Url => "url",
+            Strategy::HtmlAttr => "html_attr",
+            Strategy::Custom(ref name, _) => name,
+        }
+    }
+
+    pub fn escape(&self, text: &str) -> String {
+        match *self {
+            Strategy::Html => html(text),
+            Strategy::Js => js(text),
+            Strategy::Css => css(text),
+            Strategy::Url => url(text),
+            Strategy::HtmlAttr => html_attr(text),
+            Strategy::Custom(_, ref f) => f(text),
+        }
+    }
+}
+
+impl fmt::Debug for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Strategy::Html => write!(f, "Html"),
+            Strategy::Js => write!(f, "Js"),
+            Strategy::Css => write!(f, "Css"),
+            Strategy::Url => write!(f, "Url"),
+            Strategy::HtmlAttr => write!(f, "HtmlAttr"),
+            Strategy::Custom(ref name, _) => write!(f, "Custom({:?})", name),
+        }
+    }
+}
+
+impl PartialEq for Strategy {
+    fn eq(&self, other: &Strategy) -> bool {
+        match (self, other) {
+            (&Strategy::Custom(ref a, _), &Strategy::Custom(ref b, _)) => a == b,
+            (&Strategy::Custom(..), _) | (_, &Strategy::Custom(..)) => false,
+            _ => self.name() == other.name(),
+        }
+    }
+}
+
+/// orig: htmlspecialchars($string, ENT_QUOTES | ENT_SUBSTITUTE)
+pub fn html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#039;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// orig: preg_replace_callback('#[^a-zA-Z0-9,\._]#Su', '_twig_escape_js_callback', $string)
+pub fn js(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | ',' | '.' | '_' => escaped.push(c),
+            '\\' => escaped.push_str(r"\\"),
+            '/' => escaped.push_str(r"\/"),
+            '\x08' => escaped.push_str(r"\b"),
+            '\x0C' => escaped.push_str(r"\f"),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\t' => escaped.push_str(r"\t"),
+            _ => {
+                // characters outside the BMP are split into surrogate pairs
+                let mut buffer = [0u16; 2];
+                for unit in c.encode_utf16(&mut buffer) {
+                    write!(escaped, "\\u{:04X}", unit).unwrap();
+                }
+            }
+        }
+    }
+
+    escaped
+}
+
+/// orig: preg_replace_callback('#[^a-zA-Z0-9]#Su', '_twig_escape_css_callback', $string)
+pub fn css(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => escaped.push(c),
+            _ => write!(escaped, "\\{:X} ", c as u32).unwrap(),
+        }
+    }
+
+    escaped
+}
+
+/// orig: rawurlencode($string)
+pub fn url(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for byte in text.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                escaped.push(byte as char)
+            }
+            _ => write!(escaped, "%{:02X}", byte).unwrap(),
+        }
+    }
+
+    escaped
+}
+
+/// orig: preg_replace_callback('#[^a-zA-Z0-9,\.\-_]#Su', '_twig_escape_html_attr_callback', $string)
+pub fn html_attr(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | ',' | '.' | '-' | '_' => escaped.push(c),
+            '\t' | '\n' | '\r' => write!(escaped, "&#x{:02X};", c as u32).unwrap(),
+            // characters undefined in HTML are replaced by the unicode replacement character
+            '\x00'..='\x1f' | '\x7f' => escaped.push_str("&#xFFFD;"),
+            '"' => escaped.push_str("&quot;"),
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\x20'..='\x7e' => write!(escaped, "&#x{:02X};", c as u32).unwrap(),
+            _ => write!(escaped, "&#x{:04X};", c as u32).unwrap(),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn _html() {
+        assert_eq!(html(r#"<a href="?x=1&y='2'">ä</a>"#),
+                   "&lt;a href=&quot;?x=1&amp;y=&#039;2&#039;&quot;&gt;ä&lt;/a&gt;");
+    }
+
+    #[test]
+    pub fn _js() {
+        assert_eq!(js("a.b_c,1 </script>\n\"é😀"),
+                   r#"a.b_c,1\u0020\u003C\/script\u003E\n\u0022\u00E9\uD83D\uDE00"#);
+    }
+
+    #[test]
+    pub fn _css() {
+        assert_eq!(css("a1 #x{é}"), r"a1\20 \23 x\7B \E9 \7D ");
+    }
+
+    #[test]
+    pub fn _url() {
+        assert_eq!(url("a b&c=d/é~-_."), "a%20b%26c%3Dd%2F%C3%A9~-_.");
+    }
+
+    #[test]
+    pub fn _html_attr() {
+        assert_eq!(html_attr("a-b_c.d,e f\"<&>\t\x01\u{80}é"),
+                   "a-b_c.d,e&#x20;f&quot;&lt;&amp;&gt;&#x09;&#xFFFD;&#x0080;&#x00E9;");
+    }
+
+    #[test]
+    pub fn from_name() {
+        for strategy in vec![Strategy::Html,
+                             Strategy::Js,
+                             Strategy::Css,
+                             Strategy::Url,
+                             Strategy::HtmlAttr] {
+            assert_eq!(Strategy::from_name(strategy.name()), Some(strategy.clone()));
+        }
+
+        assert_eq!(Strategy::from_name("latex"), None);
+    }
+
+    #[test]
+    pub fn custom() {
+        let latex = Strategy::custom("latex", |text| text.replace("&", r"\&"));
+
+        assert_eq!(latex.name(), "latex");
+        assert_eq!(latex.escape("a & b"), r"a \& b");
+        assert_eq!(latex, Strategy::custom("latex", |text| text.to_string()));
+        assert!(latex != Strategy::Html);
+    }
+}
@@ -0,0 +1,90 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Escaper token parser.
+
+use extension::api::TokenParser;
+use extension::api::token_parser::TestResult;
+use extension::escaper::Strategy;
+use engine::{node, Node};
+use engine::parser::{Job, ParserError};
+use engine::parser::token::stream::Item;
+use engine::parser::token::Token;
+use api::error::{Traced, ErrorExt, Dump};
+
+/// Marks a section of a template as being escaped or not.
+///
+/// ```text
+/// {% autoescape %}..{% endautoescape %}         -> html
+/// {% autoescape 'js' %}..{% endautoescape %}    -> js
+/// {% autoescape false %}..{% endautoescape %}   -> disabled
+/// ```
+#[derive(Debug, Default)]
+pub struct AutoEscape;
+
+impl TokenParser for AutoEscape {
+    fn tag(&self) -> &'static str {
+        "autoescape"
+    }
+
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        let strategy = if let Some(&Token::BlockEnd) = job.mut_cursor().peek_token() {
+            Some(Strategy::Html)
+        } else {
+            try_traced!(self.parse_strategy(job))
+        };
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("autoescape-Block must be closed")));
+
+        job.push_autoescape(strategy);
+        let body = job.sub_parse_until(&is_autoescape_end);
+        job.pop_autoescape();
+
+        let body = try_traced!(body);
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("endautoescape-Block must be closed")));
+
+        let mut node = node::Virtual::boxed(item.position());
+        node.children_mut().extend(body);
+
+        return Ok(node);
+    }
+}
+
+impl AutoEscape {
+    fn parse_strategy(&self, job: &mut Job) -> Result<Option<Strategy>, Traced<ParserError>> {
+        match job.mut_cursor().next_token() {
+            Some(&Token::Name(ref x)) if x == "true" => Ok(Some(Strategy::Html)),
+            Some(&Token::Name(ref x)) if x == "false" => Ok(None),
+            Some(&Token::String(ref x)) => {
+                job.parser()
+                   .strategy(x)
+                   .map(Some)
+                   .ok_or_else(|| {
+                       ParserError::TokenParserError {
+                           tag: self.tag(),
+                           error: format!("Unknown escaping strategy {:?}", x),
+                           job: job.dump(),
+                       }
+                       .at(loc!())
+                   })
+            }
+            _ => {
+                traced_err!(ParserError::TokenParserError {
+                    tag: self.tag(),
+                    error: "An escaping strategy must be a string or false".to_string(),
+                    job: job.dump(),
+                })
+            }
+        }
+    }
+}
+
+pub fn is_autoescape_end(item: &Item) -> TestResult {
+    match item.token().value_as_str() {
+        Some("endautoescape") => TestResult::DropToken,
+        _ => TestResult::Continue,
+    }
+}
@@ -4,23 +4,90 @@
 // file that was distributed with this source code.
 
 //! Optimizer Extension
+//!
+//! Optimizes the node tree of compiled templates. The passes can be switched
+//! individually, e.g. `Mode::TEXT | Mode::CONSTANTS`.
 
-use super::api::Extension;
+use std::ops::{BitOr, BitAnd, Sub};
+use super::api::{Extension, NodeVisitor};
+
+pub mod node_visitor;
+pub use self::node_visitor::OptimizerVisitor;
 
 #[derive(Default, Debug, PartialEq)]
 pub struct Optimizer {
     mode: Mode,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Mode {
-    Enabled,
-    _Disabled,
+/// Set of optimization passes.
+///
+/// orig: Twig_NodeVisitor_Optimizer::OPTIMIZE_*
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mode {
+    bits: u8,
+}
+
+impl Mode {
+    /// No optimizations.
+    pub const NONE: Mode = Mode { bits: 0 };
+    /// Merges adjacent text nodes.
+    pub const TEXT: Mode = Mode { bits: 1 };
+    /// Folds constant expressions, e.g. `{{ 1 + 2 }}` or `{{ 'a' ~ 'b'|upper }}`.
+    pub const CONSTANTS: Mode = Mode { bits: 2 };
+    /// Writes `{{ parent() }}` and `{{ block('name') }}` directly instead of
+    /// buffering the block first.
+    pub const OUTPUT: Mode = Mode { bits: 4 };
+    /// Skips the `loop` variable of for-loops, which don't use it.
+    pub const LOOP: Mode = Mode { bits: 8 };
+    /// All optimizations.
+    pub const ALL: Mode = Mode { bits: 15 };
+
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Returns the passes of `bits` - unknown bits are ignored.
+    pub fn from_bits(bits: u8) -> Mode {
+        Mode { bits: bits & Mode::ALL.bits }
+    }
+
+    /// Returns true if all passes of `other` are enabled.
+    pub fn contains(&self, other: Mode) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
 }
 
 impl Default for Mode {
     fn default() -> Mode {
-        Mode::Enabled
+        Mode::ALL
+    }
+}
+
+impl BitOr for Mode {
+    type Output = Mode;
+
+    fn bitor(self, other: Mode) -> Mode {
+        Mode { bits: self.bits | other.bits }
+    }
+}
+
+impl BitAnd for Mode {
+    type Output = Mode;
+
+    fn bitand(self, other: Mode) -> Mode {
+        Mode { bits: self.bits & other.bits }
+    }
+}
+
+impl Sub for Mode {
+    type Output = Mode;
+
+    fn sub(self, other: Mode) -> Mode {
+        Mode { bits: self.bits & !other.bits }
     }
 }
 
@@ -28,10 +95,40 @@ impl Extension for Optimizer {
     fn name(&self) -> &'static str {
         "optimizer"
     }
+
+    fn node_visitors(&self) -> Vec<Box<NodeVisitor>> {
+        if self.mode.is_empty() {
+            return Vec::new();
+        }
+
+        vec![Box::new(OptimizerVisitor::new(self.mode))]
+    }
 }
 
 impl Optimizer {
     pub fn new(mode: Mode) -> Box<Optimizer> {
         Box::new(Optimizer { mode: mode })
     }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn mode() {
+        let mode = Mode::TEXT | Mode::CONSTANTS;
+
+        assert!(mode.contains(Mode::TEXT));
+        assert!(!(mode - Mode::TEXT).contains(Mode::TEXT));
+        assert!(!mode.contains(Mode::ALL));
+        assert_eq!(Mode::ALL, mode | Mode::OUTPUT | Mode::LOOP);
+        assert_eq!(mode & Mode::CONSTANTS, Mode::CONSTANTS);
+        assert_eq!(Mode::from_bits(0xff), Mode::ALL);
+        assert!(Mode::NONE.is_empty());
+    }
 }
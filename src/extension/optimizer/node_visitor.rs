@@ -0,0 +1,365 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Optimizer node visitor.
+
+use std::mem;
+use engine::Node;
+use engine::node::{self, Text, Body, Virtual, Sandbox, Profile, Print, Block, BlockReference,
+                   Include, For};
+use engine::node::expression::{self, Constant, Escape, Filter, Function, Array, Binary, Unary,
+                               Name, Arrow, GetAttr, Parent};
+use engine::parser::Parser;
+use extension::api::NodeVisitor;
+use extension::optimizer::Mode;
+use runtime::{Runtime, Job};
+
+/// Filters without side effects, which only depend on their arguments.
+const PURE_FILTERS: &'static [&'static str] = &["upper", "lower", "title", "capitalize", "trim",
+                                                "raw", "length", "first", "last", "keys", "join",
+                                                "reverse", "round", "abs", "escape", "e",
+                                                "json_encode", "url_encode"];
+
+/// Functions without side effects, which only depend on their arguments.
+const PURE_FUNCTIONS: &'static [&'static str] = &["range", "min", "max"];
+
+/// Core operators - custom operators may have side effects.
+const PURE_OPERATORS: &'static [&'static str] = &["or", "and", "==", "!=", "<", ">", "<=", ">=",
+                                                  "<=>", "+", "-", "~", "*", "/", "//", "%",
+                                                  "**", "not"];
+
+/// Applies the optimization passes of the mode.
+///
+/// orig: Twig_NodeVisitor_Optimizer
+#[derive(Debug)]
+pub struct OptimizerVisitor {
+    mode: Mode,
+}
+
+impl OptimizerVisitor {
+    pub fn new(mode: Mode) -> OptimizerVisitor {
+        OptimizerVisitor { mode: mode }
+    }
+
+    /// Evaluates expressions with constant arguments at compile time.
+    ///
+    /// The expression is kept, if the evaluation fails - to fail at runtime.
+    fn fold_constant(&self, node: Box<Node>, parser: &Parser) -> Box<Node> {
+        if !is_foldable(&*node) {
+            return node;
+        }
+
+        let mut job = Job::with_extensions(parser.extensions().clone());
+        let value = match node.evaluate(&Runtime::default(), &mut job) {
+            Ok(value) => value,
+            Err(_) => return node,
+        };
+
+        let mut constant = Constant::boxed(value, node.position());
+        expression::set_safe_for(&mut *constant, &expression::safe_for(&*node));
+        constant
+    }
+
+    /// Replaces `{{ 'constant' }}` by text.
+    fn fold_print(&self, node: Box<Node>) -> Box<Node> {
+        let text = match node.as_any().downcast_ref::<Print>() {
+            Some(print) if print.children().len() == 1 => {
+                match print.children()[0].constant() {
+                    Some(value) => value.to_string(),
+                    None => return node,
+                }
+            }
+            _ => return node,
+        };
+
+        Text::boxed(text, node.position())
+    }
+
+    /// Replaces `{{ parent() }}` and `{{ block('name') }}` by the expression,
+    /// which writes the block directly.
+    fn unwrap_print(&self, mut node: Box<Node>) -> Box<Node> {
+        let unwrap = node.as_any().is::<Print>() && node.children().len() == 1 && {
+            let expr = node.children()[0].as_any();
+            expr.is::<expression::BlockReference>() || expr.is::<Parent>()
+        };
+        if !unwrap {
+            return node;
+        }
+
+        let mut expr = node.children_mut().remove(0);
+        expr.set_attribute(expression::OUTPUT, "true");
+        expr
+    }
+
+    /// Disables the `loop` variable of for-loops, whose body doesn't use it.
+    fn skip_loop(&self, mut node: Box<Node>, parser: &Parser) -> Box<Node> {
+        let unused = node.as_any().is::<For>() &&
+                     !node.children().get(1).is_some_and(|body| uses_loop(&**body, parser, false));
+        if unused {
+            node.set_attribute(node::for_::WITH_LOOP, "false");
+        }
+
+        node
+    }
+
+    /// Merges adjacent text nodes of sequences, e.g. the template body.
+    fn merge_text(&self, mut node: Box<Node>) -> Box<Node> {
+        if !is_sequence(&*node) {
+            return node;
+        }
+
+        let children = mem::take(node.children_mut());
+        let mut merged: Vec<Box<Node>> = Vec::with_capacity(children.len());
+
+        for child in children {
+            let child = match node::downcast::<Text>(child) {
+                Ok(text) => {
+                    let previous = match merged.last() {
+                        Some(last) => last.as_any().downcast_ref::<Text>().map(|x| x.text()),
+                        None => None,
+                    }
+                    .map(|x| format!("{}{}", x, text.text()));
+
+                    if let Some(previous) = previous {
+                        let position = merged.pop().unwrap().position().clone();
+                        merged.push(Text::boxed(previous, &position));
+                        continue;
+                    }
+
+                    text as Box<Node>
+                }
+                Err(child) => child,
+            };
+
+            merged.push(child);
+        }
+
+        *node.children_mut() = merged;
+        node
+    }
+}
+
+impl NodeVisitor for OptimizerVisitor {
+    fn leave_node(&self, node: Box<Node>, parser: &Parser) -> Option<Box<Node>> {
+        let mut node = node;
+
+        if self.mode.contains(Mode::CONSTANTS) {
+            node = self.fold_constant(node, parser);
+            node = self.fold_print(node);
+        }
+
+        if self.mode.contains(Mode::OUTPUT) {
+            node = self.unwrap_print(node);
+        }
+
+        if self.mode.contains(Mode::LOOP) {
+            node = self.skip_loop(node, parser);
+        }
+
+        if self.mode.contains(Mode::TEXT) {
+            node = self.merge_text(node);
+        }
+
+        Some(node)
+    }
+
+    /// Runs after all other visitors.
+    fn priority(&self) -> i32 {
+        10
+    }
+}
+
+/// Returns true for pure expressions, whose arguments are all constant.
+fn is_foldable(node: &Node) -> bool {
+    let any = node.as_any();
+    let pure = any.is::<Escape>() || any.is::<Array>() ||
+               any.downcast_ref::<Filter>().is_some_and(|x| PURE_FILTERS.contains(&x.name())) ||
+               any.downcast_ref::<Function>()
+                  .is_some_and(|x| PURE_FUNCTIONS.contains(&x.name())) ||
+               any.downcast_ref::<Binary>()
+                  .is_some_and(|x| PURE_OPERATORS.contains(&x.operator())) ||
+               any.downcast_ref::<Unary>()
+                  .is_some_and(|x| PURE_OPERATORS.contains(&x.operator()));
+
+    pure && node.children().iter().all(|x| x.constant().is_some())
+}
+
+/// Returns true if the node may read the `loop` variable of the enclosing
+/// for-loop - `nested` within inner loops, which define their own.
+///
+/// Blocks and functions like `dump()` see the whole context, `loop.parent`
+/// gives access to the outer loops.
+fn uses_loop(node: &Node, parser: &Parser, nested: bool) -> bool {
+    let any = node.as_any();
+
+    let reads_loop = if let Some(name) = any.downcast_ref::<Name>() {
+        !nested && name.key() == "loop"
+    } else if any.is::<BlockReference>() || any.is::<expression::BlockReference>() ||
+              any.is::<Parent>() {
+        !nested
+    } else if let Some(include) = any.downcast_ref::<Include>() {
+        !include.only()
+    } else if let Some(function) = any.downcast_ref::<Function>() {
+        parser.extensions()
+              .functions()
+              .get(function.name())
+              .is_some_and(|x| x.needs_context())
+    } else if any.is::<GetAttr>() {
+        let object = node.children().first().and_then(|x| x.as_any().downcast_ref::<Name>());
+        let attribute = node.children().get(1).and_then(|x| x.constant());
+        object.is_some_and(|x| x.key() == "loop") &&
+        attribute.is_none_or(|x| x.as_str() == Some("parent"))
+    } else {
+        false
+    };
+    if reads_loop {
+        return true;
+    }
+
+    if let Some(arrow) = any.downcast_ref::<Arrow>() {
+        return uses_loop(arrow.body(), parser, nested);
+    }
+
+    // the body of inner loops - the sequence, the else-branch and the
+    // condition belong to the enclosing loop
+    let is_loop = any.is::<For>();
+    node.children()
+        .iter()
+        .enumerate()
+        .any(|(index, child)| uses_loop(&**child, parser, nested || (is_loop && index == 1)))
+}
+
+/// Returns true for nodes which execute their children in order.
+fn is_sequence(node: &Node) -> bool {
+    let any = node.as_any();
+
+    any.is::<Body>() || any.is::<Virtual>() || any.is::<Sandbox>() || any.is::<Profile>() ||
+    any.is::<Block>()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use engine::{Engine, Setup, Node};
+    use engine::node::{Text, Body, Print, For, NodeTraverser};
+    use engine::node::expression::{self, Constant, Filter, Name, Binary, GetAttr, Parent};
+    use engine::node::expression::get_attr;
+    use engine::parser::Parser;
+    use engine::parser::token::stream::Position;
+    use extension::api::NodeVisitor;
+    use extension::optimizer::Mode;
+    use runtime::Value;
+
+    fn body() -> Box<Node> {
+        let position = Position::default();
+        let upper = Filter::boxed("upper".to_string(),
+                                  Constant::boxed(Value::from("b"), &position),
+                                  vec![],
+                                  &position);
+        let name = Name::boxed("c".to_string(), &position);
+
+        Body::boxed(vec![Text::boxed("a".to_string(), &position),
+                         Print::boxed(upper, &position),
+                         Print::boxed(name, &position),
+                         Text::boxed("d".to_string(), &position),
+                         Text::boxed("e".to_string(), &position)])
+    }
+
+    fn optimize(mode: Mode) -> Box<Node> {
+        optimize_body(mode, body())
+    }
+
+    fn optimize_body(mode: Mode, body: Box<Node>) -> Box<Node> {
+        let engine = Engine::new(Setup::default()).unwrap();
+        let parser = Parser::new(&engine).unwrap();
+        let visitor = OptimizerVisitor::new(mode);
+        let mut traverser = NodeTraverser::new(&parser, &[]);
+        traverser.add_visitor(&visitor as &NodeVisitor);
+
+        traverser.traverse(body).unwrap()
+    }
+
+    fn texts(node: &Node) -> Vec<Option<&str>> {
+        node.children()
+            .iter()
+            .map(|x| x.as_any().downcast_ref::<Text>().map(|x| x.text()))
+            .collect()
+    }
+
+    #[test]
+    pub fn all() {
+        assert_eq!(texts(&*optimize(Mode::ALL)), vec![Some("aB"), None, Some("de")]);
+    }
+
+    #[test]
+    pub fn text() {
+        assert_eq!(texts(&*optimize(Mode::TEXT)),
+                   vec![Some("a"), None, None, Some("de")]);
+    }
+
+    #[test]
+    pub fn binary() {
+        let position = Position::default();
+        let constant = |x: i32| Constant::boxed(Value::from(x), &position) as Box<Node>;
+        let body = Body::boxed(vec![
+            Print::boxed(Binary::boxed("+".to_string(), constant(1), constant(2), &position),
+                         &position),
+            Print::boxed(Binary::boxed("/".to_string(), constant(1), constant(0), &position),
+                         &position),
+        ]);
+
+        // the division by zero fails at runtime
+        assert_eq!(texts(&*optimize_body(Mode::CONSTANTS, body)), vec![Some("3"), None]);
+    }
+
+    #[test]
+    pub fn output() {
+        let position = Position::default();
+        let body = Body::boxed(vec![
+            Print::boxed(Parent::boxed("title".to_string(), &position), &position),
+        ]);
+
+        let body = optimize_body(Mode::OUTPUT, body);
+        let parent = body.children()[0].as_any().downcast_ref::<Parent>();
+        assert!(parent.is_some_and(|x| x.has_attribute(expression::OUTPUT)));
+    }
+
+    #[test]
+    pub fn _loop() {
+        let position = Position::default();
+        let name = |x: &str| Name::boxed(x.to_string(), &position) as Box<Node>;
+        let attribute = |object, x: &str| {
+            GetAttr::boxed(object,
+                           Constant::boxed(Value::from(x), &position),
+                           vec![],
+                           get_attr::Kind::Any,
+                           &position) as Box<Node>
+        };
+        let for_ = |body: Box<Node>| {
+            For::boxed("_key".to_string(),
+                       "x".to_string(),
+                       name("items"),
+                       None,
+                       vec![Print::boxed(body, &position)],
+                       vec![],
+                       &position) as Box<Node>
+        };
+        let with_loop = |node: Box<Node>| {
+            let node = optimize_body(Mode::LOOP, node);
+            node.as_any().downcast_ref::<For>().unwrap().with_loop()
+        };
+
+        assert!(!with_loop(for_(name("x"))));
+        assert!(with_loop(for_(attribute(name("loop"), "index"))));
+        // the inner loop defines its own `loop` variable
+        assert!(!with_loop(for_(for_(attribute(name("loop"), "index")))));
+        assert!(with_loop(for_(for_(attribute(name("loop"), "parent")))));
+    }
+
+    #[test]
+    pub fn none() {
+        assert_eq!(texts(&*optimize(Mode::NONE)).len(), 5);
+    }
+}
@@ -0,0 +1,229 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Profile dumpers.
+
+use runtime::Value;
+use runtime::value::Map;
+use extension::escaper::strategy;
+use extension::core::filter::encode::{self, JSON_UNESCAPED_SLASHES};
+use extension::profiler::Profile;
+
+/// Converts a profile tree to a report.
+pub trait Dumper {
+    fn dump(&self, profile: &Profile) -> String;
+}
+
+/// Plain text tree, e.g.
+///
+/// ```text
+/// main 12.30ms/100%
+/// └ index.html 12.30ms/100%
+///   └ header.html 2.10ms/17%
+/// ```
+///
+/// orig: Twig_Profiler_Dumper_Text
+#[derive(Debug, Default)]
+pub struct TextDumper;
+
+impl Dumper for TextDumper {
+    fn dump(&self, profile: &Profile) -> String {
+        tree(profile, &Style::Text)
+    }
+}
+
+/// HTML tree with highlighted templates and slow calls.
+///
+/// orig: Twig_Profiler_Dumper_Html
+#[derive(Debug, Default)]
+pub struct HtmlDumper;
+
+impl Dumper for HtmlDumper {
+    fn dump(&self, profile: &Profile) -> String {
+        format!("<pre>{}</pre>", tree(profile, &Style::Html))
+    }
+}
+
+/// Chrome trace event JSON - open it in `chrome://tracing` or Perfetto.
+#[derive(Debug, Default)]
+pub struct ChromeTraceDumper;
+
+impl Dumper for ChromeTraceDumper {
+    fn dump(&self, profile: &Profile) -> String {
+        let mut events = Vec::new();
+        trace_events(profile, &mut events);
+
+        let mut trace = Map::new();
+        trace.insert("traceEvents", events);
+
+        let json = encode::json_encode(trace.into_value(),
+                                       vec![Value::Integer(JSON_UNESCAPED_SLASHES)]);
+        json.map(|x| x.to_string()).unwrap_or_default()
+    }
+}
+
+/// Appends "complete" events for the profile and the calls nested in it.
+fn trace_events(profile: &Profile, events: &mut Vec<Value>) {
+    if !profile.is_root() {
+        let mut args = Map::new();
+        args.insert("memory", profile.memory() as i64);
+
+        let mut event = Map::new();
+        event.insert("name", profile.label());
+        event.insert("cat", profile.kind().name());
+        event.insert("ph", "X");
+        event.insert("ts", micros(profile.start()));
+        event.insert("dur", micros(profile.duration()));
+        event.insert("pid", 1);
+        event.insert("tid", 1);
+        event.insert("args", args);
+        events.push(event.into_value());
+    }
+
+    for profile in profile.profiles() {
+        trace_events(profile, events);
+    }
+}
+
+fn micros(duration: ::std::time::Duration) -> i64 {
+    duration.as_secs() as i64 * 1000000 + duration.subsec_nanos() as i64 / 1000
+}
+
+fn millis(duration: ::std::time::Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1000000.0
+}
+
+#[derive(Debug)]
+enum Style {
+    Text,
+    Html,
+}
+
+impl Style {
+    fn call(&self, profile: &Profile, prefix: &str) -> String {
+        match *self {
+            Style::Text => format!("{}└ {}", prefix, profile.label()),
+            Style::Html if profile.is_template() => {
+                format!("{}└ <span style=\"background-color: #ffd\">{}</span>",
+                        prefix,
+                        strategy::html(&profile.label()))
+            }
+            Style::Html => format!("{}└ {}", prefix, strategy::html(&profile.label())),
+        }
+    }
+
+    fn time(&self, profile: &Profile, percent: f64) -> String {
+        let time = format!("{:.2}ms/{:.0}%", millis(profile.duration()), percent);
+
+        match *self {
+            Style::Text => time,
+            Style::Html => {
+                format!("<span style=\"color: {}\">{}</span>",
+                        if percent > 20.0 { "#d44" } else { "auto" },
+                        time)
+            }
+        }
+    }
+}
+
+fn tree(profile: &Profile, style: &Style) -> String {
+    let mut out = String::new();
+    dump_profile(&mut out, profile, style, millis(profile.duration()), "", false);
+
+    out
+}
+
+/// Calls shorter than a millisecond are listed without time.
+fn dump_profile(out: &mut String,
+                profile: &Profile,
+                style: &Style,
+                total: f64,
+                prefix: &str,
+                sibling: bool) {
+    let mut prefix = prefix.to_string();
+    let start = if profile.is_root() {
+        profile.name().to_string()
+    } else {
+        let start = style.call(profile, &prefix);
+        prefix.push_str(if sibling { "│ " } else { "  " });
+        start
+    };
+
+    let duration = millis(profile.duration());
+    let percent = if total > 0.0 { duration / total * 100.0 } else { 0.0 };
+    if duration < 1.0 {
+        out.push_str(&start);
+    } else {
+        out.push_str(&format!("{} {}", start, style.time(profile, percent)));
+    }
+    out.push('\n');
+
+    let count = profile.profiles().len();
+    for (i, nested) in profile.profiles().iter().enumerate() {
+        dump_profile(out, nested, style, total, &prefix, i + 1 != count);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+    use extension::profiler::{Profile, Kind};
+
+    fn profile() -> Profile {
+        let call = |kind, template: &str, name: &str, start, end| {
+            let mut profile = Profile::new(kind, template, name);
+            profile.enter(Duration::from_millis(start), 0);
+            profile.leave(Duration::from_millis(end), 10);
+            profile
+        };
+
+        let mut index = call(Kind::Template, "index.html", "index.html", 0, 20);
+        let mut layout = call(Kind::Template, "layout.html", "layout.html", 1, 11);
+        layout.add_profile(call(Kind::Template, "<user>.html", "<user>.html", 2, 5));
+        layout.add_profile(call(Kind::Block, "layout.html", "content", 6, 8));
+        index.add_profile(layout);
+        index.add_profile(call(Kind::Template, "footer.html", "footer.html", 15, 15));
+
+        let mut root = Profile::root();
+        root.add_profile(index);
+        root
+    }
+
+    #[test]
+    pub fn text() {
+        assert_eq!(TextDumper.dump(&profile()),
+                   "main 20.00ms/100%\n\
+                    └ index.html 20.00ms/100%\n  \
+                      └ layout.html 10.00ms/50%\n  \
+                      │ └ <user>.html 3.00ms/15%\n  \
+                      │ └ layout.html::block(content) 2.00ms/10%\n  \
+                      └ footer.html\n");
+    }
+
+    #[test]
+    pub fn _html() {
+        let html = HtmlDumper.dump(&profile());
+
+        assert!(html.starts_with("<pre>main <span style=\"color: #d44\">20.00ms/100%</span>\n"));
+        assert!(html.contains("│ └ <span style=\"background-color: #ffd\">&lt;user&gt;.html</span> \
+                               <span style=\"color: auto\">3.00ms/15%</span>\n"));
+        assert!(html.contains("│ └ layout.html::block(content) <span"));
+        assert!(html.contains("└ <span style=\"background-color: #ffd\">footer.html</span>\n\
+                               </pre>"));
+    }
+
+    #[test]
+    pub fn chrome_trace() {
+        let json = ChromeTraceDumper.dump(&profile());
+
+        assert!(json.starts_with("{\"traceEvents\":[{\"name\":\"index.html\",\"cat\":\"template\",\
+                                  \"ph\":\"X\",\"ts\":0,\"dur\":20000,\"pid\":1,\"tid\":1,\
+                                  \"args\":{\"memory\":10}},"));
+        assert!(json.contains("{\"name\":\"layout.html\",\"cat\":\"template\""));
+        assert!(json.contains("{\"name\":\"layout.html::block(content)\",\"cat\":\"block\""));
+        assert_eq!(json.matches("\"ph\":\"X\"").count(), 5);
+    }
+}
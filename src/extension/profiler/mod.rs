@@ -4,22 +4,115 @@
 // file that was distributed with this source code.
 
 //! Profiler Extension
+//!
+//! Records the wall time and the rendered output of each template - and of the
+//! templates it includes - in a profile tree. Templates are instrumented by a
+//! node visitor when they are compiled.
+//!
+//! # Examples
+//!
+//! ```
+//! use twig::{Engine, Setup};
+//! use twig::extension::Profiler;
+//! use twig::extension::profiler::{Dumper, TextDumper};
+//!
+//! let profiler = Profiler::new();
+//! let setup = Setup::default().add_extension(profiler.clone()).unwrap();
+//! let engine = Engine::new(setup).unwrap();
+//!
+//! // .. render templates ..
+//!
+//! println!("{}", TextDumper.dump(&profiler.profile()));
+//! ```
 
-use super::api::Extension;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use super::api::{Extension, NodeVisitor};
+use engine::Engine;
 
-#[allow(dead_code)]
-// dummy
-#[derive(Default, Debug, PartialEq)]
-pub struct Profiler;
+pub mod profile;
+pub mod dumper;
+pub mod node_visitor;
+pub use self::profile::{Profile, Kind};
+pub use self::dumper::{Dumper, TextDumper, HtmlDumper, ChromeTraceDumper};
+pub use self::node_visitor::ProfilerVisitor;
+
+/// Collects profiles - clones share the profile tree.
+///
+/// The profile tree is shared across threads, too - profile concurrent renders
+/// with separate engines.
+#[derive(Default, Debug, Clone)]
+pub struct Profiler {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    epoch: Instant,
+    root: Profile,
+    stack: Vec<Profile>,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            epoch: Instant::now(),
+            root: Profile::root(),
+            stack: Vec::new(),
+        }
+    }
+}
 
 impl Extension for Profiler {
     fn name(&self) -> &'static str {
         "profiler"
     }
+
+    fn init(&self, engine: &mut Engine) {
+        engine.set_profiler(self.clone());
+    }
+
+    fn node_visitors(&self) -> Vec<Box<NodeVisitor>> {
+        vec![Box::new(ProfilerVisitor::new(self.clone()))]
+    }
 }
 
 impl Profiler {
-    pub fn _new() -> Box<Profiler> {
-        Box::new(Profiler)
+    pub fn new() -> Box<Profiler> {
+        Box::new(Profiler::default())
+    }
+
+    /// Returns the profile tree recorded so far.
+    pub fn profile(&self) -> Profile {
+        self.state.lock().unwrap().root.clone()
+    }
+
+    /// Discards the recorded profiles.
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = State::default();
+    }
+
+    /// Starts a nested profile - `output` is the size of the output so far.
+    pub fn enter(&self, kind: Kind, template: &str, name: &str, output: usize) {
+        let mut state = self.state.lock().unwrap();
+        let mut profile = Profile::new(kind, template, name);
+        profile.enter(state.epoch.elapsed(), output);
+
+        state.stack.push(profile);
+    }
+
+    /// Stops the innermost profile - `output` is the size of the output so far.
+    pub fn leave(&self, output: usize) {
+        let mut state = self.state.lock().unwrap();
+        let mut profile = match state.stack.pop() {
+            Some(profile) => profile,
+            None => return,
+        };
+        profile.leave(state.epoch.elapsed(), output);
+
+        match state.stack.last_mut() {
+            Some(parent) => parent.add_profile(profile),
+            None => state.root.add_profile(profile),
+        }
     }
 }
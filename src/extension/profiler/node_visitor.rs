@@ -0,0 +1,79 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Profiler node visitor.
+
+use std::mem;
+use engine::Node;
+use engine::node::{self, Module, Block, Macro};
+use engine::parser::Parser;
+use extension::api::NodeVisitor;
+use extension::profiler::{Profiler, Kind};
+
+/// Wraps the body of each template, block and macro with a profile node.
+///
+/// orig: Twig_Profiler_NodeVisitor_Profiler
+#[derive(Debug)]
+pub struct ProfilerVisitor {
+    profiler: Profiler,
+}
+
+impl ProfilerVisitor {
+    pub fn new(profiler: Profiler) -> ProfilerVisitor {
+        ProfilerVisitor { profiler: profiler }
+    }
+
+    /// Moves the children of `node` into a profile node.
+    fn wrap(&self, node: &mut Node, kind: Kind, template: &str, name: &str) {
+        let nodes = mem::take(node.children_mut());
+        let position = match nodes.first() {
+            Some(node) => node.position().clone(),
+            None => return,
+        };
+
+        node.children_mut().push(node::Profile::boxed(self.profiler.clone(),
+                                                      kind,
+                                                      template,
+                                                      name,
+                                                      nodes,
+                                                      &position));
+    }
+}
+
+impl NodeVisitor for ProfilerVisitor {
+    fn leave_node(&self, mut node: Box<Node>, _parser: &Parser) -> Option<Box<Node>> {
+        if let Some(module) = node.as_any_mut().downcast_mut::<Module>() {
+            let template = module.filename().to_string();
+
+            let mut children = module.children_mut().iter_mut();
+            if let Some(body) = children.next() {
+                self.wrap(&mut **body, Kind::Template, &template, &template);
+            }
+            if let Some(blocks) = children.next() {
+                for block in blocks.children_mut() {
+                    let name = match block.as_any().downcast_ref::<Block>() {
+                        Some(block) => block.name().to_string(),
+                        None => continue,
+                    };
+                    self.wrap(&mut **block, Kind::Block, &template, &name);
+                }
+            }
+            if let Some(macros) = children.next() {
+                for macro_ in macros.children_mut() {
+                    let name = match macro_.as_any().downcast_ref::<Macro>() {
+                        Some(macro_) => macro_.name().to_string(),
+                        None => continue,
+                    };
+                    // the first child are the arguments
+                    if let Some(body) = macro_.children_mut().get_mut(1) {
+                        self.wrap(&mut **body, Kind::Macro, &template, &name);
+                    }
+                }
+            }
+        }
+
+        Some(node)
+    }
+}
@@ -0,0 +1,164 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Profile tree.
+
+use std::time::Duration;
+
+/// What a profile measures.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Kind {
+    Root,
+    Template,
+    Block,
+    Macro,
+}
+
+impl Kind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Kind::Root => "root",
+            Kind::Template => "template",
+            Kind::Block => "block",
+            Kind::Macro => "macro",
+        }
+    }
+}
+
+/// Wall time and rendered output of a template, block or macro - and of the
+/// calls nested in it.
+///
+/// Times are relative to the creation (or reset) of the profiler.
+///
+/// orig: Twig_Profiler_Profile
+#[derive(Debug, PartialEq, Clone)]
+pub struct Profile {
+    kind: Kind,
+    template: String,
+    name: String,
+    start: Duration,
+    end: Duration,
+    memory: usize,
+    output: usize,
+    profiles: Vec<Profile>,
+}
+
+impl Profile {
+    pub fn new(kind: Kind, template: &str, name: &str) -> Profile {
+        Profile {
+            kind: kind,
+            template: template.to_string(),
+            name: name.to_string(),
+            start: Duration::from_secs(0),
+            end: Duration::from_secs(0),
+            memory: 0,
+            output: 0,
+            profiles: Vec::new(),
+        }
+    }
+
+    /// The root of a profile tree.
+    pub fn root() -> Profile {
+        Profile::new(Kind::Root, "", "main")
+    }
+
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.kind == Kind::Root
+    }
+
+    pub fn is_template(&self) -> bool {
+        self.kind == Kind::Template
+    }
+
+    /// Returns the name of the call, e.g. `index.html` or `index.html::block(title)`.
+    pub fn label(&self) -> String {
+        match self.kind {
+            Kind::Root | Kind::Template => self.template.clone(),
+            kind => format!("{}::{}({})", self.template, kind.name(), self.name),
+        }
+    }
+
+    /// Returns the start time.
+    pub fn start(&self) -> Duration {
+        if self.is_root() {
+            return self.profiles.first().map_or(self.start, |x| x.start);
+        }
+
+        self.start
+    }
+
+    /// Returns the wall time - for the root from the first to the last call.
+    pub fn duration(&self) -> Duration {
+        if self.is_root() && !self.profiles.is_empty() {
+            let end = self.profiles.iter().map(|x| x.end).max().unwrap_or(self.end);
+            return end - self.start();
+        }
+
+        self.end - self.start
+    }
+
+    /// Returns the size of the rendered output in bytes.
+    pub fn memory(&self) -> usize {
+        if self.is_root() {
+            return self.profiles.iter().map(|x| x.memory()).sum();
+        }
+
+        self.memory
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn add_profile(&mut self, profile: Profile) {
+        self.profiles.push(profile)
+    }
+
+    /// Starts the measurement - `output` is the size of the output so far.
+    pub fn enter(&mut self, at: Duration, output: usize) {
+        self.start = at;
+        self.output = output;
+    }
+
+    /// Stops the measurement - `output` is the size of the output so far.
+    pub fn leave(&mut self, at: Duration, output: usize) {
+        self.end = at;
+        self.memory = output.saturating_sub(self.output);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    pub fn root() {
+        let mut root = Profile::root();
+        for &(start, end) in &[(2, 5), (6, 10)] {
+            let mut profile = Profile::new(Kind::Template, "index.html", "index.html");
+            profile.enter(Duration::from_millis(start), 100);
+            profile.leave(Duration::from_millis(end), 150);
+            root.add_profile(profile);
+        }
+
+        assert_eq!(root.start(), Duration::from_millis(2));
+        assert_eq!(root.duration(), Duration::from_millis(8));
+        assert_eq!(root.memory(), 100);
+        assert_eq!(root.profiles()[0].duration(), Duration::from_millis(3));
+    }
+}
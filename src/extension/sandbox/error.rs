@@ -0,0 +1,84 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Typisation of sandbox errors.
+
+use std::fmt::{self, Display};
+use std::error::Error;
+use engine::parser::token::stream::Position;
+
+#[derive(Debug, Clone)]
+pub enum SecurityError {
+    NotAllowedTag {
+        tag: String,
+        position: Position,
+    },
+    NotAllowedFilter {
+        filter: String,
+        position: Position,
+    },
+    NotAllowedFunction {
+        function: String,
+        position: Position,
+    },
+    NotAllowedMethod {
+        type_name: String,
+        method: String,
+        position: Position,
+    },
+    NotAllowedProperty {
+        type_name: String,
+        property: String,
+        position: Position,
+    },
+}
+
+impl Error for SecurityError {
+    fn description(&self) -> &str {
+        match *self {
+            SecurityError::NotAllowedTag{..} => "Tag not allowed by the sandbox.",
+            SecurityError::NotAllowedFilter{..} => "Filter not allowed by the sandbox.",
+            SecurityError::NotAllowedFunction{..} => "Function not allowed by the sandbox.",
+            SecurityError::NotAllowedMethod{..} => "Method not allowed by the sandbox.",
+            SecurityError::NotAllowedProperty{..} => "Property not allowed by the sandbox.",
+        }
+    }
+}
+
+impl Display for SecurityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f, "{}", self.description()));
+
+        match *self {
+            SecurityError::NotAllowedTag {
+                ref tag, ref position
+            } => write!(f, " Tag {:?} is not allowed at {}.", tag, position),
+            SecurityError::NotAllowedFilter {
+                ref filter, ref position
+            } => write!(f, " Filter {:?} is not allowed at {}.", filter, position),
+            SecurityError::NotAllowedFunction {
+                ref function, ref position
+            } => write!(f, " Function {:?} is not allowed at {}.", function, position),
+            SecurityError::NotAllowedMethod {
+                ref type_name, ref method, ref position
+            } => {
+                write!(f,
+                       " Calling {:?} method on a {:?} is not allowed at {}.",
+                       method,
+                       type_name,
+                       position)
+            }
+            SecurityError::NotAllowedProperty {
+                ref type_name, ref property, ref position
+            } => {
+                write!(f,
+                       " Calling {:?} property on a {:?} is not allowed at {}.",
+                       property,
+                       type_name,
+                       position)
+            }
+        }
+    }
+}
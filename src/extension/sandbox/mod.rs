@@ -4,22 +4,91 @@
 // file that was distributed with this source code.
 
 //! Sandbox Extension
+//!
+//! Restricts sandboxed templates to the tags, filters, functions, methods and
+//! properties allowed by a `SecurityPolicy` - e.g. for user-authored templates.
+//!
+//! Tags, filters and functions are checked when a template is compiled, method
+//! and property access is checked at runtime.
+//!
+//! A template is sandboxed
+//!
+//! * always in `Mode::Global`,
+//! * if its name is listed in `Mode::Templates`,
+//! * within a `{% sandbox %}` section - including the templates it includes:
+//!
+//! ```text
+//! {% sandbox %}{% include 'user.html' %}{% endsandbox %}
+//! ```
 
-use super::api::Extension;
+use std::collections::HashMap;
+use std::sync::Arc;
+use super::api::{self, Extension};
+use engine::Engine;
 
-#[allow(dead_code)]
-// dummy
-#[derive(Default, Debug, PartialEq)]
-pub struct Sandbox;
+pub mod error;
+pub mod policy;
+pub mod token_parser;
+pub use self::error::SecurityError;
+pub use self::policy::SecurityPolicy;
+
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct Sandbox {
+    policy: Arc<SecurityPolicy>,
+    mode: Mode,
+}
+
+/// Selects the templates, which are sandboxed.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub enum Mode {
+    /// Only `{% sandbox %}` sections.
+    #[default]
+    Local,
+    /// All templates.
+    Global,
+    /// Templates with these names - and `{% sandbox %}` sections.
+    Templates(Vec<String>),
+}
 
 impl Extension for Sandbox {
     fn name(&self) -> &'static str {
         "sandbox"
     }
+
+    fn init(&self, engine: &mut Engine) {
+        engine.set_sandbox(self.clone());
+    }
+
+    fn token_parsers(&self) -> HashMap<String, Box<api::TokenParser>> {
+        let mut p: HashMap<String, Box<api::TokenParser>> = HashMap::new();
+        p.insert("sandbox".to_string(), Box::new(token_parser::Sandbox));
+
+        return p;
+    }
 }
 
 impl Sandbox {
-    pub fn _new() -> Box<Sandbox> {
-        Box::new(Sandbox)
+    pub fn new(policy: SecurityPolicy, mode: Mode) -> Box<Sandbox> {
+        Box::new(Sandbox {
+            policy: Arc::new(policy),
+            mode: mode,
+        })
+    }
+
+    pub fn policy(&self) -> &Arc<SecurityPolicy> {
+        &self.policy
+    }
+
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    /// Returns true if the template `name` is sandboxed as a whole.
+    pub fn is_sandboxed(&self, name: &str) -> bool {
+        match self.mode {
+            Mode::Local => false,
+            Mode::Global => true,
+            Mode::Templates(ref names) => names.iter().any(|x| x == name),
+        }
     }
 }
@@ -0,0 +1,194 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Security policy of the sandbox.
+
+use std::collections::{HashMap, HashSet};
+use engine::parser::token::stream::Position;
+use extension::sandbox::SecurityError;
+use api::error::Traced;
+
+/// Allowlist of the tags, filters, functions, methods and properties a sandboxed
+/// template may use.
+///
+/// Methods and properties are allowed per value type, e.g. "date" - see
+/// `Value::type_name()`. Method names are case insensitive.
+///
+/// # Examples
+///
+/// ```
+/// use twig::extension::sandbox::SecurityPolicy;
+///
+/// let policy = SecurityPolicy::default()
+///                  .allow_tags(&["if", "for"])
+///                  .allow_filters(&["upper", "escape"])
+///                  .allow_functions(&["range"])
+///                  .allow_methods("date", &["getTimestamp"])
+///                  .allow_properties("date", &["timezone"]);
+///
+/// assert!(policy.is_method_allowed("date", "gettimestamp"));
+/// ```
+#[derive(Default, Debug, PartialEq)]
+pub struct SecurityPolicy {
+    tags: HashSet<String>,
+    filters: HashSet<String>,
+    functions: HashSet<String>,
+    methods: HashMap<String, HashSet<String>>,
+    properties: HashMap<String, HashSet<String>>,
+}
+
+impl SecurityPolicy {
+    pub fn allow_tags(mut self, tags: &[&str]) -> Self {
+        self.tags.extend(tags.iter().map(|x| x.to_string()));
+
+        self
+    }
+
+    pub fn allow_filters(mut self, filters: &[&str]) -> Self {
+        self.filters.extend(filters.iter().map(|x| x.to_string()));
+
+        self
+    }
+
+    pub fn allow_functions(mut self, functions: &[&str]) -> Self {
+        self.functions.extend(functions.iter().map(|x| x.to_string()));
+
+        self
+    }
+
+    pub fn allow_methods(mut self, type_name: &str, methods: &[&str]) -> Self {
+        self.methods
+            .entry(type_name.to_string())
+            .or_default()
+            .extend(methods.iter().map(|x| x.to_lowercase()));
+
+        self
+    }
+
+    pub fn allow_properties(mut self, type_name: &str, properties: &[&str]) -> Self {
+        self.properties
+            .entry(type_name.to_string())
+            .or_default()
+            .extend(properties.iter().map(|x| x.to_string()));
+
+        self
+    }
+
+    pub fn is_tag_allowed(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    pub fn is_filter_allowed(&self, filter: &str) -> bool {
+        self.filters.contains(filter)
+    }
+
+    pub fn is_function_allowed(&self, function: &str) -> bool {
+        self.functions.contains(function)
+    }
+
+    pub fn is_method_allowed(&self, type_name: &str, method: &str) -> bool {
+        self.methods.get(type_name).is_some_and(|x| x.contains(&method.to_lowercase()))
+    }
+
+    pub fn is_property_allowed(&self, type_name: &str, property: &str) -> bool {
+        self.properties.get(type_name).is_some_and(|x| x.contains(property))
+    }
+
+    pub fn check_tag(&self, tag: &str, position: &Position) -> Result<(), Traced<SecurityError>> {
+        if self.is_tag_allowed(tag) {
+            return Ok(());
+        }
+
+        traced_err!(SecurityError::NotAllowedTag {
+            tag: tag.to_string(),
+            position: position.clone(),
+        })
+    }
+
+    pub fn check_filter(&self,
+                        filter: &str,
+                        position: &Position)
+                        -> Result<(), Traced<SecurityError>> {
+        if self.is_filter_allowed(filter) {
+            return Ok(());
+        }
+
+        traced_err!(SecurityError::NotAllowedFilter {
+            filter: filter.to_string(),
+            position: position.clone(),
+        })
+    }
+
+    pub fn check_function(&self,
+                          function: &str,
+                          position: &Position)
+                          -> Result<(), Traced<SecurityError>> {
+        if self.is_function_allowed(function) {
+            return Ok(());
+        }
+
+        traced_err!(SecurityError::NotAllowedFunction {
+            function: function.to_string(),
+            position: position.clone(),
+        })
+    }
+
+    pub fn check_method(&self,
+                        type_name: &str,
+                        method: &str,
+                        position: &Position)
+                        -> Result<(), Traced<SecurityError>> {
+        if self.is_method_allowed(type_name, method) {
+            return Ok(());
+        }
+
+        traced_err!(SecurityError::NotAllowedMethod {
+            type_name: type_name.to_string(),
+            method: method.to_string(),
+            position: position.clone(),
+        })
+    }
+
+    pub fn check_property(&self,
+                          type_name: &str,
+                          property: &str,
+                          position: &Position)
+                          -> Result<(), Traced<SecurityError>> {
+        if self.is_property_allowed(type_name, property) {
+            return Ok(());
+        }
+
+        traced_err!(SecurityError::NotAllowedProperty {
+            type_name: type_name.to_string(),
+            property: property.to_string(),
+            position: position.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use engine::parser::token::stream::Position;
+
+    #[test]
+    pub fn allowlist() {
+        let policy = SecurityPolicy::default()
+                         .allow_tags(&["if"])
+                         .allow_filters(&["upper"])
+                         .allow_methods("date", &["getTimestamp"])
+                         .allow_properties("date", &["timezone"]);
+        let position = Position::default();
+
+        assert!(policy.check_tag("if", &position).is_ok());
+        assert!(policy.check_tag("for", &position).is_err());
+        assert!(policy.check_filter("upper", &position).is_ok());
+        assert!(policy.check_function("range", &position).is_err());
+        assert!(policy.check_method("date", "GETTIMESTAMP", &position).is_ok());
+        assert!(policy.check_method("map", "getTimestamp", &position).is_err());
+        assert!(policy.check_property("date", "timezone", &position).is_ok());
+        assert!(policy.check_property("date", "Timezone", &position).is_err());
+    }
+}
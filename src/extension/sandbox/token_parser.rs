@@ -0,0 +1,61 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Sandbox token parser.
+
+use extension::api::TokenParser;
+use extension::api::token_parser::TestResult;
+use engine::{node, Node};
+use engine::parser::{Job, ParserError};
+use engine::parser::token::stream::Item;
+use engine::parser::token::Token;
+use api::error::{Traced, Dump};
+
+/// Sandboxes a section of a template and the templates it includes.
+///
+/// ```text
+/// {% sandbox %}{% include 'user.html' %}{% endsandbox %}
+/// ```
+#[derive(Debug, Default)]
+pub struct Sandbox;
+
+impl TokenParser for Sandbox {
+    fn tag(&self) -> &'static str {
+        "sandbox"
+    }
+
+    fn parse(&self, job: &mut Job, item: &Item) -> Result<Box<Node>, Traced<ParserError>> {
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("sandbox-Block must be closed")));
+
+        let policy = match job.parser().sandbox() {
+            Some(sandbox) => sandbox.policy().clone(),
+            None => {
+                return traced_err!(ParserError::TokenParserError {
+                    tag: self.tag(),
+                    error: "The sandbox extension is not registered".to_string(),
+                    job: job.dump(),
+                })
+            }
+        };
+
+        job.push_sandbox(true);
+        let body = job.sub_parse_until(&is_sandbox_end);
+        job.pop_sandbox();
+
+        let body = try_traced!(body);
+        try_traced!(job.mut_cursor().next_expect(Token::BlockEnd,
+                                                 Some("endsandbox-Block must be closed")));
+
+        Ok(node::Sandbox::boxed(policy, body, item.position()))
+    }
+}
+
+pub fn is_sandbox_end(item: &Item) -> TestResult {
+    match item.token().value_as_str() {
+        Some("endsandbox") => TestResult::DropToken,
+        _ => TestResult::Continue,
+    }
+}
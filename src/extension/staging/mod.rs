@@ -4,22 +4,85 @@
 // file that was distributed with this source code.
 
 //! Staging Extension
+//!
+//! Collects the filters, functions, tests, token parsers and globals added
+//! via `Engine::add_*()`. It is initialized after all other extensions, as soon
+//! as the engine compiles its first template.
 
-use super::api::Extension;
+use std::sync::Mutex;
+use std::collections::HashMap;
+use std::mem;
+use super::api::{Extension, Filter, Function, Test, TokenParser, Global};
+use super::api::{SimpleFilter, SimpleFunction};
 
-#[allow(dead_code)]
-// dummy
-#[derive(Default, Debug, PartialEq)]
-pub struct Staging;
+/// orig: Twig_Extension_Staging
+#[derive(Default, Debug)]
+pub struct Staging {
+    filters: Mutex<HashMap<String, Box<Filter>>>,
+    functions: Mutex<HashMap<String, Box<Function>>>,
+    tests: Mutex<HashMap<String, Box<Test>>>,
+    token_parsers: Mutex<HashMap<String, Box<TokenParser>>>,
+    globals: Mutex<Vec<Box<Global>>>,
+}
 
 impl Extension for Staging {
     fn name(&self) -> &'static str {
         "staging"
     }
+
+    fn token_parsers(&self) -> HashMap<String, Box<TokenParser>> {
+        mem::take(&mut *self.token_parsers.lock().unwrap())
+    }
+
+    fn filters(&self) -> HashMap<String, Box<Filter>> {
+        mem::take(&mut *self.filters.lock().unwrap())
+    }
+
+    fn tests(&self) -> HashMap<String, Box<Test>> {
+        mem::take(&mut *self.tests.lock().unwrap())
+    }
+
+    fn functions(&self) -> HashMap<String, Box<Function>> {
+        mem::take(&mut *self.functions.lock().unwrap())
+    }
+
+    fn globals(&self) -> Vec<Box<Global>> {
+        mem::take(&mut *self.globals.lock().unwrap())
+    }
 }
 
 impl Staging {
     pub fn new() -> Box<Staging> {
-        Box::new(Staging)
+        Box::new(Staging::default())
+    }
+
+    pub fn add_filter(&mut self, filter: SimpleFilter) -> &mut Staging {
+        self.filters.get_mut().unwrap().insert(filter.name().to_string(), filter.boxed());
+
+        self
+    }
+
+    pub fn add_function(&mut self, function: SimpleFunction) -> &mut Staging {
+        self.functions.get_mut().unwrap().insert(function.name().to_string(), function.boxed());
+
+        self
+    }
+
+    pub fn add_test(&mut self, name: &str, test: Box<Test>) -> &mut Staging {
+        self.tests.get_mut().unwrap().insert(name.to_string(), test);
+
+        self
+    }
+
+    pub fn add_token_parser(&mut self, token_parser: Box<TokenParser>) -> &mut Staging {
+        self.token_parsers.get_mut().unwrap().insert(token_parser.tag().to_string(), token_parser);
+
+        self
+    }
+
+    pub fn add_global(&mut self, global: Box<Global>) -> &mut Staging {
+        self.globals.get_mut().unwrap().push(global);
+
+        self
     }
 }
@@ -5,10 +5,12 @@
 
 //! String Loader Extension
 
-use super::api::Extension;
+use std::collections::HashMap;
+use super::api::{Extension, Function, SimpleFunction};
+use runtime::{Runtime, Job, Execute, Value, RuntimeError};
+use api::error::Traced;
 
-#[allow(dead_code)]
-// dummy
+/// Adds the `template_from_string()` function.
 #[derive(Default, Debug, PartialEq)]
 pub struct StringLoader;
 
@@ -16,10 +18,57 @@ impl Extension for StringLoader {
     fn name(&self) -> &'static str {
         "string_loader"
     }
+
+    fn functions(&self) -> HashMap<String, Box<Function>> {
+        let function = SimpleFunction::with_job("template_from_string", template_from_string)
+                           .safe_for(&["all"]);
+
+        let mut functions = HashMap::new();
+        functions.insert(function.name().to_string(), function.boxed());
+        functions
+    }
 }
 
 impl StringLoader {
-    pub fn _new() -> Box<StringLoader> {
+    pub fn new() -> Box<StringLoader> {
         Box::new(StringLoader)
     }
 }
+
+/// Compiles a template from a string and renders it with the current context.
+///
+/// There is no `include()` yet, so the template is rendered right away, instead
+/// of being returned as a template object.
+///
+/// orig: twig_template_from_string($env, $template, $name = null)
+pub fn template_from_string(runtime: &Runtime,
+                            job: &mut Job,
+                            args: Vec<Value>)
+                            -> Result<Value, Traced<RuntimeError>> {
+    let source = args.first().map(|x| x.to_string()).unwrap_or_default();
+    let name = match args.get(1) {
+        Some(&Value::Null) | None => "__string_template__".to_string(),
+        Some(x) => x.to_string(),
+    };
+
+    let compiled = match job.templates() {
+        Some(templates) => {
+            templates.compile(&source, &name, job.policy().is_some())
+                     .map_err(|e| e.error().to_string())
+        }
+        None => Err("templates are only available when rendering with an engine".to_string()),
+    };
+    let compiled = match compiled {
+        Ok(compiled) => compiled,
+        Err(reason) => {
+            return traced_err!(RuntimeError::Template {
+                name: name,
+                reason: reason,
+            })
+        }
+    };
+
+    let output = try_traced!(job.capture(|job| compiled.execute(runtime, job)));
+
+    Ok(Value::Markup(output, "all".to_string()))
+}
@@ -27,6 +27,10 @@
 //! ```
 
 extern crate regex;
+#[cfg(feature = "date")]
+extern crate chrono;
+#[cfg(feature = "date")]
+extern crate chrono_tz;
 
 #[macro_use]pub mod api;
 pub mod engine;
@@ -34,6 +38,7 @@ pub mod runtime;
 pub mod loader;
 pub mod template;
 pub mod extension;
+pub mod build;
 
 pub use engine::Engine;
 pub use engine::Setup;
@@ -23,7 +23,7 @@ pub struct Array {
 }
 
 impl Loader for Array {
-    fn source<'a>(&'a mut self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+    fn source<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
 
         return match self.templates.get(name) {
             None => traced_err!(LoaderError::ArrayTemplateNotFound { name: name.to_string() }),
@@ -31,11 +31,11 @@ impl Loader for Array {
         };
     }
 
-    fn cache_key<'a>(&'a mut self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+    fn cache_key<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
         self.source(name)
     }
 
-    fn is_fresh(&mut self, name: &str, _time: i64) -> bool {
+    fn is_fresh(&self, name: &str, _time: i64) -> bool {
         self.exists(name)
     }
 }
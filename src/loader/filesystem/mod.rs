@@ -11,6 +11,7 @@ use std::fs::{self, File};
 use std::os::unix::fs::MetadataExt;
 use std::io::Read;
 use std::borrow::Cow;
+use std::sync::Mutex;
 use loader::{Loader, LoaderError};
 use self::namespace::Namespace;
 use api::error::Traced;
@@ -21,11 +22,11 @@ pub mod path;
 #[derive(Default, Debug)]
 pub struct Filesystem {
     namespaces: HashMap<String, Namespace>,
-    path_cache: HashMap<String, PathBuf>,
+    path_cache: Mutex<HashMap<String, PathBuf>>,
 }
 
 impl Loader for Filesystem {
-    fn source<'a>(&'a mut self, name: &str) -> Result<Cow<str>, Traced<LoaderError>> {
+    fn source<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
         let path = try_traced!(self.find_template(name));
 
         return match Self::read(&path) {
@@ -38,7 +39,7 @@ impl Loader for Filesystem {
 
                 return traced_err!(LoaderError::FileSystemTemplateNotReadable {
                     name: name.to_string(),
-                    path: path.to_path_buf(),
+                    path: path,
                     io_err: e,
                 });
             }
@@ -46,11 +47,11 @@ impl Loader for Filesystem {
         };
     }
 
-    fn cache_key<'a>(&'a mut self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
-        self.find_template(name).map(|x| Cow::Borrowed(x.to_str().unwrap())) // TODO: remove unwrap!
+    fn cache_key<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+        self.find_template(name).map(|x| Cow::Owned(x.to_string_lossy().into_owned()))
     }
 
-    fn is_fresh(&mut self, name: &str, time: i64) -> bool {
+    fn is_fresh(&self, name: &str, time: i64) -> bool {
         if let Ok(path) = self.find_template(name) {
             if let Ok(metadata) = fs::metadata(path) {
                 return metadata.mtime() <= time;
@@ -106,7 +107,7 @@ impl Filesystem {
     pub fn set_dirs<D>(&mut self, dirs: D, namespace_id: Option<&str>)
         where D: IntoIterator<Item = PathBuf>
     {
-        self.path_cache.clear();
+        self.path_cache.get_mut().unwrap().clear();
         let namespace_id = namespace_id.unwrap_or(namespace::DEFAULT);
         let namespace = self.mut_namespace_or_new(namespace_id);
 
@@ -124,7 +125,7 @@ impl Filesystem {
 
     /// Prepends a template directory for a given namespace.
     pub fn prepend_dir(&mut self, dir: PathBuf, namespace_id: Option<&str>) {
-        self.path_cache.clear();
+        self.path_cache.get_mut().unwrap().clear();
         let namespace_id = namespace_id.unwrap_or(namespace::DEFAULT);
         let namespace = self.mut_namespace_or_new(namespace_id);
 
@@ -132,25 +133,17 @@ impl Filesystem {
     }
 
     /// Find template.
-    fn find_template(&mut self, template_path: &str) -> Result<&Path, Traced<LoaderError>> {
-        if let Some(cached) = self.path_cache.get(template_path) {
+    fn find_template(&self, template_path: &str) -> Result<PathBuf, Traced<LoaderError>> {
+        if let Some(cached) = self.path_cache.lock().unwrap().get(template_path) {
             // TODO: clear cache if file vanished - else return
-
-            // #NOTE:50 working around rust limitation with conditionally escaping borrows
-            //          * https://github.com/rust-lang/rust/issues/16481
-            //          * https://github.com/rust-lang/rfcs/issues/811
-            //       Another workaround would be the Entry API HashMap::entry()
-            //       but that requires heap allocation of the key with every lookup.
-            let sneak_out: *const PathBuf = cached;
-            return Ok(unsafe { &*sneak_out }); // equivalent to `return Ok(cached)`
-            // <-- borrow ends here and is not extended
+            return Ok(cached.clone());
         }
 
         let path = try_traced!(path::TemplatePath::parse(template_path));
         let namespace_id = path.namespace_id();
         let raw_path = path.raw_path();
 
-        match self.namespaces.get_mut(namespace_id) {
+        match self.namespaces.get(namespace_id) {
             None => {
                 return traced_err!(LoaderError::FileSystemNamespaceNotInitialized {
                     namespace: namespace_id.to_string(),
@@ -160,7 +153,10 @@ impl Filesystem {
                 try_traced!(path.validate()); // #Doing:0 move these checks somewhere else :-)
                                        // e.g. postpone to reading the directoy
                 let full_path = try_traced!(namespace.find_template(raw_path));
-                self.path_cache.insert(template_path.to_string(), full_path.to_path_buf());
+                self.path_cache
+                    .lock()
+                    .unwrap()
+                    .insert(template_path.to_string(), full_path.clone());
 
                 return Ok(full_path);
             }
@@ -177,7 +173,7 @@ impl Filesystem {
     }
 
     /// Checks if the template path can be found or was previously cached.
-    pub fn exists(&mut self, template_path: &str) -> bool {
+    pub fn exists(&self, template_path: &str) -> bool {
         self.find_template(template_path).is_ok()
     }
 
@@ -188,12 +184,12 @@ impl Filesystem {
             namespace.clear_path_cache();
         }
 
-        self.path_cache.clear();
+        self.path_cache.get_mut().unwrap().clear();
     }
 
     /// Removes the path from the path cache.
     pub fn unset_cached_path(&mut self, template_path: &str) {
-        self.path_cache.remove(template_path);
+        self.path_cache.get_mut().unwrap().remove(template_path);
 
         if let Ok(path) = path::TemplatePath::parse(template_path) {
             if let Some(namespace) = self.mut_namespace(path.namespace_id()) {
@@ -8,6 +8,7 @@
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use std::fs;
+use std::sync::Mutex;
 use loader::LoaderError;
 use api::error::Traced;
 
@@ -18,7 +19,7 @@ pub const DEFAULT: &'static str = "__main__";
 pub struct Namespace {
     id: String,
     dirs: Vec<PathBuf>,
-    path_cache: HashMap<PathBuf, PathBuf>,
+    path_cache: Mutex<HashMap<PathBuf, PathBuf>>,
 }
 
 impl Namespace {
@@ -41,7 +42,7 @@ impl Namespace {
     pub fn set_dirs<D>(&mut self, dirs: D)
         where D: IntoIterator<Item = PathBuf>
     {
-        self.path_cache.clear();
+        self.path_cache.get_mut().unwrap().clear();
         self.dirs = dirs.into_iter().collect();
     }
 
@@ -59,22 +60,14 @@ impl Namespace {
     ///
     /// Panics if index is out of bounds.
     pub fn insert_dir(&mut self, index: usize, dir: PathBuf) {
-        self.path_cache.clear();
+        self.path_cache.get_mut().unwrap().clear();
         self.dirs.insert(index, dir);
     }
 
-    pub fn find_template(&mut self, raw_path: &Path) -> Result<&Path, Traced<LoaderError>> {
-        if let Some(cached) = self.path_cache.get(raw_path) {
+    pub fn find_template(&self, raw_path: &Path) -> Result<PathBuf, Traced<LoaderError>> {
+        if let Some(cached) = self.path_cache.lock().unwrap().get(raw_path) {
             // TODO: clear cache if file vanished - else return
-
-            // #NOTE:40 working around rust limitation with conditionally escaping borrows
-            //          * https://github.com/rust-lang/rust/issues/16481
-            //          * https://github.com/rust-lang/rfcs/issues/811
-            //       Another workaround would be the Entry API HashMap::entry()
-            //       but that requires heap allocation of the key with every lookup.
-            let sneak_out: *const PathBuf = cached;
-            return Ok(unsafe { &*sneak_out }); // equivalent to `return Ok(cached)`
-            // <-- borrow ends here and is not extended
+            return Ok(cached.clone());
         }
 
         if self.dirs.len() == 0 {
@@ -88,9 +81,12 @@ impl Namespace {
 
             if let Ok(metadata) = fs::metadata(&fullpath) {
                 if metadata.is_file() {
-                    return Ok(self.path_cache
-                                  .entry(PathBuf::from(raw_path))
-                                  .or_insert(fullpath));
+                    self.path_cache
+                        .lock()
+                        .unwrap()
+                        .insert(PathBuf::from(raw_path), fullpath.clone());
+
+                    return Ok(fullpath);
                 }
             }
         }
@@ -103,12 +99,12 @@ impl Namespace {
     }
 
     pub fn clear_path_cache(&mut self) {
-        self.path_cache.clear()
+        self.path_cache.get_mut().unwrap().clear()
     }
 
     /// Removes the path from the path cache.
     pub fn unset_cached_path(&mut self, raw_path: &Path) {
-        self.path_cache.remove(raw_path);
+        self.path_cache.get_mut().unwrap().remove(raw_path);
     }
 }
 
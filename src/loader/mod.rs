@@ -14,21 +14,23 @@ use api::error::Traced;
 use std::fmt::Debug;
 use std::borrow::Cow;
 
-pub trait Loader : Debug {
+/// Loads template sources - shared by all threads using the engine, i.e.
+/// caches need interior mutability.
+pub trait Loader : Debug + Send + Sync {
     /// Gets the source code of a template, given its name
     ///
     /// Returns a Cow<str> to allow for efficient caching mechanisms.
     ///
     /// # Failures
     /// * When `name` is not found
-    fn source<'a>(&'a mut self, name: &str) -> Result<Cow<str>, Traced<LoaderError>>;
+    fn source<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>>;
 
     /// Gets the cache key to use for the cache for a given template
     ///
     /// # Failures
     /// * When `name` is not found
-    fn cache_key<'a>(&'a mut self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>>;
+    fn cache_key<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>>;
 
     /// returns true if the template is still fresh
-    fn is_fresh(&mut self, name: &str, time: i64) -> bool;
+    fn is_fresh(&self, name: &str, time: i64) -> bool;
 }
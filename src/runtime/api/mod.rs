@@ -5,11 +5,26 @@
 
 //! Node of compiled templates (forming an Abstract-Syntax-Tree)
 
-use runtime::{Runtime, Job};
+use runtime::{Runtime, Job, Value, RuntimeError};
 use std::fmt::Debug;
+use api::error::Traced;
 
 pub trait Execute : Debug {
-    fn execute(&self, runtime: &Runtime, job: &mut Job);
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>>;
+
+    /// Evaluates the node as expression.
+    ///
+    /// Defaults to the output of the node as string.
+    fn evaluate(&self, runtime: &Runtime, job: &mut Job) -> Result<Value, Traced<RuntimeError>> {
+        let output = try_traced!(job.capture(|job| self.execute(runtime, job)));
+
+        Ok(Value::String(output))
+    }
+
+    /// Returns the value of constant expressions, e.g. `'html'` or `42`.
+    fn constant(&self) -> Option<&Value> {
+        None
+    }
 
     // NOTE: This function is *not* object safe, because
     //      runtime.run() requires a cast of self to object type `Execute`
@@ -21,7 +36,7 @@ pub trait Execute : Debug {
     //      object. Thus type erasure should *not* require Self:Sized
     //
     // TODO: report to upstream rust
-    fn run(&self, runtime: &Runtime) -> String
+    fn run(&self, runtime: &Runtime) -> Result<String, Traced<RuntimeError>>
         where Self: Sized
     {
         runtime.run(self)
@@ -7,12 +7,44 @@
 
 use std::fmt::{self, Display};
 use std::error::Error;
+use extension::sandbox::SecurityError;
 
 #[derive(Debug)]
 pub enum RuntimeError {
     Unreachable {
         reason: String,
     },
+    UnknownFilter {
+        name: String,
+    },
+    UnknownFunction {
+        name: String,
+    },
+    UnknownOperator {
+        name: String,
+    },
+    InvalidArgument {
+        reason: String,
+    },
+    Template {
+        name: String,
+        reason: String,
+    },
+    UnknownBlock {
+        name: String,
+        template: String,
+    },
+    UnknownMacro {
+        name: String,
+        template: String,
+    },
+    Security(SecurityError),
+}
+
+impl From<SecurityError> for RuntimeError {
+    fn from(err: SecurityError) -> RuntimeError {
+        RuntimeError::Security(err)
+    }
 }
 
 impl Error for RuntimeError {
@@ -21,6 +53,14 @@ impl Error for RuntimeError {
             RuntimeError::Unreachable{..} => {
                 "Unexptected runtime error (please report as bug with details)."
             }
+            RuntimeError::UnknownFilter{..} => "Unknown filter.",
+            RuntimeError::UnknownFunction{..} => "Unknown function.",
+            RuntimeError::UnknownOperator{..} => "Unknown operator.",
+            RuntimeError::InvalidArgument{..} => "Invalid argument.",
+            RuntimeError::Template{..} => "Template error.",
+            RuntimeError::UnknownBlock{..} => "Unknown block.",
+            RuntimeError::UnknownMacro{..} => "Unknown macro.",
+            RuntimeError::Security(..) => "Security error.",
         }
     }
 }
@@ -33,6 +73,28 @@ impl Display for RuntimeError {
             RuntimeError::Unreachable {
                 ref reason
             } => write!(f, " {}.", reason),
+            RuntimeError::UnknownFilter {
+                ref name
+            } => write!(f, " The filter {:?} is not registered.", name),
+            RuntimeError::UnknownFunction {
+                ref name
+            } => write!(f, " The function {:?} is not registered.", name),
+            RuntimeError::UnknownOperator {
+                ref name
+            } => write!(f, " The operator {:?} is not registered.", name),
+            RuntimeError::InvalidArgument {
+                ref reason
+            } => write!(f, " {}.", reason),
+            RuntimeError::Template {
+                ref name, ref reason
+            } => write!(f, " The template {:?} failed to load: {}", name, reason),
+            RuntimeError::UnknownBlock {
+                ref name, ref template
+            } => write!(f, " The block {:?} is not defined in the template {:?}.", name, template),
+            RuntimeError::UnknownMacro {
+                ref name, ref template
+            } => write!(f, " The macro {:?} is not defined in the template {:?}.", name, template),
+            RuntimeError::Security(ref e) => write!(f, " {}", e),
         }
     }
 }
@@ -0,0 +1,71 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Template frame of a runtime job.
+
+use std::sync::Arc;
+use engine::node::Module;
+
+/// The template being executed - as part of its inheritance chain.
+///
+/// The chain starts with the rendered template and ends with the root layout,
+/// e.g. `[index.html, layout.html]` for `{% extends 'layout.html' %}`. Blocks
+/// are looked up along the chain, the first definition wins.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    chain: Arc<Vec<Arc<Module>>>,
+    index: usize,
+    block: Option<String>,
+}
+
+impl Frame {
+    /// The frame of the template at `index` of the chain, outside of blocks.
+    pub fn new(chain: Arc<Vec<Arc<Module>>>, index: usize) -> Frame {
+        Frame {
+            chain: chain,
+            index: index,
+            block: None,
+        }
+    }
+
+    /// The frame of the block `name` of the template at `index` of the same chain.
+    pub fn block_at(&self, index: usize, name: &str) -> Frame {
+        Frame {
+            chain: self.chain.clone(),
+            index: index,
+            block: Some(name.to_string()),
+        }
+    }
+
+    pub fn chain(&self) -> &[Arc<Module>] {
+        &self.chain
+    }
+
+    /// Returns the position of the template in the chain.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the template being executed.
+    pub fn module(&self) -> &Arc<Module> {
+        &self.chain[self.index]
+    }
+
+    /// Returns the name of the block being executed, if any.
+    pub fn block(&self) -> Option<&str> {
+        self.block.as_ref().map(|x| &x[..])
+    }
+
+    /// Returns the position of the first template at or after `from`, which
+    /// defines the block `name`.
+    pub fn find_block(&self, name: &str, from: usize) -> Option<usize> {
+        self.chain
+            .iter()
+            .enumerate()
+            .skip(from)
+            .find(|&(_, module)| module.block(name).is_some())
+            .map(|(index, _)| index)
+    }
+}
@@ -5,19 +5,44 @@
 
 //! Runtime job.
 
-use runtime::Runtime;
+use std::mem;
+use std::sync::Arc;
+use runtime::{Runtime, RuntimeError};
 use runtime::api::Execute;
+use engine::{ExtensionRegistry, Templates, TwigError};
+use engine::parser::ParserError;
+use extension::sandbox::SecurityPolicy;
+use template::Compiled;
+use api::error::Traced;
+
+pub mod frame;
+pub use self::frame::Frame;
 
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct Job {
     writer: String, // TODO: switch to a 'more generic' string writer
+    ext: Arc<ExtensionRegistry>,
+    templates: Option<Arc<Templates>>,
+    policy: Option<Arc<SecurityPolicy>>,
+    frames: Vec<Frame>,
 }
 
 #[allow(dead_code)]
 impl Job {
     pub fn new() -> Job {
-        Job { writer: String::new() }
+        Job::with_extensions(Arc::new(ExtensionRegistry::default()))
+    }
+
+    /// Creates a job with access to the filters, functions, etc. of the engine.
+    pub fn with_extensions(ext: Arc<ExtensionRegistry>) -> Job {
+        Job {
+            writer: String::new(),
+            ext: ext,
+            templates: None,
+            policy: None,
+            frames: Vec::new(),
+        }
     }
 
     pub fn reserve_writer(&mut self, additional: usize) -> &mut Job {
@@ -26,10 +51,25 @@ impl Job {
         self
     }
 
-    pub fn run(mut self, runtime: &Runtime, template: &Execute) -> String {
-        template.execute(runtime, &mut self);
+    pub fn run(mut self,
+               runtime: &Runtime,
+               template: &Execute)
+               -> Result<String, Traced<RuntimeError>> {
+        try_traced!(template.execute(runtime, &mut self));
+
+        Ok(self.writer)
+    }
+
+    /// Calls `f` and returns everything it writes, instead of writing it.
+    pub fn capture<F>(&mut self, f: F) -> Result<String, Traced<RuntimeError>>
+        where F: FnOnce(&mut Job) -> Result<(), Traced<RuntimeError>>
+    {
+        let writer = mem::take(&mut self.writer);
+        let result = f(self);
+        let output = mem::replace(&mut self.writer, writer);
 
-        self.writer
+        try_traced!(result);
+        Ok(output)
     }
 
     pub fn write<T>(&mut self, text: T)
@@ -41,4 +81,86 @@ impl Job {
     pub fn result(&self) -> &str {
         &self.writer
     }
+
+    /// Returns the engine extensions.
+    pub fn extensions(&self) -> &Arc<ExtensionRegistry> {
+        &self.ext
+    }
+
+    /// Gives access to other templates, e.g. for `source()`.
+    pub fn set_templates(&mut self, templates: Option<Arc<Templates>>) -> &mut Job {
+        self.templates = templates;
+
+        self
+    }
+
+    /// Returns access to other templates - `None` outside of an engine.
+    pub fn templates(&self) -> Option<&Arc<Templates>> {
+        self.templates.as_ref()
+    }
+
+    /// Sandboxes the job, e.g. within `{% sandbox %}` or sandboxed templates.
+    pub fn set_policy(&mut self, policy: Option<Arc<SecurityPolicy>>) -> &mut Job {
+        self.policy = policy;
+
+        self
+    }
+
+    /// Returns the security policy, if the job is sandboxed.
+    pub fn policy(&self) -> Option<&Arc<SecurityPolicy>> {
+        self.policy.as_ref()
+    }
+
+    /// Loads another template, e.g. for `{% include %}` - sandboxed, if the job is sandboxed.
+    ///
+    /// Returns `None` if the template does not exist and `ignore_missing` is set.
+    pub fn load_template(&self,
+                         name: &str,
+                         ignore_missing: bool)
+                         -> Result<Option<Arc<Compiled>>, Traced<RuntimeError>> {
+        let templates = match self.templates {
+            Some(ref templates) => templates,
+            None => {
+                return traced_err!(RuntimeError::Template {
+                    name: name.to_string(),
+                    reason: "templates are only available when rendering with an engine"
+                                .to_string(),
+                })
+            }
+        };
+
+        // templates loaded by sandboxed templates are sandboxed, too
+        match Templates::load(templates, name, self.policy.is_some()) {
+            Ok(compiled) => Ok(Some(compiled)),
+            Err(e) => {
+                match *e.error() {
+                    TwigError::Loader(..) if ignore_missing => Ok(None),
+                    TwigError::Parser(ParserError::Security(ref error)) => {
+                        traced_err!(RuntimeError::Security(error.clone()))
+                    }
+                    ref error => {
+                        traced_err!(RuntimeError::Template {
+                            name: name.to_string(),
+                            reason: error.to_string(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// Enters a template or block, see `Frame`.
+    pub fn push_frame(&mut self, frame: Frame) {
+        self.frames.push(frame)
+    }
+
+    /// Leaves the template or block.
+    pub fn pop_frame(&mut self) -> Option<Frame> {
+        self.frames.pop()
+    }
+
+    /// Returns the template or block being executed - `None` outside of templates.
+    pub fn frame(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
 }
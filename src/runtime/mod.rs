@@ -8,56 +8,138 @@
 /// - defines all necessary interfaces
 /// - manages i/o for the compiled templates
 
+use std::sync::Arc;
 use std::collections::HashMap;
+use std::iter::FromIterator;
+use api::error::Traced;
+use self::value::{Map, Key};
 
 pub mod api;
 pub mod job;
 pub mod error;
+pub mod value;
 pub use self::api::Execute;
 pub use self::job::Job;
 pub use self::error::RuntimeError;
+pub use self::value::Value;
 
 #[allow(dead_code)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Runtime {
-    data: HashMap<String, String>,
+    data: Arc<HashMap<String, Value>>,
+    parent: Option<Arc<Runtime>>,
 }
 
 #[allow(dead_code)]
 impl Runtime {
-    pub fn new(data: HashMap<String, String>) -> Runtime {
-        Runtime { data: data }
+    pub fn new(data: HashMap<String, Value>) -> Runtime {
+        Runtime {
+            data: Arc::new(data),
+            parent: None,
+        }
     }
 
-    pub fn run(&self, template: &Execute) -> String {
+    /// Layers `data` on top of the `parent` scope, which is shared and not copied.
+    pub fn layered(data: HashMap<String, Value>, parent: Arc<Runtime>) -> Runtime {
+        Runtime {
+            data: Arc::new(data),
+            parent: Some(parent),
+        }
+    }
+
+    pub fn run(&self, template: &Execute) -> Result<String, Traced<RuntimeError>> {
         // TODO debug-switch
         Job::new().run(self, template)
     }
 
-    pub fn data(&self) -> &HashMap<String, String> {
+    /// The variables of the innermost scope.
+    pub fn data(&self) -> &HashMap<String, Value> {
         &self.data
     }
 
-    pub fn data_mut(&mut self) -> &mut HashMap<String, String> {
-        &mut self.data
+    pub fn data_mut(&mut self) -> &mut HashMap<String, Value> {
+        Arc::make_mut(&mut self.data)
+    }
+
+    /// All visible variables, the inner scopes shadowing the outer ones.
+    pub fn variables(&self) -> HashMap<String, Value> {
+        let mut variables = match self.parent {
+            Some(ref parent) => parent.variables(),
+            None => HashMap::new(),
+        };
+        for (key, value) in self.data.iter() {
+            variables.insert(key.clone(), value.clone());
+        }
+
+        variables
     }
 
     pub fn clear_data(&mut self) {
-        self.data.clear()
+        self.data = Arc::default();
+        self.parent = None;
     }
 
-    pub fn get(&self, key: &str) -> Option<&str> {
-        self.data.get(key).map(|x| x.as_ref())
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self.data.get(key) {
+            Some(value) => Some(value),
+            None => self.parent.as_ref().and_then(|parent| parent.get(key)),
+        }
     }
 
     pub fn has(&self, key: &str) -> bool {
-        self.data.contains_key(key)
+        self.get(key).is_some()
     }
 
-    pub fn set<K, V>(&mut self, key: K, value: V) -> Option<String>
+    pub fn set<K, V>(&mut self, key: K, value: V) -> Option<Value>
         where K: Into<String>,
-              V: Into<String>
+              V: Into<Value>
     {
-        self.data.insert(key.into(), value.into())
+        self.data_mut().insert(key.into(), value.into())
+    }
+}
+
+impl From<HashMap<String, Value>> for Runtime {
+    fn from(data: HashMap<String, Value>) -> Runtime {
+        Runtime::new(data)
+    }
+}
+
+/// The keys of the map become the variable names.
+impl From<Map> for Runtime {
+    fn from(map: Map) -> Runtime {
+        map.into_iter()
+           .map(|(key, value)| {
+               let key = match key {
+                   Key::Integer(i) => i.to_string(),
+                   Key::String(s) => s,
+               };
+               (key, value)
+           })
+           .collect()
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for Runtime
+    where K: Into<String>,
+          V: Into<Value>
+{
+    fn from(data: Vec<(K, V)>) -> Runtime {
+        data.into_iter().collect()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Runtime
+    where K: Into<String>,
+          V: Into<Value>
+{
+    fn from_iter<I>(iter: I) -> Runtime
+        where I: IntoIterator<Item = (K, V)>
+    {
+        let mut runtime = Runtime::default();
+        for (key, value) in iter {
+            runtime.set(key, value);
+        }
+
+        runtime
     }
 }
@@ -0,0 +1,62 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Arrow functions as runtime values.
+
+use std::fmt;
+use std::sync::Arc;
+use runtime::{Runtime, Job, Value, RuntimeError};
+use engine::Node;
+use api::error::Traced;
+
+/// Arrow function, e.g. `(v, k) => v.price * 2`.
+///
+/// The surrounding scope is captured when the arrow function is evaluated
+/// and shared by all calls, each of which layers its arguments on top.
+#[derive(Clone)]
+pub struct Closure {
+    params: Vec<String>,
+    body: Arc<Node>,
+    scope: Arc<Runtime>,
+}
+
+impl Closure {
+    pub fn new(params: Vec<String>, body: Arc<Node>, scope: Arc<Runtime>) -> Closure {
+        Closure {
+            params: params,
+            body: body,
+            scope: scope,
+        }
+    }
+
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Evaluates the body with `args` bound to the parameters.
+    ///
+    /// Missing arguments are null, surplus arguments are ignored.
+    pub fn call(&self, job: &mut Job, args: Vec<Value>) -> Result<Value, Traced<RuntimeError>> {
+        let mut args = args.into_iter();
+        let params = self.params
+                         .iter()
+                         .map(|param| (param.clone(), args.next().unwrap_or_default()))
+                         .collect();
+
+        self.body.evaluate(&Runtime::layered(params, self.scope.clone()), job)
+    }
+}
+
+impl PartialEq for Closure {
+    fn eq(&self, other: &Closure) -> bool {
+        Arc::ptr_eq(&self.body, &other.body) && self.scope == other.scope
+    }
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Closure({}) => {:?}", self.params.join(", "), self.body)
+    }
+}
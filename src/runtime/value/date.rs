@@ -0,0 +1,126 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Dates as runtime values.
+
+use std::fmt;
+use runtime::value::Map;
+
+/// Point in time together with the timezone it is displayed in.
+///
+/// Dates are created by the `date()` function and the `date_modify` filter.
+///
+/// orig: \DateTime
+#[derive(Clone, Debug, PartialEq)]
+pub struct Date {
+    timestamp: i64,
+    micros: u32,
+    offset: i32,
+    timezone: String,
+}
+
+impl Date {
+    /// Creates a date from the seconds since the unix epoch.
+    ///
+    /// `offset` are the seconds east of UTC at that time in `timezone`,
+    /// e.g. `"Europe/Paris"` or `"+02:00"`.
+    pub fn new(timestamp: i64, micros: u32, offset: i32, timezone: &str) -> Date {
+        Date {
+            timestamp: timestamp,
+            micros: micros,
+            offset: offset,
+            timezone: timezone.to_string(),
+        }
+    }
+
+    /// Returns the seconds since the unix epoch.
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    pub fn micros(&self) -> u32 {
+        self.micros
+    }
+
+    /// Returns the seconds east of UTC.
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    pub fn timezone(&self) -> &str {
+        &self.timezone
+    }
+
+    /// Returns the properties PHP exposes for `\DateTime` objects.
+    ///
+    /// e.g. `{date: "2004-02-12 16:19:21.000000", timezone_type: 3, timezone: "Europe/Paris"}`
+    pub fn properties(&self) -> Map {
+        // e.g. "2004-02-12T16:19:21+01:00"
+        let iso = self.to_string();
+        let timezone_type = match self.timezone.chars().next() {
+            Some('+') | Some('-') => 1,
+            _ => 3,
+        };
+
+        let mut properties = Map::new();
+        properties.insert("date",
+                          format!("{} {}.{:06}", &iso[..10], &iso[11..19], self.micros));
+        properties.insert("timezone_type", timezone_type);
+        properties.insert("timezone", self.timezone.as_str());
+
+        properties
+    }
+}
+
+impl fmt::Display for Date {
+    /// Formats the date as ISO 8601, e.g. "2004-02-12T15:19:21+00:00".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let local = self.timestamp + self.offset as i64;
+        let (days, seconds) = (local.div_euclid(86400), local.rem_euclid(86400));
+        let (year, month, day) = civil_from_days(days);
+        let offset = self.offset.abs();
+
+        write!(f,
+               "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+               year,
+               month,
+               day,
+               seconds / 3600,
+               seconds / 60 % 60,
+               seconds % 60,
+               if self.offset < 0 { '-' } else { '+' },
+               offset / 3600,
+               offset / 60 % 60)
+    }
+}
+
+/// Returns the (year, month, day) of the days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn display() {
+        assert_eq!(Date::new(0, 0, 0, "UTC").to_string(), "1970-01-01T00:00:00+00:00");
+        assert_eq!(Date::new(1076599161, 0, 3600, "Europe/Paris").to_string(),
+                   "2004-02-12T16:19:21+01:00");
+        assert_eq!(Date::new(-1, 0, -34200, "-09:30").to_string(),
+                   "1969-12-31T14:29:59-09:30");
+    }
+}
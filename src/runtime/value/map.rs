@@ -0,0 +1,272 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Ordered map - modeled after PHP arrays.
+
+use std::fmt;
+use std::cmp;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use std::slice;
+use std::vec;
+use runtime::Value;
+
+/// Map key - integer or string.
+///
+/// Strings in canonical integer notation are converted to integers, e.g.
+/// "12" becomes `Key::Integer(12)`, while "012" stays a string (like PHP).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Integer(i64),
+    String(String),
+}
+
+impl<'a> From<&'a str> for Key {
+    fn from(x: &'a str) -> Key {
+        match x.parse::<i64>() {
+            Ok(i) if i.to_string() == x => Key::Integer(i),
+            _ => Key::String(x.to_string()),
+        }
+    }
+}
+
+impl From<String> for Key {
+    fn from(x: String) -> Key {
+        match x.parse::<i64>() {
+            Ok(i) if i.to_string() == x => Key::Integer(i),
+            _ => Key::String(x),
+        }
+    }
+}
+
+impl From<i64> for Key {
+    fn from(x: i64) -> Key {
+        Key::Integer(x)
+    }
+}
+
+impl From<i32> for Key {
+    fn from(x: i32) -> Key {
+        Key::Integer(x as i64)
+    }
+}
+
+impl<'a> From<&'a Value> for Key {
+    /// orig: $array[$value]
+    fn from(x: &'a Value) -> Key {
+        match *x {
+            Value::Integer(i) => Key::Integer(i),
+            Value::Null => Key::String(String::new()),
+            Value::Boolean(_) | Value::Float(_) => Key::Integer(x.to_int()),
+            _ => Key::from(x.to_string()),
+        }
+    }
+}
+
+impl From<Key> for Value {
+    fn from(x: Key) -> Value {
+        match x {
+            Key::Integer(i) => Value::Integer(i),
+            Key::String(s) => Value::String(s),
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Key::Integer(i) => write!(f, "{}", i),
+            Key::String(ref s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Entries in insertion order - with an index of the keys for lookups.
+#[derive(Clone, Debug, Default)]
+pub struct Map {
+    entries: Vec<(Key, Value)>,
+    index: HashMap<Key, usize>,
+    /// The key of the next `push()` - the largest integer key plus one.
+    next: Option<i64>,
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Map) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get<K>(&self, key: K) -> Option<&Value>
+        where K: Into<Key>
+    {
+        self.index.get(&key.into()).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn contains_key<K>(&self, key: K) -> bool
+        where K: Into<Key>
+    {
+        self.get(key).is_some()
+    }
+
+    /// Inserts a value - existing keys keep their position.
+    pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<Value>
+        where K: Into<Key>,
+              V: Into<Value>
+    {
+        let key = key.into();
+        let value = value.into();
+
+        if let Some(&i) = self.index.get(&key) {
+            return Some(::std::mem::replace(&mut self.entries[i].1, value));
+        }
+
+        if let Key::Integer(i) = key {
+            let next = i.saturating_add(1);
+            self.next = Some(cmp::max(self.next.unwrap_or(next), next));
+        }
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+        None
+    }
+
+    /// Appends a value with the next free integer key.
+    ///
+    /// orig: $array[] = $value
+    pub fn push<V>(&mut self, value: V)
+        where V: Into<Value>
+    {
+        let key = self.next.unwrap_or(0);
+
+        self.insert(key, value);
+    }
+
+    /// Creates a map with the keys 0, 1, 2, ..
+    pub fn from_sequence(values: Vec<Value>) -> Map {
+        let mut map = Map::new();
+        for value in values {
+            map.push(value);
+        }
+
+        map
+    }
+
+    /// Returns true if the keys are 0, 1, 2, ..
+    ///
+    /// orig: array_is_list($array)
+    pub fn is_list(&self) -> bool {
+        self.entries.iter().enumerate().all(|(i, entry)| entry.0 == Key::Integer(i as i64))
+    }
+
+    /// Converts lists to sequences - all other maps are kept.
+    pub fn into_value(self) -> Value {
+        if self.is_list() {
+            Value::Sequence(self.entries.into_iter().map(|entry| entry.1).collect())
+        } else {
+            Value::Map(self)
+        }
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, (Key, Value)> {
+        self.entries.iter()
+    }
+
+    pub fn keys<'a>(&'a self) -> Box<Iterator<Item = &'a Key> + 'a> {
+        Box::new(self.entries.iter().map(|entry| &entry.0))
+    }
+
+    pub fn values<'a>(&'a self) -> Box<Iterator<Item = &'a Value> + 'a> {
+        Box::new(self.entries.iter().map(|entry| &entry.1))
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (Key, Value);
+    type IntoIter = vec::IntoIter<(Key, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Map
+    where K: Into<Key>,
+          V: Into<Value>
+{
+    fn from_iter<I>(iter: I) -> Map
+        where I: IntoIterator<Item = (K, V)>
+    {
+        let mut map = Map::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use runtime::Value;
+
+    #[test]
+    pub fn key() {
+        assert_eq!(Key::from("12"), Key::Integer(12));
+        assert_eq!(Key::from("-3"), Key::Integer(-3));
+        assert_eq!(Key::from("012"), Key::String("012".to_string()));
+        assert_eq!(Key::from("1.5"), Key::String("1.5".to_string()));
+    }
+
+    #[test]
+    pub fn insert() {
+        let mut map: Map = vec![("a", 1), ("b", 2)].into_iter().collect();
+        assert_eq!(map.insert("a", 3), Some(Value::Integer(1)));
+        map.push("x");
+        map.insert(7, "y");
+        map.push("z");
+
+        assert_eq!(map.iter().map(|e| e.0.to_string()).collect::<Vec<_>>(),
+                   vec!["a", "b", "0", "7", "8"]);
+        assert_eq!(map.get("a"), Some(&Value::Integer(3)));
+        assert_eq!(map.get("7"), Some(&Value::from("y")));
+    }
+
+    #[test]
+    pub fn many_keys() {
+        let mut map: Map = (0..10000).map(|i| (i.to_string(), i)).collect();
+        for i in (0..10000).rev() {
+            map.insert(i, i * 2);
+        }
+
+        assert_eq!(map.len(), 10000);
+        assert_eq!(map.get(9999), Some(&Value::Integer(19998)));
+        assert_eq!(map.keys().next(), Some(&Key::Integer(0)));
+        assert_eq!(map, (0..10000).map(|i| (i, i * 2)).collect());
+    }
+
+    #[test]
+    pub fn into_value() {
+        let list = Map::from_sequence(vec![Value::from(1), Value::from(2)]);
+        assert!(list.is_list());
+        assert_eq!(list.into_value(), Value::from(vec![1, 2]));
+
+        let map: Map = vec![(1, "a"), (0, "b")].into_iter().collect();
+        assert!(!map.is_list());
+        assert_eq!(map.clone().into_value(), Value::Map(map));
+    }
+}
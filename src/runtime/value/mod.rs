@@ -0,0 +1,426 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Runtime values.
+//!
+//! Values are converted to strings with PHP semantics (`true` -> "1",
+//! `null` -> "", floats with 14 significant digits, ..).
+
+use std::fmt;
+use std::cmp::Ordering;
+
+pub mod map;
+pub mod closure;
+pub mod date;
+pub use self::map::{Map, Key};
+pub use self::closure::Closure;
+pub use self::date::Date;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    /// A string which is safe to output with the escaping strategy, e.g. "html"
+    /// or "all" - i.e. it will not be escaped (again) with that strategy.
+    Markup(String, String),
+    Sequence(Vec<Value>),
+    Map(Map),
+    /// Arrow function, e.g. `v => v * 2`.
+    Closure(Closure),
+    Date(Date),
+}
+
+impl Value {
+    /// Returns the string slice of strings and markup.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref x) | Value::Markup(ref x, _) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn is_markup(&self) -> bool {
+        matches!(*self, Value::Markup(..))
+    }
+
+    /// Evaluates the value in a boolean context.
+    ///
+    /// orig: (bool) $value
+    pub fn is_true(&self) -> bool {
+        match *self {
+            Value::Null => false,
+            Value::Boolean(x) => x,
+            Value::Integer(x) => x != 0,
+            Value::Float(x) => x != 0.0,
+            Value::String(ref x) | Value::Markup(ref x, _) => !(x.is_empty() || x == "0"),
+            Value::Sequence(ref x) => !x.is_empty(),
+            Value::Map(ref x) => !x.is_empty(),
+            Value::Closure(_) | Value::Date(_) => true,
+        }
+    }
+
+    /// Converts the value to an integer.
+    ///
+    /// orig: (int) $value
+    pub fn to_int(&self) -> i64 {
+        match *self {
+            Value::Null => 0,
+            Value::Boolean(x) => x as i64,
+            Value::Integer(x) => x,
+            Value::Float(x) => if x.is_finite() { x as i64 } else { 0 },
+            Value::String(ref x) | Value::Markup(ref x, _) => {
+                let number = numeric_prefix(x);
+                match number.parse::<i64>() {
+                    Ok(i) => i,
+                    Err(_) => number.parse::<f64>().map(|f| f as i64).unwrap_or(0),
+                }
+            }
+            Value::Sequence(ref x) => !x.is_empty() as i64,
+            Value::Map(ref x) => !x.is_empty() as i64,
+            Value::Closure(_) | Value::Date(_) => 1,
+        }
+    }
+
+    /// Converts the value to a float.
+    ///
+    /// orig: (float) $value
+    pub fn to_float(&self) -> f64 {
+        match *self {
+            Value::Integer(x) => x as f64,
+            Value::Float(x) => x,
+            Value::String(ref x) | Value::Markup(ref x, _) => {
+                numeric_prefix(x).parse::<f64>().unwrap_or(0.0)
+            }
+            _ => self.to_int() as f64,
+        }
+    }
+
+    /// Returns the number of numbers and numeric strings, like " 1.5".
+    pub fn as_number(&self) -> Option<f64> {
+        match *self {
+            Value::Integer(x) => Some(x as f64),
+            Value::Float(x) => Some(x),
+            Value::String(ref x) | Value::Markup(ref x, _) => {
+                let trimmed = x.trim_matches(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r');
+                let number = numeric_prefix(trimmed);
+                if !number.is_empty() && number.len() == trimmed.len() {
+                    number.parse().ok()
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the value to an integer or a float.
+    ///
+    /// orig: +$value
+    pub fn to_number(&self) -> Value {
+        match *self {
+            Value::Integer(_) | Value::Float(_) => self.clone(),
+            Value::String(ref x) | Value::Markup(ref x, _) => {
+                let number = numeric_prefix(x);
+                match number.parse::<i64>() {
+                    Ok(i) => Value::Integer(i),
+                    Err(_) if number.is_empty() => Value::Integer(0),
+                    Err(_) => Value::Float(number.parse().unwrap_or(0.0)),
+                }
+            }
+            _ => Value::Integer(self.to_int()),
+        }
+    }
+
+    /// Compares two values like PHP 8.
+    ///
+    /// orig: $a <=> $b
+    pub fn compare(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (&Value::Null, &Value::String(_)) |
+            (&Value::Null, &Value::Markup(..)) |
+            (&Value::String(_), &Value::Null) |
+            (&Value::Markup(..), &Value::Null) => self.to_string().cmp(&other.to_string()),
+            (&Value::Null, _) | (_, &Value::Null) | (&Value::Boolean(_), _) | (_, &Value::Boolean(_)) => {
+                self.is_true().cmp(&other.is_true())
+            }
+            (&Value::Integer(a), &Value::Integer(b)) => a.cmp(&b),
+            (&Value::Date(ref a), &Value::Date(ref b)) => {
+                (a.timestamp(), a.micros()).cmp(&(b.timestamp(), b.micros()))
+            }
+            (&Value::Sequence(_), &Value::Sequence(_)) |
+            (&Value::Sequence(_), &Value::Map(_)) |
+            (&Value::Map(_), &Value::Sequence(_)) |
+            (&Value::Map(_), &Value::Map(_)) => compare_arrays(self, other),
+            (&Value::Sequence(_), _) | (&Value::Map(_), _) => Ordering::Greater,
+            (_, &Value::Sequence(_)) | (_, &Value::Map(_)) => Ordering::Less,
+            _ => {
+                match (self.as_number(), other.as_number()) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                    _ => self.to_string().cmp(&other.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Returns the type name used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Markup(..) => "markup",
+            Value::Sequence(_) => "sequence",
+            Value::Map(_) => "map",
+            Value::Closure(_) => "closure",
+            Value::Date(_) => "date",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Null | Value::Boolean(false) => Ok(()),
+            Value::Boolean(true) => write!(f, "1"),
+            Value::Integer(x) => write!(f, "{}", x),
+            Value::Float(x) => write!(f, "{}", float_to_string(x)),
+            Value::String(ref x) | Value::Markup(ref x, _) => write!(f, "{}", x),
+            Value::Sequence(_) | Value::Map(_) => write!(f, "Array"),
+            Value::Closure(_) => write!(f, "Closure"),
+            Value::Date(ref x) => write!(f, "{}", x),
+        }
+    }
+}
+
+/// Compares arrays by size first and then element by element.
+fn compare_arrays(a: &Value, b: &Value) -> Ordering {
+    let values = |x: &Value| -> Vec<Value> {
+        match *x {
+            Value::Sequence(ref x) => x.clone(),
+            Value::Map(ref x) => x.values().cloned().collect(),
+            _ => Vec::new(),
+        }
+    };
+    let (a, b) = (values(a), values(b));
+
+    a.len().cmp(&b.len()).then_with(|| {
+        a.iter()
+         .zip(b.iter())
+         .map(|(x, y)| x.compare(y))
+         .find(|x| *x != Ordering::Equal)
+         .unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Returns the leading numeric part of a string, e.g. "12.5" of " 12.5kg".
+fn numeric_prefix(x: &str) -> &str {
+    let x = x.trim_start_matches([' ', '\t', '\n', '\r']);
+    let bytes = x.as_bytes();
+    let digits = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+
+    let mut end = 0;
+    if end < bytes.len() && (bytes[end] == b'+' || bytes[end] == b'-') {
+        end += 1;
+    }
+    let start = end;
+    end = digits(end);
+    if end < bytes.len() && bytes[end] == b'.' {
+        end = digits(end + 1);
+    }
+    if end == start || &x[start..end] == "." {
+        return "";
+    }
+    if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+        let mut exp = end + 1;
+        if exp < bytes.len() && (bytes[exp] == b'+' || bytes[exp] == b'-') {
+            exp += 1;
+        }
+        if digits(exp) > exp {
+            end = digits(exp);
+        }
+    }
+
+    &x[..end]
+}
+
+/// Converts a float to string like PHP with `precision = 14`.
+///
+/// orig: zend_gcvt(value, 14, '.', 'E', buf)
+pub fn float_to_string(x: f64) -> String {
+    if x.is_nan() {
+        return "NAN".to_string();
+    }
+    if x.is_infinite() {
+        return if x > 0.0 { "INF" } else { "-INF" }.to_string();
+    }
+    if x == 0.0 {
+        return if x.is_sign_negative() { "-0" } else { "0" }.to_string();
+    }
+
+    let repr = format!("{:.13e}", x); // e.g. "-1.2340000000000e-5"
+    let (mantissa, exponent) = repr.split_at(repr.find('e').unwrap());
+    let exponent: i32 = exponent[1..].parse().unwrap();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace(".", "");
+    let digits = digits.trim_end_matches('0');
+
+    if !(-4..14).contains(&exponent) {
+        let (first, rest) = digits.split_at(1);
+        format!("{sign}{first}.{rest}E{exp_sign}{exp}",
+                sign = sign,
+                first = first,
+                rest = if rest.is_empty() { "0" } else { rest },
+                exp_sign = if exponent < 0 { '-' } else { '+' },
+                exp = exponent.abs())
+    } else if exponent < 0 {
+        format!("{}0.{}{}", sign, "0".repeat((-exponent - 1) as usize), digits)
+    } else {
+        let point = exponent as usize + 1;
+        if digits.len() > point {
+            format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+        } else {
+            format!("{}{}{}", sign, digits, "0".repeat(point - digits.len()))
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(x: &'a str) -> Value {
+        Value::String(x.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Value {
+        Value::String(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Value {
+        Value::Boolean(x)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(x: i32) -> Value {
+        Value::Integer(x as i64)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(x: i64) -> Value {
+        Value::Integer(x)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Value {
+        Value::Float(x)
+    }
+}
+
+impl<T> From<Vec<T>> for Value where T: Into<Value>
+{
+    fn from(x: Vec<T>) -> Value {
+        Value::Sequence(x.into_iter().map(|v| v.into()).collect())
+    }
+}
+
+impl From<Map> for Value {
+    fn from(x: Map) -> Value {
+        Value::Map(x)
+    }
+}
+
+impl<T> From<Option<T>> for Value where T: Into<Value>
+{
+    fn from(x: Option<T>) -> Value {
+        match x {
+            Some(v) => v.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn to_string() {
+        assert_eq!(Value::Null.to_string(), "");
+        assert_eq!(Value::from(true).to_string(), "1");
+        assert_eq!(Value::from(false).to_string(), "");
+        assert_eq!(Value::from(-42).to_string(), "-42");
+        assert_eq!(Value::from(vec![1, 2]).to_string(), "Array");
+    }
+
+    #[test]
+    pub fn _float_to_string() {
+        assert_eq!(float_to_string(1.0), "1");
+        assert_eq!(float_to_string(-1.5), "-1.5");
+        assert_eq!(float_to_string(0.1 + 0.2), "0.3");
+        assert_eq!(float_to_string(100.0), "100");
+        assert_eq!(float_to_string(0.0001), "0.0001");
+        assert_eq!(float_to_string(0.00001), "1.0E-5");
+        assert_eq!(float_to_string(1e14), "1.0E+14");
+        assert_eq!(float_to_string(1.5e25), "1.5E+25");
+        assert_eq!(float_to_string(12345678901234.0), "12345678901234");
+        assert_eq!(float_to_string(1.0 / 3.0), "0.33333333333333");
+    }
+
+    #[test]
+    pub fn to_int() {
+        assert_eq!(Value::from(" 12kg").to_int(), 12);
+        assert_eq!(Value::from("-1.9").to_int(), -1);
+        assert_eq!(Value::from("1e3").to_int(), 1000);
+        assert_eq!(Value::from("abc").to_int(), 0);
+        assert_eq!(Value::from(2.7).to_int(), 2);
+        assert_eq!(Value::from(".5").to_float(), 0.5);
+        assert_eq!(Value::from("3.").to_float(), 3.0);
+        assert_eq!(Value::from(true).to_float(), 1.0);
+        assert_eq!(Value::from(" 12kg").to_number(), Value::from(12));
+        assert_eq!(Value::from("1e3").to_number(), Value::from(1000.0));
+        assert_eq!(Value::from("").to_number(), Value::from(0));
+    }
+
+    #[test]
+    pub fn compare() {
+        use std::cmp::Ordering::*;
+
+        assert_eq!(Value::from(2).compare(&Value::from("10")), Less);
+        assert_eq!(Value::from("2").compare(&Value::from("10")), Less);
+        assert_eq!(Value::from("abc").compare(&Value::from("abd")), Less);
+        assert_eq!(Value::from(1.5).compare(&Value::from(" 1.5")), Equal);
+        assert_eq!(Value::from(10).compare(&Value::from("9a")), Less);
+        assert_eq!(Value::Null.compare(&Value::from(false)), Equal);
+        assert_eq!(Value::from(vec![1, 2]).compare(&Value::from(vec![3])), Greater);
+        assert_eq!(Value::from(vec![1]).compare(&Value::from(99)), Greater);
+    }
+
+    #[test]
+    pub fn is_true() {
+        assert!(!Value::from("0").is_true());
+        assert!(!Value::from("").is_true());
+        assert!(Value::from("0.0").is_true());
+        assert!(!Value::from(Vec::<i32>::new()).is_true());
+        assert!(Value::from(0.5).is_true());
+    }
+}
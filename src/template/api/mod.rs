@@ -5,6 +5,7 @@
 
 //! Twig Template API.
 
+use std::io::Write;
 use engine::TwigError;
 use runtime::Runtime;
 use api::error::Traced;
@@ -17,11 +18,10 @@ pub trait Template {
     /// Renders the template with the given context and returns it as string.
     fn render(&self, runtime: &Runtime) -> Result<String, Traced<TwigError>>;
 
-    /// Displays the template with the given context.
+    /// Displays the template with the given context - i.e. writes it to `out`.
     ///
-    /// context is an array of parameters to pass to the template
-    /// blocks is an array of blocks to pass to the template
-    fn display(&self, runtime: &Runtime, blocks: Option<Vec<()>>); // TODO: error handling
+    /// The output is written at once - nothing is written, if rendering fails.
+    fn display(&self, runtime: &Runtime, out: &mut Write) -> Result<(), Traced<TwigError>>;
 
 // /**
 //  * Get the bound engine for this template.
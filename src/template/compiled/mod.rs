@@ -6,43 +6,77 @@
 //! Default base class for compiled templates.
 
 use template::api::Template;
-use engine::TwigError;
-use runtime::{Runtime, Job};
+use engine::{TwigError, ExtensionRegistry, Templates};
+use extension::sandbox::SecurityPolicy;
+use runtime::{Runtime, Job, RuntimeError};
 use runtime::api::Execute;
 use engine::node;
 use api::error::Traced;
+use std::io::Write;
+use std::sync::Arc;
 
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct Compiled {
-    root: node::Module, /* TODO move *some* information from node::Module to template::Compiled
+    root: Arc<node::Module>, /* TODO move *some* information from node::Module to template::Compiled
                          *  -> easier accessible for runtime
                          *  -> less accessible for node traverser (optimizer?) ... */
+    ext: Arc<ExtensionRegistry>,
+    templates: Option<Arc<Templates>>,
+    policy: Option<Arc<SecurityPolicy>>,
 }
 
 impl Compiled {
-    pub fn new(root: node::Module) -> Compiled {
-        Compiled { root: root }
+    pub fn new(root: node::Module, ext: Arc<ExtensionRegistry>) -> Compiled {
+        Compiled {
+            root: Arc::new(root),
+            ext: ext,
+            templates: None,
+            policy: None,
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn module(&self) -> &node::Module {
+    /// Gives the template access to other templates while rendering, e.g. for `source()`.
+    pub fn set_templates(&mut self, templates: Arc<Templates>) {
+        self.templates = Some(templates);
+    }
+
+    /// Sandboxes the template at runtime, e.g. attribute access.
+    pub fn set_policy(&mut self, policy: Option<Arc<SecurityPolicy>>) {
+        self.policy = policy;
+    }
+
+    /// Returns the security policy, if the template is sandboxed.
+    pub fn policy(&self) -> Option<&Arc<SecurityPolicy>> {
+        self.policy.as_ref()
+    }
+
+    /// Returns the module - shared with the templates which extend it.
+    pub fn module(&self) -> &Arc<node::Module> {
         &self.root
     }
 }
 
 impl Template for Compiled {
     fn render(&self, runtime: &Runtime) -> Result<String, Traced<TwigError>> {
-        Ok(runtime.run(&self.root))
+        let mut job = Job::with_extensions(self.ext.clone());
+        job.set_templates(self.templates.clone());
+        job.set_policy(self.policy.clone());
+
+        Ok(try_traced!(job.run(runtime, self)))
     }
 
-    fn display(&self, _runtime: &Runtime, _blocks: Option<Vec<()>>) {
-        unimplemented!()
+    fn display(&self, runtime: &Runtime, out: &mut Write) -> Result<(), Traced<TwigError>> {
+        let output = try_traced!(self.render(runtime));
+        try_untraced!(out.write_all(output.as_bytes()).map_err(TwigError::Output));
+
+        Ok(())
     }
 }
 
+/// Displays the template and its parents.
 impl Execute for Compiled {
-    fn execute(&self, runtime: &Runtime, job: &mut Job) {
-        self.root.execute(runtime, job);
+    fn execute(&self, runtime: &Runtime, job: &mut Job) -> Result<(), Traced<RuntimeError>> {
+        node::module::display(&self.root, runtime, job)
     }
 }
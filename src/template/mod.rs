@@ -8,5 +8,7 @@
 pub mod compiled;
 pub mod raw;
 pub mod api;
+pub mod precompiled;
 pub use self::compiled::Compiled;
 pub use self::raw::Raw;
+pub use self::precompiled::Precompiled;
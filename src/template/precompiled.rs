@@ -0,0 +1,96 @@
+// This file is part of rust-web/twig
+//
+// For the copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Templates compiled to Rust code ahead of time, see `twig::build`.
+//!
+//! The generated code builds the parsed module of each template - the engine
+//! applies the node visitors when the template is loaded, like for templates
+//! compiled from source.
+
+use std::collections::HashMap;
+use engine::Node;
+use engine::node::Module;
+use engine::parser::token::stream::Position;
+
+/// Builds the parsed module of a template.
+pub type Constructor = fn() -> Module;
+
+/// Precompiled templates by name - see `Engine::set_precompiled()`.
+#[derive(Debug, Default)]
+pub struct Precompiled {
+    modules: HashMap<String, Constructor>,
+    fingerprint: Option<u64>,
+}
+
+impl Precompiled {
+    pub fn new() -> Precompiled {
+        Precompiled::default()
+    }
+
+    /// Templates built with the engine settings of `fingerprint`, see
+    /// `Templates::fingerprint()` - used by generated code.
+    pub fn built_with(fingerprint: u64) -> Precompiled {
+        Precompiled {
+            fingerprint: Some(fingerprint),
+            ..Precompiled::default()
+        }
+    }
+
+    /// Returns the fingerprint of the engine settings at build time - if known.
+    pub fn fingerprint(&self) -> Option<u64> {
+        self.fingerprint
+    }
+
+    pub fn add(&mut self, name: &str, module: Constructor) -> &mut Precompiled {
+        self.modules.insert(name.to_string(), module);
+
+        self
+    }
+
+    /// Returns the parsed module of the template `name`.
+    pub fn module(&self, name: &str) -> Option<Module> {
+        self.modules.get(name).map(|constructor| constructor())
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.modules.contains_key(name)
+    }
+
+    /// Returns the template names in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.modules.keys().map(|x| &x[..]).collect();
+        names.sort();
+
+        names
+    }
+}
+
+/// Sets the attributes of a node - used by generated code.
+pub fn node<T>(mut node: Box<T>, attributes: &[(&str, &str)]) -> Box<Node>
+    where T: Node + 'static
+{
+    for &(key, value) in attributes {
+        node.set_attribute(key, value);
+    }
+
+    node
+}
+
+/// Appends the children of a node - used by generated code.
+pub fn with_children<T>(mut node: Box<T>, children: Vec<Box<Node>>) -> Box<T>
+    where T: Node
+{
+    node.children_mut().extend(children);
+
+    node
+}
+
+/// Returns the position in the template source - used by generated code.
+pub fn position(line: usize, column: usize) -> Position {
+    Position {
+        line: line,
+        column: column,
+    }
+}
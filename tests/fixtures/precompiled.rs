@@ -0,0 +1,182 @@
+// Generated by twig::build - do not edit.
+
+/// Returns the precompiled templates, see `twig::Engine::set_precompiled()`.
+pub fn templates() -> ::twig::template::Precompiled {
+    #[allow(unused_mut)]
+    let mut templates = ::twig::template::Precompiled::built_with(0x8b70e9bce7f21dad);
+    templates.add("index.html", __TwigTemplate_2dc3d18016211f22);
+    templates.add("layout.html", __TwigTemplate_de7f1087cd0f99ae);
+    templates.add("page.html", __TwigTemplate_b177e50235cab45d);
+    templates.add("partials/greeting.html", __TwigTemplate_37dbc0b65582e1e2);
+    templates
+}
+
+/// "index.html"
+#[allow(non_snake_case)]
+fn __TwigTemplate_2dc3d18016211f22() -> ::twig::engine::node::Module {
+    use ::twig::engine::node::{self as n, expression as e};
+    use ::twig::template::precompiled::{node, position as at};
+    #[allow(unused_imports)]
+    use ::twig::runtime::Value as V;
+    #[allow(unused_imports)]
+    use ::twig::runtime::value::Key as K;
+    #[allow(unused_imports)]
+    use ::twig::extension::escaper::Strategy as S;
+
+    n::Module::new(n::Body::boxed(vec![
+        node(n::Text::boxed("<h1>".to_string(), &at(1, 1)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Filter::boxed("title".to_string(), node(e::Name::boxed("title".to_string(), &at(1, 8)), &[]), vec![], &at(1, 14)), &[]), S::Html, &at(1, 7)), &[("is_safe", "html")]), &at(1, 7)), &[]),
+        node(n::Text::boxed("</h1>\n".to_string(), &at(1, 22)), &[]),
+        node(n::Include::boxed(node(e::Constant::boxed(V::String("partials/greeting.html".to_string()), &at(2, 12)), &[("is_safe", "all")]), Some(node(e::Array::hash(vec![
+            node(e::Constant::boxed(V::String("name".to_string()), &at(2, 43)), &[("is_safe", "all")]),
+            node(e::GetAttr::boxed(node(e::Name::boxed("user".to_string(), &at(2, 49)), &[]), node(e::Constant::boxed(V::String("name".to_string()), &at(2, 54)), &[("is_safe", "all")]), vec![], e::get_attr::Kind::Any, &at(2, 53)), &[])
+        ], &at(2, 42)), &[])), false, false, &at(2, 4)), &[]),
+        node(n::Text::boxed("".to_string(), &at(3, 1)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Filter::boxed("json_encode".to_string(), node(e::Array::sequence(vec![
+            node(e::Constant::boxed(V::Integer(1i64), &at(3, 5)), &[("is_safe", "all")]),
+            node(e::Constant::boxed(V::Float(f64::from_bits(0x4004000000000000)), &at(3, 8)), &[("is_safe", "all")]),
+            node(e::Constant::boxed(V::Boolean(true), &at(3, 13)), &[("is_safe", "all")]),
+            node(e::Constant::boxed(V::Null, &at(3, 19)), &[("is_safe", "all")])
+        ], &at(3, 4)), &[]), vec![], &at(3, 25)), &[("is_safe", "js")]), S::Html, &at(3, 3)), &[("is_safe", "html")]), &at(3, 3)), &[]),
+        node(n::Text::boxed(" ".to_string(), &at(3, 39)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Filter::boxed("join".to_string(), node(e::Filter::boxed("keys".to_string(), node(e::Array::hash(vec![
+            node(e::Constant::boxed(V::String("a".to_string()), &at(3, 44)), &[("is_safe", "all")]),
+            node(e::Constant::boxed(V::String("x".to_string()), &at(3, 47)), &[("is_safe", "all")]),
+            node(e::Constant::boxed(V::Integer(1i64), &at(3, 52)), &[("is_safe", "all")]),
+            node(e::Constant::boxed(V::String("y".to_string()), &at(3, 55)), &[("is_safe", "all")])
+        ], &at(3, 43)), &[]), vec![], &at(3, 60)), &[]), vec![
+            node(e::Constant::boxed(V::String(",".to_string()), &at(3, 70)), &[("is_safe", "all")])
+        ], &at(3, 65)), &[]), S::Html, &at(3, 42)), &[("is_safe", "html")]), &at(3, 42)), &[]),
+        node(n::Text::boxed("\n".to_string(), &at(3, 77)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Filter::boxed("join".to_string(), node(e::Filter::boxed("map".to_string(), node(e::Name::boxed("items".to_string(), &at(4, 4)), &[]), vec![
+            node(e::Arrow::boxed(vec!["v".to_string()], node(e::Filter::boxed("upper".to_string(), node(e::Name::boxed("v".to_string(), &at(4, 19)), &[]), vec![], &at(4, 21)), &[]), &at(4, 14)), &[])
+        ], &at(4, 10)), &[]), vec![
+            node(e::Constant::boxed(V::String(", ".to_string()), &at(4, 33)), &[("is_safe", "all")])
+        ], &at(4, 28)), &[]), S::Html, &at(4, 3)), &[("is_safe", "html")]), &at(4, 3)), &[]),
+        node(n::Text::boxed(" ".to_string(), &at(4, 41)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Binary::boxed("+".to_string(), node(e::Binary::boxed("*".to_string(), node(e::Filter::boxed("length".to_string(), node(e::Name::boxed("items".to_string(), &at(4, 45)), &[]), vec![], &at(4, 51)), &[]), node(e::Constant::boxed(V::Integer(2i64), &at(4, 60)), &[("is_safe", "all")]), &at(4, 58)), &[]), node(e::Constant::boxed(V::Integer(1i64), &at(4, 64)), &[("is_safe", "all")]), &at(4, 62)), &[]), S::Html, &at(4, 44)), &[("is_safe", "html")]), &at(4, 44)), &[]),
+        node(n::Text::boxed(" ".to_string(), &at(4, 68)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Unary::boxed("-".to_string(), node(e::Filter::boxed("length".to_string(), node(e::Name::boxed("items".to_string(), &at(4, 73)), &[]), vec![], &at(4, 79)), &[]), &at(4, 72)), &[]), S::Html, &at(4, 71)), &[("is_safe", "html")]), &at(4, 71)), &[]),
+        node(n::Text::boxed("\n".to_string(), &at(4, 88)), &[])
+    ]),
+                   None,
+                   vec![],
+                   vec![],
+                   vec![], vec![], "index.html")
+}
+
+/// "layout.html"
+#[allow(non_snake_case)]
+fn __TwigTemplate_de7f1087cd0f99ae() -> ::twig::engine::node::Module {
+    use ::twig::engine::node::{self as n, expression as e};
+    use ::twig::template::precompiled::{node, position as at};
+    #[allow(unused_imports)]
+    use ::twig::runtime::Value as V;
+    #[allow(unused_imports)]
+    use ::twig::runtime::value::Key as K;
+    #[allow(unused_imports)]
+    use ::twig::extension::escaper::Strategy as S;
+
+    n::Module::new(n::Body::boxed(vec![
+        node(n::Text::boxed("<title>".to_string(), &at(1, 1)), &[]),
+        node(n::BlockReference::boxed("title".to_string(), &at(1, 11)), &[]),
+        node(n::Text::boxed("</title>\n".to_string(), &at(1, 43)), &[]),
+        node(n::BlockReference::boxed("content".to_string(), &at(2, 4)), &[]),
+        node(n::Text::boxed("".to_string(), &at(3, 1)), &[])
+    ]),
+                   None,
+                   vec![
+        node(n::Block::boxed("title".to_string(), vec![
+            node(n::Text::boxed("Site".to_string(), &at(1, 25)), &[])
+        ], &at(1, 11)), &[]),
+        node(n::Block::boxed("content".to_string(), vec![
+            node(n::Text::boxed("".to_string(), &at(2, 20)), &[])
+        ], &at(2, 4)), &[])
+    ],
+                   vec![],
+                   vec![], vec![], "layout.html")
+}
+
+/// "page.html"
+#[allow(non_snake_case)]
+fn __TwigTemplate_b177e50235cab45d() -> ::twig::engine::node::Module {
+    use ::twig::engine::node::{self as n, expression as e};
+    use ::twig::template::precompiled::{node, position as at};
+    #[allow(unused_imports)]
+    use ::twig::runtime::Value as V;
+    #[allow(unused_imports)]
+    use ::twig::runtime::value::Key as K;
+    #[allow(unused_imports)]
+    use ::twig::extension::escaper::Strategy as S;
+
+    n::Module::new(n::Body::boxed(vec![
+        node(n::Virtual::boxed(&at(1, 4)), &[]),
+        node(n::Virtual::boxed(&at(2, 4)), &[]),
+        node(n::Virtual::boxed(&at(3, 4)), &[])
+    ]),
+                   Some(node(e::Constant::boxed(V::String("layout.html".to_string()), &at(1, 12)), &[("is_safe", "all")])),
+                   vec![
+        node(n::Block::boxed("title".to_string(), vec![
+            node(n::Text::boxed("Page - ".to_string(), &at(4, 18)), &[]),
+            node(n::Print::boxed(node(e::Parent::boxed("title".to_string(), &at(4, 28)), &[("is_safe", "all")]), &at(4, 27)), &[]),
+            node(n::Text::boxed("".to_string(), &at(4, 39)), &[])
+        ], &at(4, 4)), &[]),
+        node(n::Block::boxed("content".to_string(), vec![
+            node(n::Text::boxed("".to_string(), &at(5, 20)), &[]),
+            node(n::Print::boxed(node(e::MethodCall::boxed(node(e::Constant::boxed(V::String("page.html".to_string()), &at(5, 28)), &[("is_safe", "all")]), "input".to_string(), vec![
+                node(e::Constant::boxed(V::String("email".to_string()), &at(5, 35)), &[("is_safe", "all")])
+            ], &at(5, 28)), &[("is_safe", "all")]), &at(5, 22)), &[]),
+            node(n::Text::boxed("".to_string(), &at(5, 46)), &[]),
+            node(n::Print::boxed(node(e::BlockReference::boxed(node(e::Constant::boxed(V::String("title".to_string()), &at(5, 55)), &[("is_safe", "all")]), &at(5, 49)), &[("is_safe", "all")]), &at(5, 48)), &[]),
+            node(n::Text::boxed("\n".to_string(), &at(5, 66)), &[]),
+            node(n::For::boxed("_key".to_string(), "item".to_string(), node(e::Name::boxed("items".to_string(), &at(6, 16)), &[]), None, vec![
+                node(n::Text::boxed("".to_string(), &at(6, 24)), &[]),
+                node(n::Print::boxed(node(e::Escape::boxed(node(e::GetAttr::boxed(node(e::Name::boxed("loop".to_string(), &at(6, 27)), &[]), node(e::Constant::boxed(V::String("index".to_string()), &at(6, 32)), &[("is_safe", "all")]), vec![], e::get_attr::Kind::Any, &at(6, 31)), &[]), S::Html, &at(6, 26)), &[("is_safe", "html")]), &at(6, 26)), &[]),
+                node(n::Text::boxed(". ".to_string(), &at(6, 40)), &[]),
+                node(n::Print::boxed(node(e::Escape::boxed(node(e::Name::boxed("item".to_string(), &at(6, 45)), &[]), S::Html, &at(6, 44)), &[("is_safe", "html")]), &at(6, 44)), &[]),
+                node(n::Text::boxed(" ".to_string(), &at(6, 52)), &[])
+            ], vec![
+                node(n::Text::boxed("empty".to_string(), &at(6, 63)), &[])
+            ], &at(6, 4)), &[]),
+            node(n::Text::boxed("".to_string(), &at(6, 80)), &[])
+        ], &at(5, 4)), &[])
+    ],
+                   vec![
+        node(n::Macro::boxed("input".to_string(), node(e::Array::hash(vec![
+            node(e::Constant::boxed(V::String("name".to_string()), &at(3, 16)), &[]),
+            node(e::Constant::boxed(V::Null, &at(3, 16)), &[]),
+            node(e::Constant::boxed(V::String("type".to_string()), &at(3, 22)), &[]),
+            node(e::Constant::boxed(V::String("text".to_string()), &at(3, 29)), &[("is_safe", "all")])
+        ], &at(3, 15)), &[]), vec![
+            node(n::Text::boxed("<input type=\"".to_string(), &at(3, 39)), &[]),
+            node(n::Print::boxed(node(e::Escape::boxed(node(e::Name::boxed("type".to_string(), &at(3, 55)), &[]), S::Html, &at(3, 54)), &[("is_safe", "html")]), &at(3, 54)), &[]),
+            node(n::Text::boxed("\" name=\"".to_string(), &at(3, 62)), &[]),
+            node(n::Print::boxed(node(e::Escape::boxed(node(e::Name::boxed("name".to_string(), &at(3, 73)), &[]), S::Html, &at(3, 72)), &[("is_safe", "html")]), &at(3, 72)), &[]),
+            node(n::Text::boxed("\">".to_string(), &at(3, 80)), &[])
+        ], &at(3, 4)), &[])
+    ],
+                   vec![], vec![], "page.html")
+}
+
+/// "partials/greeting.html"
+#[allow(non_snake_case)]
+fn __TwigTemplate_37dbc0b65582e1e2() -> ::twig::engine::node::Module {
+    use ::twig::engine::node::{self as n, expression as e};
+    use ::twig::template::precompiled::{node, position as at};
+    #[allow(unused_imports)]
+    use ::twig::runtime::Value as V;
+    #[allow(unused_imports)]
+    use ::twig::runtime::value::Key as K;
+    #[allow(unused_imports)]
+    use ::twig::extension::escaper::Strategy as S;
+
+    n::Module::new(n::Body::boxed(vec![
+        node(n::Text::boxed("Hello ".to_string(), &at(1, 1)), &[]),
+        node(n::Print::boxed(node(e::Escape::boxed(node(e::Name::boxed("name".to_string(), &at(1, 10)), &[]), S::Html, &at(1, 9)), &[("is_safe", "html")]), &at(1, 9)), &[]),
+        node(n::Text::boxed("!".to_string(), &at(1, 17)), &[])
+    ]),
+                   None,
+                   vec![],
+                   vec![],
+                   vec![], vec![], "partials/greeting.html")
+}
@@ -13,10 +13,27 @@
 /////////////
 
 extern crate twig;
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::sync::Arc;
 use twig::{Engine, Setup};
-use twig::loader;
+use twig::engine::parser::lexer::{self, Lexer};
+use twig::loader::{self, Loader, LoaderError};
+use twig::api::error::Traced;
 use twig::runtime::Runtime;
 use twig::template::api::Template;
+use twig::runtime::Value;
+use twig::runtime::value::{Map, Date};
+use twig::extension::sandbox::{self, Sandbox, SecurityPolicy};
+use twig::extension::Profiler;
+use twig::extension::optimizer;
+use twig::extension::api::{SimpleFilter, SimpleFunction, SimpleGlobal};
+use twig::extension::profiler::{Kind, Dumper, TextDumper};
 
 /////////////
 // exports //
@@ -53,6 +70,725 @@ fn hello_world_variable() {
     assert_eq!(&compiled.render(&runtime).unwrap(), "Hello world!")
 }
 
+#[test]
+fn autoescape_html() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test","<p>{{ text }}</p>");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "Tom & \"Jerry\" <3");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "<p>Tom &amp; &quot;Jerry&quot; &lt;3</p>")
+}
+
+#[test]
+/// http://twig.sensiolabs.org/doc/tags/autoescape.html
+fn autoescape_tag() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test","\
+        {% autoescape 'js' %}var x = '{{ text }}';{% endautoescape %}\n\n\
+        {% autoescape false %}{{ text }}{% autoescape %} {{ text }}{% endautoescape %}{% endautoescape %}\n\n\
+        {{ text }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "<a&b>");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "var x = '\\u003Ca\\u0026b\\u003E';\n<a&b> &lt;a&amp;b&gt;\n&lt;a&amp;b&gt;")
+}
+
+#[test]
+fn autoescape_disabled() {
+    use twig::extension::escaper::Mode;
+
+    let mut loader = loader::array::Array::default();
+    let mut engine = Setup::default().set_autoescape(Mode::Disabled).engine().unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test","{{ text }}{% autoescape 'url' %} {{ text }}{% endautoescape %}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "<a&b>");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(), "<a&b> %3Ca%26b%3E")
+}
+
+#[test]
+fn autoescape_filename() {
+    use twig::extension::escaper::Mode;
+
+    let mut loader = loader::array::Array::default();
+    let mut engine = Setup::default().set_autoescape(Mode::Filename).engine().unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("page.html.twig","{{ text }}");
+    loader.set_template("data.json.twig","{{ text }}");
+    loader.set_template("mail.txt.twig","{{ text }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "<a&b>");
+
+    let render = |engine: &mut Engine, name| engine.load_template(name, None).unwrap()
+                                                   .render(&runtime).unwrap();
+
+    assert_eq!(render(&mut engine, "page.html.twig"), "&lt;a&amp;b&gt;");
+    assert_eq!(render(&mut engine, "data.json.twig"), r"\u003Ca\u0026b\u003E");
+    assert_eq!(render(&mut engine, "mail.txt.twig"), "<a&b>");
+}
+
+#[test]
+fn autoescape_callback() {
+    use twig::extension::escaper::{Mode, Strategy};
+
+    let mut loader = loader::array::Array::default();
+    let mode = Mode::callback(|name| if name.starts_with("css/") { Some(Strategy::Css) } else { None });
+    let mut engine = Setup::default().set_autoescape(mode).engine().unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("css/main","{{ text }}");
+    loader.set_template("plain","{{ text }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "a b");
+
+    let render = |engine: &mut Engine, name| engine.load_template(name, None).unwrap()
+                                                   .render(&runtime).unwrap();
+
+    assert_eq!(render(&mut engine, "css/main"), r"a\20 b");
+    assert_eq!(render(&mut engine, "plain"), "a b");
+}
+
+#[test]
+fn autoescape_unknown_strategy() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+
+    loader.set_template("test","{% autoescape 'latex' %}{{ text }}{% endautoescape %}");
+    engine.set_loader(Box::new(loader));
+
+    assert!(engine.load_template("test", None).is_err());
+}
+
+#[test]
+fn custom_escaper() {
+    use twig::extension::escaper::Mode;
+
+    let mut loader = loader::array::Array::default();
+    let mut engine = Setup::default()
+                         .set_autoescape(Mode::Filename)
+                         .add_escaper("latex", |text| text.replace("&", r"\&").replace("%", r"\%"))
+                         .engine()
+                         .unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("report.latex.twig", "{{ text }}");
+    // latex markup is escaped again for html - unless it is raw
+    loader.set_template("page.html", "{{ text|e('latex') }}|\
+                                      {% autoescape 'latex' %}{{ text }}{% endautoescape %}|\
+                                      {{ text|e('latex')|raw }}|\
+                                      {% autoescape 'latex' %}{{ text|e('latex') }}{% endautoescape %}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "50% & more");
+
+    let render = |engine: &mut Engine, name| engine.load_template(name, None).unwrap()
+                                                   .render(&runtime).unwrap();
+
+    assert_eq!(render(&mut engine, "report.latex.twig"), r"50\% \& more");
+    assert_eq!(render(&mut engine, "page.html"),
+               r"50\% \&amp; more|50\% \& more|50\% \& more|50\% \& more");
+}
+
+#[test]
+fn safe_markup() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ html|raw }}|{{ text|e }}|{{ text|escape('html') }}|{{ '<br>' }}|{{ text }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("html", "<b>bold</b>");
+    runtime.set("text", "a & b");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "<b>bold</b>|a &amp; b|a &amp; b|<br>|a &amp; b")
+}
+
+#[test]
+fn safe_markup_strategy() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    // escaped for js, but not (yet) for html
+    loader.set_template("test",
+                        "{{ text|e('js') }}|{% autoescape 'js' %}{{ text|e('js') }}{% endautoescape %}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "<a>");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "\\u003Ca\\u003E|\\u003Ca\\u003E")
+}
+
+#[test]
+fn chained_escaping() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    // markup is only safe for the strategy which produced it
+    loader.set_template("test",
+                        "{{ text|e('html')|e('js') }}|{{ text|raw|e('js') }}|{{ text|e('js')|raw }}|\
+                         {{ text|e|e }}|\
+                         {% autoescape 'js' %}{{ text|e('html') }}|{{ text|e('js') }}{% endautoescape %}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("text", "a & b");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "a\\u0020\\u0026amp\\u003B\\u0020b|a\\u0020\\u0026\\u0020b|\
+                a\\u0020\\u0026\\u0020b|a &amp;amp; b|\
+                a\\u0020\\u0026amp\\u003B\\u0020b|a\\u0020\\u0026\\u0020b")
+}
+
+#[test]
+fn string_filters() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ name|title }}|{{ name|upper|trim('.') }}|{{ text|nl2br }}|\
+                         {{ '<i>\n</i>'|nl2br }}|{{ '%s=%03d'|format(name|capitalize, 7) }}|\
+                         {{ html|striptags('<b>') }}|{{ html|striptags|raw }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("name", "élan vital.");
+    runtime.set("text", "<a>\nb");
+    runtime.set("html", "<p><b>x</b> &amp; y</p>");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "Élan Vital.|ÉLAN VITAL|&lt;a&gt;<br />\nb|<i><br />\n</i>|Élan vital.=007|\
+                &lt;b&gt;x&lt;/b&gt; &amp;amp; y|x &amp; y")
+}
+
+#[test]
+fn collection_filters() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ items|length }}|{{ items|first }}|{{ items|last }}|\
+                         {{ items|slice(1, 2)|join(',') }}|{{ items|reverse|join }}|\
+                         {{ items|sort|join(', ', ' and ') }}|\
+                         {{ [1, 2]|merge([3])|join }}|{{ {a: 1, 'b': 2, 3: 'c'}|keys|join }}|\
+                         {{ [1, 2, 3]|batch(2, 0)|last|join }}|\
+                         {{ [{id: 'x', n: 1}, {id: 'y', n: 2}]|column('n', 'id')|keys|join }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("items", vec![3, 1, 2]);
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "3|3|2|1,2|213|1, 2 and 3|123|ab3|30|xy")
+}
+
+#[test]
+fn arrow_functions() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ items|map(v => v|upper)|join(',') }}|\
+                         {{ items|map((v, k) => k)|join(',') }}|\
+                         {{ items|filter(v => v|slice(1))|join(',') }}|\
+                         {{ items|reduce((carry, v) => carry|merge([v|first]), [])|join }}|\
+                         {{ items|find(v => v|split('e')|slice(1)|length) }}|\
+                         {{ items|sort((a, b) => b|length <=> a|length)|join(',') }}|\
+                         {{ items|map(v => [(prefix), v]|join)|first }}|\
+                         {{ products|map((v, k) => v.price * 2)|join(',') }}|\
+                         {{ items|map(v => items|map(w => v ~ w)|first)|join(',') }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("items", vec!["a", "bc", "def"]);
+    runtime.set("prefix", "-");
+    runtime.set("products",
+                vec![vec![("price", Value::from(3))].into_iter().collect::<Map>(),
+                     vec![("price", Value::from(1.25))].into_iter().collect::<Map>()]);
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "A,BC,DEF|0,1,2|bc,def|abd|def|def,bc,a|-a|6,2.5|aa,bca,defa")
+}
+
+#[test]
+fn binary_operators() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ 1 + 2 * 3 }} {{ (1 + 2) * 3 }} {{ 2 ** 3 ** 2 }} {{ 10 - 2 - 3 }} \
+                         {{ 7 / 2 }} {{ 7 // 2 }} {{ 7 % 3 }} {{ 'a' ~ (1 + 2) ~ 'b' }} {{ 1 + 2 ~ 3 }}|\
+                         {{ 1 < 2 }}{{ 2 <= 1 }}{{ '1e1' == 10 }}{{ 'a' != 'a' }}|\
+                         {{ 2 <=> 1 }}{{ 1 <=> 1 }}{{ 1 <=> 2 }}|\
+                         {{ 0 or 'x' }}{{ 1 and none }}{{ none and missing.attribute }}|\
+                         {{ (1 + 1..2 + 2)|join(',') }}|{{ price * 2|abs }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("price", 1.5);
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "7 9 512 5 3.5 3 1 a3b 24|11|10-1|1|2,3,4|3");
+}
+
+#[test]
+fn unary_operators() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ items|slice(-2)|join }}|{{ -price }} {{ +'3' }} {{ 1 - -1 }} \
+                         {{ -(1 + 2) * 2 }}|{{ not false }}{{ not (1 and 0) }}{{ not items }}|\
+                         {{ users|filter(v => not v.hidden)|map(v => v.name)|join(',') }}|\
+                         {{ 1234.5|round(-1) }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("items", vec!["a", "b", "c", "d"]);
+    runtime.set("price", 1.5);
+    runtime.set("users",
+                vec![vec![("name", Value::from("Jo")), ("hidden", Value::from(false))]
+                         .into_iter()
+                         .collect::<Map>(),
+                     vec![("name", Value::from("Max")), ("hidden", Value::from(true))]
+                         .into_iter()
+                         .collect::<Map>()]);
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(), "cd|-1.5 3 2 -6|11|Jo|1230");
+}
+
+#[test]
+fn number_filters() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default().set_number_format(2, ',', '.')).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ price|number_format }}|{{ price|number_format(0, '.', ' ') }}|\
+                         {{ price|round }}|{{ price|round(1, 'floor') }}|{{ '-3'|abs }}|\
+                         {{ '-2.5'|abs }}|{{ price|format_number({max_fraction_digit: 1}) }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("price", 1234.567);
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "1.234,57|1 235|1235|1234.5|3|2.5|1,234.6")
+}
+
+#[test]
+fn encoding_filters() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "<script>var config = {% autoescape 'js' %}{{ {'path': '/a', 'ids': [1, 2]}|json_encode(64) }}\
+                         {% endautoescape %};</script>\n\
+                         <a href=\"/search?{{ {'q': 'rust & twig', 'page': 2}|url_encode }}\">{{ 'a/b c'|url_encode }}</a>");
+    engine.set_loader(Box::new(loader));
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "<script>var config = {\"path\":\"/a\",\"ids\":[1,2]};</script>\n\
+                <a href=\"/search?q=rust%20%26%20twig&amp;page=2\">a%2Fb%20c</a>")
+}
+
+#[cfg(feature = "date")]
+#[test]
+fn date_filters() {
+    let mut loader = loader::array::Array::default();
+    let setup = Setup::default().set_date_format("d/m/Y H:i").set_timezone(Some("Europe/Paris"));
+    let mut engine = Engine::new(setup).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ posted|date }}|{{ posted|date('D, jS F Y g:ia T', 'UTC') }}|\
+                         {{ posted|date_modify('+1 month')|date('Y-m-d') }}|\
+                         {{ date('2004-02-12 15:19 America/New_York')|date('c') }}|\
+                         {{ date(posted, 'Asia/Tokyo')|date('H:i e', false) }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("posted", 1076599161);
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "12/02/2004 16:19|Thu, 12th February 2004 3:19pm UTC|2004-03-12|\
+                2004-02-12T21:19:00+01:00|00:19 Asia/Tokyo")
+}
+
+#[test]
+fn core_functions() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default().set_random_seed(Some(42))).unwrap();
+    let runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{{ range(0, 6, 2)|join(',') }}|{{ (1..3)|join(',') }}|{{ ('a'..'c')|join }}|\
+                         {{ cycle(['odd', 'even'], 3) }}|{{ min(3, 1, 2) }}|{{ max([4, 9, 2]) }}|\
+                         {{ constant('PHP_INT_MAX') }}|\
+                         {{ source('partial') }}|{{ source('missing', true) }}");
+    loader.set_template("partial", "<b>{{ name }}</b>");
+    engine.set_loader(Box::new(loader));
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "0,2,4,6|1,2,3|abc|even|1|9|9223372036854775807|<b>{{ name }}</b>|")
+}
+
+#[test]
+fn random_seed() {
+    let render = |seed| {
+        let mut loader = loader::array::Array::default();
+        let mut engine = Engine::new(Setup::default().set_random_seed(Some(seed))).unwrap();
+
+        loader.set_template("test", "{{ random() }}-{{ random(100) }}-{{ random('abcdef') }}");
+        engine.set_loader(Box::new(loader));
+
+        engine.load_template("test", None).unwrap().render(&Runtime::default()).unwrap()
+    };
+
+    assert_eq!(render(7), render(7));
+}
+
+#[test]
+fn template_from_string() {
+    let mut loader = loader::array::Array::default();
+    let setup = Setup::default().add_extension(twig::extension::StringLoader::new()).unwrap();
+    let mut engine = Engine::new(setup).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test", "{{ template_from_string('Hello {{ name|upper }}!') }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("name", "<world>");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(), "Hello &lt;WORLD&gt;!")
+}
+
+#[test]
+fn debug_dump() {
+    let render = |debug| {
+        let mut loader = loader::array::Array::default();
+        let setup = Setup::default()
+                        .set_debug(debug)
+                        .add_extension(twig::extension::Debug::new())
+                        .unwrap();
+        let mut engine = Engine::new(setup).unwrap();
+        let mut runtime = Runtime::default();
+
+        loader.set_template("test", "{{ dump(user, 'a&b') }}|{{ dump() }}");
+        engine.set_loader(Box::new(loader));
+        runtime.set("user", vec![1]);
+
+        engine.load_template("test", None).unwrap().render(&runtime).unwrap()
+    };
+
+    assert_eq!(render(true),
+               "array(1) {\n  [0]=&gt;\n  int(1)\n}\nstring(3) &quot;a&amp;b&quot;\n|\
+                array(1) {\n  [&quot;user&quot;]=&gt;\n  array(1) {\n    [0]=&gt;\n    int(1)\n  }\n}\n");
+    assert_eq!(render(false), "|");
+}
+
+#[test]
+fn attributes() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+    let mut user = Map::new();
+    user.insert("name", "Jo");
+    user.insert("tags", vec!["a", "b"]);
+
+    loader.set_template("test",
+                        "{{ user.name }}|{{ user['name'] }}|{{ user.tags.1 }}|{{ user.tags[0] }}|\
+                         {{ user.missing }}|{{ posted.timestamp }}|{{ posted.getTimezone() }}|\
+                         {{ posted.timezone }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("user", user.into_value());
+    runtime.set("posted", Value::Date(Date::new(1076599161, 0, 3600, "Europe/Paris")));
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(),
+               "Jo|Jo|b|a||1076599161|Europe/Paris|Europe/Paris")
+}
+
+#[test]
+fn include() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("test",
+                        "{% include 'hello' %}|{% include 'hello' with {'name': 'Max'} %}|\
+                         {% include 'hello' with {'greeting': 'Hi'} only %}|\
+                         {% include 'missing' ignore missing %}");
+    loader.set_template("hello", "{{ greeting }} {{ name }}!");
+    engine.set_loader(Box::new(loader));
+    runtime.set("greeting", "Hello");
+    runtime.set("name", "Jo");
+
+    let compiled = engine.load_template("test", None).unwrap();
+
+    assert_eq!(&compiled.render(&runtime).unwrap(), "Hello Jo!|Hello Max!|Hi !|");
+}
+
+#[test]
+fn blocks() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("layout",
+                        "<title>{% block title %}Site{% endblock %}</title>\
+                         {% block content %}{% block inner 'inner' %}{% endblock content %}");
+    loader.set_template("page",
+                        "{% extends 'layout' %}\n\
+                         {% block title %}{{ name }} - {{ parent() }}{% endblock %}");
+    loader.set_template("article",
+                        "{% extends layout %}\
+                         {% block inner %}[{{ parent()|upper }}]{% endblock %}\
+                         {% block title %}{{ block('inner') }}, {{ parent() }}{% endblock %}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("name", "<Jo>");
+    runtime.set("layout", "page");
+
+    let render = |name| engine.load_template(name, None).unwrap().render(&runtime).unwrap();
+    assert_eq!(render("layout"), "<title>Site</title>inner");
+    assert_eq!(render("page"), "<title>&lt;Jo&gt; - Site</title>inner");
+    assert_eq!(render("article"), "<title>[INNER], &lt;Jo&gt; - Site</title>[INNER]");
+
+    let sources = [("{% extends 'layout' %}text", "content outside Twig blocks"),
+                   ("{% block a %}{% endblock %}{% block a %}{% endblock %}", "already been defined"),
+                   ("{% block a %}{% endblock b %}", "Expected endblock for block \"a\""),
+                   ("{% block a %}{{ parent() }}{% endblock %}", "extends another one"),
+                   ("{{ parent() }}", "within a block"),
+                   ("{% extends 'a' %}{% extends 'b' %}", "Multiple extends")];
+    for &(source, reason) in &sources {
+        let mut loader = loader::array::Array::default();
+        let mut engine = Engine::new(Setup::default()).unwrap();
+
+        loader.set_template("test", source);
+        engine.set_loader(Box::new(loader));
+
+        let error = engine.load_template("test", None).unwrap_err();
+        assert!(error.to_string().contains(reason), "{}: {}", source, error);
+    }
+
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    loader.set_template("test", "{{ block('missing') }}");
+    loader.set_template("self", "{% extends 'self' %}");
+    engine.set_loader(Box::new(loader));
+
+    let error = engine.load_template("test", None).unwrap().render(&runtime).unwrap_err();
+    assert!(error.to_string().contains("The block \"missing\" is not defined"));
+    let error = engine.load_template("self", None).unwrap().render(&runtime).unwrap_err();
+    assert!(error.to_string().contains("circular"));
+}
+
+#[test]
+fn macros() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("forms",
+                        "{% macro input(name, type = 'text') %}\
+                         <input type=\"{{ type }}\" name=\"{{ name }}\">{{ varargs|join }}\
+                         {% endmacro %}");
+    loader.set_template("test",
+                        "{% import 'forms' as forms %}{% from 'forms' import input as field %}\
+                         {% macro hello(name) %}Hello {{ name }}{{ title }}!{% endmacro hello %}\
+                         {{ _self.hello('<Jo>') }}|{{ forms.input('email') }}|\
+                         {{ field('pass', 'password', 1, 2) }}");
+    loader.set_template("unknown", "{% import 'forms' as forms %}{{ forms.missing() }}");
+    engine.set_loader(Box::new(loader));
+    runtime.set("title", "Dr.");
+
+    let compiled = engine.load_template("test", None).unwrap();
+    assert_eq!(compiled.render(&runtime).unwrap(),
+               "Hello &lt;Jo&gt;!|<input type=\"text\" name=\"email\">|\
+                <input type=\"password\" name=\"pass\">12");
+
+    let compiled = engine.load_template("unknown", None).unwrap();
+    let error = compiled.render(&runtime).unwrap_err();
+    assert!(error.to_string().contains("The macro \"missing\" is not defined in the template \
+                                        \"forms\""));
+}
+
+#[test]
+fn for_loop() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    loader.set_template("list",
+                        "{% for user in users %}{{ loop.index }}/{{ loop.length }} {{ user }}\
+                         {{ loop.first }}{{ loop.last }};{% endfor %}");
+    loader.set_template("keys",
+                        "{% for k, v in {'a': 1, 'b': 0, 'c': 3} if v %}\
+                         {{ k }}={{ v }}{{ loop.revindex0 }};{% endfor %}");
+    loader.set_template("else", "{% for x in missing %}{{ x }}{% else %}none{% endfor %}");
+    loader.set_template("nested",
+                        "{% for a in [1, 2] %}{% for b in ['x', 'y'] %}\
+                         {{ loop.parent.loop.index }}{{ b }}{{ loop.parent.name }} \
+                         {% endfor %}{% endfor %}[{{ a }}{{ loop }}]");
+    engine.set_loader(Box::new(loader));
+    runtime.set("users", vec!["Jo", "Al"]);
+    runtime.set("name", "!");
+
+    let render = |name| engine.load_template(name, None).unwrap().render(&runtime).unwrap();
+    assert_eq!(render("list"), "1/2 Jo1;2/2 Al1;");
+    assert_eq!(render("keys"), "a=11;c=30;");
+    assert_eq!(render("else"), "none");
+    assert_eq!(render("nested"), "1x! 1y! 2x! 2y! []");
+
+    let sources = [("{% for x in y %}", "endfor"),
+                   ("{% for a, b, c in y %}{% endfor %}", "at most a key and a value"),
+                   ("{% for x y %}{% endfor %}", "Expected \"in\"")];
+    for &(source, reason) in &sources {
+        let mut loader = loader::array::Array::default();
+        let mut engine = Engine::new(Setup::default()).unwrap();
+
+        loader.set_template("test", source);
+        engine.set_loader(Box::new(loader));
+
+        let error = engine.load_template("test", None).unwrap_err();
+        assert!(error.to_string().contains(reason), "{}: {}", source, error);
+    }
+}
+
+#[test]
+fn sandbox() {
+    let policy = || {
+        SecurityPolicy::default()
+            .allow_tags(&["include"])
+            .allow_filters(&["upper"])
+            .allow_methods("date", &["getTimestamp"])
+    };
+    let engine = |mode| {
+        let mut loader = loader::array::Array::default();
+        loader.set_template("allowed", "{{ name|upper }}");
+        loader.set_template("filter", "{{ name|lower }}");
+        loader.set_template("function", "{{ range(1, 2)|join }}");
+        loader.set_template("tag", "{% autoescape false %}{{ name }}{% endautoescape %}");
+        loader.set_template("method", "{{ posted.timestamp }}");
+        loader.set_template("property", "{{ posted.timezone }}");
+        loader.set_template("section",
+                            "{{ name|lower }} {% sandbox %}{% include 'allowed' %} \
+                             {% include 'filter' %}{% endsandbox %}");
+
+        let setup = Setup::default().add_extension(Sandbox::new(policy(), mode)).unwrap();
+        let mut engine = Engine::new(setup).unwrap();
+        engine.set_loader(Box::new(loader));
+        engine
+    };
+    let mut runtime = Runtime::default();
+    runtime.set("name", "Jo");
+    runtime.set("posted", Value::Date(Date::new(1076599161, 0, 0, "UTC")));
+
+    let mut global = engine(sandbox::Mode::Global);
+    assert_eq!(&global.load_template("allowed", None).unwrap().render(&runtime).unwrap(), "JO");
+    assert_eq!(&global.load_template("method", None).unwrap().render(&runtime).unwrap(),
+               "1076599161");
+    for name in &["filter", "function", "tag"] {
+        let error = global.load_template(name, None).unwrap_err();
+        assert!(error.to_string().contains("not allowed"), "{}", error);
+    }
+    let error = global.load_template("property", None).unwrap().render(&runtime).unwrap_err();
+    assert!(error.to_string().contains("Calling \"timezone\" property on a \"date\""), "{}", error);
+
+    let mut templates = engine(sandbox::Mode::Templates(vec!["filter".to_string()]));
+    assert!(templates.load_template("filter", None).is_err());
+    assert!(templates.load_template("function", None).is_ok());
+
+    let mut local = engine(sandbox::Mode::Local);
+    assert!(local.load_template("filter", None).is_ok());
+    let error = local.load_template("section", None).unwrap().render(&runtime).unwrap_err();
+    assert!(error.to_string().contains("Filter \"lower\" is not allowed"), "{}", error);
+}
+
+#[test]
+fn unknown_filter() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+
+    loader.set_template("test", "{{ text|nonexistent }}");
+    engine.set_loader(Box::new(loader));
+
+    assert!(engine.load_template("test", None).is_err());
+}
+
+#[test]
+fn range_without_operand() {
+    let sources = [("{{ ..3 }}", "needs a lower bound"),
+                   ("{{ [1, ..] }}", "needs a lower bound"),
+                   ("{{ x|map(v => ..) }}", "needs a lower bound"),
+                   ("{{ 1.. }}", "needs an upper bound"),
+                   ("{{ [1.., 2] }}", "needs an upper bound")];
+
+    for &(source, reason) in &sources {
+        let mut loader = loader::array::Array::default();
+        let mut engine = Engine::new(Setup::default()).unwrap();
+
+        loader.set_template("test", source);
+        engine.set_loader(Box::new(loader));
+
+        let error = engine.load_template("test", None).unwrap_err();
+        assert!(error.to_string().contains(reason), "{}: {}", source, error);
+    }
+}
+
+#[test]
+fn unexpected_token() {
+    for source in &["{{ => }}", "{{ }}", "{{ = }}", "{{ , }}", "{{ [1, ] }}{{ [=] }}"] {
+        let mut loader = loader::array::Array::default();
+        let mut engine = Engine::new(Setup::default()).unwrap();
+
+        loader.set_template("test", source);
+        engine.set_loader(Box::new(loader));
+
+        let error = engine.load_template("test", None).unwrap_err();
+        assert!(error.to_string().contains("Expected to find an expression"),
+                "{}: {}",
+                source,
+                error);
+    }
+}
+
 // #[test]
 /// http://twig.sensiolabs.org/doc/tags/if.html
 fn _if_elseif_else() {
@@ -90,3 +826,442 @@ fn _if_elseif_else() {
     }
 
 }
+
+#[test]
+fn profiler() {
+    let profiler = Profiler::new();
+    let mut loader = loader::array::Array::default();
+    let setup = Setup::default().add_extension(profiler.clone()).unwrap();
+    let mut engine = Engine::new(setup).unwrap();
+    let runtime = Runtime::default();
+
+    loader.set_template("test", "<{% include 'hello' %}>");
+    loader.set_template("hello", "Hello World!");
+    engine.set_loader(Box::new(loader));
+
+    let compiled = engine.load_template("test", None).unwrap();
+    assert_eq!(&compiled.render(&runtime).unwrap(), "<Hello World!>");
+
+    let profile = profiler.profile();
+    assert_eq!(profile.profiles().len(), 1);
+
+    let test = &profile.profiles()[0];
+    assert_eq!((test.kind(), test.name(), test.memory()), (Kind::Template, "test", 14));
+    assert_eq!(test.profiles().len(), 1);
+
+    let hello = &test.profiles()[0];
+    assert_eq!((hello.kind(), hello.name(), hello.memory()), (Kind::Template, "hello", 12));
+    assert!(hello.duration() <= test.duration());
+
+    let text = TextDumper.dump(&profile);
+    assert!(text.starts_with("main"));
+    assert!(text.contains("└ test"));
+    assert!(text.contains("  └ hello"));
+
+    profiler.reset();
+    assert!(profiler.profile().profiles().is_empty());
+}
+
+#[test]
+fn profiler_blocks_and_macros() {
+    let profiler = Profiler::new();
+    let mut loader = loader::array::Array::default();
+    let setup = Setup::default().add_extension(profiler.clone()).unwrap();
+    let mut engine = Engine::new(setup).unwrap();
+
+    loader.set_template("layout", "<{% block content %}{% endblock %}>");
+    loader.set_template("page",
+                        "{% extends 'layout' %}\
+                         {% macro hello(name) %}Hello {{ name }}!{% endmacro %}\
+                         {% block content %}{{ _self.hello('Jo') }}{% endblock %}");
+    engine.set_loader(Box::new(loader));
+
+    let compiled = engine.load_template("page", None).unwrap();
+    assert_eq!(&compiled.render(&Runtime::default()).unwrap(), "<Hello Jo!>");
+
+    let profile = profiler.profile();
+    let calls: Vec<_> = profile.profiles().iter().map(|x| (x.kind(), x.name())).collect();
+    assert_eq!(calls, vec![(Kind::Template, "page"), (Kind::Template, "layout")]);
+
+    let content = &profile.profiles()[1].profiles()[0];
+    assert_eq!((content.kind(), content.template(), content.name(), content.memory()),
+               (Kind::Block, "page", "content", 9));
+
+    let hello = &content.profiles()[0];
+    assert_eq!((hello.kind(), hello.template(), hello.name(), hello.memory()),
+               (Kind::Macro, "page", "hello", 9));
+
+    let text = TextDumper.dump(&profile);
+    assert!(text.contains("└ layout\n  └ page::block(content)\n    └ page::macro(hello)\n"),
+            "{}",
+            text);
+}
+
+#[test]
+fn optimizer() {
+    let render = |mode| {
+        let mut loader = loader::array::Array::default();
+        let mut engine = Engine::new(Setup::default().set_optimizations(mode)).unwrap();
+        let mut runtime = Runtime::default();
+
+        loader.set_template("test",
+                            "a{{ '<b>'|upper }}{{ [1, 2]|join('-') }}{{ name }}{{ (1..3)|length }}\
+                             {{ 1 + 2 * 3 }}{{ 'a' ~ 'b'|upper }}");
+        loader.set_template("layout", "{% block a %}a{% endblock %}{% block b %}b{% endblock %}");
+        loader.set_template("page",
+                            "{% extends 'layout' %}{% block a %}{{ parent() }}{{ block('b') }}\
+                             {% for x in [1, 2] %}{{ x }}{% for y in [3] %}{{ loop.index }}\
+                             {% endfor %}{% endfor %}{% endblock %}");
+        engine.set_loader(Box::new(loader));
+        runtime.set("name", "&");
+
+        let render = |name| engine.load_template(name, None).unwrap().render(&runtime).unwrap();
+        (render("test"), render("page"))
+    };
+
+    assert_eq!(render(optimizer::Mode::ALL).0, "a&lt;B&gt;1-2&amp;37aB");
+    assert_eq!(render(optimizer::Mode::ALL).1, "ab1121b");
+    assert_eq!(render(optimizer::Mode::ALL), render(optimizer::Mode::NONE));
+    assert_eq!(render(optimizer::Mode::TEXT), render(optimizer::Mode::CONSTANTS));
+    assert_eq!(render(optimizer::Mode::OUTPUT), render(optimizer::Mode::LOOP));
+}
+
+#[test]
+fn staging() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let runtime = Runtime::default();
+
+    engine.add_filter(SimpleFilter::new("shout", |input, _args| {
+              Ok(Value::from(input.to_string().to_uppercase() + "!"))
+          }))
+          .unwrap()
+          .add_function(SimpleFunction::new("answer", |_args| Ok(Value::Integer(42))))
+          .unwrap();
+
+    loader.set_template("test", "{{ 'hi'|shout }} {{ answer() }}");
+    engine.set_loader(Box::new(loader));
+
+    let compiled = engine.load_template("test", None).unwrap();
+    assert_eq!(&compiled.render(&runtime).unwrap(), "HI! 42");
+
+    // too late - the extensions are initialized
+    assert!(engine.add_filter(SimpleFilter::new("whisper", |input, _args| Ok(input))).is_err());
+}
+
+#[test]
+fn staging_kept_on_error() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let runtime = Runtime::default();
+
+    engine.add_filter(SimpleFilter::new("shout", |input, _args| {
+              Ok(Value::from(input.to_string().to_uppercase() + "!"))
+          }))
+          .unwrap();
+
+    loader.set_template("test", "{{ 'hi'|shout }}");
+    engine.set_loader(Box::new(loader));
+
+    // the shared extensions can't take the staged filter
+    let extensions = engine.extensions().unwrap();
+    assert!(engine.load_template("test", None).is_err());
+    assert!(engine.load_template("test", None).is_err());
+
+    drop(extensions);
+    let compiled = engine.load_template("test", None).unwrap();
+    assert_eq!(&compiled.render(&runtime).unwrap(), "HI!");
+}
+
+#[test]
+fn globals() {
+    let mut loader = loader::array::Array::default();
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    let mut runtime = Runtime::default();
+
+    engine.add_global(SimpleGlobal::new("site_name", "Example").boxed())
+          .unwrap()
+          .add_global(SimpleGlobal::lazy("current_user", || Value::from("jo")).boxed())
+          .unwrap();
+
+    loader.set_template("test", "{{ site_name }}|{{ current_user }}|{% include 'user' %}");
+    loader.set_template("user", "{{ current_user|upper }}");
+    engine.set_loader(Box::new(loader));
+
+    let compiled = engine.load_template("test", None).unwrap();
+    assert_eq!(&compiled.render(&runtime).unwrap(), "Example|jo|JO");
+
+    // variables of the context take precedence
+    runtime.set("site_name", "Other");
+    assert_eq!(&compiled.render(&runtime).unwrap(), "Other|jo|JO");
+
+    // too late - the extensions are initialized
+    assert!(engine.add_global(SimpleGlobal::new("app", "twig").boxed()).is_err());
+}
+
+/// Reports all templates as modified.
+#[derive(Debug)]
+struct StaleLoader(loader::array::Array);
+
+impl Loader for StaleLoader {
+    fn source<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+        self.0.source(name)
+    }
+
+    fn cache_key<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+        self.0.cache_key(name)
+    }
+
+    fn is_fresh(&self, _name: &str, _time: i64) -> bool {
+        false
+    }
+}
+
+#[test]
+fn template_cache() {
+    let engine = |auto_reload| {
+        let mut loader = loader::array::Array::default();
+        loader.set_template("test", "Hello {% include 'name' %}!");
+        loader.set_template("name", "World");
+
+        let setup = Setup::default().set_auto_reload(Some(auto_reload));
+        let mut engine = Engine::new(setup).unwrap();
+        engine.set_loader(Box::new(StaleLoader(loader)));
+        engine
+    };
+
+    let mut cached = engine(false);
+    let first = cached.load_template("test", None).unwrap();
+    assert_eq!(&first.render(&Runtime::default()).unwrap(), "Hello World!");
+    assert!(Arc::ptr_eq(&first, &cached.load_template("test", None).unwrap()));
+    assert!(cached.evict_template("name"));
+    assert!(!cached.evict_template("name"));
+    assert!(cached.evict_template("test"));
+    assert!(!Arc::ptr_eq(&first, &cached.load_template("test", None).unwrap()));
+
+    let first = cached.load_template("test", None).unwrap();
+    cached.clear_template_cache();
+    assert!(!Arc::ptr_eq(&first, &cached.load_template("test", None).unwrap()));
+
+    // the loader reports changes
+    let mut reloaded = engine(true);
+    let first = reloaded.load_template("test", None).unwrap();
+    assert!(!Arc::ptr_eq(&first, &reloaded.load_template("test", None).unwrap()));
+}
+
+#[test]
+fn threads() {
+    let mut loader = loader::array::Array::default();
+    loader.set_template("test", "{{ name|shout }} {% include 'count' %}");
+    loader.set_template("count", "{{ items|length }}");
+
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    engine.set_loader(Box::new(loader));
+    engine.add_filter(SimpleFilter::new("shout", |input, _| {
+              Ok(Value::String(format!("{}!", input.to_string().to_uppercase())))
+          }))
+          .unwrap();
+    let engine = Arc::new(engine);
+
+    let handles: Vec<_> = (0..8)
+                              .map(|i| {
+                                  let engine = engine.clone();
+                                  thread::spawn(move || {
+                                      let mut runtime = Runtime::default();
+                                      runtime.set("name", format!("worker {}", i));
+                                      runtime.set("items", vec![0; i]);
+
+                                      let template = engine.load_template("test", None).unwrap();
+                                      (template.render(&runtime).unwrap(), template)
+                                  })
+                              })
+                              .collect();
+
+    let results: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    let cached = engine.load_template("test", None).unwrap();
+
+    for (i, (output, template)) in results.into_iter().enumerate() {
+        assert_eq!(output, format!("WORKER {}! {}", i, i));
+        assert!(Arc::ptr_eq(&cached, &template));
+    }
+}
+
+/// Fails to write anything.
+struct FullWriter;
+
+impl Write for FullWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn render_and_display() {
+    let mut loader = loader::array::Array::default();
+    loader.set_template("hello", "Hello {{ name }}{{ punctuation }}");
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    engine.set_loader(Box::new(loader));
+
+    let mut context = Map::new();
+    context.insert("name", "World");
+    context.insert("punctuation", "!");
+    assert_eq!(engine.render("hello", context).unwrap(), "Hello World!");
+    assert_eq!(engine.render("hello", vec![("name", "Jo"), ("punctuation", "?")]).unwrap(),
+               "Hello Jo?");
+
+    let mut runtime = Runtime::default();
+    runtime.set("name", "Max");
+    runtime.set("punctuation", ".");
+    assert_eq!(engine.render("hello", runtime).unwrap(), "Hello Max.");
+
+    let mut out = Vec::new();
+    engine.display("hello", vec![("name", "Jo"), ("punctuation", "!")], &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "Hello Jo!");
+
+    // nothing is written, if rendering fails
+    let mut out = Vec::new();
+    assert!(engine.display("missing", Runtime::default(), &mut out).is_err());
+    assert!(out.is_empty());
+
+    let error = engine.display("hello", Runtime::default(), &mut FullWriter).unwrap_err();
+    assert_eq!(error.error().to_string(), "Cannot write the template output. disk full.");
+}
+
+/// Identifies templates by name - changes are only detected by `is_fresh()`.
+#[derive(Debug)]
+struct NamedLoader(loader::array::Array, bool);
+
+impl Loader for NamedLoader {
+    fn source<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+        self.0.source(name)
+    }
+
+    fn cache_key<'a>(&'a self, name: &str) -> Result<Cow<'a, str>, Traced<LoaderError>> {
+        Ok(Cow::Owned(name.to_string()))
+    }
+
+    fn is_fresh(&self, _name: &str, _time: i64) -> bool {
+        self.1
+    }
+}
+
+#[test]
+fn filecache() {
+    let dir = env::temp_dir().join(format!("twig-filecache-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let engine = |source: &str, auto_reload: bool| {
+        let mut loader = loader::array::Array::default();
+        loader.set_template("test", source);
+
+        let setup = Setup::default().set_cache(Some(&dir)).set_auto_reload(Some(auto_reload));
+        let mut engine = Engine::new(setup).unwrap();
+        engine.set_loader(Box::new(NamedLoader(loader, false)));
+        engine
+    };
+    let render = |engine: &mut Engine| {
+        let mut runtime = Runtime::default();
+        runtime.set("name", "world");
+
+        engine.load_template("test", None).unwrap().render(&runtime).unwrap()
+    };
+
+    assert_eq!(render(&mut engine("Hello {{ name|upper }}!", false)), "Hello WORLD!");
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    // the parsed template is loaded from the cache directory
+    assert_eq!(render(&mut engine("Changed", false)), "Hello WORLD!");
+
+    // the loader reports changes
+    assert_eq!(render(&mut engine("Changed", true)), "Changed");
+    assert_eq!(render(&mut engine("Other", false)), "Changed");
+
+    let mut cleared = engine("Other", false);
+    cleared.clear_cache_files().unwrap();
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    assert_eq!(render(&mut cleared), "Other");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn filecache_lexer_options() {
+    let dir = env::temp_dir().join(format!("twig-filecache-lexer-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let render = |prefix: &str| {
+        let mut loader = loader::array::Array::default();
+        loader.set_template("test", "%% include 'a'\n## include 'b'\n");
+        loader.set_template("a", "A");
+        loader.set_template("b", "B");
+
+        let mut engine = Engine::new(Setup::default().set_cache(Some(&dir))).unwrap();
+        let options = lexer::Options {
+            line_statement_prefix: Some(prefix.into()),
+            ..lexer::Options::default()
+        };
+        let lexer = Lexer::new(&engine, options).unwrap();
+        engine.set_lexer(lexer).set_loader(Box::new(NamedLoader(loader, true)));
+
+        engine.load_template("test", None).unwrap().render(&Runtime::default()).unwrap()
+    };
+
+    // the engines share the cache directory, but not the parsed templates
+    assert_eq!(render("%%"), "A## include 'b'\n");
+    assert_eq!(render("##"), "%% include 'a'\nB");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+mod precompiled {
+    include!("fixtures/precompiled.rs");
+}
+
+#[test]
+fn precompiled() {
+    let dir = env::temp_dir().join(format!("twig-precompiled-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    // the fixture is up to date
+    let out = dir.join("templates.rs");
+    let paths = twig::build::compile_templates(Path::new("tests/fixtures/templates"), &out)
+                    .unwrap();
+    assert_eq!(paths.len(), 4);
+    assert_eq!(fs::read_to_string(&out).unwrap(),
+               fs::read_to_string("tests/fixtures/precompiled.rs").unwrap());
+
+    // syntax errors fail the build
+    fs::create_dir_all(dir.join("broken")).unwrap();
+    fs::write(dir.join("broken/index.html"), "{{ name").unwrap();
+    assert!(twig::build::compile_templates(&dir.join("broken"), &out).is_err());
+
+    // no loader is needed
+    let mut engine = Engine::new(Setup::default()).unwrap();
+    engine.set_precompiled(precompiled::templates());
+    let mut runtime = Runtime::default();
+    let mut user = Map::new();
+    user.insert("name", "Jo");
+    runtime.set("title", "precompiled templates");
+    runtime.set("user", user.into_value());
+    runtime.set("items", vec!["a", "b"]);
+
+    assert_eq!(engine.load_template("index.html", None).unwrap().render(&runtime).unwrap(),
+               "<h1>Precompiled Templates</h1>\nHello Jo![1,2.5,true,null] a,1\nA, B 5 -2\n");
+    assert_eq!(engine.load_template("page.html", None).unwrap().render(&runtime).unwrap(),
+               "<title>Page - Site</title>\n<input type=\"text\" name=\"email\">Page - Site\n\
+                1. a 2. b ");
+    assert!(engine.load_template("missing.html", None).is_err());
+
+    // the templates were built with other settings
+    use twig::extension::escaper::Mode;
+    let mut engine = Setup::default().set_autoescape(Mode::Disabled).engine().unwrap();
+    engine.set_precompiled(precompiled::templates());
+    assert!(engine.load_template("index.html", None).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}